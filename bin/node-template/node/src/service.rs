@@ -219,6 +219,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let backoff_authoring_blocks: Option<()> = None;
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
+	let justification_archive_period = config.grandpa_justification_archive_period;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
 	let rpc_extensions_builder = {
@@ -306,6 +307,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			// FIXME #1578 make this available through chainspec
 			gossip_duration: Duration::from_millis(333),
 			justification_period: 512,
+			justification_archive_period,
 			name: Some(name),
 			observer_enabled: false,
 			keystore,
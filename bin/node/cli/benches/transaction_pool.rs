@@ -26,8 +26,9 @@ use node_primitives::AccountId;
 use sc_client_api::execution_extensions::ExecutionStrategies;
 use sc_service::{
 	config::{
-		BlocksPruning, DatabaseSource, KeystoreConfig, NetworkConfiguration, OffchainWorkerConfig,
-		PruningMode, TransactionPoolOptions, WasmExecutionMethod,
+		BlocksPruning, DatabaseSource, KeystoreConfig, NetworkConfiguration, OffchainIndexPruning,
+		OffchainWorkerConfig, PruningMode, TransactionPoolOptions, WasmExecutionMethod,
+		DEFAULT_MAX_PINNED_BLOCKS,
 	},
 	BasePath, Configuration, Role,
 };
@@ -69,6 +70,8 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		trie_cache_maximum_size: Some(64 * 1024 * 1024),
 		state_pruning: Some(PruningMode::ArchiveAll),
 		blocks_pruning: BlocksPruning::All,
+		offchain_index_pruning: OffchainIndexPruning::All,
+		max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
 		chain_spec: spec,
 		wasm_method: WasmExecutionMethod::Interpreted,
 		// NOTE: we enforce the use of the native runtime to make the errors more debuggable
@@ -79,6 +82,8 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 			offchain_worker: sc_client_api::ExecutionStrategy::NativeWhenPossible,
 			other: sc_client_api::ExecutionStrategy::NativeWhenPossible,
 		},
+		verification_workers: None,
+		execution_timeouts: Default::default(),
 		rpc_http: None,
 		rpc_ws: None,
 		rpc_ipc: None,
@@ -90,13 +95,21 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		rpc_max_response_size: None,
 		rpc_id_provider: None,
 		rpc_max_subs_per_conn: None,
+		rpc_limits: Default::default(),
 		ws_max_out_buffer_capacity: None,
 		prometheus_config: None,
+		health: Default::default(),
 		telemetry_endpoints: None,
 		default_heap_pages: None,
-		offchain_worker: OffchainWorkerConfig { enabled: true, indexing_enabled: false },
+		offchain_worker: OffchainWorkerConfig {
+			enabled: true,
+			indexing_enabled: false,
+			http: Default::default(),
+			scheduling: Default::default(),
+		},
 		force_authoring: false,
 		disable_grandpa: false,
+		grandpa_justification_archive_period: None,
 		dev_key_seed: Some(Sr25519Keyring::Alice.to_seed()),
 		tracing_targets: None,
 		tracing_receiver: Default::default(),
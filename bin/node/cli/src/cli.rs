@@ -89,12 +89,21 @@ pub enum Subcommand {
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Export the GRANDPA justifications stored for a range of blocks.
+	ExportJustifications(sc_cli::ExportJustificationsCmd),
+
+	/// Import GRANDPA justifications into the justification archive.
+	ImportJustifications(sc_cli::ImportJustificationsCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Compile a runtime ahead of time into the executor cache.
+	PrecompileRuntime(sc_cli::PrecompileRuntimeCmd),
+
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
 }
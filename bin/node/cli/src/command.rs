@@ -19,7 +19,7 @@
 use super::benchmarking::{inherent_benchmark_data, RemarkBuilder, TransferKeepAliveBuilder};
 use crate::{
 	chain_spec, service,
-	service::{new_partial, FullClient},
+	service::{new_partial, FullBackend, FullClient},
 	Cli, Subcommand,
 };
 use frame_benchmarking_cli::*;
@@ -203,6 +203,32 @@ pub fn run() -> Result<()> {
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		},
+		Some(Subcommand::ExportJustifications(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents { task_manager, backend, .. } = new_partial(&config)?;
+				let export = Box::new(|backend: Arc<FullBackend>, from, to, output| {
+					grandpa::export_justifications::<Block, _>(&*backend, from, to, output)
+						.map_err(|e| sc_cli::Error::Application(Box::new(e)))
+				});
+				Ok((cmd.run(backend, export), task_manager))
+			})
+		},
+		Some(Subcommand::ImportJustifications(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents { client, task_manager, backend, .. } = new_partial(&config)?;
+				let import = Box::new(move |backend: Arc<FullBackend>, input| {
+					grandpa::import_justifications::<Block, _>(
+						&*backend,
+						&(client as Arc<_>),
+						input,
+					)
+					.map_err(|e| sc_cli::Error::Application(Box::new(e)))
+				});
+				Ok((cmd.run(backend, import), task_manager))
+			})
+		},
 		Some(Subcommand::PurgeChain(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.database))
@@ -219,6 +245,19 @@ pub fn run() -> Result<()> {
 				Ok((cmd.run(client, backend, Some(aux_revert)), task_manager))
 			})
 		},
+		Some(Subcommand::PrecompileRuntime(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents { client, task_manager, .. } = new_partial(&config)?;
+				let executor = service::new_executor(&config, None);
+				let precompile = Box::new(move |code: &[u8], heap_pages| {
+					executor
+						.precompile_runtime(code, heap_pages)
+						.map_err(|e| sc_cli::Error::Application(Box::new(e)))
+				});
+				Ok((cmd.run(client, config.chain_spec, precompile), task_manager))
+			})
+		},
 		#[cfg(feature = "try-runtime")]
 		Some(Subcommand::TryRuntime(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
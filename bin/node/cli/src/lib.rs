@@ -32,6 +32,7 @@
 
 pub mod chain_spec;
 
+mod runtime_precompilation;
 #[macro_use]
 pub mod service;
 #[cfg(feature = "cli")]
@@ -0,0 +1,165 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Ahead-of-time compilation of upcoming runtime upgrades.
+//!
+//! Compiling a new runtime takes a while, which stalls block import when the upgraded runtime is
+//! executed for the first time. To avoid that, the node watches for `set_code` calls being noted
+//! as preimages or scheduled, and compiles their code into the executor cache right away.
+
+use crate::service::FullClient;
+use codec::Decode;
+use frame_support::traits::schedule::MaybeHashed;
+use futures::prelude::*;
+use kitchensink_runtime::{Call, Event, Runtime};
+use node_executor::ExecutorDispatch;
+use node_primitives::Hash;
+use sc_client_api::{BlockchainEvents, StorageProvider};
+use sc_executor::NativeElseWasmExecutor;
+use sp_core::{
+	hashing::blake2_256,
+	storage::{well_known_keys, StorageKey},
+};
+use sp_runtime::generic::BlockId;
+use std::{collections::HashSet, sync::Arc};
+
+const LOG_TARGET: &str = "runtime-precompilation";
+
+type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// Watches the new best blocks for runtime upgrades and precompiles them with `executor`.
+pub async fn precompile_runtime_upgrades(
+	client: Arc<FullClient>,
+	executor: NativeElseWasmExecutor<ExecutorDispatch>,
+) {
+	let mut precompiled = HashSet::new();
+	let mut imported_blocks = client.import_notification_stream();
+
+	while let Some(notification) = imported_blocks.next().await {
+		if !notification.is_new_best {
+			continue
+		}
+
+		let block = BlockId::Hash(notification.hash);
+		let heap_pages = storage(&client, &block, well_known_keys::HEAP_PAGES.to_vec())
+			.and_then(|pages| u64::decode(&mut &pages[..]).ok());
+
+		for code in upcoming_runtime_upgrades(&client, &block) {
+			if !precompiled.insert(blake2_256(&code)) {
+				continue
+			}
+
+			log::info!(target: LOG_TARGET, "Precompiling upcoming runtime upgrade");
+			match executor.precompile_runtime(&code, heap_pages) {
+				Ok(true) => log::info!(target: LOG_TARGET, "Upcoming runtime upgrade precompiled"),
+				Ok(false) => log::debug!(
+					target: LOG_TARGET,
+					"Upcoming runtime upgrade not precompiled, the executor doesn't support it",
+				),
+				Err(e) => log::warn!(
+					target: LOG_TARGET,
+					"Failed to precompile upcoming runtime upgrade: {}",
+					e,
+				),
+			}
+		}
+	}
+}
+
+fn storage(
+	client: &FullClient,
+	block: &BlockId<node_primitives::Block>,
+	key: Vec<u8>,
+) -> Option<Vec<u8>> {
+	match client.storage(block, &StorageKey(key)) {
+		Ok(data) => data.map(|data| data.0),
+		Err(e) => {
+			log::debug!(target: LOG_TARGET, "Failed to fetch storage at {}: {}", block, e);
+			None
+		},
+	}
+}
+
+/// The code of the `set_code` calls noted as preimages or scheduled in the given block.
+fn upcoming_runtime_upgrades(
+	client: &FullClient,
+	block: &BlockId<node_primitives::Block>,
+) -> Vec<Vec<u8>> {
+	let events_key = frame_support::storage::storage_prefix(b"System", b"Events").to_vec();
+	let events = match storage(client, block, events_key)
+		.map(|events| Vec::<EventRecord>::decode(&mut &events[..]))
+	{
+		Some(Ok(events)) => events,
+		Some(Err(e)) => {
+			log::debug!(target: LOG_TARGET, "Failed to decode events at {}: {}", block, e);
+			return Vec::new()
+		},
+		None => return Vec::new(),
+	};
+
+	events
+		.into_iter()
+		.filter_map(|record| {
+			let call = match record.event {
+				Event::Preimage(pallet_preimage::Event::Noted { hash }) =>
+					noted_call(client, block, hash),
+				Event::Scheduler(pallet_scheduler::Event::Scheduled { when, index }) =>
+					scheduled_call(client, block, when, index),
+				_ => None,
+			}?;
+
+			match call {
+				Call::System(frame_system::Call::set_code { code }) |
+				Call::System(frame_system::Call::set_code_without_checks { code }) => Some(code),
+				_ => None,
+			}
+		})
+		.collect()
+}
+
+fn noted_call(
+	client: &FullClient,
+	block: &BlockId<node_primitives::Block>,
+	hash: Hash,
+) -> Option<Call> {
+	let mut key = frame_support::storage::storage_prefix(b"Preimage", b"PreimageFor").to_vec();
+	key.extend(hash.as_bytes());
+
+	let preimage = storage(client, block, key)?;
+	// The preimage is stored as a `BoundedVec`, i.e. prefixed with its length.
+	let preimage = Vec::<u8>::decode(&mut &preimage[..]).ok()?;
+	Call::decode(&mut &preimage[..]).ok()
+}
+
+fn scheduled_call(
+	client: &FullClient,
+	block: &BlockId<node_primitives::Block>,
+	when: node_primitives::BlockNumber,
+	index: u32,
+) -> Option<Call> {
+	let key = pallet_scheduler::Agenda::<Runtime>::hashed_key_for(when);
+	let agenda = storage(client, block, key)?;
+	let agenda =
+		Vec::<Option<pallet_scheduler::ScheduledV3Of<Runtime>>>::decode(&mut &agenda[..]).ok()?;
+
+	match agenda.get(index as usize)?.as_ref()?.call() {
+		MaybeHashed::Value(call) => Some(call.clone()),
+		// The preimage of the call is handled once it is noted.
+		MaybeHashed::Hash(_) => None,
+	}
+}
@@ -26,9 +26,10 @@ use futures::prelude::*;
 use kitchensink_runtime::RuntimeApi;
 use node_executor::ExecutorDispatch;
 use node_primitives::Block;
+use prometheus_endpoint::Registry;
 use sc_client_api::{BlockBackend, ExecutorProvider};
 use sc_consensus_babe::{self, SlotProportion};
-use sc_executor::NativeElseWasmExecutor;
+use sc_executor::{NativeElseWasmExecutor, WasmExecutor};
 use sc_network::NetworkService;
 use sc_network_common::{protocol::event::Event, service::NetworkEventStream};
 use sc_service::{config::Configuration, error::Error as ServiceError, RpcHandlers, TaskManager};
@@ -41,7 +42,7 @@ use std::sync::Arc;
 /// The full client type definition.
 pub type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
-type FullBackend = sc_service::TFullBackend<Block>;
+pub type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 type FullGrandpaBlockImport =
 	grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
@@ -123,6 +124,27 @@ pub fn create_extrinsic(
 	)
 }
 
+/// Creates the executor of the node, caching the compiled runtimes in the node's base path.
+pub fn new_executor(
+	config: &Configuration,
+	prometheus_registry: Option<&Registry>,
+) -> NativeElseWasmExecutor<ExecutorDispatch> {
+	let mut wasm_executor = WasmExecutor::new(
+		config.wasm_method,
+		config.default_heap_pages,
+		config.max_runtime_instances,
+		config.runtime_cache_path(),
+		config.runtime_cache_size,
+	);
+	if let Some(registry) = prometheus_registry {
+		if let Err(err) = wasm_executor.register_metrics(registry) {
+			log::warn!("Failed to register executor prometheus metrics: {}", err);
+		}
+	}
+
+	NativeElseWasmExecutor::<ExecutorDispatch>::new_with_wasm_executor(wasm_executor)
+}
+
 /// Creates a new partial node.
 pub fn new_partial(
 	config: &Configuration,
@@ -160,12 +182,7 @@ pub fn new_partial(
 		})
 		.transpose()?;
 
-	let executor = NativeElseWasmExecutor::<ExecutorDispatch>::new(
-		config.wasm_method,
-		config.default_heap_pages,
-		config.max_runtime_instances,
-		config.runtime_cache_size,
-	);
+	let executor = new_executor(config, config.prometheus_registry());
 
 	let (client, backend, keystore_container, task_manager) =
 		sc_service::new_full_parts::<Block, RuntimeApi, _>(
@@ -381,7 +398,9 @@ pub fn new_full_base(
 		Some(sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging::default());
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
+	let justification_archive_period = config.grandpa_justification_archive_period;
 	let prometheus_registry = config.prometheus_registry().cloned();
+	let executor = new_executor(&config, None);
 
 	let rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		config,
@@ -409,6 +428,12 @@ pub fn new_full_base(
 		}
 	}
 
+	task_manager.spawn_handle().spawn_blocking(
+		"runtime-precompilation",
+		None,
+		crate::runtime_precompilation::precompile_runtime_upgrades(client.clone(), executor),
+	);
+
 	let (block_import, grandpa_link, babe_link) = import_setup;
 
 	(with_startup_data)(&block_import, &babe_link);
@@ -517,6 +542,7 @@ pub fn new_full_base(
 		// FIXME #1578 make this available through chainspec
 		gossip_duration: std::time::Duration::from_millis(333),
 		justification_period: 512,
+		justification_archive_period,
 		name: Some(name),
 		observer_enabled: false,
 		keystore,
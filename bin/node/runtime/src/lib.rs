@@ -125,7 +125,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 268,
+	spec_version: 269,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 2,
@@ -1919,6 +1919,73 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl frame_system_rpc_runtime_api::DryRunApi<Block, AccountId, Balance> for Runtime {
+		fn dry_run_extrinsic(
+			extrinsic: <Block as BlockT>::Extrinsic,
+		) -> frame_system_rpc_runtime_api::DryRunEffects<Balance> {
+			System::reset_events();
+			let weight_before = System::block_weight().total();
+			let result = Executive::apply_extrinsic(extrinsic);
+			let weight = System::block_weight().total().saturating_sub(weight_before);
+
+			let events = System::read_events_no_consensus();
+			let fee = events
+				.iter()
+				.find_map(|record| match &record.event {
+					Event::TransactionPayment(
+						pallet_transaction_payment::Event::TransactionFeePaid { actual_fee, .. },
+					) => Some(*actual_fee),
+					_ => None,
+				})
+				.unwrap_or_default();
+
+			frame_system_rpc_runtime_api::DryRunEffects {
+				result: result.encode(),
+				events: events.encode(),
+				weight: weight.ref_time(),
+				fee,
+			}
+		}
+
+		fn dry_run_call(
+			origin: AccountId,
+			call: Vec<u8>,
+		) -> frame_system_rpc_runtime_api::DryRunEffects<Balance> {
+			use codec::DecodeLimit;
+			use frame_support::dispatch::{
+				DispatchResultWithPostInfo, Dispatchable, GetDispatchInfo,
+			};
+
+			System::reset_events();
+			let len = call.len() as u32;
+			let decoded = Call::decode_with_depth_limit(sp_api::MAX_EXTRINSIC_DEPTH, &mut &call[..]);
+			let (result, weight, fee) = match decoded {
+				Ok(call) => {
+					let info = call.get_dispatch_info();
+					let result = call.dispatch(Origin::signed(origin));
+					let post_info = match &result {
+						Ok(post_info) => *post_info,
+						Err(err) => err.post_info,
+					};
+					let fee = TransactionPayment::compute_actual_fee(len, &info, &post_info, 0);
+					(result, post_info.calc_actual_weight(&info), fee)
+				},
+				Err(_) => {
+					let result: DispatchResultWithPostInfo =
+						Err(sp_runtime::DispatchError::Other("Unable to decode call").into());
+					(result, Weight::zero(), 0)
+				},
+			};
+
+			frame_system_rpc_runtime_api::DryRunEffects {
+				result: result.encode(),
+				events: System::read_events_no_consensus().encode(),
+				weight: weight.ref_time(),
+				fee,
+			}
+		}
+	}
+
 	impl pallet_contracts_rpc_runtime_api::ContractsApi<
 		Block, AccountId, Balance, BlockNumber, Hash,
 	>
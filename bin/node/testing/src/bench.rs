@@ -392,6 +392,8 @@ impl BenchDb {
 			state_pruning: Some(PruningMode::ArchiveAll),
 			source: database_type.into_settings(dir.into()),
 			blocks_pruning: sc_client_db::BlocksPruning::All,
+			offchain_index_pruning: sc_client_db::OffchainIndexPruning::All,
+			max_pinned_blocks: sc_client_db::DEFAULT_MAX_PINNED_BLOCKS,
 		};
 		let task_executor = TaskExecutor::new();
 
@@ -341,6 +341,46 @@ pub struct AllocationStats {
 	/// Currently the bumper's only ever incremented, so this is simultaneously
 	/// the current value as well as the peak value.
 	pub address_space_used: u32,
+
+	/// The sum of bytes wasted by every allocation ever made.
+	///
+	/// Every allocation is rounded up to its size class, i.e. the next power of two. This
+	/// counts the difference between the rounded and the requested size.
+	pub bytes_wasted_sum: u128,
+
+	/// The number of allocations made per size class.
+	///
+	/// Use [`AllocationStats::allocations_per_size_class`] to get them together with the
+	/// size of the class.
+	allocations_per_order: [u64; N_ORDERS],
+}
+
+impl AllocationStats {
+	/// Returns the number of allocations made per size class, together with the size of the
+	/// class in bytes, excluding the allocation header.
+	///
+	/// Size classes that were never allocated from are skipped.
+	pub fn allocations_per_size_class(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+		self.allocations_per_order
+			.iter()
+			.enumerate()
+			.filter(|(_, count)| **count > 0)
+			.map(|(order, count)| (Order(order as u32).size(), *count))
+	}
+
+	/// Returns the total number of allocations ever made.
+	pub fn allocations(&self) -> u64 {
+		self.allocations_per_order.iter().sum()
+	}
+
+	/// Returns the number of bytes that are part of the used address space, but are currently
+	/// not allocated.
+	///
+	/// This is memory that is sitting in the free lists, which is a measure of the heap
+	/// fragmentation.
+	pub fn bytes_free(&self) -> u32 {
+		self.address_space_used.saturating_sub(self.bytes_allocated)
+	}
 }
 
 /// An implementation of freeing bump allocator.
@@ -439,6 +479,8 @@ impl FreeingBumpHeapAllocator {
 		self.stats.bytes_allocated_peak =
 			std::cmp::max(self.stats.bytes_allocated_peak, self.stats.bytes_allocated);
 		self.stats.address_space_used = self.bumper - self.original_heap_base;
+		self.stats.bytes_wasted_sum += u128::from(order.size().saturating_sub(size));
+		self.stats.allocations_per_order[order.0 as usize] += 1;
 
 		log::trace!(target: LOG_TARGET, "after allocation: {:?}", self.stats);
 
@@ -1001,4 +1043,23 @@ mod tests {
 			_ => panic!(),
 		}
 	}
+
+	#[test]
+	fn should_track_size_classes_and_wasted_bytes() {
+		let mut mem = [0u8; PAGE_SIZE as usize];
+		let mut heap = FreeingBumpHeapAllocator::new(0);
+
+		let ptr = heap.allocate(&mut mem[..], 1).unwrap();
+		heap.allocate(&mut mem[..], 8).unwrap();
+		heap.allocate(&mut mem[..], 20).unwrap();
+		heap.deallocate(&mut mem[..], ptr).unwrap();
+
+		let stats = heap.stats();
+		assert_eq!(stats.allocations(), 3);
+		assert_eq!(stats.allocations_per_size_class().collect::<Vec<_>>(), vec![(8, 2), (32, 1)]);
+		// `1` is rounded up to `8` and `20` to `32`.
+		assert_eq!(stats.bytes_wasted_sum, 7 + 12);
+		// The first allocation is sitting in the free list.
+		assert_eq!(stats.bytes_free(), 8 + HEADER_SIZE);
+	}
 }
@@ -38,7 +38,7 @@ use sp_state_machine::{
 use sp_storage::{ChildInfo, StorageData, StorageKey};
 use std::collections::{HashMap, HashSet};
 
-pub use sp_state_machine::{Backend as StateBackend, KeyValueStates};
+pub use sp_state_machine::{Backend as StateBackend, KeyValueStates, StorageDiffEntry};
 use std::marker::PhantomData;
 
 /// Extracts the state backend type for the given backend.
@@ -50,6 +50,16 @@ pub type TransactionForSB<B, Block> = <B as StateBackend<HashFor<Block>>>::Trans
 /// Extracts the transaction for the given backend.
 pub type TransactionFor<B, Block> = TransactionForSB<StateBackendFor<B, Block>, Block>;
 
+/// Prefix of the offchain storage entries that journal the offchain indexing changes of each
+/// block. The entries are keyed by block hash and hold an encoded [`OffchainIndexJournal`].
+pub const OFFCHAIN_INDEX_JOURNAL_PREFIX: &[u8] = b"offchain_index_journal";
+
+/// Offchain indexing changes introduced by a single block.
+///
+/// Every entry is a `(prefix, key)` pair and the value written by the block, `None` standing for
+/// a removal.
+pub type OffchainIndexJournal = Vec<((Vec<u8>, Vec<u8>), Option<Vec<u8>>)>;
+
 /// Import operation summary.
 ///
 /// Contains information about the block that just got imported,
@@ -431,6 +441,20 @@ pub trait StorageProvider<Block: BlockT, B: Backend<Block>> {
 		child_info: &ChildInfo,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<Option<Block::Hash>>;
+
+	/// Given two `BlockId`s, return the storage entries, including the entries of child tries,
+	/// that differ between the states of the two blocks.
+	///
+	/// Only keys starting with `prefix` are compared. At most `count` entries are returned,
+	/// starting after the entry with the child trie and key given by `start_at`, if any.
+	fn storage_diff(
+		&self,
+		from: &BlockId<Block>,
+		to: &BlockId<Block>,
+		prefix: Option<&StorageKey>,
+		start_at: Option<(Option<&ChildInfo>, &StorageKey)>,
+		count: usize,
+	) -> sp_blockchain::Result<Vec<StorageDiffEntry>>;
 }
 
 /// Client backend.
@@ -556,6 +580,19 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 
 	/// Tells whether the backend requires full-sync mode.
 	fn requires_full_sync(&self) -> bool;
+
+	/// Pin the block to keep its state and body available after pruning.
+	///
+	/// Headers and justifications are not subject to pruning and stay available regardless.
+	/// Pins are reference counted: every call to `pin_block` must be paired with a call to
+	/// [`Self::unpin_block`]. Fails if the state of the block is already discarded or if too
+	/// many blocks are pinned.
+	fn pin_block(&self, hash: &Block::Hash) -> sp_blockchain::Result<()>;
+
+	/// Release a pin previously acquired with [`Self::pin_block`].
+	///
+	/// The block may be pruned once all of its pins are released.
+	fn unpin_block(&self, hash: &Block::Hash);
 }
 
 /// Mark for all Backend implementations, that are making use of state data, stored locally.
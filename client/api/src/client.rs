@@ -220,6 +220,8 @@ pub struct MemoryInfo {
 	pub database_cache: MemorySize,
 	/// Size of the state db.
 	pub state_db: StateDbMemoryInfo,
+	/// Number of blocks pinned with [`crate::backend::Backend::pin_block`].
+	pub pinned_blocks: u64,
 }
 
 /// I/O statistics for client instance.
@@ -268,12 +270,14 @@ impl fmt::Display for UsageInfo {
 			f,
 			"caches: ({} state, {} db overlay), \
 			 state db: ({} non-canonical, {} pruning, {} pinned), \
+			 {} pinned blocks, \
 			 i/o: ({} tx, {} write, {} read, {} avg tx, {}/{} key cache reads/total, {} trie nodes writes)",
 			self.memory.state_cache,
 			self.memory.database_cache,
 			self.memory.state_db.non_canonical,
 			self.memory.state_db.pruning.unwrap_or_default(),
 			self.memory.state_db.pinned,
+			self.memory.pinned_blocks,
 			self.io.transactions,
 			self.io.bytes_written,
 			self.io.bytes_read,
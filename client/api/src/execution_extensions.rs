@@ -27,6 +27,7 @@ use parking_lot::RwLock;
 use sc_transaction_pool_api::OffchainSubmitTransaction;
 use sp_core::{
 	offchain::{self, OffchainDbExt, OffchainWorkerExt, TransactionPoolExt},
+	traits::ExecutionTimeoutExt,
 	ExecutionContext,
 };
 use sp_externalities::Extensions;
@@ -34,7 +35,10 @@ use sp_keystore::{KeystoreExt, SyncCryptoStorePtr};
 use sp_runtime::{generic::BlockId, traits};
 pub use sp_state_machine::ExecutionStrategy;
 use sp_state_machine::{DefaultHandler, ExecutionManager};
-use std::sync::{Arc, Weak};
+use std::{
+	sync::{Arc, Weak},
+	time::Duration,
+};
 
 /// Execution strategies settings.
 #[derive(Debug, Clone)]
@@ -63,6 +67,20 @@ impl Default for ExecutionStrategies {
 	}
 }
 
+/// Execution timeouts settings.
+///
+/// Only calls made by offchain workers and RPC are subject to a timeout. Aborting any other call
+/// would make the outcome depend on the speed of the machine.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutionTimeouts {
+	/// Timeout of runtime calls made by offchain workers.
+	pub offchain_worker: Option<Duration>,
+	/// Timeout of runtime calls made by RPC, e.g. `state_call`.
+	///
+	/// See [`ExecutionExtensions::rpc_call_extensions`].
+	pub rpc: Option<Duration>,
+}
+
 /// Generate the starting set of ExternalitiesExtensions based upon the given capabilities
 pub trait ExtensionsFactory: Send + Sync {
 	/// Make `Extensions` for given `Capabilities`.
@@ -94,6 +112,7 @@ impl<T: offchain::DbExternalities + Clone + Sync + Send + 'static> DbExternaliti
 /// for each call, based on required `Capabilities`.
 pub struct ExecutionExtensions<Block: traits::Block> {
 	strategies: ExecutionStrategies,
+	timeouts: ExecutionTimeouts,
 	keystore: Option<SyncCryptoStorePtr>,
 	offchain_db: Option<Box<dyn DbExternalitiesFactory>>,
 	// FIXME: these two are only RwLock because of https://github.com/paritytech/substrate/issues/4587
@@ -110,6 +129,7 @@ impl<Block: traits::Block> Default for ExecutionExtensions<Block> {
 	fn default() -> Self {
 		Self {
 			strategies: Default::default(),
+			timeouts: Default::default(),
 			keystore: None,
 			offchain_db: None,
 			transaction_pool: RwLock::new(None),
@@ -129,6 +149,7 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 		let extensions_factory = Box::new(());
 		Self {
 			strategies,
+			timeouts: Default::default(),
 			keystore,
 			offchain_db,
 			extensions_factory: RwLock::new(extensions_factory),
@@ -136,6 +157,12 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 		}
 	}
 
+	/// Set the timeouts of the runtime calls made by offchain workers and RPC.
+	pub fn with_timeouts(mut self, timeouts: ExecutionTimeouts) -> Self {
+		self.timeouts = timeouts;
+		self
+	}
+
 	/// Get a reference to the execution strategies.
 	pub fn strategies(&self) -> &ExecutionStrategies {
 		&self.strategies
@@ -188,6 +215,10 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 		}
 
 		if let ExecutionContext::OffchainCall(Some(ext)) = context {
+			if let Some(timeout) = self.timeouts.offchain_worker {
+				extensions.register(ExecutionTimeoutExt(timeout));
+			}
+
 			extensions.register(OffchainWorkerExt::new(offchain::LimitedExternalities::new(
 				capabilities,
 				ext.0,
@@ -197,6 +228,18 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 		extensions
 	}
 
+	/// Create the `Extensions` for a runtime call made by RPC.
+	///
+	/// They enforce the [`ExecutionTimeouts::rpc`] timeout, so they must only be passed to calls
+	/// made on behalf of RPC clients.
+	pub fn rpc_call_extensions(&self) -> Extensions {
+		let mut extensions = Extensions::new();
+		if let Some(timeout) = self.timeouts.rpc {
+			extensions.register(ExecutionTimeoutExt(timeout));
+		}
+		extensions
+	}
+
 	/// Create `ExecutionManager` and `Extensions` for given offchain call.
 	///
 	/// Based on the execution context and capabilities it produces
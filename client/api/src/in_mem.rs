@@ -799,6 +799,13 @@ where
 	fn requires_full_sync(&self) -> bool {
 		false
 	}
+
+	fn pin_block(&self, _hash: &Block::Hash) -> sp_blockchain::Result<()> {
+		// Nothing is ever pruned from the in-memory backend.
+		Ok(())
+	}
+
+	fn unpin_block(&self, _hash: &Block::Hash) {}
 }
 
 impl<Block: BlockT> backend::LocalBackend<Block> for Backend<Block> where Block::Hash: Ord {}
@@ -20,7 +20,7 @@ use beefy_primitives::{BeefyApi, MmrRootHash};
 use prometheus::Registry;
 use sc_client_api::{Backend, BlockchainEvents, Finalizer};
 use sc_consensus::BlockImport;
-use sc_network::ProtocolName;
+use sc_network::{NetworkRequest, ProtocolName};
 use sc_network_gossip::Network as GossipNetwork;
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
@@ -40,6 +40,7 @@ mod worker;
 pub mod import;
 pub mod justification;
 pub mod notification;
+pub mod request_response;
 
 #[cfg(test)]
 mod tests;
@@ -50,18 +51,32 @@ use crate::{
 		BeefyBestBlockSender, BeefyBestBlockStream, BeefyVersionedFinalityProofSender,
 		BeefyVersionedFinalityProofStream,
 	},
+	request_response::outgoing_requests_engine::OnDemandJustificationsEngine,
 };
 
-pub use beefy_protocol_name::standard_name as protocol_standard_name;
+pub use beefy_protocol_name::{
+	justifications_protocol_name, standard_name as protocol_standard_name,
+};
 
 pub(crate) mod beefy_protocol_name {
 	use sc_chain_spec::ChainSpec;
 	use sc_network::ProtocolName;
 
 	const NAME: &str = "/beefy/1";
+	const JUSTIFICATIONS_NAME: &str = "/beefy/justifications/1";
 	/// Old names for the notifications protocol, used for backward compatibility.
 	pub(crate) const LEGACY_NAMES: [&str; 1] = ["/paritytech/beefy/1"];
 
+	fn chain_prefix<Hash: AsRef<[u8]>>(
+		genesis_hash: &Hash,
+		chain_spec: &Box<dyn ChainSpec>,
+	) -> String {
+		match chain_spec.fork_id() {
+			Some(fork_id) => format!("/{}/{}", hex::encode(genesis_hash), fork_id),
+			None => format!("/{}", hex::encode(genesis_hash)),
+		}
+	}
+
 	/// Name of the notifications protocol used by BEEFY.
 	///
 	/// Must be registered towards the networking in order for BEEFY to properly function.
@@ -69,11 +84,17 @@ pub(crate) mod beefy_protocol_name {
 		genesis_hash: &Hash,
 		chain_spec: &Box<dyn ChainSpec>,
 	) -> ProtocolName {
-		let chain_prefix = match chain_spec.fork_id() {
-			Some(fork_id) => format!("/{}/{}", hex::encode(genesis_hash), fork_id),
-			None => format!("/{}", hex::encode(genesis_hash)),
-		};
-		format!("{}{}", chain_prefix, NAME).into()
+		format!("{}{}", chain_prefix(genesis_hash, chain_spec), NAME).into()
+	}
+
+	/// Name of the request-response protocol used by BEEFY to fetch justifications from peers.
+	///
+	/// See [`crate::request_response::justifications_protocol_config`].
+	pub fn justifications_protocol_name<Hash: AsRef<[u8]>>(
+		genesis_hash: &Hash,
+		chain_spec: &Box<dyn ChainSpec>,
+	) -> ProtocolName {
+		format!("{}{}", chain_prefix(genesis_hash, chain_spec), JUSTIFICATIONS_NAME).into()
 	}
 }
 
@@ -187,7 +208,7 @@ where
 	C: Client<B, BE>,
 	R: ProvideRuntimeApi<B>,
 	R::Api: BeefyApi<B> + MmrApi<B, MmrRootHash>,
-	N: GossipNetwork<B> + Clone + SyncOracle + Send + Sync + 'static,
+	N: GossipNetwork<B> + NetworkRequest + Clone + SyncOracle + Send + Sync + 'static,
 {
 	/// BEEFY client
 	pub client: Arc<C>,
@@ -197,7 +218,7 @@ where
 	pub runtime: Arc<R>,
 	/// Local key store
 	pub key_store: Option<SyncCryptoStorePtr>,
-	/// Gossip network
+	/// Gossip network, also used to fetch justifications from peers
 	pub network: N,
 	/// Minimal delta between blocks, BEEFY should vote for
 	pub min_block_delta: u32,
@@ -205,6 +226,9 @@ where
 	pub prometheus_registry: Option<Registry>,
 	/// Chain specific GRANDPA protocol name. See [`beefy_protocol_name::standard_name`].
 	pub protocol_name: ProtocolName,
+	/// Chain specific justifications protocol name.
+	/// See [`beefy_protocol_name::justifications_protocol_name`].
+	pub justifications_protocol_name: ProtocolName,
 	/// Links between the block importer, the background voter and the RPC layer.
 	pub links: BeefyVoterLinks<B>,
 }
@@ -219,7 +243,7 @@ where
 	C: Client<B, BE>,
 	R: ProvideRuntimeApi<B>,
 	R::Api: BeefyApi<B> + MmrApi<B, MmrRootHash>,
-	N: GossipNetwork<B> + Clone + SyncOracle + Send + Sync + 'static,
+	N: GossipNetwork<B> + NetworkRequest + Clone + SyncOracle + Send + Sync + 'static,
 {
 	let BeefyParams {
 		client,
@@ -230,17 +254,22 @@ where
 		min_block_delta,
 		prometheus_registry,
 		protocol_name,
+		justifications_protocol_name,
 		links,
 	} = beefy_params;
 
-	let sync_oracle = network.clone();
 	let gossip_validator = Arc::new(gossip::GossipValidator::new());
 	let gossip_engine = sc_network_gossip::GossipEngine::new(
-		network,
-		protocol_name,
+		network.clone(),
+		protocol_name.clone(),
 		gossip_validator.clone(),
 		None,
 	);
+	let on_demand_justifications = OnDemandJustificationsEngine::new(
+		network.clone(),
+		justifications_protocol_name,
+		protocol_name,
+	);
 
 	let metrics =
 		prometheus_registry.as_ref().map(metrics::Metrics::register).and_then(
@@ -260,10 +289,11 @@ where
 		client,
 		backend,
 		runtime,
-		sync_oracle,
+		network,
 		key_store: key_store.into(),
 		gossip_engine,
 		gossip_validator,
+		on_demand_justifications,
 		links,
 		metrics,
 		min_block_delta,
@@ -0,0 +1,113 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helper for handling (i.e. answering) BEEFY justifications requests from a remote peer.
+
+use beefy_primitives::BEEFY_ENGINE_ID;
+use codec::Decode;
+use futures::{
+	channel::{mpsc, oneshot},
+	StreamExt,
+};
+use log::debug;
+use sc_client_api::BlockBackend;
+use sc_network::config::{IncomingRequest, OutgoingResponse};
+use sp_runtime::{generic::BlockId, traits::Block};
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::request_response::{cost, JustificationRequest};
+
+/// Handler for incoming BEEFY justifications requests from a remote peer.
+pub struct BeefyJustifsRequestHandler<B, Client> {
+	request_receiver: mpsc::Receiver<IncomingRequest>,
+	client: Arc<Client>,
+	_block: PhantomData<B>,
+}
+
+impl<B, Client> BeefyJustifsRequestHandler<B, Client>
+where
+	B: Block,
+	Client: BlockBackend<B> + Send + Sync,
+{
+	/// Create a new [`BeefyJustifsRequestHandler`].
+	///
+	/// `request_receiver` is obtained from [`super::justifications_protocol_config`].
+	pub fn new(request_receiver: mpsc::Receiver<IncomingRequest>, client: Arc<Client>) -> Self {
+		Self { request_receiver, client, _block: PhantomData }
+	}
+
+	fn handle_request(
+		&self,
+		payload: Vec<u8>,
+		pending_response: oneshot::Sender<OutgoingResponse>,
+	) -> Result<(), HandleRequestError> {
+		let request = match JustificationRequest::<B>::decode(&mut &payload[..]) {
+			Ok(request) => request,
+			Err(err) => {
+				let _ = pending_response.send(OutgoingResponse {
+					result: Err(()),
+					reputation_changes: vec![cost::MALFORMED_REQUEST],
+					sent_feedback: None,
+				});
+				return Err(err.into())
+			},
+		};
+
+		// Not having the justification is not the requester's fault, answer with an empty
+		// response so that it can move on to another peer.
+		let result = self
+			.client
+			.justifications(&BlockId::Number(request.begin))?
+			.and_then(|justifs| justifs.into_justification(BEEFY_ENGINE_ID))
+			.ok_or(());
+
+		pending_response
+			.send(OutgoingResponse { result, reputation_changes: Vec::new(), sent_feedback: None })
+			.map_err(|_| HandleRequestError::SendResponse)
+	}
+
+	/// Run [`BeefyJustifsRequestHandler`].
+	pub async fn run(mut self) {
+		while let Some(request) = self.request_receiver.next().await {
+			let IncomingRequest { peer, payload, pending_response } = request;
+
+			match self.handle_request(payload, pending_response) {
+				Ok(()) => {
+					debug!(target: "beefy", "🥩 Handled BEEFY justification request from {}.", peer)
+				},
+				Err(e) => debug!(
+					target: "beefy",
+					"🥩 Failed to handle BEEFY justification request from {}: {}",
+					peer, e,
+				),
+			}
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+enum HandleRequestError {
+	#[error("Failed to decode request: {0}.")]
+	DecodeScale(#[from] codec::Error),
+
+	#[error("Client error: {0}.")]
+	Client(#[from] sp_blockchain::Error),
+
+	#[error("Failed to send response.")]
+	SendResponse,
+}
@@ -0,0 +1,86 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Request/response protocol for fetching BEEFY justifications from peers.
+//!
+//! BEEFY justifications are normally learned through gossip. A node that was offline or syncing
+//! misses them, and without the justification of a session's mandatory block the voter can't
+//! make progress. This protocol lets the voter ask its peers for the justification of a given
+//! block, verifying the answers against the validator set of the session.
+
+mod incoming_requests_handler;
+pub(crate) mod outgoing_requests_engine;
+
+pub use incoming_requests_handler::BeefyJustifsRequestHandler;
+
+use codec::{Decode, Encode};
+use futures::channel::mpsc;
+use sc_network::{
+	config::{IncomingRequest, RequestResponseConfig},
+	ProtocolName,
+};
+use sp_runtime::traits::{Block, NumberFor};
+use std::time::Duration;
+
+/// Size of the queue of incoming requests.
+const INBOUND_QUEUE_SIZE: usize = 20;
+
+/// A justification holds one signature per validator, which makes 1 MiB plenty.
+const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+
+/// Peers are expected to answer from their database, without doing any work.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Reputation changes applied to peers misbehaving on the justifications protocol.
+pub(crate) mod cost {
+	use sc_network::ReputationChange as Rep;
+
+	/// The peer sent a request we couldn't decode.
+	pub(crate) const MALFORMED_REQUEST: Rep =
+		Rep::new(-(1 << 10), "BEEFY: Malformed justification request");
+	/// The peer answered with a justification that failed verification.
+	pub(crate) const INVALID_PROOF: Rep =
+		Rep::new(-(1 << 12), "BEEFY: Invalid justification response");
+}
+
+/// Request for the BEEFY justification of a block.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct JustificationRequest<B: Block> {
+	/// Number of the block the justification is requested for.
+	pub begin: NumberFor<B>,
+}
+
+/// Generates a [`RequestResponseConfig`] for the BEEFY justifications protocol, along with the
+/// receiving end of its incoming requests.
+///
+/// The receiver is meant to be passed to [`BeefyJustifsRequestHandler::new`]. For the protocol
+/// name see [`crate::justifications_protocol_name`].
+pub fn justifications_protocol_config(
+	protocol_name: ProtocolName,
+) -> (mpsc::Receiver<IncomingRequest>, RequestResponseConfig) {
+	let (tx, rx) = mpsc::channel(INBOUND_QUEUE_SIZE);
+	let config = RequestResponseConfig {
+		name: protocol_name,
+		fallback_names: Vec::new(),
+		max_request_size: 32,
+		max_response_size: MAX_RESPONSE_SIZE,
+		request_timeout: REQUEST_TIMEOUT,
+		inbound_queue: Some(tx),
+	};
+	(rx, config)
+}
@@ -0,0 +1,269 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Generating request logic for request/response protocol for syncing BEEFY justifications.
+
+use beefy_primitives::{crypto::AuthorityId, ValidatorSet};
+use codec::Encode;
+use futures::{
+	channel::{oneshot, oneshot::Canceled},
+	stream::{Fuse, FusedStream, Stream},
+	StreamExt,
+};
+use log::debug;
+use sc_network::{Event, IfDisconnected, NetworkRequest, PeerId, ProtocolName, RequestFailure};
+use sc_network_gossip::Network as GossipNetwork;
+use sp_runtime::traits::{Block, NumberFor};
+use std::{collections::HashSet, pin::Pin};
+
+use crate::{
+	justification::{decode_and_verify_finality_proof, BeefyVersionedFinalityProof},
+	request_response::{cost, JustificationRequest},
+};
+
+/// Response type received from network.
+type Response = Result<Vec<u8>, RequestFailure>;
+/// Used to receive a response from the network.
+type ResponseReceiver = oneshot::Receiver<Response>;
+
+/// The block a justification is requested for, and the validator set expected to have signed it.
+#[derive(Clone, Debug)]
+struct RequestInfo<B: Block> {
+	block: NumberFor<B>,
+	active_set: ValidatorSet<AuthorityId>,
+}
+
+enum State<B: Block> {
+	Idle,
+	/// All live peers were asked already, waiting for another one to connect.
+	AwaitingPeer(RequestInfo<B>),
+	AwaitingResponse(PeerId, RequestInfo<B>, ResponseReceiver),
+}
+
+/// What woke up the engine.
+enum Incoming {
+	NetworkEvent(Option<Event>),
+	Response(Result<Response, Canceled>),
+}
+
+/// Requests BEEFY justifications from peers, one peer at a time.
+///
+/// Only peers connected on the BEEFY gossip protocol are asked. Every peer is asked at most once
+/// per request, the engine moves on to the next peer as long as the answers are missing or
+/// invalid.
+pub(crate) struct OnDemandJustificationsEngine<B: Block, N> {
+	network: N,
+	protocol_name: ProtocolName,
+	gossip_protocol_name: ProtocolName,
+	network_events: Fuse<Pin<Box<dyn Stream<Item = Event> + Send>>>,
+	/// Peers with an open BEEFY gossip substream.
+	live_peers: HashSet<PeerId>,
+	/// Peers already asked as part of the current request.
+	tried_peers: HashSet<PeerId>,
+	state: State<B>,
+}
+
+impl<B, N> OnDemandJustificationsEngine<B, N>
+where
+	B: Block,
+	N: GossipNetwork<B> + NetworkRequest,
+{
+	/// Create a new engine requesting justifications on `protocol_name`, from the peers connected
+	/// on `gossip_protocol_name`.
+	pub(crate) fn new(
+		network: N,
+		protocol_name: ProtocolName,
+		gossip_protocol_name: ProtocolName,
+	) -> Self {
+		let network_events = network.event_stream("beefy-on-demand-justifications").fuse();
+		Self {
+			network,
+			protocol_name,
+			gossip_protocol_name,
+			network_events,
+			live_peers: HashSet::new(),
+			tried_peers: HashSet::new(),
+			state: State::Idle,
+		}
+	}
+
+	/// Start requesting the justification for `block`, signed by `active_set`.
+	///
+	/// Ignored while a request is already in flight. Replaces a request still waiting for a peer.
+	pub(crate) fn request(&mut self, block: NumberFor<B>, active_set: ValidatorSet<AuthorityId>) {
+		if let State::AwaitingResponse(..) = self.state {
+			return
+		}
+
+		self.tried_peers.clear();
+		self.request_from_next_peer(RequestInfo { block, active_set });
+	}
+
+	/// Cancel the pending request, if it is for a block at or below `block`.
+	pub(crate) fn cancel_requests_older_than(&mut self, block: NumberFor<B>) {
+		let info = match &self.state {
+			State::Idle => return,
+			State::AwaitingPeer(info) | State::AwaitingResponse(_, info, _) => info,
+		};
+		if info.block <= block {
+			debug!(
+				target: "beefy",
+				"🥩 cancel pending request for justification #{:?}",
+				block
+			);
+			self.state = State::Idle;
+		}
+	}
+
+	/// Wait for the next valid justification received from a peer.
+	///
+	/// Network events are processed while waiting, so this should be polled continuously.
+	pub(crate) async fn next(&mut self) -> Option<BeefyVersionedFinalityProof<B>> {
+		loop {
+			let incoming = match &mut self.state {
+				// Network is gone, the in flight request is the last one we can make.
+				State::AwaitingResponse(_, _, receiver) if self.network_events.is_terminated() =>
+					Incoming::Response(receiver.await),
+				State::AwaitingResponse(_, _, receiver) => futures::select_biased! {
+					response = receiver => Incoming::Response(response),
+					event = self.network_events.next() => Incoming::NetworkEvent(event),
+				},
+				// Network is gone, no more requests can be made.
+				_ if self.network_events.is_terminated() => return futures::future::pending().await,
+				State::Idle | State::AwaitingPeer(_) =>
+					Incoming::NetworkEvent(self.network_events.next().await),
+			};
+
+			match incoming {
+				Incoming::NetworkEvent(Some(event)) => self.handle_network_event(event),
+				Incoming::NetworkEvent(None) => {},
+				Incoming::Response(response) => {
+					let (peer, info) = match std::mem::replace(&mut self.state, State::Idle) {
+						State::AwaitingResponse(peer, info, _) => (peer, info),
+						state => {
+							self.state = state;
+							continue
+						},
+					};
+
+					match self.process_response(peer, &info, response) {
+						Some(proof) => return Some(proof),
+						None => self.request_from_next_peer(info),
+					}
+				},
+			}
+		}
+	}
+
+	fn handle_network_event(&mut self, event: Event) {
+		match event {
+			Event::NotificationStreamOpened { remote, protocol, .. }
+				if protocol == self.gossip_protocol_name =>
+			{
+				self.live_peers.insert(remote);
+				match std::mem::replace(&mut self.state, State::Idle) {
+					State::AwaitingPeer(info) => self.request_from_next_peer(info),
+					state => self.state = state,
+				}
+			},
+			Event::NotificationStreamClosed { remote, protocol }
+				if protocol == self.gossip_protocol_name =>
+			{
+				self.live_peers.remove(&remote);
+			},
+			_ => {},
+		}
+	}
+
+	fn request_from_next_peer(&mut self, info: RequestInfo<B>) {
+		let peer = match self.live_peers.iter().find(|peer| !self.tried_peers.contains(*peer)) {
+			Some(peer) => *peer,
+			None => {
+				debug!(
+					target: "beefy",
+					"🥩 no good peers to request justification #{:?} from, waiting for one",
+					info.block
+				);
+				self.state = State::AwaitingPeer(info);
+				return
+			},
+		};
+		self.tried_peers.insert(peer);
+
+		debug!(target: "beefy", "🥩 requesting justification #{:?} from peer {:?}", info.block, peer);
+
+		let payload = JustificationRequest::<B> { begin: info.block }.encode();
+		let (tx, rx) = oneshot::channel();
+		self.network.start_request(
+			peer,
+			self.protocol_name.clone(),
+			payload,
+			tx,
+			IfDisconnected::ImmediateError,
+		);
+
+		self.state = State::AwaitingResponse(peer, info, rx);
+	}
+
+	fn process_response(
+		&mut self,
+		peer: PeerId,
+		info: &RequestInfo<B>,
+		response: Result<Response, Canceled>,
+	) -> Option<BeefyVersionedFinalityProof<B>> {
+		let encoded = match response {
+			Ok(Ok(encoded)) => encoded,
+			Ok(Err(err)) => {
+				debug!(
+					target: "beefy",
+					"🥩 on-demand justification #{:?} request to peer {:?} failed: {}",
+					info.block, peer, err
+				);
+				return None
+			},
+			Err(_) => {
+				debug!(
+					target: "beefy",
+					"🥩 on-demand justification #{:?} request to peer {:?} canceled",
+					info.block, peer
+				);
+				return None
+			},
+		};
+
+		match decode_and_verify_finality_proof::<B>(&encoded[..], info.block, &info.active_set) {
+			Ok(proof) => {
+				debug!(
+					target: "beefy",
+					"🥩 received valid justification #{:?} from peer {:?}",
+					info.block, peer
+				);
+				Some(proof)
+			},
+			Err(err) => {
+				debug!(
+					target: "beefy",
+					"🥩 invalid justification #{:?} from peer {:?}: {:?}",
+					info.block, peer, err
+				);
+				self.network.report_peer(peer, cost::INVALID_PROOF);
+				None
+			},
+		}
+	}
+}
@@ -96,6 +96,10 @@ where
 		self.validator_set.id()
 	}
 
+	pub(crate) fn validator_set(&self) -> &ValidatorSet<Public> {
+		&self.validator_set
+	}
+
 	pub(crate) fn validators(&self) -> &[Public] {
 		self.validator_set.validators()
 	}
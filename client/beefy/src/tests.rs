@@ -18,7 +18,7 @@
 
 //! Tests and test helpers for BEEFY.
 
-use futures::{future, stream::FuturesUnordered, Future, StreamExt};
+use futures::{channel::mpsc, future, stream::FuturesUnordered, Future, FutureExt, StreamExt};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc, task::Poll};
@@ -31,6 +31,7 @@ use sc_consensus::{
 	ImportedAux,
 };
 use sc_keystore::LocalKeystore;
+use sc_network::{config::IncomingRequest, NetworkService};
 use sc_network_test::{
 	Block, BlockImportAdapter, FullPeerConfig, PassThroughVerifier, Peer, PeersClient,
 	TestNetFactory,
@@ -60,11 +61,18 @@ use sp_runtime::{
 use substrate_test_runtime_client::{runtime::Header, ClientExt};
 
 use crate::{
-	beefy_block_import_and_links, beefy_protocol_name, justification::*,
-	keystore::tests::Keyring as BeefyKeyring, BeefyRPCLinks, BeefyVoterLinks,
+	beefy_block_import_and_links, beefy_protocol_name,
+	justification::*,
+	keystore::tests::Keyring as BeefyKeyring,
+	request_response::{
+		justifications_protocol_config, outgoing_requests_engine::OnDemandJustificationsEngine,
+		BeefyJustifsRequestHandler,
+	},
+	BeefyRPCLinks, BeefyVoterLinks,
 };
 
 pub(crate) const BEEFY_PROTOCOL_NAME: &'static str = "/beefy/1";
+pub(crate) const JUSTIFICATIONS_PROTOCOL_NAME: &'static str = "/beefy/justifications/1";
 const GOOD_MMR_ROOT: MmrRootHash = MmrRootHash::repeat_byte(0xbf);
 const BAD_MMR_ROOT: MmrRootHash = MmrRootHash::repeat_byte(0x42);
 
@@ -112,12 +120,19 @@ fn beefy_protocol_name() {
 		"/32043c7b3a6ad8f6c2bc8bc121d4caab09377b5e082b0cfbbb39ad13bc4acd93/beefy/1".to_string();
 	let proto_name = beefy_protocol_name::standard_name(&genesis_hash, &chain_spec);
 	assert_eq!(proto_name.to_string(), expected);
+
+	let expected =
+		"/32043c7b3a6ad8f6c2bc8bc121d4caab09377b5e082b0cfbbb39ad13bc4acd93/beefy/justifications/1"
+			.to_string();
+	let proto_name = beefy_protocol_name::justifications_protocol_name(&genesis_hash, &chain_spec);
+	assert_eq!(proto_name.to_string(), expected);
 }
 
 #[derive(Default)]
 pub(crate) struct PeerData {
 	pub(crate) beefy_rpc_links: Mutex<Option<BeefyRPCLinks<Block>>>,
 	pub(crate) beefy_voter_links: Mutex<Option<BeefyVoterLinks<Block>>>,
+	pub(crate) beefy_justif_req_receiver: Mutex<Option<mpsc::Receiver<IncomingRequest>>>,
 }
 
 #[derive(Default)]
@@ -138,11 +153,20 @@ impl BeefyTestNet {
 	}
 
 	pub(crate) fn add_authority_peer(&mut self) {
+		self.add_beefy_peer(true)
+	}
+
+	fn add_beefy_peer(&mut self, is_authority: bool) {
+		let (justif_req_receiver, justif_protocol_config) =
+			justifications_protocol_config(JUSTIFICATIONS_PROTOCOL_NAME.into());
 		self.add_full_peer_with_config(FullPeerConfig {
 			notifications_protocols: vec![BEEFY_PROTOCOL_NAME.into()],
-			is_authority: true,
+			request_response_protocols: vec![justif_protocol_config],
+			is_authority,
 			..Default::default()
-		})
+		});
+		let peer = self.peers.last().expect("peer was just added; qed");
+		*peer.data.beefy_justif_req_receiver.lock() = Some(justif_req_receiver);
 	}
 
 	pub(crate) fn generate_blocks_and_sync(
@@ -198,6 +222,7 @@ impl TestNetFactory for BeefyTestNet {
 		let peer_data = PeerData {
 			beefy_rpc_links: Mutex::new(Some(rpc_links)),
 			beefy_voter_links: Mutex::new(Some(voter_links)),
+			..Default::default()
 		};
 		(BlockImportAdapter::new(block_import), None, peer_data)
 	}
@@ -215,11 +240,7 @@ impl TestNetFactory for BeefyTestNet {
 	}
 
 	fn add_full_peer(&mut self) {
-		self.add_full_peer_with_config(FullPeerConfig {
-			notifications_protocols: vec![BEEFY_PROTOCOL_NAME.into()],
-			is_authority: false,
-			..Default::default()
-		})
+		self.add_beefy_peer(false)
 	}
 }
 
@@ -347,7 +368,7 @@ where
 	API: ProvideRuntimeApi<Block> + Default + Sync + Send,
 	API::Api: BeefyApi<Block> + MmrApi<Block, MmrRootHash>,
 {
-	let voters = FuturesUnordered::new();
+	let voters = FuturesUnordered::<future::BoxFuture<'static, ()>>::new();
 
 	for (peer_id, key, api) in peers.into_iter() {
 		let peer = &net.peers[peer_id];
@@ -355,7 +376,7 @@ where
 		let keystore = create_beefy_keystore(*key);
 
 		let (_, _, peer_data) = net.make_block_import(peer.client().clone());
-		let PeerData { beefy_rpc_links, beefy_voter_links } = peer_data;
+		let PeerData { beefy_rpc_links, beefy_voter_links, .. } = peer_data;
 
 		let beefy_voter_links = beefy_voter_links.lock().take();
 		*peer.data.beefy_rpc_links.lock() = beefy_rpc_links.lock().take();
@@ -371,12 +392,19 @@ where
 			min_block_delta,
 			prometheus_registry: None,
 			protocol_name: BEEFY_PROTOCOL_NAME.into(),
+			justifications_protocol_name: JUSTIFICATIONS_PROTOCOL_NAME.into(),
 		};
 		let gadget = crate::start_beefy_gadget::<_, _, _, _, _>(beefy_params);
 
 		fn assert_send<T: Send>(_: &T) {}
 		assert_send(&gadget);
-		voters.push(gadget);
+		voters.push(gadget.boxed());
+
+		// Answer justification requests from other peers.
+		if let Some(receiver) = peer.data.beefy_justif_req_receiver.lock().take() {
+			let handler = BeefyJustifsRequestHandler::new(receiver, peer.client().as_client());
+			voters.push(handler.run().boxed());
+		}
 	}
 
 	voters.for_each(|_| async move {})
@@ -484,6 +512,20 @@ fn streams_empty_after_timeout<T>(
 	});
 }
 
+// Drive the network until `engine` yields a justification, or `timeout` elapses.
+fn wait_for_on_demand_justif(
+	engine: &mut OnDemandJustificationsEngine<Block, Arc<NetworkService<Block, H256>>>,
+	net: &Arc<Mutex<BeefyTestNet>>,
+	runtime: &mut Runtime,
+	timeout: Duration,
+) -> Option<BeefyVersionedFinalityProof<Block>> {
+	let mut justif = None;
+	let next = Box::pin(async { justif = engine.next().await });
+	let timeout = Box::pin(async move { tokio::time::sleep(timeout).await });
+	block_until(future::select(next, timeout), net, runtime);
+	justif
+}
+
 fn finalize_block_and_wait_for_beefy(
 	net: &Arc<Mutex<BeefyTestNet>>,
 	peers: &[BeefyKeyring],
@@ -704,7 +746,7 @@ fn beefy_importing_blocks() {
 
 	let client = net.peer(0).client().clone();
 	let (mut block_import, _, peer_data) = net.make_block_import(client.clone());
-	let PeerData { beefy_rpc_links: _, beefy_voter_links } = peer_data;
+	let PeerData { beefy_voter_links, .. } = peer_data;
 	let justif_stream = beefy_voter_links.lock().take().unwrap().from_block_import_justif_stream;
 
 	let params = |block: Block, justifications: Option<Justifications>| {
@@ -811,3 +853,92 @@ fn beefy_importing_blocks() {
 		}));
 	}
 }
+
+#[test]
+fn on_demand_beefy_justification_sync() {
+	sp_tracing::try_init_simple();
+
+	let mut runtime = Runtime::new().unwrap();
+	let keys = &[BeefyKeyring::Alice, BeefyKeyring::Bob];
+	let validator_set = ValidatorSet::new(make_beefy_ids(keys), 0).unwrap();
+
+	let mut net = BeefyTestNet::new(2, 0);
+
+	// Create the engine before peers connect, so that it learns about the connection.
+	let mut engine = OnDemandJustificationsEngine::new(
+		net.peer(1).network_service().clone(),
+		JUSTIFICATIONS_PROTOCOL_NAME.into(),
+		BEEFY_PROTOCOL_NAME.into(),
+	);
+
+	net.generate_blocks_and_sync(10, 5, &validator_set, false);
+
+	// Peer #0 knows the justification of block #5 and answers justification requests.
+	let proof = crate::justification::tests::new_finality_proof(5, &validator_set, keys);
+	net.peer(0)
+		.client()
+		.as_client()
+		.finalize_block(BlockId::number(5), Some((BEEFY_ENGINE_ID, proof.encode())))
+		.unwrap();
+	let receiver = net.peer(0).data.beefy_justif_req_receiver.lock().take().unwrap();
+	let handler = BeefyJustifsRequestHandler::new(receiver, net.peer(0).client().as_client());
+	runtime.spawn(handler.run());
+
+	let net = Arc::new(Mutex::new(net));
+	let timeout = Duration::from_millis(250);
+
+	// Let the engine process the network events; there's nothing requested yet.
+	assert_eq!(wait_for_on_demand_justif(&mut engine, &net, &mut runtime, timeout), None);
+
+	// Peer #1 fetches the justification of block #5 from peer #0.
+	engine.request(5, validator_set.clone());
+	let justif =
+		wait_for_on_demand_justif(&mut engine, &net, &mut runtime, Duration::from_secs(10));
+	assert_eq!(justif, Some(proof));
+
+	// Peer #0 has no justification for block #6.
+	engine.request(6, validator_set.clone());
+	assert_eq!(wait_for_on_demand_justif(&mut engine, &net, &mut runtime, timeout), None);
+
+	// The justification of block #5 was not signed by this validator set.
+	let other_set = ValidatorSet::new(make_beefy_ids(keys), 1).unwrap();
+	engine.request(5, other_set);
+	assert_eq!(wait_for_on_demand_justif(&mut engine, &net, &mut runtime, timeout), None);
+}
+
+#[test]
+fn on_demand_beefy_justification_request_waits_for_peers() {
+	sp_tracing::try_init_simple();
+
+	let mut runtime = Runtime::new().unwrap();
+	let keys = &[BeefyKeyring::Alice, BeefyKeyring::Bob];
+	let validator_set = ValidatorSet::new(make_beefy_ids(keys), 0).unwrap();
+
+	let mut net = BeefyTestNet::new(2, 0);
+
+	let mut engine = OnDemandJustificationsEngine::new(
+		net.peer(1).network_service().clone(),
+		JUSTIFICATIONS_PROTOCOL_NAME.into(),
+		BEEFY_PROTOCOL_NAME.into(),
+	);
+
+	net.generate_blocks_and_sync(10, 5, &validator_set, false);
+
+	let proof = crate::justification::tests::new_finality_proof(5, &validator_set, keys);
+	net.peer(0)
+		.client()
+		.as_client()
+		.finalize_block(BlockId::number(5), Some((BEEFY_ENGINE_ID, proof.encode())))
+		.unwrap();
+	let receiver = net.peer(0).data.beefy_justif_req_receiver.lock().take().unwrap();
+	let handler = BeefyJustifsRequestHandler::new(receiver, net.peer(0).client().as_client());
+	runtime.spawn(handler.run());
+
+	let net = Arc::new(Mutex::new(net));
+
+	// The engine didn't process the connection to peer #0 yet, the request is kept until it does.
+	engine.request(5, validator_set.clone());
+	let justif =
+		wait_for_on_demand_justif(&mut engine, &net, &mut runtime, Duration::from_secs(10));
+	assert_eq!(justif, Some(proof));
+}
@@ -24,11 +24,12 @@ use std::{
 };
 
 use codec::{Codec, Decode, Encode};
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use log::{debug, error, info, log_enabled, trace, warn};
 
 use sc_client_api::{Backend, FinalityNotification, HeaderBackend};
-use sc_network_gossip::GossipEngine;
+use sc_network::NetworkRequest;
+use sc_network_gossip::{GossipEngine, Network as GossipNetwork};
 
 use sp_api::{BlockId, ProvideRuntimeApi};
 use sp_arithmetic::traits::{AtLeast32Bit, Saturating};
@@ -54,6 +55,7 @@ use crate::{
 	keystore::BeefyKeystore,
 	metric_inc, metric_set,
 	metrics::Metrics,
+	request_response::outgoing_requests_engine::OnDemandJustificationsEngine,
 	round::Rounds,
 	BeefyVoterLinks, Client,
 };
@@ -90,6 +92,12 @@ impl<B: Block> VoterOracle<B> {
 		}
 	}
 
+	/// Return reference to rounds pertaining to first session in the queue.
+	/// Voting will always happen at the head of the queue.
+	pub fn rounds(&self) -> Option<&Rounds<Payload, B>> {
+		self.sessions.front()
+	}
+
 	/// Return mutable reference to rounds pertaining to first session in the queue.
 	/// Voting will always happen at the head of the queue.
 	pub fn rounds_mut(&mut self) -> Option<&mut Rounds<Payload, B>> {
@@ -175,29 +183,32 @@ impl<B: Block> VoterOracle<B> {
 	}
 }
 
-pub(crate) struct WorkerParams<B: Block, BE, C, R, SO> {
+pub(crate) struct WorkerParams<B: Block, BE, C, R, N> {
 	pub client: Arc<C>,
 	pub backend: Arc<BE>,
 	pub runtime: Arc<R>,
-	pub sync_oracle: SO,
+	pub network: N,
 	pub key_store: BeefyKeystore,
 	pub gossip_engine: GossipEngine<B>,
 	pub gossip_validator: Arc<GossipValidator<B>>,
+	pub on_demand_justifications: OnDemandJustificationsEngine<B, N>,
 	pub links: BeefyVoterLinks<B>,
 	pub metrics: Option<Metrics>,
 	pub min_block_delta: u32,
 }
 
 /// A BEEFY worker plays the BEEFY protocol
-pub(crate) struct BeefyWorker<B: Block, BE, C, R, SO> {
+pub(crate) struct BeefyWorker<B: Block, BE, C, R, N> {
 	// utilities
 	client: Arc<C>,
 	backend: Arc<BE>,
 	runtime: Arc<R>,
-	sync_oracle: SO,
+	network: N,
 	key_store: BeefyKeystore,
 	gossip_engine: GossipEngine<B>,
 	gossip_validator: Arc<GossipValidator<B>>,
+	/// Fetches justifications of mandatory blocks from peers.
+	on_demand_justifications: OnDemandJustificationsEngine<B, N>,
 
 	// channels
 	/// Links between the block importer, the background voter and the RPC layer.
@@ -218,14 +229,14 @@ pub(crate) struct BeefyWorker<B: Block, BE, C, R, SO> {
 	voting_oracle: VoterOracle<B>,
 }
 
-impl<B, BE, C, R, SO> BeefyWorker<B, BE, C, R, SO>
+impl<B, BE, C, R, N> BeefyWorker<B, BE, C, R, N>
 where
 	B: Block + Codec,
 	BE: Backend<B>,
 	C: Client<B, BE>,
 	R: ProvideRuntimeApi<B>,
 	R::Api: BeefyApi<B> + MmrApi<B, MmrRootHash>,
-	SO: SyncOracle + Send + Sync + Clone + 'static,
+	N: GossipNetwork<B> + NetworkRequest + SyncOracle + Send + Sync + Clone + 'static,
 {
 	/// Return a new BEEFY worker instance.
 	///
@@ -233,15 +244,16 @@ where
 	/// BEEFY pallet has been deployed on-chain.
 	///
 	/// The BEEFY pallet is needed in order to keep track of the BEEFY authority set.
-	pub(crate) fn new(worker_params: WorkerParams<B, BE, C, R, SO>) -> Self {
+	pub(crate) fn new(worker_params: WorkerParams<B, BE, C, R, N>) -> Self {
 		let WorkerParams {
 			client,
 			backend,
 			runtime,
 			key_store,
-			sync_oracle,
+			network,
 			gossip_engine,
 			gossip_validator,
+			on_demand_justifications,
 			links,
 			metrics,
 			min_block_delta,
@@ -256,10 +268,11 @@ where
 			client: client.clone(),
 			backend,
 			runtime,
-			sync_oracle,
+			network,
 			key_store,
 			gossip_engine,
 			gossip_validator,
+			on_demand_justifications,
 			links,
 			metrics,
 			best_grandpa_block_header: last_finalized_header,
@@ -353,6 +366,7 @@ where
 
 			// Check all (newly) finalized blocks for new session(s).
 			let backend = self.backend.clone();
+			let mut new_session_added = false;
 			for header in notification
 				.tree_route
 				.iter()
@@ -366,10 +380,26 @@ where
 			{
 				if let Some(new_validator_set) = find_authorities_change::<B>(&header) {
 					self.init_session_at(new_validator_set, *header.number());
-					// TODO (grandpa-bridge-gadget/issues/20): when adding SYNC protocol,
-					// fire up a request for justification for this mandatory block here.
+					new_session_added = true;
 				}
 			}
+
+			if new_session_added {
+				self.request_mandatory_justif();
+			}
+		}
+	}
+
+	/// Fire up a request for the justification of the oldest mandatory block that is not yet
+	/// BEEFY finalized, unless it is the most recent GRANDPA finalized block, in which case it is
+	/// still being voted on and will be gossiped.
+	fn request_mandatory_justif(&mut self) {
+		let best_grandpa = *self.best_grandpa_block_header.number();
+		if let Some(rounds) = self.voting_oracle.rounds() {
+			if !rounds.mandatory_done() && rounds.session_start() < best_grandpa {
+				self.on_demand_justifications
+					.request(rounds.session_start(), rounds.validator_set().clone());
+			}
 		}
 	}
 
@@ -468,6 +498,8 @@ where
 		self.voting_oracle.rounds_mut().ok_or(Error::UninitSession)?.conclude(block_num);
 		// Prune any now "finalized" sessions from queue.
 		self.voting_oracle.try_prune();
+		// No need to keep fetching justifications we already have.
+		self.on_demand_justifications.cancel_requests_older_than(block_num);
 
 		if Some(block_num) > self.best_beefy_block {
 			// Set new best BEEFY block number.
@@ -736,7 +768,9 @@ where
 					let at = BlockId::hash(notif.header.hash());
 					if let Some(active) = self.runtime.runtime_api().validator_set(&at).ok().flatten() {
 						self.initialize_voter(&notif.header, active);
-						if !self.sync_oracle.is_major_syncing() {
+						// Voter might have been initialized at a past session boundary.
+						self.request_mandatory_justif();
+						if !self.network.is_major_syncing() {
 							if let Err(err) = self.try_to_vote() {
 								debug!(target: "beefy", "🥩 {}", err);
 							}
@@ -794,8 +828,17 @@ where
 						return;
 					}
 				},
-				// TODO: when adding SYNC protocol, join the on-demand justifications stream to
-				// this one, and handle them both here.
+				justif = self.on_demand_justifications.next().fuse() => {
+					if let Some(justif) = justif {
+						// On-demand justifications have already been verified to be valid
+						// by the `OnDemandJustificationsEngine`.
+						if let Err(err) = self.triage_incoming_justif(justif) {
+							debug!(target: "beefy", "🥩 {}", err);
+						}
+						// Move on to the next lagging session, if any.
+						self.request_mandatory_justif();
+					}
+				},
 				justif = block_import_justif.next() => {
 					if let Some(justif) = justif {
 						// Block import justifications have already been verified to be valid
@@ -829,7 +872,7 @@ where
 			}
 
 			// Don't bother voting during major sync.
-			if !self.sync_oracle.is_major_syncing() {
+			if !self.network.is_major_syncing() {
 				// There were external events, 'state' is changed, author a vote if needed/possible.
 				if let Err(err) = self.try_to_vote() {
 					debug!(target: "beefy", "🥩 {}", err);
@@ -935,7 +978,7 @@ pub(crate) mod tests {
 		notification::{BeefyBestBlockStream, BeefyVersionedFinalityProofStream},
 		tests::{
 			create_beefy_keystore, get_beefy_streams, make_beefy_ids, two_validators::TestApi,
-			BeefyPeer, BeefyTestNet, BEEFY_PROTOCOL_NAME,
+			BeefyPeer, BeefyTestNet, BEEFY_PROTOCOL_NAME, JUSTIFICATIONS_PROTOCOL_NAME,
 		},
 		BeefyRPCLinks,
 	};
@@ -978,10 +1021,14 @@ pub(crate) mod tests {
 
 		let api = Arc::new(TestApi {});
 		let network = peer.network_service().clone();
-		let sync_oracle = network.clone();
 		let gossip_validator = Arc::new(crate::gossip::GossipValidator::new());
 		let gossip_engine =
-			GossipEngine::new(network, BEEFY_PROTOCOL_NAME, gossip_validator.clone(), None);
+			GossipEngine::new(network.clone(), BEEFY_PROTOCOL_NAME, gossip_validator.clone(), None);
+		let on_demand_justifications = OnDemandJustificationsEngine::new(
+			network.clone(),
+			JUSTIFICATIONS_PROTOCOL_NAME.into(),
+			BEEFY_PROTOCOL_NAME.into(),
+		);
 		let worker_params = crate::worker::WorkerParams {
 			client: peer.client().as_client(),
 			backend: peer.client().as_backend(),
@@ -990,9 +1037,10 @@ pub(crate) mod tests {
 			links,
 			gossip_engine,
 			gossip_validator,
+			on_demand_justifications,
 			min_block_delta,
 			metrics: None,
-			sync_oracle,
+			network,
 		};
 		BeefyWorker::<_, _, _, _, _>::new(worker_params)
 	}
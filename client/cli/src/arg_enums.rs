@@ -295,6 +295,27 @@ pub enum OffchainWorkerEnabled {
 	WhenValidating,
 }
 
+/// Blocks the off-chain workers are run for.
+#[derive(Debug, Clone, Copy, ArgEnum, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum OffchainWorkerSchedule {
+	/// Run for every imported block that becomes the new best block.
+	BestBlocks,
+	/// Run for every finalized block.
+	FinalizedBlocks,
+}
+
+impl Into<sc_service::config::OffchainWorkerSchedule> for OffchainWorkerSchedule {
+	fn into(self) -> sc_service::config::OffchainWorkerSchedule {
+		match self {
+			OffchainWorkerSchedule::BestBlocks =>
+				sc_service::config::OffchainWorkerSchedule::BestBlocks,
+			OffchainWorkerSchedule::FinalizedBlocks =>
+				sc_service::config::OffchainWorkerSchedule::FinalizedBlocks,
+		}
+	}
+}
+
 /// Syncing mode.
 #[derive(Debug, Clone, Copy, ArgEnum, PartialEq)]
 #[clap(rename_all = "kebab-case")]
@@ -77,6 +77,8 @@ impl ChainInfoCmd {
 			state_pruning: config.state_pruning.clone(),
 			source: config.database.clone(),
 			blocks_pruning: config.blocks_pruning,
+			offchain_index_pruning: config.offchain_index_pruning,
+			max_pinned_blocks: config.max_pinned_blocks,
 		};
 		let backend = sc_service::new_db_backend::<B>(db_config)?;
 		let info: ChainInfo<B> = backend.blockchain().info().into();
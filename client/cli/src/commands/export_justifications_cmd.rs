@@ -0,0 +1,114 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{DatabaseParams, GenericNumber, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::info;
+use sc_client_api::{Backend, HeaderBackend};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+use std::{fmt::Debug, fs, io, path::PathBuf, str::FromStr, sync::Arc};
+
+/// The `export-justifications` command used to export the finality justifications stored for a
+/// range of blocks.
+#[derive(Debug, Clone, Parser)]
+pub struct ExportJustificationsCmd {
+	/// Output file name or stdout if unspecified.
+	#[clap(parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Specify starting block number.
+	///
+	/// Default is 1.
+	#[clap(long, value_name = "BLOCK")]
+	pub from: Option<GenericNumber>,
+
+	/// Specify last block number.
+	///
+	/// Default is the last finalized block.
+	#[clap(long, value_name = "BLOCK")]
+	pub to: Option<GenericNumber>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// Export handler for justifications, writing the justifications of the blocks in the given
+/// inclusive range and returning how many were exported.
+type ExportHandler<BA, B> = Box<
+	dyn FnOnce(Arc<BA>, NumberFor<B>, NumberFor<B>, Box<dyn io::Write>) -> error::Result<usize>,
+>;
+
+impl ExportJustificationsCmd {
+	/// Run the export-justifications command
+	pub async fn run<B, BA>(
+		&self,
+		backend: Arc<BA>,
+		export: ExportHandler<BA, B>,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let from = match &self.from {
+			Some(from) => from.parse()?,
+			None => 1u32.into(),
+		};
+		let to = match &self.to {
+			Some(to) => to.parse()?,
+			None => backend.blockchain().info().finalized_number,
+		};
+
+		let file: Box<dyn io::Write> = match &self.output {
+			Some(filename) => Box::new(fs::File::create(filename)?),
+			None => Box::new(io::stdout()),
+		};
+
+		let exported = export(backend, from, to, file)?;
+		info!("Exported {} justifications of blocks #{} to #{}", exported, from, to);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportJustificationsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -0,0 +1,84 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::info;
+use std::{
+	fs,
+	io::{self, Read},
+	path::PathBuf,
+	sync::Arc,
+};
+
+/// The `import-justifications` command used to import finality justifications exported with
+/// the `export-justifications` command.
+#[derive(Debug, Clone, Parser)]
+pub struct ImportJustificationsCmd {
+	/// Input file or stdin if unspecified.
+	#[clap(parse(from_os_str))]
+	pub input: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// Import handler for justifications, returning how many were imported.
+type ImportHandler<BA> = Box<dyn FnOnce(Arc<BA>, Box<dyn Read>) -> error::Result<usize>>;
+
+impl ImportJustificationsCmd {
+	/// Run the import-justifications command
+	pub async fn run<BA>(&self, backend: Arc<BA>, import: ImportHandler<BA>) -> error::Result<()> {
+		let file: Box<dyn Read> = match &self.input {
+			Some(filename) => Box::new(fs::File::open(filename)?),
+			None => Box::new(io::stdin()),
+		};
+
+		let imported = import(backend, file)?;
+		info!("Imported {} justifications", imported);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportJustificationsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
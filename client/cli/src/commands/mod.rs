@@ -19,14 +19,17 @@ mod build_spec_cmd;
 mod chain_info_cmd;
 mod check_block_cmd;
 mod export_blocks_cmd;
+mod export_justifications_cmd;
 mod export_state_cmd;
 mod generate;
 mod generate_node_key;
 mod import_blocks_cmd;
+mod import_justifications_cmd;
 mod insert_key;
 mod inspect_key;
 mod inspect_node_key;
 mod key;
+mod precompile_runtime_cmd;
 mod purge_chain_cmd;
 mod revert_cmd;
 mod run_cmd;
@@ -37,9 +40,11 @@ mod verify;
 
 pub use self::{
 	build_spec_cmd::BuildSpecCmd, chain_info_cmd::ChainInfoCmd, check_block_cmd::CheckBlockCmd,
-	export_blocks_cmd::ExportBlocksCmd, export_state_cmd::ExportStateCmd, generate::GenerateCmd,
-	generate_node_key::GenerateNodeKeyCmd, import_blocks_cmd::ImportBlocksCmd,
+	export_blocks_cmd::ExportBlocksCmd, export_justifications_cmd::ExportJustificationsCmd,
+	export_state_cmd::ExportStateCmd, generate::GenerateCmd, generate_node_key::GenerateNodeKeyCmd,
+	import_blocks_cmd::ImportBlocksCmd, import_justifications_cmd::ImportJustificationsCmd,
 	insert_key::InsertKeyCmd, inspect_key::InspectKeyCmd, inspect_node_key::InspectNodeKeyCmd,
-	key::KeySubcommand, purge_chain_cmd::PurgeChainCmd, revert_cmd::RevertCmd, run_cmd::RunCmd,
-	sign::SignCmd, vanity::VanityCmd, verify::VerifyCmd,
+	key::KeySubcommand, precompile_runtime_cmd::PrecompileRuntimeCmd,
+	purge_chain_cmd::PurgeChainCmd, revert_cmd::RevertCmd, run_cmd::RunCmd, sign::SignCmd,
+	vanity::VanityCmd, verify::VerifyCmd,
 };
@@ -0,0 +1,138 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{BlockNumberOrHash, DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::info;
+use parity_scale_codec::Decode;
+use sc_client_api::{backend::Backend, StorageProvider};
+use sp_core::storage::{well_known_keys, StorageKey};
+use sp_runtime::{
+	traits::{Block as BlockT, NumberFor},
+	BuildStorage,
+};
+use std::{fmt::Debug, path::PathBuf, str::FromStr, sync::Arc};
+
+/// The `precompile-runtime` command used to compile a runtime ahead of time into the executor
+/// cache, e.g. before an upcoming runtime upgrade.
+#[derive(Debug, Clone, Parser)]
+pub struct PrecompileRuntimeCmd {
+	/// Path to the Wasm runtime to precompile.
+	///
+	/// Defaults to the runtime of the chain spec, or of the block given with `--at`.
+	#[clap(long, value_name = "PATH", conflicts_with = "at")]
+	pub runtime: Option<PathBuf>,
+
+	/// Precompile the runtime of the given block hash or number.
+	#[clap(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// The number of 64KB heap pages the runtime will be executed with.
+	///
+	/// Defaults to the heap pages of the chain spec or of the block given with `--at`, or to the
+	/// default heap pages of the executor.
+	#[clap(long)]
+	pub heap_pages: Option<u64>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// Precompiles the given runtime code with the given heap pages, returning whether the runtime
+/// was compiled.
+type PrecompileHandler = Box<dyn FnOnce(&[u8], Option<u64>) -> error::Result<bool>>;
+
+impl PrecompileRuntimeCmd {
+	/// Run the `precompile-runtime` command
+	pub async fn run<B, BA, C>(
+		&self,
+		client: Arc<C>,
+		chain_spec: Box<dyn sc_service::ChainSpec>,
+		precompile: PrecompileHandler,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: StorageProvider<B, BA>,
+		B::Hash: FromStr,
+		<B::Hash as FromStr>::Err: Debug,
+		NumberFor<B>: FromStr,
+		<NumberFor<B> as FromStr>::Err: Debug,
+	{
+		let (code, heap_pages) = match (&self.runtime, &self.at) {
+			(Some(path), _) => (std::fs::read(path)?, None),
+			(None, Some(at)) => {
+				let block = at.parse::<B>()?;
+				let storage = |key: &[u8]| client.storage(&block, &StorageKey(key.to_vec()));
+				let code = storage(well_known_keys::CODE)?
+					.ok_or_else(|| format!("No runtime code at block {}", block))?;
+				(code.0, storage(well_known_keys::HEAP_PAGES)?.map(|pages| pages.0))
+			},
+			(None, None) => {
+				let mut storage = chain_spec.build_storage()?;
+				let code = storage
+					.top
+					.remove(well_known_keys::CODE)
+					.ok_or("No runtime code in the chain spec")?;
+				(code, storage.top.remove(well_known_keys::HEAP_PAGES))
+			},
+		};
+
+		let heap_pages = match (self.heap_pages, heap_pages) {
+			(Some(heap_pages), _) => Some(heap_pages),
+			(None, Some(encoded)) => Some(u64::decode(&mut &encoded[..])?),
+			(None, None) => None,
+		};
+
+		info!("Precompiling runtime of {} bytes...", code.len());
+		if precompile(&code, heap_pages)? {
+			info!("Runtime precompiled.");
+		} else {
+			info!("Runtime not precompiled: the executor doesn't compile runtimes ahead of time.");
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for PrecompileRuntimeCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -28,11 +28,15 @@ use crate::{
 use clap::Parser;
 use regex::Regex;
 use sc_service::{
-	config::{BasePath, PrometheusConfig, TransactionPoolOptions},
+	config::{BasePath, HealthConfig, PrometheusConfig, RpcLimits, TransactionPoolOptions},
 	ChainSpec, Role,
 };
 use sc_telemetry::TelemetryEndpoints;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	num::NonZeroU32,
+	time::Duration,
+};
 
 /// The `run` command used to run a node.
 #[derive(Debug, Clone, Parser)]
@@ -50,6 +54,14 @@ pub struct RunCmd {
 	#[clap(long)]
 	pub no_grandpa: bool,
 
+	/// Archive the GRANDPA justification of a finalized block at least every given number of
+	/// blocks.
+	///
+	/// Archived justifications are kept independently of the ones stored with the blocks, they can
+	/// be exported and are served as range finality proofs.
+	#[clap(long, value_name = "BLOCKS")]
+	pub grandpa_justification_archive_period: Option<u32>,
+
 	/// Listen to all RPC interfaces.
 	///
 	/// Default is local. Note: not all RPC methods are safe to be exposed publicly. Use an RPC
@@ -116,6 +128,40 @@ pub struct RunCmd {
 	#[clap(long)]
 	pub rpc_max_subscriptions_per_connection: Option<usize>,
 
+	/// Maximum total cost of the RPC calls accepted per second on a single connection.
+	///
+	/// Calls cost 1 unless configured otherwise with `--rpc-method-cost`. Calls exceeding
+	/// the limit are rejected with an error, subscriptions are counted but never rejected.
+	/// Default is unlimited.
+	#[clap(long, value_name = "COST")]
+	pub rpc_rate_limit: Option<NonZeroU32>,
+
+	/// Maximum total cost of the RPC calls accepted per second from a single IP address.
+	///
+	/// Applies on top of `--rpc-rate-limit`. Default is unlimited.
+	#[clap(long, value_name = "COST")]
+	pub rpc_rate_limit_per_ip: Option<NonZeroU32>,
+
+	/// Cost of calling an RPC method, counted against `--rpc-rate-limit` and
+	/// `--rpc-rate-limit-per-ip`.
+	///
+	/// This flag can be passed multiple times.
+	/// Expected format is 'METHOD=COST', e.g. `--rpc-method-cost state_getKeysPaged=10`.
+	#[clap(long, value_name = "METHOD=COST", parse(try_from_str = parse_method_cost))]
+	pub rpc_method_cost: Vec<(String, u32)>,
+
+	/// Comma-separated list of the only RPC methods that may be called.
+	///
+	/// Default is to allow all methods.
+	#[clap(long, value_name = "METHODS", use_value_delimiter = true)]
+	pub rpc_methods_allow: Vec<String>,
+
+	/// Comma-separated list of RPC methods that may not be called.
+	///
+	/// Takes precedence over `--rpc-methods-allow`.
+	#[clap(long, value_name = "METHODS", use_value_delimiter = true)]
+	pub rpc_methods_deny: Vec<String>,
+
 	/// Expose Prometheus exporter on all interfaces.
 	///
 	/// Default is local.
@@ -161,6 +207,20 @@ pub struct RunCmd {
 	#[clap(long)]
 	pub no_prometheus: bool,
 
+	/// Minimum number of connected peers for the `/health` endpoint of the Prometheus exporter
+	/// to report the node as healthy.
+	///
+	/// Default is 1, or 0 when running in --dev mode.
+	#[clap(long, value_name = "COUNT")]
+	pub health_min_peers: Option<usize>,
+
+	/// Maximum number of seconds since the last imported block for the `/health` endpoint of
+	/// the Prometheus exporter to report the node as healthy.
+	///
+	/// Default is 60. A value of 0 disables the check.
+	#[clap(long, value_name = "SECONDS")]
+	pub health_max_block_age: Option<u64>,
+
 	/// The human-readable name for this node.
 	///
 	/// The node name will be reported to the telemetry server, if enabled.
@@ -390,10 +450,27 @@ impl CliConfiguration for RunCmd {
 		})
 	}
 
+	fn health_config(&self, is_dev: bool) -> Result<HealthConfig> {
+		let default = HealthConfig::default();
+
+		Ok(HealthConfig {
+			min_peers: self.health_min_peers.unwrap_or(if is_dev { 0 } else { default.min_peers }),
+			max_block_age: match self.health_max_block_age {
+				Some(0) => None,
+				Some(secs) => Some(Duration::from_secs(secs)),
+				None => default.max_block_age,
+			},
+		})
+	}
+
 	fn disable_grandpa(&self) -> Result<bool> {
 		Ok(self.no_grandpa)
 	}
 
+	fn grandpa_justification_archive_period(&self) -> Result<Option<u32>> {
+		Ok(self.grandpa_justification_archive_period)
+	}
+
 	fn rpc_ws_max_connections(&self) -> Result<Option<usize>> {
 		Ok(self.ws_max_connections)
 	}
@@ -465,6 +542,17 @@ impl CliConfiguration for RunCmd {
 		Ok(self.rpc_max_subscriptions_per_connection)
 	}
 
+	fn rpc_limits(&self) -> Result<RpcLimits> {
+		Ok(RpcLimits {
+			max_cost_per_sec: self.rpc_rate_limit,
+			max_cost_per_sec_per_ip: self.rpc_rate_limit_per_ip,
+			method_costs: self.rpc_method_cost.iter().cloned().collect(),
+			allowed_methods: (!self.rpc_methods_allow.is_empty())
+				.then(|| self.rpc_methods_allow.clone()),
+			denied_methods: self.rpc_methods_deny.clone(),
+		})
+	}
+
 	fn ws_max_out_buffer_capacity(&self) -> Result<Option<usize>> {
 		Ok(self.ws_max_out_buffer_capacity)
 	}
@@ -576,6 +664,14 @@ fn parse_telemetry_endpoints(s: &str) -> std::result::Result<(String, u8), Telem
 	}
 }
 
+fn parse_method_cost(s: &str) -> std::result::Result<(String, u32), String> {
+	let (method, cost) = s
+		.split_once('=')
+		.ok_or_else(|| format!("Expected 'METHOD=COST', got '{}'", s))?;
+	let cost = cost.parse().map_err(|e| format!("Invalid cost of {}: {}", method, e))?;
+	Ok((method.to_string(), cost))
+}
+
 /// CORS setting
 ///
 /// The type is introduced to overcome `Option<Option<T>>` handling of `clap`.
@@ -621,6 +717,16 @@ fn parse_cors(s: &str) -> Cors {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn rpc_method_cost_is_parsed() {
+		assert_eq!(
+			parse_method_cost("state_getKeysPaged=10"),
+			Ok(("state_getKeysPaged".to_string(), 10))
+		);
+		assert!(parse_method_cost("state_getKeysPaged").is_err());
+		assert!(parse_method_cost("state_getKeysPaged=x").is_err());
+	}
+
 	#[test]
 	fn tests_node_name_good() {
 		assert!(is_node_name_valid("short name").is_ok());
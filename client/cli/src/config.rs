@@ -24,17 +24,18 @@ use crate::{
 };
 use log::warn;
 use names::{Generator, Name};
-use sc_client_api::execution_extensions::ExecutionStrategies;
+use sc_client_api::execution_extensions::{ExecutionStrategies, ExecutionTimeouts};
 use sc_service::{
 	config::{
-		BasePath, Configuration, DatabaseSource, KeystoreConfig, NetworkConfiguration,
-		NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
-		TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
+		BasePath, Configuration, DatabaseSource, HealthConfig, KeystoreConfig,
+		NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode,
+		Role, RpcLimits, RpcMethods, TelemetryEndpoints, TransactionPoolOptions,
+		WasmExecutionMethod, DEFAULT_MAX_PINNED_BLOCKS,
 	},
-	BlocksPruning, ChainSpec, TracingReceiver,
+	BlocksPruning, ChainSpec, OffchainIndexPruning, TracingReceiver,
 };
 use sc_tracing::logging::LoggerBuilder;
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, num::NonZeroUsize, path::PathBuf};
 
 /// The maximum number of characters for a node name.
 pub(crate) const NODE_NAME_MAX_LENGTH: usize = 64;
@@ -205,6 +206,17 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(self.database_params().and_then(|x| x.database()))
 	}
 
+	/// Get the maximum number of blocks that can be pinned at the same time.
+	///
+	/// By default this is retrieved from `DatabaseParams` if it is available. Otherwise its
+	/// [`DEFAULT_MAX_PINNED_BLOCKS`].
+	fn max_pinned_blocks(&self) -> Result<usize> {
+		Ok(self
+			.database_params()
+			.and_then(|x| x.max_pinned_blocks())
+			.unwrap_or(DEFAULT_MAX_PINNED_BLOCKS))
+	}
+
 	/// Get the database configuration object for the parameters provided
 	fn database_config(
 		&self,
@@ -258,6 +270,16 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			.unwrap_or_else(|| Ok(BlocksPruning::All))
 	}
 
+	/// Get the offchain indexing pruning mode.
+	///
+	/// By default this is retrieved from `offchain_index_pruning` if it is available. Otherwise
+	/// its `OffchainIndexPruning::All`.
+	fn offchain_index_pruning(&self) -> Result<OffchainIndexPruning> {
+		self.pruning_params()
+			.map(|x| x.offchain_index_pruning())
+			.unwrap_or_else(|| Ok(OffchainIndexPruning::All))
+	}
+
 	/// Get the chain ID (string).
 	///
 	/// By default this is retrieved from `SharedParams`.
@@ -302,6 +324,22 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			.unwrap_or_default())
 	}
 
+	/// Get the number of background tasks verifying blocks ahead of their import.
+	///
+	/// By default this is retrieved from `ImportParams` if it is available. Otherwise blocks are
+	/// verified right before they are imported.
+	fn verification_workers(&self) -> Result<Option<NonZeroUsize>> {
+		Ok(self.import_params().and_then(|x| x.verification_workers))
+	}
+
+	/// Get the execution timeouts.
+	///
+	/// By default this is retrieved from `ImportParams` if it is available. Otherwise no timeouts
+	/// are applied.
+	fn execution_timeouts(&self) -> Result<ExecutionTimeouts> {
+		Ok(self.import_params().map(|x| x.execution_timeouts()).unwrap_or_default())
+	}
+
 	/// Get the RPC HTTP address (`None` if disabled).
 	///
 	/// By default this is `None`.
@@ -365,6 +403,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(None)
 	}
 
+	/// Get the limits applied to the calls of the RPC servers.
+	///
+	/// By default no limits are applied.
+	fn rpc_limits(&self) -> Result<RpcLimits> {
+		Ok(Default::default())
+	}
+
 	/// Get maximum WS output buffer capacity.
 	fn ws_max_out_buffer_capacity(&self) -> Result<Option<usize>> {
 		Ok(None)
@@ -381,6 +426,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(None)
 	}
 
+	/// Get the thresholds of the health endpoints served alongside the Prometheus metrics.
+	///
+	/// By default this is [`HealthConfig::default`].
+	fn health_config(&self, _is_dev: bool) -> Result<HealthConfig> {
+		Ok(Default::default())
+	}
+
 	/// Get the telemetry endpoints (if any)
 	///
 	/// By default this is retrieved from the chain spec loaded by `load_spec`.
@@ -421,6 +473,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(Default::default())
 	}
 
+	/// Get the period (in blocks) at which GRANDPA justifications are archived.
+	///
+	/// By default this is `None`, nothing is archived.
+	fn grandpa_justification_archive_period(&self) -> Result<Option<u32>> {
+		Ok(Default::default())
+	}
+
 	/// Get the development key seed from the current object
 	///
 	/// By default this is `None`.
@@ -530,9 +589,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			trie_cache_maximum_size: self.trie_cache_maximum_size()?,
 			state_pruning: self.state_pruning()?,
 			blocks_pruning: self.blocks_pruning()?,
+			offchain_index_pruning: self.offchain_index_pruning()?,
+			max_pinned_blocks: self.max_pinned_blocks()?,
 			wasm_method: self.wasm_method()?,
 			wasm_runtime_overrides: self.wasm_runtime_overrides(),
 			execution_strategies: self.execution_strategies(is_dev, is_validator)?,
+			verification_workers: self.verification_workers()?,
+			execution_timeouts: self.execution_timeouts()?,
 			rpc_http: self.rpc_http(DCV::rpc_http_listen_port())?,
 			rpc_ws: self.rpc_ws(DCV::rpc_ws_listen_port())?,
 			rpc_ipc: self.rpc_ipc()?,
@@ -544,14 +607,17 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			rpc_max_response_size: self.rpc_max_response_size()?,
 			rpc_id_provider: None,
 			rpc_max_subs_per_conn: self.rpc_max_subscriptions_per_connection()?,
+			rpc_limits: self.rpc_limits()?,
 			ws_max_out_buffer_capacity: self.ws_max_out_buffer_capacity()?,
 			prometheus_config: self
 				.prometheus_config(DCV::prometheus_listen_port(), &chain_spec)?,
+			health: self.health_config(is_dev)?,
 			telemetry_endpoints,
 			default_heap_pages: self.default_heap_pages()?,
 			offchain_worker: self.offchain_worker(&role)?,
 			force_authoring: self.force_authoring()?,
 			disable_grandpa: self.disable_grandpa()?,
+			grandpa_justification_archive_period: self.grandpa_justification_archive_period()?,
 			dev_key_seed: self.dev_key_seed(is_dev)?,
 			tracing_targets: self.tracing_targets()?,
 			tracing_receiver: self.tracing_receiver()?,
@@ -35,6 +35,12 @@ pub struct DatabaseParams {
 	/// Limit the memory the database cache can use.
 	#[clap(long = "db-cache", value_name = "MiB")]
 	pub database_cache_size: Option<usize>,
+
+	/// Maximum number of blocks that can be pinned at the same time, e.g. by RPC subscriptions.
+	///
+	/// Defaults to 1024.
+	#[clap(long = "db-max-pinned-blocks", value_name = "COUNT")]
+	pub max_pinned_blocks: Option<usize>,
 }
 
 impl DatabaseParams {
@@ -47,4 +53,9 @@ impl DatabaseParams {
 	pub fn database_cache_size(&self) -> Option<usize> {
 		self.database_cache_size
 	}
+
+	/// Maximum number of blocks that can be pinned at the same time.
+	pub fn max_pinned_blocks(&self) -> Option<usize> {
+		self.max_pinned_blocks
+	}
 }
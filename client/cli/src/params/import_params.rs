@@ -27,8 +27,8 @@ use crate::{
 	params::{DatabaseParams, PruningParams},
 };
 use clap::Args;
-use sc_client_api::execution_extensions::ExecutionStrategies;
-use std::path::PathBuf;
+use sc_client_api::execution_extensions::{ExecutionStrategies, ExecutionTimeouts};
+use std::{num::NonZeroUsize, path::PathBuf, time::Duration};
 
 /// Parameters for block import.
 #[derive(Debug, Clone, Args)]
@@ -94,6 +94,29 @@ pub struct ImportParams {
 	#[clap(flatten)]
 	pub execution_strategies: ExecutionStrategiesParams,
 
+	/// Abort runtime calls of off-chain workers that execute longer than the given number of
+	/// milliseconds.
+	///
+	/// Only has an effect when `wasm-execution` is set to `compiled` and the call is executed in
+	/// wasm.
+	#[clap(long, value_name = "MILLISECONDS")]
+	pub execution_timeout_offchain_worker: Option<u64>,
+
+	/// Abort runtime calls made by RPC, e.g. `state_call`, that execute longer than the given
+	/// number of milliseconds.
+	///
+	/// Only has an effect when `wasm-execution` is set to `compiled` and the call is executed in
+	/// wasm.
+	#[clap(long, value_name = "MILLISECONDS")]
+	pub execution_timeout_rpc: Option<u64>,
+
+	/// Verify blocks ahead of their import on the given number of background tasks.
+	///
+	/// Blocks are still imported one after the other. By default every block is verified right
+	/// before it is imported.
+	#[clap(long, value_name = "COUNT")]
+	pub verification_workers: Option<NonZeroUsize>,
+
 	/// Specify the state cache size.
 	///
 	/// Providing `0` will disable the cache.
@@ -161,6 +184,14 @@ impl ImportParams {
 			other: exec_all_or(exec.execution_other, DEFAULT_EXECUTION_OTHER),
 		}
 	}
+
+	/// Get the execution timeouts for the parameters
+	pub fn execution_timeouts(&self) -> ExecutionTimeouts {
+		ExecutionTimeouts {
+			offchain_worker: self.execution_timeout_offchain_worker.map(Duration::from_millis),
+			rpc: self.execution_timeout_rpc.map(Duration::from_millis),
+		}
+	}
 }
 
 /// Execution strategies parameters.
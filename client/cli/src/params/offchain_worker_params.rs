@@ -25,9 +25,10 @@
 
 use clap::Args;
 use sc_network::config::Role;
-use sc_service::config::OffchainWorkerConfig;
+use sc_service::config::{OffchainHttpConfig, OffchainWorkerConfig, OffchainWorkerScheduling};
+use std::{path::PathBuf, time::Duration};
 
-use crate::{error, OffchainWorkerEnabled};
+use crate::{error, OffchainWorkerEnabled, OffchainWorkerSchedule};
 
 /// Offchain worker related parameters.
 #[derive(Debug, Clone, Args)]
@@ -44,12 +45,72 @@ pub struct OffchainWorkerParams {
 	)]
 	pub enabled: OffchainWorkerEnabled,
 
+	/// Blocks to execute offchain workers for.
+	#[clap(
+		long = "offchain-worker-schedule",
+		value_name = "SCHEDULE",
+		arg_enum,
+		ignore_case = true,
+		default_value = "best-blocks"
+	)]
+	pub schedule: OffchainWorkerSchedule,
+
+	/// Only execute offchain workers for blocks whose number is a multiple of the given interval.
+	#[clap(long = "offchain-worker-interval", value_name = "BLOCKS", default_value = "1")]
+	pub interval: u32,
+
+	/// Maximum number of offchain workers executed concurrently.
+	///
+	/// By default it's the number of CPUs. Offchain workers of further blocks are queued, unless
+	/// `--offchain-worker-skip-if-busy` is passed.
+	#[clap(long = "offchain-worker-max-concurrent", value_name = "COUNT")]
+	pub max_concurrent: Option<usize>,
+
+	/// Don't execute the offchain workers of blocks imported or finalized while the maximum
+	/// number of offchain workers is executing, instead of queueing them.
+	#[clap(long = "offchain-worker-skip-if-busy")]
+	pub skip_if_busy: bool,
+
 	/// Enable Offchain Indexing API, which allows block import to write to Offchain DB.
 	///
 	/// Enables a runtime to write directly to a offchain workers
 	/// DB during block import.
 	#[clap(long = "enable-offchain-indexing", value_name = "ENABLE_OFFCHAIN_INDEXING")]
 	pub indexing_enabled: bool,
+
+	/// Send the plain HTTP requests of offchain workers through the given HTTP proxy.
+	///
+	/// Proxies requiring authentication are not supported.
+	#[clap(long, value_name = "URL")]
+	pub offchain_http_proxy: Option<String>,
+
+	/// Tunnel the HTTPS requests of offchain workers through the given HTTP proxy.
+	///
+	/// Proxies requiring authentication are not supported.
+	#[clap(long, value_name = "URL")]
+	pub offchain_https_proxy: Option<String>,
+
+	/// Trust the PEM encoded root certificates in the given file for HTTPS requests of offchain
+	/// workers, in addition to the root certificates of the system.
+	///
+	/// Can be passed multiple times.
+	#[clap(long, value_name = "PATH", parse(from_os_str))]
+	pub offchain_http_root_certificate: Vec<PathBuf>,
+
+	/// Maximum number of HTTP requests that are executed concurrently by all offchain workers.
+	///
+	/// Further requests are delayed until one of the executed requests finished.
+	#[clap(long, value_name = "COUNT")]
+	pub offchain_http_max_concurrent_requests: Option<usize>,
+
+	/// Maximum size in bytes of the body of a response to an HTTP request of an offchain worker.
+	#[clap(long, value_name = "BYTES")]
+	pub offchain_http_max_response_body_size: Option<u64>,
+
+	/// Abort HTTP requests of offchain workers that take longer than the given number of
+	/// milliseconds.
+	#[clap(long, value_name = "MILLISECONDS")]
+	pub offchain_http_request_timeout: Option<u64>,
 }
 
 impl OffchainWorkerParams {
@@ -62,7 +123,59 @@ impl OffchainWorkerParams {
 			(OffchainWorkerEnabled::WhenValidating, _) => false,
 		};
 
+		if self.max_concurrent == Some(0) {
+			return Err(error::Error::Input(
+				"--offchain-worker-max-concurrent must be greater than 0".into(),
+			))
+		}
+
 		let indexing_enabled = self.indexing_enabled;
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+		let scheduling = OffchainWorkerScheduling {
+			schedule: self.schedule.into(),
+			interval: self.interval,
+			max_concurrent_workers: self.max_concurrent,
+			skip_if_busy: self.skip_if_busy,
+		};
+		Ok(OffchainWorkerConfig {
+			enabled,
+			indexing_enabled,
+			http: self.http_config()?,
+			scheduling,
+		})
+	}
+
+	/// Get the configuration of the HTTP client of offchain workers.
+	pub fn http_config(&self) -> error::Result<OffchainHttpConfig> {
+		if self.offchain_http_max_concurrent_requests == Some(0) {
+			return Err(error::Error::Input(
+				"--offchain-http-max-concurrent-requests must be greater than 0".into(),
+			))
+		}
+
+		let root_certificates = self
+			.offchain_http_root_certificate
+			.iter()
+			.map(|path| {
+				std::fs::read(path).map_err(|e| {
+					error::Error::Input(format!(
+						"Failed to read root certificates from {}: {}",
+						path.display(),
+						e
+					))
+				})
+			})
+			.collect::<error::Result<_>>()?;
+
+		let config = OffchainHttpConfig {
+			http_proxy: self.offchain_http_proxy.clone(),
+			https_proxy: self.offchain_https_proxy.clone(),
+			root_certificates,
+			max_concurrent_requests: self.offchain_http_max_concurrent_requests,
+			max_response_body_size: self.offchain_http_max_response_body_size,
+			request_timeout: self.offchain_http_request_timeout.map(Duration::from_millis),
+		};
+		config.check().map_err(error::Error::Input)?;
+
+		Ok(config)
 	}
 }
@@ -18,7 +18,7 @@
 
 use crate::error;
 use clap::Args;
-use sc_service::{BlocksPruning, PruningMode};
+use sc_service::{BlocksPruning, OffchainIndexPruning, PruningMode};
 
 /// Parameters to define the pruning mode
 #[derive(Debug, Clone, PartialEq, Args)]
@@ -37,6 +37,14 @@ pub struct PruningParams {
 	/// NOTE: only finalized blocks are subject for removal!
 	#[clap(alias = "keep-blocks", long, value_name = "COUNT")]
 	pub blocks_pruning: Option<u32>,
+	/// Specify the retention policy of the data written through offchain indexing, a number of
+	/// finalized blocks to keep it for, 'archive' or 'archive-canonical'.
+	///
+	/// Default is to keep all of it (i.e 'archive'). 'archive-canonical' only discards the data
+	/// written by forks that got displaced by finality. The changes of every block, as returned
+	/// by the `offchain_indexChanges` RPC, are kept as long as the data they wrote.
+	#[clap(long, value_name = "PRUNING_MODE")]
+	pub offchain_index_pruning: Option<String>,
 }
 
 impl PruningParams {
@@ -61,4 +69,15 @@ impl PruningParams {
 			None => BlocksPruning::All,
 		})
 	}
+
+	/// Get the offchain indexing pruning value from the parameters
+	pub fn offchain_index_pruning(&self) -> error::Result<OffchainIndexPruning> {
+		Ok(match self.offchain_index_pruning.as_deref() {
+			None | Some("archive") => OffchainIndexPruning::All,
+			Some("archive-canonical") => OffchainIndexPruning::Canonical,
+			Some(n) => n.parse().map(OffchainIndexPruning::Some).map_err(|_| {
+				error::Error::Input("Invalid offchain index pruning mode specified".to_string())
+			})?,
+		})
+	}
 }
@@ -29,6 +29,7 @@
 
 use std::{collections::HashMap, iter::FromIterator};
 
+use futures::channel::oneshot;
 use log::{debug, trace};
 use sp_runtime::{
 	traits::{Block as BlockT, Header as _, NumberFor},
@@ -53,6 +54,7 @@ pub type DefaultImportQueue<Block, Client> =
 
 mod basic_queue;
 pub mod buffered_link;
+mod verification_pool;
 
 /// Shared block import struct used by the queue.
 pub type BoxBlockImport<B, Transaction> =
@@ -102,6 +104,22 @@ pub trait Verifier<B: BlockT>: Send + Sync {
 	) -> Result<(BlockImportParams<B, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String>;
 }
 
+/// Verify the parts of a block that don't depend on its parent.
+///
+/// Unlike [`Verifier::verify`], this doesn't need the parent of the block to be imported and can
+/// thus run for many blocks at once, see [`BasicQueue::with_verification_workers`].
+#[async_trait::async_trait]
+pub trait PreVerifier<B: BlockT>: Send + Sync {
+	/// Verify the given block without looking at its parent.
+	///
+	/// The returned block is passed on to [`Verifier::verify`], so the checks done here don't
+	/// have to be repeated there. Blocks failing the pre-verification are not imported.
+	async fn pre_verify(
+		&self,
+		block: BlockImportParams<B, ()>,
+	) -> Result<BlockImportParams<B, ()>, String>;
+}
+
 /// Blocks import queue API.
 ///
 /// The `import_*` methods can be called in order to send elements for the import queue to verify.
@@ -211,10 +229,42 @@ pub async fn import_single_block<B: BlockT, V: Verifier<B>, Transaction: Send +
 	block: IncomingBlock<B>,
 	verifier: &mut V,
 ) -> BlockImportResult<B> {
-	import_single_block_metered(import_handle, block_origin, block, verifier, None).await
+	import_single_block_metered(import_handle, block_origin, block, verifier, None, None).await
+}
+
+/// Result of pre-verifying a block ahead of its import, see [`PreVerifier`].
+pub(crate) type PreVerification<B> = Result<BlockImportParams<B, ()>, String>;
+
+/// Builds the parameters passed to the verifier for the given incoming block.
+///
+/// Returns `None` if the block is missing its header.
+pub(crate) fn incoming_block_import_params<B: BlockT>(
+	block_origin: BlockOrigin,
+	block: IncomingBlock<B>,
+) -> Option<BlockImportParams<B, ()>> {
+	let mut import_block = BlockImportParams::new(block_origin, block.header?);
+	import_block.body = block.body;
+	import_block.justifications = block.justifications;
+	import_block.post_hash = Some(block.hash);
+	import_block.import_existing = block.import_existing;
+	import_block.indexed_body = block.indexed_body;
+
+	if let Some(state) = block.state {
+		let changes = crate::block_import::StorageChanges::Import(state);
+		import_block.state_action = StateAction::ApplyChanges(changes);
+	} else if block.skip_execution {
+		import_block.state_action = StateAction::Skip;
+	} else if block.allow_missing_state {
+		import_block.state_action = StateAction::ExecuteIfPossible;
+	}
+
+	Some(import_block)
 }
 
 /// Single block import function with metering.
+///
+/// If `pre_verification` is given, the block was already handed to a verification worker and
+/// `verifier` is called on the pre-verified block instead of the incoming one.
 pub(crate) async fn import_single_block_metered<
 	B: BlockT,
 	V: Verifier<B>,
@@ -224,26 +274,27 @@ pub(crate) async fn import_single_block_metered<
 	block_origin: BlockOrigin,
 	block: IncomingBlock<B>,
 	verifier: &mut V,
+	pre_verification: Option<oneshot::Receiver<PreVerification<B>>>,
 	metrics: Option<Metrics>,
 ) -> BlockImportResult<B> {
 	let peer = block.origin;
+	let hash = block.hash;
 
-	let (header, justifications) = match (block.header, block.justifications) {
-		(Some(header), justifications) => (header, justifications),
-		(None, _) => {
+	let header = match block.header.as_ref() {
+		Some(header) => header,
+		None => {
 			if let Some(ref peer) = peer {
-				debug!(target: "sync", "Header {} was not provided by {} ", block.hash, peer);
+				debug!(target: "sync", "Header {} was not provided by {} ", hash, peer);
 			} else {
-				debug!(target: "sync", "Header {} was not provided ", block.hash);
+				debug!(target: "sync", "Header {} was not provided ", hash);
 			}
 			return Err(BlockImportError::IncompleteHeader(peer))
 		},
 	};
 
-	trace!(target: "sync", "Header {} has {:?} logs", block.hash, header.digest().logs().len());
+	trace!(target: "sync", "Header {} has {:?} logs", hash, header.digest().logs().len());
 
 	let number = *header.number();
-	let hash = block.hash;
 	let parent_hash = *header.parent_hash();
 
 	let import_handler = |import| match import {
@@ -291,23 +342,7 @@ pub(crate) async fn import_single_block_metered<
 
 	let started = std::time::Instant::now();
 
-	let mut import_block = BlockImportParams::new(block_origin, header);
-	import_block.body = block.body;
-	import_block.justifications = justifications;
-	import_block.post_hash = Some(hash);
-	import_block.import_existing = block.import_existing;
-	import_block.indexed_body = block.indexed_body;
-
-	if let Some(state) = block.state {
-		let changes = crate::block_import::StorageChanges::Import(state);
-		import_block.state_action = StateAction::ApplyChanges(changes);
-	} else if block.skip_execution {
-		import_block.state_action = StateAction::Skip;
-	} else if block.allow_missing_state {
-		import_block.state_action = StateAction::ExecuteIfPossible;
-	}
-
-	let (import_block, maybe_keys) = verifier.verify(import_block).await.map_err(|msg| {
+	let verification_failed = |msg: String| {
 		if let Some(ref peer) = peer {
 			trace!(target: "sync", "Verifying {}({}) from {} failed: {}", number, hash, peer, msg);
 		} else {
@@ -317,7 +352,20 @@ pub(crate) async fn import_single_block_metered<
 			metrics.report_verification(false, started.elapsed());
 		}
 		BlockImportError::VerificationFailed(peer, msg)
-	})?;
+	};
+
+	let pre_verified = match pre_verification {
+		Some(receiver) => receiver.await.ok().transpose().map_err(verification_failed)?,
+		None => None,
+	};
+	let import_block = match pre_verified {
+		Some(import_block) => import_block,
+		None => incoming_block_import_params(block_origin, block)
+			.expect("block header presence is checked above; qed"),
+	};
+
+	let (import_block, maybe_keys) =
+		verifier.verify(import_block).await.map_err(verification_failed)?;
 
 	if let Some(metrics) = metrics.as_ref() {
 		metrics.report_verification(true, started.elapsed());
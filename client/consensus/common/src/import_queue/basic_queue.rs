@@ -28,13 +28,15 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	Justification, Justifications,
 };
-use std::{marker::PhantomData, pin::Pin, time::Duration};
+use std::{marker::PhantomData, num::NonZeroUsize, pin::Pin, time::Duration};
 
 use crate::{
 	import_queue::{
 		buffered_link::{self, BufferedLinkReceiver, BufferedLinkSender},
-		import_single_block_metered, BlockImportError, BlockImportStatus, BoxBlockImport,
-		BoxJustificationImport, ImportQueue, IncomingBlock, Link, Origin, Verifier,
+		import_single_block_metered, incoming_block_import_params,
+		verification_pool::VerificationPool,
+		BlockImportError, BlockImportStatus, BoxBlockImport, BoxJustificationImport, ImportQueue,
+		IncomingBlock, Link, Origin, PreVerifier, Verifier,
 	},
 	metrics::Metrics,
 };
@@ -70,6 +72,51 @@ impl<B: BlockT, Transaction: Send + 'static> BasicQueue<B, Transaction> {
 		justification_import: Option<BoxJustificationImport<B>>,
 		spawner: &impl sp_core::traits::SpawnEssentialNamed,
 		prometheus_registry: Option<&Registry>,
+	) -> Self {
+		Self::new_inner(
+			verifier,
+			block_import,
+			justification_import,
+			None,
+			spawner,
+			prometheus_registry,
+		)
+	}
+
+	/// Instantiate a new basic queue that pre-verifies blocks concurrently on
+	/// `verification_workers` background tasks, all sharing the given pre-verifier.
+	///
+	/// Blocks are still verified by `verifier` and imported strictly in order. `verifier` is
+	/// called with the block returned by `pre_verifier`, or with the incoming block if it wasn't
+	/// pre-verified, e.g. because it carries state from state sync.
+	pub fn with_verification_workers<V: 'static + Verifier<B>, P: 'static + PreVerifier<B>>(
+		verifier: V,
+		pre_verifier: P,
+		block_import: BoxBlockImport<B, Transaction>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		verification_workers: NonZeroUsize,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		prometheus_registry: Option<&Registry>,
+	) -> Self {
+		let verification_pool = VerificationPool::new(pre_verifier, verification_workers, spawner);
+
+		Self::new_inner(
+			verifier,
+			block_import,
+			justification_import,
+			Some(verification_pool),
+			spawner,
+			prometheus_registry,
+		)
+	}
+
+	fn new_inner<V: 'static + Verifier<B>>(
+		verifier: V,
+		block_import: BoxBlockImport<B, Transaction>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		verification_pool: Option<VerificationPool<B>>,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		prometheus_registry: Option<&Registry>,
 	) -> Self {
 		let (result_sender, result_port) = buffered_link::buffered_link();
 
@@ -86,6 +133,7 @@ impl<B: BlockT, Transaction: Send + 'static> BasicQueue<B, Transaction> {
 			verifier,
 			block_import,
 			justification_import,
+			verification_pool,
 			metrics,
 		);
 
@@ -171,6 +219,7 @@ async fn block_import_process<B: BlockT, Transaction: Send + 'static>(
 	mut verifier: impl Verifier<B>,
 	mut result_sender: BufferedLinkSender<B>,
 	mut block_import_receiver: TracingUnboundedReceiver<worker_messages::ImportBlocks<B>>,
+	mut verification_pool: Option<VerificationPool<B>>,
 	metrics: Option<Metrics>,
 	delay_between_blocks: Duration,
 ) {
@@ -192,6 +241,7 @@ async fn block_import_process<B: BlockT, Transaction: Send + 'static>(
 			origin,
 			blocks,
 			&mut verifier,
+			verification_pool.as_mut(),
 			delay_between_blocks,
 			metrics.clone(),
 		)
@@ -213,6 +263,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 		verifier: V,
 		block_import: BoxBlockImport<B, Transaction>,
 		justification_import: Option<BoxJustificationImport<B>>,
+		verification_pool: Option<VerificationPool<B>>,
 		metrics: Option<Metrics>,
 	) -> (
 		impl Future<Output = ()> + Send,
@@ -244,6 +295,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 				verifier,
 				worker.result_sender.clone(),
 				block_import_port,
+				verification_pool,
 				worker.metrics.clone(),
 				delay_between_blocks,
 			);
@@ -337,11 +389,15 @@ struct ImportManyBlocksResult<B: BlockT> {
 ///
 /// This will yield after each imported block once, to ensure that other futures can
 /// be called as well.
+///
+/// If a `verification_pool` is given, all blocks are handed to it for pre-verification up front,
+/// while the verification and import still happen one block after the other.
 async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'static>(
 	import_handle: &mut BoxBlockImport<B, Transaction>,
 	blocks_origin: BlockOrigin,
 	blocks: Vec<IncomingBlock<B>>,
 	verifier: &mut V,
+	verification_pool: Option<&mut VerificationPool<B>>,
 	delay_between_blocks: Duration,
 	metrics: Option<Metrics>,
 ) -> ImportManyBlocksResult<B> {
@@ -358,6 +414,23 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'stat
 
 	trace!(target: "sync", "Starting import of {} blocks {}", count, blocks_range);
 
+	let mut pre_verifications = match verification_pool {
+		Some(pool) => blocks
+			.iter()
+			.map(|block| {
+				// Blocks carrying state come from state sync, cloning the state isn't worth it.
+				if block.state.is_some() {
+					return None
+				}
+
+				incoming_block_import_params(blocks_origin, block.clone())
+					.and_then(|block| pool.pre_verify(block))
+			})
+			.collect(),
+		None => Vec::new(),
+	}
+	.into_iter();
+
 	let mut imported = 0;
 	let mut results = vec![];
 	let mut has_error = false;
@@ -376,6 +449,7 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'stat
 
 		let block_number = block.header.as_ref().map(|h| *h.number());
 		let block_hash = block.hash;
+		let pre_verification = pre_verifications.next().flatten();
 		let import_result = if has_error {
 			Err(BlockImportError::Cancelled)
 		} else {
@@ -385,6 +459,7 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>, Transaction: Send + 'stat
 				blocks_origin,
 				block,
 				verifier,
+				pre_verification,
 				metrics.clone(),
 			)
 			.await
@@ -544,7 +619,7 @@ mod tests {
 		let (result_sender, mut result_port) = buffered_link::buffered_link();
 
 		let (worker, mut finality_sender, mut block_import_sender) =
-			BlockImportWorker::new(result_sender, (), Box::new(()), Some(Box::new(())), None);
+			BlockImportWorker::new(result_sender, (), Box::new(()), Some(Box::new(())), None, None);
 		futures::pin_mut!(worker);
 
 		let mut import_block = |n| {
@@ -634,4 +709,144 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	fn pre_verifies_blocks_concurrently_and_verifies_them_once() {
+		use std::sync::{
+			atomic::{AtomicUsize, Ordering},
+			Arc,
+		};
+
+		const PRE_VERIFIED: &[u8] = b"pre_verified";
+
+		/// Keeps every pre-verification running for a while and records how many overlapped.
+		#[derive(Default)]
+		struct TrackingPreVerifier {
+			in_flight: AtomicUsize,
+			max_in_flight: Arc<AtomicUsize>,
+		}
+
+		#[async_trait::async_trait]
+		impl PreVerifier<Block> for TrackingPreVerifier {
+			async fn pre_verify(
+				&self,
+				mut block: BlockImportParams<Block, ()>,
+			) -> Result<BlockImportParams<Block, ()>, String> {
+				let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+				self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+				Delay::new(Duration::from_millis(50)).await;
+				self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+				block.auxiliary.push((PRE_VERIFIED.to_vec(), None));
+				Ok(block)
+			}
+		}
+
+		/// Only accepts pre-verified blocks and counts them.
+		struct CountingVerifier(Arc<AtomicUsize>);
+
+		#[async_trait::async_trait]
+		impl Verifier<Block> for CountingVerifier {
+			async fn verify(
+				&mut self,
+				block: BlockImportParams<Block, ()>,
+			) -> Result<(BlockImportParams<Block, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String>
+			{
+				if !block.auxiliary.iter().any(|(key, _)| key == PRE_VERIFIED) {
+					return Err("Block was not pre-verified".into())
+				}
+
+				self.0.fetch_add(1, Ordering::SeqCst);
+				Ok((block, None))
+			}
+		}
+
+		#[derive(Default)]
+		struct BatchLink {
+			batches: Vec<Vec<Hash>>,
+		}
+
+		impl Link<Block> for BatchLink {
+			fn blocks_processed(
+				&mut self,
+				_imported: usize,
+				_count: usize,
+				results: Vec<(Result<BlockImportStatus<BlockNumber>, BlockImportError>, Hash)>,
+			) {
+				self.batches
+					.push(results.into_iter().filter_map(|(r, h)| r.ok().map(|_| h)).collect());
+			}
+		}
+
+		let (result_sender, mut result_port) = buffered_link::buffered_link();
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pre_verifier = TrackingPreVerifier::default();
+		let max_in_flight = pre_verifier.max_in_flight.clone();
+		let verification_pool = VerificationPool::new(
+			pre_verifier,
+			NonZeroUsize::new(4).expect("4 is not zero; qed"),
+			&spawner,
+		);
+		let verified = Arc::new(AtomicUsize::new(0));
+
+		let (worker, _finality_sender, mut block_import_sender) = BlockImportWorker::new(
+			result_sender,
+			CountingVerifier(verified.clone()),
+			Box::new(()),
+			None,
+			Some(verification_pool),
+			None,
+		);
+		futures::pin_mut!(worker);
+
+		let blocks = (1..=8)
+			.map(|n| {
+				let header = Header {
+					parent_hash: Hash::random(),
+					number: n,
+					extrinsics_root: Hash::random(),
+					state_root: Default::default(),
+					digest: Default::default(),
+				};
+
+				IncomingBlock {
+					hash: header.hash(),
+					header: Some(header),
+					body: None,
+					indexed_body: None,
+					justifications: None,
+					origin: None,
+					allow_missing_state: false,
+					import_existing: false,
+					state: None,
+					skip_execution: false,
+				}
+			})
+			.collect::<Vec<_>>();
+		let hashes = blocks.iter().map(|b| b.hash).collect::<Vec<_>>();
+
+		block_on(block_import_sender.send(worker_messages::ImportBlocks(BlockOrigin::Own, blocks)))
+			.unwrap();
+
+		let mut link = BatchLink::default();
+		block_on(futures::future::poll_fn(|cx| {
+			if let Poll::Ready(()) = Future::poll(Pin::new(&mut worker), cx) {
+				panic!("import queue worker should not conclude.");
+			}
+
+			result_port.poll_actions(cx, &mut link).unwrap();
+
+			if link.batches.is_empty() {
+				Poll::Pending
+			} else {
+				Poll::Ready(())
+			}
+		}));
+
+		// All blocks are imported in order, each of them verified exactly once.
+		assert_eq!(link.batches, vec![hashes]);
+		assert_eq!(verified.load(Ordering::SeqCst), 8);
+		// The pre-verifications of different blocks overlapped.
+		assert!(max_in_flight.load(Ordering::SeqCst) > 1);
+	}
 }
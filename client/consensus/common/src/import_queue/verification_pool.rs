@@ -0,0 +1,104 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A pool of background tasks pre-verifying blocks ahead of their import.
+//!
+//! All workers share the [`PreVerifier`]. Blocks are handed out to the workers in a round-robin
+//! fashion and the results are sent back over a oneshot channel, which allows the import queue to
+//! keep importing the blocks strictly in order.
+
+use futures::{channel::oneshot, prelude::*};
+use log::trace;
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
+use sp_runtime::traits::Block as BlockT;
+use std::{num::NonZeroUsize, sync::Arc};
+
+use crate::{
+	block_import::BlockImportParams,
+	import_queue::{PreVerification, PreVerifier},
+};
+
+/// A block to pre-verify, along with the channel to report the result to.
+struct VerificationJob<B: BlockT> {
+	block: BlockImportParams<B, ()>,
+	result: oneshot::Sender<PreVerification<B>>,
+}
+
+/// Handle to the verification workers.
+pub(crate) struct VerificationPool<B: BlockT> {
+	workers: Vec<TracingUnboundedSender<VerificationJob<B>>>,
+	next: usize,
+}
+
+impl<B: BlockT> VerificationPool<B> {
+	/// Spawn `workers` verification tasks, all sharing `pre_verifier`.
+	pub(crate) fn new<P: PreVerifier<B> + 'static>(
+		pre_verifier: P,
+		workers: NonZeroUsize,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+	) -> Self {
+		let pre_verifier = Arc::new(pre_verifier);
+		let workers = (0..workers.get())
+			.map(|_| {
+				let (sender, mut receiver) =
+					tracing_unbounded::<VerificationJob<B>>("mpsc_import_queue_verification");
+				let pre_verifier = pre_verifier.clone();
+
+				let future = async move {
+					while let Some(VerificationJob { block, result }) = receiver.next().await {
+						// The import of a previous block failed, nobody waits for this one.
+						if result.is_canceled() {
+							continue
+						}
+
+						let _ = result.send(pre_verifier.pre_verify(block).await);
+					}
+
+					trace!(
+						target: "block-import",
+						"Stopping block verification worker because the channel was closed!",
+					);
+				};
+
+				spawner.spawn_essential_blocking(
+					"basic-block-verification-worker",
+					Some("block-import"),
+					future.boxed(),
+				);
+
+				sender
+			})
+			.collect();
+
+		Self { workers, next: 0 }
+	}
+
+	/// Schedule the pre-verification of the given block on the next worker.
+	///
+	/// Returns `None` if the worker is no longer running.
+	pub(crate) fn pre_verify(
+		&mut self,
+		block: BlockImportParams<B, ()>,
+	) -> Option<oneshot::Receiver<PreVerification<B>>> {
+		let (result, receiver) = oneshot::channel();
+		let worker = &self.workers[self.next];
+		self.next = (self.next + 1) % self.workers.len();
+
+		worker.unbounded_send(VerificationJob { block, result }).ok().map(|_| receiver)
+	}
+}
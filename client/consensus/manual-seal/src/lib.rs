@@ -21,12 +21,12 @@
 
 use futures::prelude::*;
 use prometheus_endpoint::Registry;
-use sc_client_api::backend::{Backend as ClientBackend, Finalizer};
+use sc_client_api::backend::{Backend as ClientBackend, Finalizer, LockImportRun};
 use sc_consensus::{
 	block_import::{BlockImport, BlockImportParams, ForkChoiceStrategy},
 	import_queue::{BasicQueue, BoxBlockImport, Verifier},
 };
-use sp_blockchain::HeaderBackend;
+use sp_blockchain::{HeaderBackend, HeaderMetadata};
 use sp_consensus::{CacheKeyId, Environment, Proposer, SelectChain};
 use sp_inherents::CreateInherentDataProviders;
 use sp_runtime::{traits::Block as BlockT, ConsensusEngineId};
@@ -35,6 +35,7 @@ use std::{marker::PhantomData, sync::Arc};
 mod error;
 mod finalize_block;
 mod seal_block;
+mod set_best_block;
 
 pub mod consensus;
 pub mod rpc;
@@ -43,8 +44,9 @@ pub use self::{
 	consensus::ConsensusDataProvider,
 	error::Error,
 	finalize_block::{finalize_block, FinalizeBlockParams},
-	rpc::{CreatedBlock, EngineCommand},
+	rpc::{create_fork, CreatedBlock, EngineCommand},
 	seal_block::{seal_block, SealBlockParams, MAX_PROPOSAL_DURATION},
+	set_best_block::{set_best_block, SetBestBlockParams},
 };
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::{ProvideRuntimeApi, TransactionFor};
@@ -152,7 +154,12 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 		+ Send
 		+ Sync
 		+ 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B>
+		+ HeaderMetadata<B, Error = sp_blockchain::Error>
+		+ Finalizer<B, CB>
+		+ LockImportRun<B, CB>
+		+ ProvideRuntimeApi<B>
+		+ 'static,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P, Transaction = TransactionFor<C, B>>,
@@ -192,6 +199,14 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 				})
 				.await
 			},
+			EngineCommand::SetBestBlock { hash, sender } =>
+				set_best_block(SetBestBlockParams {
+					hash,
+					sender,
+					client: client.clone(),
+					_phantom: PhantomData,
+				})
+				.await,
 		}
 	}
 }
@@ -215,7 +230,12 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		+ Send
 		+ Sync
 		+ 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B>
+		+ HeaderMetadata<B, Error = sp_blockchain::Error>
+		+ Finalizer<B, CB>
+		+ LockImportRun<B, CB>
+		+ ProvideRuntimeApi<B>
+		+ 'static,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P, Transaction = TransactionFor<C, B>>,
@@ -250,8 +270,9 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use codec::Encode;
 	use sc_basic_authorship::ProposerFactory;
-	use sc_client_api::BlockBackend;
+	use sc_client_api::{BlockBackend, BlockchainEvents};
 	use sc_consensus::ImportedAux;
 	use sc_transaction_pool::{BasicPool, Options, RevalidationType};
 	use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool, TransactionSource};
@@ -299,6 +320,40 @@ mod tests {
 		}
 	}
 
+	/// Pushes a unique post digest, so that blocks built on the same parent differ.
+	struct UniqueDigestProvider<C> {
+		_client: Arc<C>,
+		counter: std::sync::atomic::AtomicU64,
+	}
+	impl<B, C> ConsensusDataProvider<B> for UniqueDigestProvider<C>
+	where
+		B: BlockT,
+		C: ProvideRuntimeApi<B> + Send + Sync,
+	{
+		type Transaction = TransactionFor<C, B>;
+		type Proof = ();
+
+		fn create_digest(
+			&self,
+			_parent: &B::Header,
+			_inherents: &InherentData,
+		) -> Result<Digest, Error> {
+			Ok(Digest { logs: vec![] })
+		}
+
+		fn append_block_import(
+			&self,
+			_parent: &B::Header,
+			params: &mut BlockImportParams<B, Self::Transaction>,
+			_inherents: &InherentData,
+			_proof: Self::Proof,
+		) -> Result<(), Error> {
+			let id = self.counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+			params.post_digests.push(DigestItem::Other(id.encode()));
+			Ok(())
+		}
+	}
+
 	#[tokio::test]
 	async fn instant_seal() {
 		let builder = TestClientBuilder::new();
@@ -602,4 +657,167 @@ mod tests {
 		let header = client.header(&BlockId::Number(1)).unwrap().unwrap();
 		assert_eq!(header.hash(), created_block.hash);
 	}
+
+	#[tokio::test]
+	async fn manual_seal_fork_reorg_and_set_best_block() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(UniqueDigestProvider {
+				_client: client.clone(),
+				counter: Default::default(),
+			})),
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+		let mut notifications = client.import_notification_stream();
+		let genesis_hash = client.info().genesis_hash;
+
+		// build `a1` on top of genesis.
+		let a1 = create_fork(&mut sink, genesis_hash, 1, true).await.unwrap().remove(0);
+		let notification = notifications.next().await.unwrap();
+		assert_eq!(notification.hash, a1.hash);
+		assert!(notification.is_new_best);
+		assert!(notification.tree_route.is_none());
+
+		// build a longer fork `b1 -> b2` on top of genesis, which re-orgs `a1` out.
+		let fork = create_fork(&mut sink, genesis_hash, 2, true).await.unwrap();
+		assert_eq!(fork.len(), 2);
+		assert!(!fork[0].aux.is_new_best);
+		assert!(fork[1].aux.is_new_best);
+		assert_eq!(client.info().best_hash, fork[1].hash);
+
+		let notification = notifications.next().await.unwrap();
+		assert_eq!(notification.hash, fork[0].hash);
+		assert!(!notification.is_new_best);
+		let notification = notifications.next().await.unwrap();
+		assert_eq!(notification.hash, fork[1].hash);
+		let tree_route = notification.tree_route.as_ref().unwrap();
+		assert_eq!(
+			tree_route.retracted().iter().map(|b| b.hash).collect::<Vec<_>>(),
+			vec![a1.hash]
+		);
+		assert_eq!(
+			tree_route.enacted().iter().map(|b| b.hash).collect::<Vec<_>>(),
+			vec![fork[0].hash]
+		);
+
+		// switch back to the shorter chain.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SetBestBlock { hash: a1.hash, sender: Some(tx) })
+			.await
+			.unwrap();
+		rx.await.unwrap().unwrap();
+		assert_eq!(client.info().best_hash, a1.hash);
+
+		let notification = notifications.next().await.unwrap();
+		assert_eq!(notification.hash, a1.hash);
+		assert!(notification.is_new_best);
+		let tree_route = notification.tree_route.as_ref().unwrap();
+		assert_eq!(
+			tree_route.retracted().iter().map(|b| b.hash).collect::<Vec<_>>(),
+			vec![fork[1].hash, fork[0].hash]
+		);
+		assert_eq!(tree_route.enacted().iter().map(|b| b.hash).collect::<Vec<_>>(), vec![a1.hash]);
+
+		// new blocks are built on top of the selected best block.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlock {
+			parent_hash: None,
+			sender: Some(tx),
+			create_empty: true,
+			finalize: false,
+		})
+		.await
+		.unwrap();
+		let a2 = rx.await.unwrap().unwrap();
+		let header = client.header(&BlockId::Hash(a2.hash)).unwrap().unwrap();
+		assert_eq!(header.parent_hash, a1.hash);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_set_ancestor_as_best_block() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+		let mut notifications = client.import_notification_stream();
+
+		// build `#1 -> .. -> #5` on top of genesis.
+		let genesis_hash = client.info().genesis_hash;
+		let chain = create_fork(&mut sink, genesis_hash, 5, true).await.unwrap();
+		assert_eq!(client.info().best_number, 5);
+		for block in &chain {
+			assert_eq!(notifications.next().await.unwrap().hash, block.hash);
+		}
+
+		// set `#3` as the new best block.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SetBestBlock { hash: chain[2].hash, sender: Some(tx) })
+			.await
+			.unwrap();
+		rx.await.unwrap().unwrap();
+		assert_eq!(client.info().best_hash, chain[2].hash);
+		assert_eq!(client.info().best_number, 3);
+
+		let notification = notifications.next().await.unwrap();
+		assert_eq!(notification.hash, chain[2].hash);
+		assert!(notification.is_new_best);
+		let tree_route = notification.tree_route.as_ref().unwrap();
+		assert_eq!(
+			tree_route.retracted().iter().map(|b| b.hash).collect::<Vec<_>>(),
+			vec![chain[4].hash, chain[3].hash]
+		);
+		assert_eq!(tree_route.common_block().hash, chain[2].hash);
+		assert!(tree_route.enacted().is_empty());
+	}
 }
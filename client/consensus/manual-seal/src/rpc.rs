@@ -62,6 +62,14 @@ pub enum EngineCommand<Hash> {
 		/// finalization justification
 		justification: Option<EncodedJustification>,
 	},
+	/// Tells the engine to make the block with the supplied hash the new best block,
+	/// regardless of the longest chain rule.
+	SetBestBlock {
+		/// hash of the block
+		hash: Hash,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
 }
 
 /// RPC trait that provides methods for interacting with the manual-seal authorship task over rpc.
@@ -83,6 +91,20 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<EncodedJustification>,
 	) -> RpcResult<bool>;
+
+	/// Instructs the manual-seal authorship task to build a chain of `length` blocks on top of
+	/// `parent_hash`, returning the created blocks in order.
+	#[method(name = "engine_createFork")]
+	async fn create_fork(
+		&self,
+		parent_hash: Hash,
+		length: u32,
+		create_empty: bool,
+	) -> RpcResult<Vec<CreatedBlock<Hash>>>;
+
+	/// Instructs the manual-seal authorship task to make the given block the new best block
+	#[method(name = "engine_setBestBlock")]
+	async fn set_best_block(&self, hash: Hash) -> RpcResult<bool>;
 }
 
 /// A struct that implements the [`ManualSealApiServer`].
@@ -107,7 +129,7 @@ impl<Hash> ManualSeal<Hash> {
 }
 
 #[async_trait]
-impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
+impl<Hash: Clone + Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 	async fn create_block(
 		&self,
 		create_empty: bool,
@@ -144,6 +166,64 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		sink.send(command).await?;
 		receiver.await.map(|_| true).map_err(|e| JsonRpseeError::to_call_error(e))
 	}
+
+	async fn create_fork(
+		&self,
+		parent_hash: Hash,
+		length: u32,
+		create_empty: bool,
+	) -> RpcResult<Vec<CreatedBlock<Hash>>> {
+		let mut sink = self.import_block_channel.clone();
+		create_fork(&mut sink, parent_hash, length, create_empty)
+			.await
+			.map_err(Into::into)
+	}
+
+	async fn set_best_block(&self, hash: Hash) -> RpcResult<bool> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		let command = EngineCommand::SetBestBlock { hash, sender: Some(sender) };
+		sink.send(command).await?;
+
+		match receiver.await {
+			Ok(Ok(())) => Ok(true),
+			Ok(Err(e)) => Err(e.into()),
+			Err(e) => Err(JsonRpseeError::to_call_error(e)),
+		}
+	}
+}
+
+/// Builds a chain of `length` blocks on top of `parent_hash` by sending consecutive
+/// [`EngineCommand::SealNewBlock`] commands to the authorship task.
+///
+/// The blocks are imported with the usual fork choice rule, so the fork only becomes the best
+/// chain once it is longer than the current one. Use [`EngineCommand::SetBestBlock`] to switch to
+/// it earlier.
+pub async fn create_fork<Hash: Clone>(
+	sink: &mut mpsc::Sender<EngineCommand<Hash>>,
+	parent_hash: Hash,
+	length: u32,
+	create_empty: bool,
+) -> Result<Vec<CreatedBlock<Hash>>, Error> {
+	let mut created = Vec::with_capacity(length as usize);
+	let mut parent_hash = parent_hash;
+
+	for _ in 0..length {
+		let (sender, receiver) = oneshot::channel();
+		sink.send(EngineCommand::SealNewBlock {
+			create_empty,
+			finalize: false,
+			parent_hash: Some(parent_hash),
+			sender: Some(sender),
+		})
+		.await?;
+
+		let block = receiver.await??;
+		parent_hash = block.hash.clone();
+		created.push(block);
+	}
+
+	Ok(created)
 }
 
 /// report any errors or successes encountered by the authorship task back
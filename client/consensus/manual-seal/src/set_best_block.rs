@@ -0,0 +1,87 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Best block selection utilities
+
+use crate::{rpc, Error};
+use sc_client_api::backend::{Backend as ClientBackend, ImportSummary, LockImportRun};
+use sp_blockchain::{HeaderBackend, HeaderMetadata};
+use sp_consensus::BlockOrigin;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::{marker::PhantomData, sync::Arc};
+
+/// params for setting the best block.
+pub struct SetBestBlockParams<B: BlockT, C, CB> {
+	/// hash of the block that should become the new best block
+	pub hash: <B as BlockT>::Hash,
+	/// sender to report errors/success to the rpc.
+	pub sender: rpc::Sender<()>,
+	/// Client instance.
+	pub client: Arc<C>,
+	/// phantom type to pin the Backend type
+	pub _phantom: PhantomData<CB>,
+}
+
+/// Marks the given block as the new best block, overriding the longest chain rule.
+///
+/// An import notification is emitted for the new best block, carrying the tree route from the
+/// old best block to the new one, so that listeners can retract the abandoned blocks.
+pub async fn set_best_block<B, C, CB>(params: SetBestBlockParams<B, C, CB>)
+where
+	B: BlockT,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = sp_blockchain::Error> + LockImportRun<B, CB>,
+	CB: ClientBackend<B>,
+{
+	let SetBestBlockParams { hash, mut sender, client, .. } = params;
+
+	let result = client.lock_import_and_run(|operation| {
+		let header = client
+			.header(BlockId::Hash(hash))?
+			.ok_or_else(|| Error::BlockNotFound(format!("{}", hash)))?;
+		let best_hash = client.info().best_hash;
+
+		if best_hash == hash {
+			return Ok(())
+		}
+
+		let tree_route = sp_blockchain::tree_route(&*client, best_hash, hash)?;
+
+		operation.op.mark_head(BlockId::Hash(hash))?;
+		operation.notify_imported = Some(ImportSummary {
+			hash,
+			origin: BlockOrigin::Own,
+			header,
+			is_new_best: true,
+			storage_changes: None,
+			tree_route: Some(tree_route),
+		});
+
+		Ok::<_, Error>(())
+	});
+
+	match result {
+		Err(e) => {
+			log::warn!("Failed to set best block {}", e);
+			rpc::send_result(&mut sender, Err(e))
+		},
+		Ok(()) => {
+			log::info!("✅ Successfully set best block: {}", hash);
+			rpc::send_result(&mut sender, Ok(()))
+		},
+	}
+}
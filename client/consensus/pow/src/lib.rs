@@ -29,7 +29,9 @@
 //! the worker handle, you can pull the metadata needed to start the
 //! mining process via [`MiningHandle::metadata`], and then do the actual
 //! mining on a standalone thread. Finally, when a seal is found, call
-//! [`MiningHandle::submit`] to build the block.
+//! [`MiningHandle::submit`] to build the block. For algorithms implementing
+//! [`CpuMiningAlgorithm`], the [`CpuMiner`] provides a reference
+//! multi-threaded mining loop on top of the worker handle.
 //!
 //! The auxiliary storage for PoW engine only stores the total difficulty.
 //! For other storage requirements for particular PoW algorithm (such as
@@ -39,9 +41,13 @@
 //! as the storage, but it is not recommended as it won't work well with light
 //! clients.
 
+mod miner;
 mod worker;
 
-pub use crate::worker::{MiningBuild, MiningHandle, MiningMetadata};
+pub use crate::{
+	miner::{CpuMiner, CpuMinerConfig, CpuMiningAlgorithm},
+	worker::{MiningBuild, MiningHandle, MiningMetadata},
+};
 
 use crate::worker::UntilImportedOrTimeout;
 use codec::{Decode, Encode};
@@ -51,7 +57,7 @@ use prometheus_endpoint::Registry;
 use sc_client_api::{self, backend::AuxStore, BlockOf, BlockchainEvents};
 use sc_consensus::{
 	BasicQueue, BlockCheckParams, BlockImport, BlockImportParams, BoxBlockImport,
-	BoxJustificationImport, ForkChoiceStrategy, ImportResult, Verifier,
+	BoxJustificationImport, ForkChoiceStrategy, ImportResult, PreVerifier, Verifier,
 };
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
@@ -68,8 +74,8 @@ use sp_runtime::{
 	RuntimeString,
 };
 use std::{
-	borrow::Cow, cmp::Ordering, collections::HashMap, marker::PhantomData, sync::Arc,
-	time::Duration,
+	borrow::Cow, cmp::Ordering, collections::HashMap, marker::PhantomData, num::NonZeroUsize,
+	sync::Arc, time::Duration,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -457,15 +463,15 @@ impl<B: BlockT, Algorithm> PowVerifier<B, Algorithm> {
 }
 
 #[async_trait::async_trait]
-impl<B: BlockT, Algorithm> Verifier<B> for PowVerifier<B, Algorithm>
+impl<B: BlockT, Algorithm> PreVerifier<B> for PowVerifier<B, Algorithm>
 where
 	Algorithm: PowAlgorithm<B> + Send + Sync,
 	Algorithm::Difficulty: 'static + Send,
 {
-	async fn verify(
-		&mut self,
+	async fn pre_verify(
+		&self,
 		mut block: BlockImportParams<B, ()>,
-	) -> Result<(BlockImportParams<B, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
+	) -> Result<BlockImportParams<B, ()>, String> {
 		let hash = block.header.hash();
 		let (checked_header, seal) = self.check_header(block.header)?;
 
@@ -477,7 +483,26 @@ where
 			.insert(Cow::from(INTERMEDIATE_KEY), Box::new(intermediate) as Box<_>);
 		block.post_hash = Some(hash);
 
-		Ok((block, None))
+		Ok(block)
+	}
+}
+
+#[async_trait::async_trait]
+impl<B: BlockT, Algorithm> Verifier<B> for PowVerifier<B, Algorithm>
+where
+	Algorithm: PowAlgorithm<B> + Send + Sync,
+	Algorithm::Difficulty: 'static + Send,
+{
+	async fn verify(
+		&mut self,
+		block: BlockImportParams<B, ()>,
+	) -> Result<(BlockImportParams<B, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
+		// The seal of pre-verified blocks is already checked and moved to the post digests.
+		if block.intermediates.contains_key(INTERMEDIATE_KEY) {
+			return Ok((block, None))
+		}
+
+		Ok((self.pre_verify(block).await?, None))
 	}
 }
 
@@ -503,6 +528,33 @@ where
 	Ok(BasicQueue::new(verifier, block_import, justification_import, spawner, registry))
 }
 
+/// Import queue for PoW engine, checking the seals of the blocks ahead of their import on
+/// `verification_workers` background tasks.
+pub fn import_queue_with_verification_workers<B, Transaction, Algorithm>(
+	block_import: BoxBlockImport<B, Transaction>,
+	justification_import: Option<BoxJustificationImport<B>>,
+	algorithm: Algorithm,
+	verification_workers: NonZeroUsize,
+	spawner: &impl sp_core::traits::SpawnEssentialNamed,
+	registry: Option<&Registry>,
+) -> Result<PowImportQueue<B, Transaction>, sp_consensus::Error>
+where
+	B: BlockT,
+	Transaction: Send + Sync + 'static,
+	Algorithm: PowAlgorithm<B> + Clone + Send + Sync + 'static,
+	Algorithm::Difficulty: Send,
+{
+	Ok(BasicQueue::with_verification_workers(
+		PowVerifier::new(algorithm.clone()),
+		PowVerifier::new(algorithm),
+		block_import,
+		justification_import,
+		verification_workers,
+		spawner,
+		registry,
+	))
+}
+
 /// Start the mining worker for PoW. This function provides the necessary helper functions that can
 /// be used to implement a miner. However, it does not do the CPU-intensive mining itself.
 ///
@@ -713,3 +765,159 @@ fn fetch_seal<B: BlockT>(digest: Option<&DigestItem>, hash: B::Hash) -> Result<V
 		_ => Err(Error::<B>::HeaderUnsealed(hash)),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+	use parking_lot::Mutex;
+	use sc_consensus::{BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link};
+	use sp_consensus::BlockOrigin;
+	use sp_core::{testing::TaskExecutor, H256, U256};
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, Header};
+	use std::task::Poll;
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	const VALID_SEAL: &[u8] = b"valid";
+
+	/// Seals are valid if they equal `VALID_SEAL`, checked without the parent.
+	#[derive(Clone)]
+	struct TestAlgorithm;
+
+	impl PowAlgorithm<Block> for TestAlgorithm {
+		type Difficulty = U256;
+
+		fn difficulty(&self, _parent: H256) -> Result<U256, Error<Block>> {
+			Ok(U256::one())
+		}
+
+		fn preliminary_verify(
+			&self,
+			_pre_hash: &H256,
+			seal: &Seal,
+		) -> Result<Option<bool>, Error<Block>> {
+			Ok(Some(seal == VALID_SEAL))
+		}
+
+		fn verify(
+			&self,
+			_parent: &BlockId<Block>,
+			_pre_hash: &H256,
+			_pre_digest: Option<&[u8]>,
+			seal: &Seal,
+			_difficulty: U256,
+		) -> Result<bool, Error<Block>> {
+			Ok(seal == VALID_SEAL)
+		}
+	}
+
+	/// Records the post hash of every imported block.
+	struct RecordingImport(Arc<Mutex<Vec<H256>>>);
+
+	#[async_trait::async_trait]
+	impl BlockImport<Block> for RecordingImport {
+		type Error = ConsensusError;
+		type Transaction = ();
+
+		async fn check_block(
+			&mut self,
+			_block: BlockCheckParams<Block>,
+		) -> Result<ImportResult, Self::Error> {
+			Ok(ImportResult::imported(false))
+		}
+
+		async fn import_block(
+			&mut self,
+			block: BlockImportParams<Block, Self::Transaction>,
+			_cache: HashMap<CacheKeyId, Vec<u8>>,
+		) -> Result<ImportResult, Self::Error> {
+			self.0.lock().push(block.post_hash());
+			Ok(ImportResult::imported(true))
+		}
+	}
+
+	#[derive(Default)]
+	struct TestLink {
+		results: Vec<(Result<BlockImportStatus<u64>, BlockImportError>, H256)>,
+	}
+
+	impl Link<Block> for TestLink {
+		fn blocks_processed(
+			&mut self,
+			_imported: usize,
+			_count: usize,
+			results: Vec<(Result<BlockImportStatus<u64>, BlockImportError>, H256)>,
+		) {
+			self.results.extend(results);
+		}
+	}
+
+	fn sealed_block(number: u64, parent_hash: H256, seal: &[u8]) -> IncomingBlock<Block> {
+		let mut header = Header {
+			parent_hash,
+			number,
+			state_root: Default::default(),
+			extrinsics_root: Default::default(),
+			digest: Default::default(),
+		};
+		header.digest.push(DigestItem::Seal(POW_ENGINE_ID, seal.to_vec()));
+
+		IncomingBlock {
+			hash: header.hash(),
+			header: Some(header),
+			body: None,
+			indexed_body: None,
+			justifications: None,
+			origin: None,
+			allow_missing_state: false,
+			import_existing: false,
+			state: None,
+			skip_execution: false,
+		}
+	}
+
+	#[test]
+	fn verification_workers_check_pow_seals() {
+		let imported = Arc::new(Mutex::new(Vec::new()));
+		let mut queue = import_queue_with_verification_workers(
+			Box::new(RecordingImport(imported.clone())),
+			None,
+			TestAlgorithm,
+			NonZeroUsize::new(4).expect("4 is not zero; qed"),
+			&TaskExecutor::new(),
+			None,
+		)
+		.unwrap();
+
+		let mut parent_hash = H256::repeat_byte(1);
+		let blocks = (1..=6)
+			.map(|number| {
+				let seal = if number == 5 { &b"invalid"[..] } else { VALID_SEAL };
+				let block = sealed_block(number, parent_hash, seal);
+				parent_hash = block.hash;
+				block
+			})
+			.collect::<Vec<_>>();
+		let hashes = blocks.iter().map(|b| b.hash).collect::<Vec<_>>();
+
+		queue.import_blocks(BlockOrigin::NetworkInitialSync, blocks);
+
+		let mut link = TestLink::default();
+		block_on(futures::future::poll_fn(|cx| {
+			queue.poll_actions(cx, &mut link);
+
+			if link.results.len() < hashes.len() {
+				Poll::Pending
+			} else {
+				Poll::Ready(())
+			}
+		}));
+
+		assert_eq!(link.results.iter().map(|(_, hash)| *hash).collect::<Vec<_>>(), hashes);
+		assert!(link.results[..4].iter().all(|(result, _)| result.is_ok()));
+		assert!(matches!(link.results[4].0, Err(BlockImportError::VerificationFailed(..))));
+		assert!(matches!(link.results[5].0, Err(BlockImportError::Cancelled)));
+		assert_eq!(*imported.lock(), hashes[..4]);
+	}
+}
@@ -0,0 +1,355 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A reference multi-threaded CPU miner.
+//!
+//! The miner spawns a number of threads that poll the [`MiningHandle`] for the current mining
+//! metadata and try nonces until a valid seal is found, which is then submitted through
+//! [`MiningHandle::submit`]. The nonce space is partitioned between the threads: thread `i` of
+//! `n` only tries the nonces `i`, `i + n`, `i + 2n`, ...
+
+use log::*;
+use prometheus_endpoint::{register, Counter, GaugeVec, Opts, PrometheusError, Registry, U64};
+use sp_consensus_pow::Seal;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	num::NonZeroUsize,
+	sync::{
+		atomic::{AtomicBool, AtomicU64, Ordering},
+		Arc,
+	},
+	thread::JoinHandle,
+	time::{Duration, Instant},
+};
+
+use crate::{Error, MiningHandle, MiningMetadata, PowAlgorithm};
+
+/// How long a mining thread sleeps when there is nothing to mine.
+const IDLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A [`PowAlgorithm`] that can be mined by trying nonces.
+pub trait CpuMiningAlgorithm<B: BlockT>: PowAlgorithm<B> {
+	/// Try to seal the block described by `metadata` using the given `nonce`.
+	///
+	/// Returns the seal if it satisfies the difficulty of `metadata`, `None` otherwise.
+	fn try_nonce(
+		&self,
+		metadata: &MiningMetadata<B::Hash, Self::Difficulty>,
+		nonce: u64,
+	) -> Result<Option<Seal>, Error<B>>;
+}
+
+/// Configuration of the [`CpuMiner`].
+#[derive(Clone, Debug)]
+pub struct CpuMinerConfig {
+	/// Number of mining threads.
+	pub threads: NonZeroUsize,
+	/// Number of nonces each thread tries before checking whether the mining metadata changed.
+	pub nonces_per_round: u64,
+}
+
+impl Default for CpuMinerConfig {
+	fn default() -> Self {
+		Self {
+			threads: std::thread::available_parallelism()
+				.unwrap_or_else(|_| NonZeroUsize::new(1).expect("1 is not zero; qed")),
+			nonces_per_round: 10_000,
+		}
+	}
+}
+
+/// Prometheus metrics of the [`CpuMiner`].
+#[derive(Clone)]
+struct Metrics {
+	hashes: Counter<U64>,
+	hash_rate: GaugeVec<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			hashes: register(
+				Counter::new("substrate_pow_miner_hashes_total", "Total number of hashes tried")?,
+				registry,
+			)?,
+			hash_rate: register(
+				GaugeVec::new(
+					Opts::new(
+						"substrate_pow_miner_hash_rate",
+						"Hashes per second of the last mining round",
+					),
+					&["thread"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Multi-threaded CPU miner.
+///
+/// Mining stops when the miner is dropped.
+pub struct CpuMiner {
+	stop: Arc<AtomicBool>,
+	hashes: Arc<AtomicU64>,
+	threads: Vec<JoinHandle<()>>,
+}
+
+impl CpuMiner {
+	/// Start mining on the builds provided by `handle`.
+	pub fn start<Block, Algorithm, C, L, Proof>(
+		handle: MiningHandle<Block, Algorithm, C, L, Proof>,
+		algorithm: Algorithm,
+		config: CpuMinerConfig,
+		registry: Option<&Registry>,
+	) -> std::io::Result<Self>
+	where
+		Block: BlockT,
+		Algorithm: CpuMiningAlgorithm<Block> + Clone + Send + 'static,
+		Algorithm::Difficulty: Send + 'static,
+		C: sp_api::ProvideRuntimeApi<Block>,
+		L: sc_consensus::JustificationSyncLink<Block>,
+		sp_api::TransactionFor<C, Block>: Send + 'static,
+		MiningHandle<Block, Algorithm, C, L, Proof>: Send + 'static,
+	{
+		let metrics = registry.and_then(|r| {
+			Metrics::register(r)
+				.map_err(
+					|err| warn!(target: "pow", "Failed to register Prometheus metrics: {}", err),
+				)
+				.ok()
+		});
+
+		let stop = Arc::new(AtomicBool::new(false));
+		let hashes = Arc::new(AtomicU64::new(0));
+		let thread_count = config.threads.get();
+
+		let threads = (0..thread_count)
+			.map(|index| {
+				let handle = handle.clone();
+				let algorithm = algorithm.clone();
+				let stop = stop.clone();
+				let hashes = hashes.clone();
+				let metrics = metrics.clone();
+				let nonces_per_round = config.nonces_per_round.max(1);
+
+				std::thread::Builder::new().name(format!("pow-miner-{}", index)).spawn(move || {
+					let mut current = None;
+					let thread_label = index.to_string();
+
+					while !stop.load(Ordering::Relaxed) {
+						let version = handle.version();
+						let metadata = match handle.metadata() {
+							Some(metadata) => metadata,
+							None => {
+								std::thread::sleep(IDLE_INTERVAL);
+								continue
+							},
+						};
+
+						let start_nonce = match current {
+							Some((v, nonce)) if v == version => nonce,
+							_ => index as u64,
+						};
+
+						let started = Instant::now();
+						let round = mine_round::<Block, _>(
+							&algorithm,
+							&metadata,
+							start_nonce,
+							thread_count as u64,
+							nonces_per_round,
+						);
+
+						hashes.fetch_add(round.tried, Ordering::Relaxed);
+						if let Some(metrics) = metrics.as_ref() {
+							metrics.hashes.inc_by(round.tried);
+							let elapsed = started.elapsed().as_secs_f64();
+							if elapsed > 0.0 {
+								metrics
+									.hash_rate
+									.with_label_values(&[thread_label.as_str()])
+									.set((round.tried as f64 / elapsed) as u64);
+							}
+						}
+
+						current = Some((version, round.next_nonce));
+
+						match round.result {
+							Ok(Some(seal)) =>
+								if !futures::executor::block_on(handle.submit(seal)) {
+									debug!(
+										target: "pow",
+										"Mining thread {} failed to submit seal",
+										index,
+									);
+								},
+							Ok(None) => {},
+							Err(err) => {
+								warn!(target: "pow", "Mining thread {} failed: {}", index, err);
+								std::thread::sleep(IDLE_INTERVAL);
+							},
+						}
+					}
+				})
+			})
+			.collect::<std::io::Result<Vec<_>>>()?;
+
+		Ok(Self { stop, hashes, threads })
+	}
+
+	/// Total number of hashes tried by all mining threads.
+	pub fn hashes(&self) -> u64 {
+		self.hashes.load(Ordering::Relaxed)
+	}
+
+	/// Stop mining and wait for all mining threads to finish.
+	pub fn stop(mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+		for thread in self.threads.drain(..) {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl Drop for CpuMiner {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+/// Outcome of a single mining round.
+struct MiningRound<B: BlockT> {
+	/// The seal that was found, if any.
+	result: Result<Option<Seal>, Error<B>>,
+	/// The number of nonces tried.
+	tried: u64,
+	/// The nonce to continue with in the next round.
+	next_nonce: u64,
+}
+
+/// Try `count` nonces, starting at `start` and advancing by `step`.
+fn mine_round<B: BlockT, Algorithm: CpuMiningAlgorithm<B>>(
+	algorithm: &Algorithm,
+	metadata: &MiningMetadata<B::Hash, Algorithm::Difficulty>,
+	start: u64,
+	step: u64,
+	count: u64,
+) -> MiningRound<B> {
+	let mut nonce = start;
+
+	for tried in 1..=count {
+		let result = algorithm.try_nonce(metadata, nonce);
+		nonce = nonce.wrapping_add(step);
+
+		match result {
+			Ok(None) => {},
+			result => return MiningRound { result, tried, next_nonce: nonce },
+		}
+	}
+
+	MiningRound { result: Ok(None), tried: count, next_nonce: nonce }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use parking_lot::Mutex;
+	use sp_core::{hashing::blake2_256, H256, U256};
+	use sp_runtime::{
+		generic::BlockId,
+		testing::{Block as RawBlock, ExtrinsicWrapper},
+	};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	/// Seals are valid if `blake2(pre_hash ++ nonce) * difficulty` doesn't overflow.
+	#[derive(Default)]
+	struct TestAlgorithm {
+		tried: Mutex<Vec<u64>>,
+	}
+
+	fn seal_is_valid(pre_hash: &H256, seal: &Seal, difficulty: U256) -> bool {
+		let hash = blake2_256(&(pre_hash, seal).encode());
+		!U256::from(&hash[..]).overflowing_mul(difficulty).1
+	}
+
+	impl PowAlgorithm<Block> for TestAlgorithm {
+		type Difficulty = U256;
+
+		fn difficulty(&self, _parent: H256) -> Result<U256, Error<Block>> {
+			Ok(U256::one())
+		}
+
+		fn verify(
+			&self,
+			_parent: &BlockId<Block>,
+			pre_hash: &H256,
+			_pre_digest: Option<&[u8]>,
+			seal: &Seal,
+			difficulty: U256,
+		) -> Result<bool, Error<Block>> {
+			Ok(seal_is_valid(pre_hash, seal, difficulty))
+		}
+	}
+
+	impl CpuMiningAlgorithm<Block> for TestAlgorithm {
+		fn try_nonce(
+			&self,
+			metadata: &MiningMetadata<H256, U256>,
+			nonce: u64,
+		) -> Result<Option<Seal>, Error<Block>> {
+			self.tried.lock().push(nonce);
+			let seal = nonce.encode();
+			Ok(seal_is_valid(&metadata.pre_hash, &seal, metadata.difficulty).then(|| seal))
+		}
+	}
+
+	fn metadata(difficulty: U256) -> MiningMetadata<H256, U256> {
+		MiningMetadata {
+			best_hash: H256::repeat_byte(1),
+			pre_hash: H256::repeat_byte(2),
+			pre_runtime: None,
+			difficulty,
+		}
+	}
+
+	#[test]
+	fn mine_round_partitions_nonces() {
+		let algorithm = TestAlgorithm::default();
+		let round = mine_round::<Block, _>(&algorithm, &metadata(U256::MAX), 2, 4, 5);
+
+		assert!(matches!(round.result, Ok(None)));
+		assert_eq!(round.tried, 5);
+		assert_eq!(round.next_nonce, 22);
+		assert_eq!(*algorithm.tried.lock(), vec![2, 6, 10, 14, 18]);
+	}
+
+	#[test]
+	fn mine_round_stops_at_valid_seal() {
+		let algorithm = TestAlgorithm::default();
+		let difficulty = U256::from(1_000);
+		let round = mine_round::<Block, _>(&algorithm, &metadata(difficulty), 0, 1, 1_000_000);
+
+		let seal = round.result.unwrap().expect("a seal is found well within a million tries");
+		assert!(seal_is_valid(&H256::repeat_byte(2), &seal, difficulty));
+		assert_eq!(round.tried as usize, algorithm.tried.lock().len());
+		assert_eq!(round.next_nonce, round.tried);
+	}
+}
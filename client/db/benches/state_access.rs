@@ -19,7 +19,10 @@
 use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
 use rand::{distributions::Uniform, rngs::StdRng, Rng, SeedableRng};
 use sc_client_api::{Backend as _, BlockImportOperation, NewBlockState, StateBackend};
-use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode};
+use sc_client_db::{
+	Backend, BlocksPruning, DatabaseSettings, DatabaseSource, OffchainIndexPruning, PruningMode,
+	DEFAULT_MAX_PINNED_BLOCKS,
+};
 use sp_core::H256;
 use sp_runtime::{
 	generic::BlockId,
@@ -123,6 +126,8 @@ fn create_backend(config: BenchmarkConfig, temp_dir: &TempDir) -> Backend<Block>
 		state_pruning: Some(PruningMode::ArchiveAll),
 		source: DatabaseSource::ParityDb { path },
 		blocks_pruning: BlocksPruning::All,
+		offchain_index_pruning: OffchainIndexPruning::All,
+		max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
 	};
 
 	Backend::new(settings, 100).expect("Creates backend")
@@ -34,6 +34,7 @@ pub mod bench;
 
 mod children;
 mod parity_db;
+mod pinned_blocks_cache;
 mod record_stats_state;
 mod stats;
 #[cfg(any(feature = "rocksdb", test))]
@@ -51,6 +52,7 @@ use std::{
 };
 
 use crate::{
+	pinned_blocks_cache::PinnedBlocksCache,
 	record_stats_state::RecordStatsState,
 	stats::StateUsageStats,
 	utils::{meta_keys, read_db, read_meta, DatabaseType, Meta},
@@ -58,7 +60,7 @@ use crate::{
 use codec::{Decode, Encode};
 use hash_db::Prefix;
 use sc_client_api::{
-	backend::NewBlockState,
+	backend::{NewBlockState, OffchainIndexJournal},
 	leaves::{FinalizationOutcome, LeafSet},
 	utils::is_descendent_of,
 	IoInfo, MemoryInfo, MemorySize, UsageInfo,
@@ -90,6 +92,7 @@ use sp_state_machine::{
 use sp_trie::{cache::SharedTrieCache, prefixed_key, MemoryDB, PrefixedMemoryDB};
 
 // Re-export the Database trait so that one can pass an implementation of it.
+pub use pinned_blocks_cache::DEFAULT_MAX_PINNED_BLOCKS;
 pub use sc_state_db::PruningMode;
 pub use sp_database::Database;
 
@@ -317,6 +320,12 @@ pub struct DatabaseSettings {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Retention policy of the data written through offchain indexing.
+	pub offchain_index_pruning: OffchainIndexPruning,
+	/// Maximum number of distinct blocks that can be pinned at the same time.
+	///
+	/// See [`DEFAULT_MAX_PINNED_BLOCKS`] for the default.
+	pub max_pinned_blocks: usize,
 }
 
 /// Block pruning settings.
@@ -328,6 +337,30 @@ pub enum BlocksPruning {
 	Some(u32),
 }
 
+/// Offchain indexing pruning settings.
+///
+/// Pruning a block reverts its changes, unless a later block changed the same keys. Changes of
+/// blocks of displaced forks are reverted to the previous value, while changes of canonical blocks
+/// are removed.
+///
+/// The changes of every block are journaled, to be queried by block, and are discarded together
+/// with the block's data. They are kept forever with [`Self::All`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffchainIndexPruning {
+	/// Keep all the data written through offchain indexing.
+	All,
+	/// Discard the data written by blocks of forks that were displaced by finality.
+	Canonical,
+	/// Keep the data written by the N recent finalized blocks and discard displaced forks.
+	Some(u32),
+}
+
+impl Default for OffchainIndexPruning {
+	fn default() -> Self {
+		Self::All
+	}
+}
+
 /// Where to find the database..
 #[derive(Debug, Clone)]
 pub enum DatabaseSource {
@@ -479,10 +512,11 @@ pub struct BlockchainDb<Block: BlockT> {
 	leaves: RwLock<LeafSet<Block::Hash, NumberFor<Block>>>,
 	header_metadata_cache: Arc<HeaderMetadataCache<Block>>,
 	header_cache: Mutex<LinkedHashMap<Block::Hash, Option<Block::Header>>>,
+	pinned_blocks_cache: RwLock<PinnedBlocksCache<Block>>,
 }
 
 impl<Block: BlockT> BlockchainDb<Block> {
-	fn new(db: Arc<dyn Database<DbHash>>) -> ClientResult<Self> {
+	fn new(db: Arc<dyn Database<DbHash>>, max_pinned_blocks: usize) -> ClientResult<Self> {
 		let meta = read_meta::<Block>(&*db, columns::HEADER)?;
 		let leaves = LeafSet::read_from_db(&*db, columns::META, meta_keys::LEAF_PREFIX)?;
 		Ok(BlockchainDb {
@@ -491,9 +525,29 @@ impl<Block: BlockT> BlockchainDb<Block> {
 			meta: Arc::new(RwLock::new(meta)),
 			header_metadata_cache: Arc::new(HeaderMetadataCache::default()),
 			header_cache: Default::default(),
+			pinned_blocks_cache: RwLock::new(PinnedBlocksCache::new(max_pinned_blocks)),
 		})
 	}
 
+	/// Returns the body of a pinned block that was pruned from the database.
+	fn pinned_body(
+		&self,
+		id: BlockId<Block>,
+	) -> ClientResult<Option<Option<Vec<Block::Extrinsic>>>> {
+		if self.pinned_blocks_cache.read().is_empty() {
+			return Ok(None)
+		}
+
+		let hash = match id {
+			BlockId::Hash(hash) => hash,
+			BlockId::Number(number) => match self.hash(number)? {
+				Some(hash) => hash,
+				None => return Ok(None),
+			},
+		};
+		Ok(self.pinned_blocks_cache.read().body(&hash))
+	}
+
 	fn update_meta(&self, update: MetaUpdate<Block>) {
 		let MetaUpdate { hash, number, is_best, is_finalized, with_state } = update;
 		let mut meta = self.meta.write();
@@ -576,6 +630,10 @@ impl<Block: BlockT> sc_client_api::blockchain::HeaderBackend<Block> for Blockcha
 
 impl<Block: BlockT> sc_client_api::blockchain::Backend<Block> for BlockchainDb<Block> {
 	fn body(&self, id: BlockId<Block>) -> ClientResult<Option<Vec<Block::Extrinsic>>> {
+		if let Some(body) = self.pinned_body(id)? {
+			return Ok(body)
+		}
+
 		if let Some(body) = read_db(&*self.db, columns::KEY_LOOKUP, columns::BODY, id)? {
 			// Plain body
 			match Decode::decode(&mut &body[..]) {
@@ -761,21 +819,32 @@ pub struct BlockImportOperation<Block: BlockT> {
 }
 
 impl<Block: BlockT> BlockImportOperation<Block> {
-	fn apply_offchain(&mut self, transaction: &mut Transaction<DbHash>) {
-		let mut count = 0;
+	/// Applies the offchain indexing changes and returns them in the journal format.
+	fn apply_offchain(&mut self, transaction: &mut Transaction<DbHash>) -> OffchainIndexJournal {
+		let mut journal = OffchainIndexJournal::with_capacity(self.offchain_storage_updates.len());
 		for ((prefix, key), value_operation) in self.offchain_storage_updates.drain(..) {
-			count += 1;
-			let key = crate::offchain::concatenate_prefix_and_key(&prefix, &key);
-			match value_operation {
-				OffchainOverlayedChange::SetValue(val) =>
-					transaction.set_from_vec(columns::OFFCHAIN, &key, val),
-				OffchainOverlayedChange::Remove => transaction.remove(columns::OFFCHAIN, &key),
-			}
+			let db_key = crate::offchain::concatenate_prefix_and_key(&prefix, &key);
+			let value = match value_operation {
+				OffchainOverlayedChange::SetValue(val) => {
+					transaction.set(columns::OFFCHAIN, &db_key, &val);
+					Some(val)
+				},
+				OffchainOverlayedChange::Remove => {
+					transaction.remove(columns::OFFCHAIN, &db_key);
+					None
+				},
+			};
+			journal.push(((prefix, key), value));
 		}
 
-		if count > 0 {
-			log::debug!(target: "sc_offchain", "Applied {} offchain indexing changes.", count);
+		if !journal.is_empty() {
+			log::debug!(
+				target: "sc_offchain",
+				"Applied {} offchain indexing changes.",
+				journal.len(),
+			);
 		}
+		journal
 	}
 
 	fn apply_aux(&mut self, transaction: &mut Transaction<DbHash>) {
@@ -1029,6 +1098,7 @@ pub struct Backend<Block: BlockT> {
 	import_lock: Arc<RwLock<()>>,
 	is_archive: bool,
 	blocks_pruning: BlocksPruning,
+	offchain_index_pruning: OffchainIndexPruning,
 	io_stats: FrozenForDuration<(kvdb::IoStats, StateUsageInfo)>,
 	state_usage: Arc<StateUsageStats>,
 	genesis_state: RwLock<Option<Arc<DbGenesisStorage<Block>>>>,
@@ -1074,6 +1144,8 @@ impl<Block: BlockT> Backend<Block> {
 			state_pruning: Some(PruningMode::blocks_pruning(blocks_pruning)),
 			source: DatabaseSource::Custom { db, require_create_flag: true },
 			blocks_pruning: BlocksPruning::Some(blocks_pruning),
+			offchain_index_pruning: OffchainIndexPruning::All,
+			max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
@@ -1120,7 +1192,7 @@ impl<Block: BlockT> Backend<Block> {
 
 		let state_pruning_used = state_db.pruning_mode();
 		let is_archive_pruning = state_pruning_used.is_archive();
-		let blockchain = BlockchainDb::new(db.clone())?;
+		let blockchain = BlockchainDb::new(db.clone(), config.max_pinned_blocks)?;
 
 		let storage_db =
 			StorageDb { db: db.clone(), state_db, prefix_keys: !db.supports_ref_counting() };
@@ -1137,6 +1209,7 @@ impl<Block: BlockT> Backend<Block> {
 			io_stats: FrozenForDuration::new(std::time::Duration::from_secs(1)),
 			state_usage: Arc::new(StateUsageStats::new()),
 			blocks_pruning: config.blocks_pruning,
+			offchain_index_pruning: config.offchain_index_pruning,
 			genesis_state: RwLock::new(None),
 			shared_trie_cache: config.trie_cache_maximum_size.map(|maximum_size| {
 				SharedTrieCache::new(sp_trie::cache::CacheSize::Maximum(maximum_size))
@@ -1333,7 +1406,7 @@ impl<Block: BlockT> Backend<Block> {
 		let mut finalization_displaced_leaves = None;
 
 		operation.apply_aux(&mut transaction);
-		operation.apply_offchain(&mut transaction);
+		let offchain_index_journal = operation.apply_offchain(&mut transaction);
 
 		let mut meta_updates = Vec::with_capacity(operation.finalized_blocks.len());
 		let (best_num, mut last_finalized_hash, mut last_finalized_num, mut block_gap) = {
@@ -1381,6 +1454,7 @@ impl<Block: BlockT> Backend<Block> {
 			utils::insert_hash_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
 
 			transaction.set_from_vec(columns::HEADER, &lookup_key, pending_block.header.encode());
+			self.write_offchain_index_journal(&mut transaction, hash, &offchain_index_journal);
 			if let Some(body) = pending_block.body {
 				// If we have any index operations we save block in the new format with indexed
 				// extrinsic headers Otherwise we save the body as a single blob.
@@ -1692,6 +1766,7 @@ impl<Block: BlockT> Backend<Block> {
 		}
 
 		let new_displaced = self.blockchain.leaves.write().finalize_height(f_num);
+		self.prune_offchain_index(transaction, f_num, &new_displaced)?;
 		self.prune_blocks(transaction, f_num, &new_displaced)?;
 		match displaced {
 			x @ &mut None => *x = Some(new_displaced),
@@ -1739,12 +1814,188 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(())
 	}
 
+	fn prune_offchain_index(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		finalized: NumberFor<Block>,
+		displaced: &FinalizationOutcome<Block::Hash, NumberFor<Block>>,
+	) -> ClientResult<()> {
+		if self.offchain_index_pruning == OffchainIndexPruning::All {
+			return Ok(())
+		}
+
+		let mut writers = HashMap::new();
+		if let OffchainIndexPruning::Some(keep) = self.offchain_index_pruning {
+			let keep = std::cmp::max(keep, 1);
+			if finalized >= keep.into() {
+				let number = finalized.saturating_sub(keep.into());
+				if let Some(hash) = self.blockchain.hash(number)? {
+					self.prune_offchain_index_journal(transaction, hash, false, &mut writers);
+				}
+			}
+		}
+
+		// Follow displaced chains back until we reach the canonical chain, the same way
+		// `prune_blocks` does.
+		for h in displaced.leaves() {
+			let mut number = finalized;
+			let mut hash = *h;
+			while self.blockchain.hash(number)? != Some(hash) {
+				match self.blockchain.header(BlockId::<Block>::hash(hash))? {
+					Some(header) => {
+						self.prune_offchain_index_journal(transaction, hash, true, &mut writers);
+						number = header.number().saturating_sub(One::one());
+						hash = *header.parent_hash();
+					},
+					None => break,
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Stores the offchain indexing changes of the given block, together with what is needed to
+	/// revert them once the block is pruned, unless all the data is kept.
+	fn write_offchain_index_journal(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		hash: Block::Hash,
+		journal: &OffchainIndexJournal,
+	) {
+		if journal.is_empty() {
+			return
+		}
+
+		// The changes are never reverted if all the data is kept.
+		if self.offchain_index_pruning != OffchainIndexPruning::All {
+			for ((prefix, key), _) in journal {
+				let db_key = offchain::concatenate_prefix_and_key(prefix, key);
+				let writer_key = offchain::index_writer_key(&db_key);
+				let previous_writer = self.offchain_index_writer(&writer_key);
+				let previous_value = self.storage.db.get(columns::OFFCHAIN, &db_key);
+				transaction.set_from_vec(
+					columns::OFFCHAIN,
+					&offchain::index_undo_key(hash.as_ref(), &db_key),
+					(previous_writer, previous_value).encode(),
+				);
+				transaction.set_from_vec(columns::OFFCHAIN, &writer_key, hash.encode());
+			}
+		}
+
+		transaction.set_from_vec(
+			columns::OFFCHAIN,
+			&offchain::index_journal_key(hash.as_ref()),
+			journal.encode(),
+		);
+	}
+
+	/// Returns the block that changed the offchain key of the given writer entry last.
+	fn offchain_index_writer(&self, writer_key: &[u8]) -> Option<Block::Hash> {
+		let writer = self.storage.db.get(columns::OFFCHAIN, writer_key)?;
+		Decode::decode(&mut &writer[..]).ok()
+	}
+
+	/// Removes the journal of the given block and reverts its offchain indexing changes, skipping
+	/// the keys that were changed by a later block.
+	///
+	/// The keys changed by a `displaced` block get back the value they had before, while the keys
+	/// changed by a canonical block leaving the retention window are removed. `writers` holds the
+	/// writers updated by the pruning so far, as they are not in the database yet.
+	fn prune_offchain_index_journal(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		hash: Block::Hash,
+		displaced: bool,
+		writers: &mut HashMap<Vec<u8>, Option<Block::Hash>>,
+	) {
+		let journal_key = offchain::index_journal_key(hash.as_ref());
+		let journal = match self.storage.db.get(columns::OFFCHAIN, &journal_key) {
+			Some(journal) => journal,
+			None => return,
+		};
+
+		match OffchainIndexJournal::decode(&mut &journal[..]) {
+			Ok(journal) => {
+				debug!(
+					target: "db",
+					"Pruning {} offchain indexing changes of block {:?}",
+					journal.len(),
+					hash,
+				);
+				for ((prefix, key), _) in journal {
+					let db_key = offchain::concatenate_prefix_and_key(&prefix, &key);
+					let undo_key = offchain::index_undo_key(hash.as_ref(), &db_key);
+					let undo = self.storage.db.get(columns::OFFCHAIN, &undo_key);
+					transaction.remove(columns::OFFCHAIN, &undo_key);
+
+					let writer_key = offchain::index_writer_key(&db_key);
+					let writer = match writers.get(&db_key) {
+						Some(writer) => *writer,
+						None => self.offchain_index_writer(&writer_key),
+					};
+					if writer != Some(hash) {
+						continue
+					}
+
+					let (mut previous_writer, mut previous_value) = match undo.and_then(|undo| {
+						<(Option<Block::Hash>, Option<Vec<u8>>)>::decode(&mut &undo[..]).ok()
+					}) {
+						Some(undo) if displaced => undo,
+						_ => (None, None),
+					};
+					// Don't bring back a value whose writer was pruned already.
+					if previous_writer.map_or(false, |writer| {
+						!self.storage.db.contains(
+							columns::OFFCHAIN,
+							&offchain::index_journal_key(writer.as_ref()),
+						)
+					}) {
+						previous_writer = None;
+						previous_value = None;
+					}
+
+					match previous_value {
+						Some(value) => transaction.set_from_vec(columns::OFFCHAIN, &db_key, value),
+						None => transaction.remove(columns::OFFCHAIN, &db_key),
+					}
+					match previous_writer {
+						Some(writer) => transaction.set_from_vec(
+							columns::OFFCHAIN,
+							&writer_key,
+							writer.encode(),
+						),
+						None => transaction.remove(columns::OFFCHAIN, &writer_key),
+					}
+					writers.insert(db_key, previous_writer);
+				}
+			},
+			Err(err) => warn!(
+				target: "db",
+				"Error decoding offchain indexing journal of block {:?}: {}",
+				hash,
+				err,
+			),
+		}
+		transaction.remove(columns::OFFCHAIN, &journal_key);
+	}
+
 	fn prune_block(
 		&self,
 		transaction: &mut Transaction<DbHash>,
 		id: BlockId<Block>,
 	) -> ClientResult<()> {
 		debug!(target: "db", "Removing block #{}", id);
+		let hash = match id {
+			BlockId::Hash(hash) => Some(hash),
+			BlockId::Number(number) => self.blockchain.hash(number)?,
+		};
+		if let Some(hash) = hash {
+			if self.blockchain.pinned_blocks_cache.read().contains(&hash) {
+				// Keep the body of a pinned block around until the block is unpinned.
+				let body = self.blockchain.body(id)?;
+				self.blockchain.pinned_blocks_cache.write().insert_body(&hash, body);
+			}
+		}
 		utils::remove_from_db(
 			transaction,
 			&*self.storage.db,
@@ -2042,7 +2293,12 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		let state_db = self.storage.state_db.memory_info();
 
 		Some(UsageInfo {
-			memory: MemoryInfo { state_cache, database_cache, state_db },
+			memory: MemoryInfo {
+				state_cache,
+				database_cache,
+				state_db,
+				pinned_blocks: self.blockchain.pinned_blocks_cache.read().len() as u64,
+			},
 			io: IoInfo {
 				transactions: io_stats.transactions,
 				bytes_read: io_stats.bytes_read,
@@ -2366,6 +2622,49 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical
 		)
 	}
+
+	fn pin_block(&self, hash: &Block::Hash) -> ClientResult<()> {
+		let hdr = self.blockchain.header_metadata(*hash)?;
+		let mut pinned_blocks = self.blockchain.pinned_blocks_cache.write();
+		if !pinned_blocks.pin(*hash) {
+			return Err(sp_blockchain::Error::Backend(format!(
+				"Cannot pin block {:?}, {} blocks are already pinned",
+				hash,
+				pinned_blocks.max_pinned_blocks(),
+			)))
+		}
+		drop(pinned_blocks);
+
+		let hint = || {
+			sc_state_db::NodeDb::get(self.storage.as_ref(), hdr.state_root.as_ref())
+				.unwrap_or(None)
+				.is_some()
+		};
+		if self
+			.storage
+			.state_db
+			.pin(hash, hdr.number.saturated_into::<u64>(), hint)
+			.is_err()
+		{
+			self.blockchain.pinned_blocks_cache.write().unpin(hash);
+			return Err(sp_blockchain::Error::UnknownBlock(format!(
+				"State already discarded for {:?}",
+				hash
+			)))
+		}
+
+		trace!(target: "db", "Pinned block {:?}", hash);
+		Ok(())
+	}
+
+	fn unpin_block(&self, hash: &Block::Hash) {
+		let mut pinned_blocks = self.blockchain.pinned_blocks_cache.write();
+		if pinned_blocks.contains(hash) {
+			pinned_blocks.unpin(hash);
+			self.storage.state_db.unpin(hash);
+			trace!(target: "db", "Unpinned block {:?}", hash);
+		}
+	}
 }
 
 impl<Block: BlockT> sc_client_api::backend::LocalBackend<Block> for Backend<Block> {}
@@ -2507,6 +2806,8 @@ pub(crate) mod tests {
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				source: DatabaseSource::Custom { db: backing, require_create_flag: false },
 				blocks_pruning: BlocksPruning::All,
+				offchain_index_pruning: OffchainIndexPruning::All,
+				max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
 			},
 			0,
 		)
@@ -3210,6 +3511,91 @@ pub(crate) mod tests {
 		assert_eq!(Some(vec![4.into()]), bc.body(BlockId::hash(blocks[4])).unwrap());
 	}
 
+	#[test]
+	fn pinned_blocks_survive_pruning() {
+		let backend = Backend::<Block>::new_test_with_tx_storage(2, 0);
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..6 {
+			let hash = insert_block(
+				&backend,
+				i,
+				prev_hash,
+				None,
+				Default::default(),
+				vec![i.into()],
+				None,
+			)
+			.unwrap();
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		backend.pin_block(&blocks[1]).unwrap();
+		backend.pin_block(&blocks[1]).unwrap();
+		assert_eq!(backend.usage_info().unwrap().memory.pinned_blocks, 1);
+
+		let finalize = |hash| {
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, BlockId::Hash(blocks[5])).unwrap();
+			op.mark_finalized(BlockId::Hash(hash), None).unwrap();
+			backend.commit_operation(op).unwrap();
+		};
+		for i in 1..5 {
+			finalize(blocks[i]);
+		}
+
+		let bc = backend.blockchain();
+		assert_eq!(None, bc.body(BlockId::hash(blocks[0])).unwrap());
+		assert_eq!(Some(vec![1.into()]), bc.body(BlockId::hash(blocks[1])).unwrap());
+		assert_eq!(Some(vec![1.into()]), bc.body(BlockId::number(1)).unwrap());
+		assert_eq!(None, bc.body(BlockId::hash(blocks[2])).unwrap());
+		assert!(backend.state_at(BlockId::Hash(blocks[1])).is_ok());
+
+		// A single unpin leaves one reference behind.
+		backend.unpin_block(&blocks[1]);
+		assert_eq!(Some(vec![1.into()]), bc.body(BlockId::hash(blocks[1])).unwrap());
+
+		backend.unpin_block(&blocks[1]);
+		assert_eq!(backend.usage_info().unwrap().memory.pinned_blocks, 0);
+		assert_eq!(None, bc.body(BlockId::hash(blocks[1])).unwrap());
+
+		finalize(blocks[5]);
+		assert!(backend.state_at(BlockId::Hash(blocks[1])).is_err());
+		assert!(backend.pin_block(&blocks[1]).is_err());
+		assert_eq!(backend.usage_info().unwrap().memory.pinned_blocks, 0);
+	}
+
+	#[test]
+	fn pinned_blocks_are_capped() {
+		let backend = Backend::<Block>::new(
+			DatabaseSettings {
+				trie_cache_maximum_size: None,
+				state_pruning: Some(PruningMode::blocks_pruning(2)),
+				source: DatabaseSource::Custom {
+					db: sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS)),
+					require_create_flag: true,
+				},
+				blocks_pruning: BlocksPruning::Some(2),
+				offchain_index_pruning: OffchainIndexPruning::All,
+				max_pinned_blocks: 1,
+			},
+			0,
+		)
+		.unwrap();
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let block1 = insert_header(&backend, 1, block0, None, Default::default());
+
+		backend.pin_block(&block0).unwrap();
+		assert!(backend.pin_block(&block1).is_err());
+		// Already pinned blocks can still be pinned again.
+		backend.pin_block(&block0).unwrap();
+
+		backend.unpin_block(&block0);
+		backend.unpin_block(&block0);
+		backend.pin_block(&block1).unwrap();
+	}
+
 	#[test]
 	fn prune_blocks_on_finalize_with_fork() {
 		let backend = Backend::<Block>::new_test_with_tx_storage(2, 10);
@@ -3271,6 +3657,141 @@ pub(crate) mod tests {
 		assert_eq!(Some(vec![4.into()]), bc.body(BlockId::hash(blocks[4])).unwrap());
 	}
 
+	#[test]
+	fn prune_offchain_index_on_finalize() {
+		use sc_client_api::backend::OFFCHAIN_INDEX_JOURNAL_PREFIX;
+		use sp_core::offchain::OffchainStorage;
+
+		let backend = Backend::<Block>::new(
+			DatabaseSettings {
+				trie_cache_maximum_size: Some(16 * 1024 * 1024),
+				state_pruning: Some(PruningMode::blocks_pruning(10)),
+				source: DatabaseSource::Custom {
+					db: sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS)),
+					require_create_flag: true,
+				},
+				blocks_pruning: BlocksPruning::All,
+				offchain_index_pruning: OffchainIndexPruning::Some(2),
+				max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
+			},
+			10,
+		)
+		.unwrap();
+		let offchain = |key: &[u8]| {
+			backend.offchain_storage().unwrap().get(sp_core::offchain::STORAGE_PREFIX, key)
+		};
+		let insert = |number: u64, parent_hash: H256, key: &[u8], value: &[u8], best: bool| {
+			let header = Header {
+				number,
+				parent_hash,
+				state_root: BlakeTwo256::trie_root(Vec::new(), StateVersion::V1),
+				digest: Default::default(),
+				extrinsics_root: H256::random(),
+			};
+			let hash = header.hash();
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, BlockId::Hash(parent_hash)).unwrap();
+			let state = if best { NewBlockState::Best } else { NewBlockState::Normal };
+			op.set_block_data(header, Some(Vec::new()), None, None, state).unwrap();
+			op.update_offchain_storage(vec![(
+				(sp_core::offchain::STORAGE_PREFIX.to_vec(), key.to_vec()),
+				OffchainOverlayedChange::SetValue(value.to_vec()),
+			)])
+			.unwrap();
+			backend.commit_operation(op).unwrap();
+			hash
+		};
+
+		let mut blocks =
+			vec![insert_header(&backend, 0, Default::default(), None, Default::default())];
+		blocks.push(insert(1, blocks[0], b"a", b"1", true));
+		blocks.push(insert(2, blocks[1], b"b", b"2", true));
+		blocks.push(insert(3, blocks[2], b"a", b"3", true));
+		blocks.push(insert(4, blocks[3], b"c", b"4", true));
+		let fork = insert(2, blocks[1], b"fork", b"2", false);
+		// Writes the same value as block #4 did before.
+		let fork_c = insert(2, blocks[1], b"c", b"4", false);
+
+		for hash in &blocks[1..] {
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, BlockId::Hash(*hash)).unwrap();
+			op.mark_finalized(BlockId::Hash(*hash), None).unwrap();
+			backend.commit_operation(op).unwrap();
+		}
+
+		// The forks are reverted, block #2 is out of the retention window and the entry written by
+		// block #1 was overwritten by block #3.
+		assert_eq!(offchain(b"fork"), None);
+		assert_eq!(offchain(b"b"), None);
+		assert_eq!(offchain(b"a"), Some(b"3".to_vec()));
+		assert_eq!(offchain(b"c"), Some(b"4".to_vec()));
+
+		let journal = |hash: H256| {
+			backend
+				.offchain_storage()
+				.unwrap()
+				.get(OFFCHAIN_INDEX_JOURNAL_PREFIX, hash.as_ref())
+		};
+		assert!(journal(fork).is_none());
+		assert!(journal(fork_c).is_none());
+		assert!(journal(blocks[2]).is_none());
+		assert!(journal(blocks[3]).is_some());
+	}
+
+	#[test]
+	fn journal_offchain_index_with_default_pruning() {
+		use sc_client_api::backend::OFFCHAIN_INDEX_JOURNAL_PREFIX;
+		use sp_core::offchain::OffchainStorage;
+
+		let backend = Backend::<Block>::new(
+			DatabaseSettings {
+				trie_cache_maximum_size: Some(16 * 1024 * 1024),
+				state_pruning: Some(PruningMode::blocks_pruning(10)),
+				source: DatabaseSource::Custom {
+					db: sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS)),
+					require_create_flag: true,
+				},
+				blocks_pruning: BlocksPruning::All,
+				offchain_index_pruning: Default::default(),
+				max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
+			},
+			10,
+		)
+		.unwrap();
+
+		let genesis = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let header = Header {
+			number: 1,
+			parent_hash: genesis,
+			state_root: BlakeTwo256::trie_root(Vec::new(), StateVersion::V1),
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash = header.hash();
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(genesis)).unwrap();
+		op.set_block_data(header, Some(Vec::new()), None, None, NewBlockState::Best)
+			.unwrap();
+		let change = (sp_core::offchain::STORAGE_PREFIX.to_vec(), b"a".to_vec());
+		op.update_offchain_storage(vec![(
+			change.clone(),
+			OffchainOverlayedChange::SetValue(b"1".to_vec()),
+		)])
+		.unwrap();
+		backend.commit_operation(op).unwrap();
+
+		// The changes can be queried by block, while nothing is stored to revert them.
+		let storage = backend.offchain_storage().unwrap();
+		let journal: OffchainIndexJournal = vec![(change, Some(b"1".to_vec()))];
+		assert_eq!(
+			storage.get(OFFCHAIN_INDEX_JOURNAL_PREFIX, hash.as_ref()),
+			Some(journal.encode())
+		);
+		assert_eq!(storage.get(sp_core::offchain::STORAGE_PREFIX, b"a"), Some(b"1".to_vec()));
+		let db_key = offchain::concatenate_prefix_and_key(sp_core::offchain::STORAGE_PREFIX, b"a");
+		assert!(backend.offchain_index_writer(&offchain::index_writer_key(&db_key)).is_none());
+	}
+
 	#[test]
 	fn indexed_data_block_body() {
 		let backend = Backend::<Block>::new_test_with_tx_storage(1, 10);
@@ -23,6 +23,7 @@ use std::{collections::HashMap, sync::Arc};
 use crate::{columns, Database, DbHash, Transaction};
 use log::error;
 use parking_lot::Mutex;
+use sc_client_api::backend::OFFCHAIN_INDEX_JOURNAL_PREFIX;
 
 /// Offchain local storage
 #[derive(Clone)]
@@ -75,6 +76,42 @@ impl sp_core::offchain::OffchainStorage for LocalStorage {
 		self.db.get(columns::OFFCHAIN, &concatenate_prefix_and_key(prefix, key))
 	}
 
+	fn keys(
+		&self,
+		prefix: &[u8],
+		key_prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		let mut keys = Vec::new();
+		if count == 0 {
+			return Some(keys)
+		}
+
+		let key_prefix = concatenate_prefix_and_key(prefix, key_prefix);
+		let mut push_key = |key: &[u8], _: &[u8]| {
+			keys.push(key[prefix.len()..].to_vec());
+			keys.len() < count
+		};
+		let result = match start_key {
+			Some(start_key) => self.db.iter_with_prefix_from(
+				columns::OFFCHAIN,
+				&key_prefix,
+				&concatenate_prefix_and_key(prefix, start_key),
+				&mut push_key,
+			),
+			None => self.db.iter_with_prefix(columns::OFFCHAIN, &key_prefix, &mut push_key),
+		};
+
+		match result {
+			Ok(()) => Some(keys),
+			Err(err) => {
+				error!("Error iterating over local storage: {}", err);
+				None
+			},
+		}
+	}
+
 	fn compare_and_set(
 		&mut self,
 		prefix: &[u8],
@@ -117,6 +154,23 @@ pub(crate) fn concatenate_prefix_and_key(prefix: &[u8], key: &[u8]) -> Vec<u8> {
 	prefix.iter().chain(key.iter()).cloned().collect()
 }
 
+/// Key of the entry holding the offchain indexing journal of the block with the given hash.
+pub(crate) fn index_journal_key(hash: &[u8]) -> Vec<u8> {
+	concatenate_prefix_and_key(OFFCHAIN_INDEX_JOURNAL_PREFIX, hash)
+}
+
+/// Key of the entry holding the hash of the block that changed the given offchain key last
+/// through offchain indexing.
+pub(crate) fn index_writer_key(key: &[u8]) -> Vec<u8> {
+	concatenate_prefix_and_key(b"offchain_index_writer", key)
+}
+
+/// Key of the entry holding the writer and the value the given offchain key had before the block
+/// with the given hash changed it through offchain indexing.
+pub(crate) fn index_undo_key(hash: &[u8], key: &[u8]) -> Vec<u8> {
+	[&b"offchain_index_undo"[..], hash, key].concat()
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -137,6 +191,66 @@ mod tests {
 		assert!(storage.locks.lock().is_empty(), "Locks map should be empty!");
 	}
 
+	#[test]
+	fn should_list_keys_with_prefix() {
+		let mut storage = LocalStorage::new_test();
+		let prefix = b"prefix";
+
+		for key in [&b"b1"[..], b"a", b"b3", b"b2", b"c"] {
+			storage.set(prefix, key, b"value");
+		}
+		storage.set(b"other", b"b4", b"value");
+
+		assert_eq!(
+			storage.keys(prefix, b"b", None, 10),
+			Some(vec![b"b1".to_vec(), b"b2".to_vec(), b"b3".to_vec()])
+		);
+		assert_eq!(storage.keys(prefix, b"b", None, 2), Some(vec![b"b1".to_vec(), b"b2".to_vec()]));
+		assert_eq!(storage.keys(prefix, b"b", Some(b"b2"), 2), Some(vec![b"b3".to_vec()]));
+		assert_eq!(storage.keys(prefix, b"", Some(b"b3"), 10), Some(vec![b"c".to_vec()]));
+		assert_eq!(storage.keys(prefix, b"b", None, 0), Some(Vec::new()));
+	}
+
+	#[test]
+	fn should_list_keys_after_start_key() {
+		let mut storage = LocalStorage::new_test();
+		let prefix = b"prefix";
+
+		let all_keys =
+			[&b"a"[..], b"b", b"b\x00", b"b\x01\xff", b"b\x02", b"b\xff", b"b\xff\x00", b"c"];
+		for key in all_keys {
+			storage.set(prefix, key, b"value");
+		}
+		storage.set(b"prefiy", b"a", b"value");
+
+		let keys_after = |start_key: &[u8]| -> Vec<Vec<u8>> {
+			all_keys
+				.iter()
+				.filter(|key| **key > start_key)
+				.map(|key| key.to_vec())
+				.collect()
+		};
+		for start_key in
+			[&b""[..], b"a", b"b", b"b\x00", b"b\x01", b"b\x01\xff\xff", b"b\xff", b"c"]
+		{
+			assert_eq!(
+				storage.keys(prefix, b"", Some(start_key), 10),
+				Some(keys_after(start_key)),
+				"start key {:?}",
+				start_key,
+			);
+		}
+
+		// the start key is out of the range of keys with the given prefix
+		assert_eq!(storage.keys(prefix, b"b", Some(b"a"), 1), Some(vec![b"b".to_vec()]));
+		assert_eq!(storage.keys(prefix, b"b", Some(b"c"), 10), Some(Vec::new()));
+		// the start key itself and keys following the requested count are not returned
+		assert_eq!(
+			storage.keys(prefix, b"b", Some(b"b\x00"), 2),
+			Some(vec![b"b\x01\xff".to_vec(), b"b\x02".to_vec()])
+		);
+	}
+
 	#[test]
 	fn should_compare_and_set_on_empty_field() {
 		let mut storage = LocalStorage::new_test();
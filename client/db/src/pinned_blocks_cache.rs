@@ -0,0 +1,162 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bookkeeping of blocks pinned through [`sc_client_api::Backend::pin_block`].
+//!
+//! The state of a pinned block is kept by `sc-state-db`, while the body of a pinned block that
+//! is pruned from the database under `BlocksPruning` is kept in memory until the block is
+//! unpinned.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use sp_runtime::traits::Block as BlockT;
+
+/// Default maximum number of distinct blocks that can be pinned at the same time.
+pub const DEFAULT_MAX_PINNED_BLOCKS: usize = 1024;
+
+/// Entry of a single pinned block.
+struct PinnedBlock<Block: BlockT> {
+	/// Number of outstanding pins.
+	ref_count: u32,
+	/// Body of the block, set once the block got pruned from the database.
+	body: Option<Option<Vec<Block::Extrinsic>>>,
+}
+
+/// Reference counted set of pinned blocks.
+pub struct PinnedBlocksCache<Block: BlockT> {
+	blocks: HashMap<Block::Hash, PinnedBlock<Block>>,
+	max_pinned_blocks: usize,
+}
+
+impl<Block: BlockT> PinnedBlocksCache<Block> {
+	/// Create a new cache that holds at most `max_pinned_blocks` distinct blocks.
+	pub fn new(max_pinned_blocks: usize) -> Self {
+		Self { blocks: HashMap::new(), max_pinned_blocks }
+	}
+
+	/// Add a reference to the given block.
+	///
+	/// Returns `false` if the block is not pinned yet and the cache is full.
+	pub fn pin(&mut self, hash: Block::Hash) -> bool {
+		match self.blocks.entry(hash) {
+			Entry::Occupied(mut entry) => {
+				entry.get_mut().ref_count += 1;
+				true
+			},
+			Entry::Vacant(entry) => {
+				if self.blocks.len() >= self.max_pinned_blocks {
+					return false
+				}
+				entry.insert(PinnedBlock { ref_count: 1, body: None });
+				true
+			},
+		}
+	}
+
+	/// Release a reference to the given block.
+	///
+	/// Returns `true` if this was the last reference and the block is no longer pinned.
+	pub fn unpin(&mut self, hash: &Block::Hash) -> bool {
+		match self.blocks.entry(*hash) {
+			Entry::Occupied(mut entry) => {
+				entry.get_mut().ref_count -= 1;
+				if entry.get().ref_count == 0 {
+					entry.remove();
+					true
+				} else {
+					false
+				}
+			},
+			Entry::Vacant(_) => false,
+		}
+	}
+
+	/// Returns `true` if the given block is pinned.
+	pub fn contains(&self, hash: &Block::Hash) -> bool {
+		self.blocks.contains_key(hash)
+	}
+
+	/// Returns `true` if no block is pinned.
+	pub fn is_empty(&self) -> bool {
+		self.blocks.is_empty()
+	}
+
+	/// Number of pinned blocks.
+	pub fn len(&self) -> usize {
+		self.blocks.len()
+	}
+
+	/// Maximum number of distinct blocks that can be pinned at the same time.
+	pub fn max_pinned_blocks(&self) -> usize {
+		self.max_pinned_blocks
+	}
+
+	/// Keep the body of a pinned block that is about to be pruned from the database.
+	///
+	/// Does nothing if the block is not pinned.
+	pub fn insert_body(&mut self, hash: &Block::Hash, body: Option<Vec<Block::Extrinsic>>) {
+		if let Some(block) = self.blocks.get_mut(hash) {
+			block.body = Some(body);
+		}
+	}
+
+	/// Returns the body kept for a pruned pinned block.
+	///
+	/// The outer `Option` is `None` if the body was not pruned from the database.
+	pub fn body(&self, hash: &Block::Hash) -> Option<Option<Vec<Block::Extrinsic>>> {
+		self.blocks.get(hash).and_then(|block| block.body.clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	#[test]
+	fn pins_are_reference_counted_and_capped() {
+		let mut cache = PinnedBlocksCache::<Block>::new(2);
+		let (a, b, c) = (H256::repeat_byte(1), H256::repeat_byte(2), H256::repeat_byte(3));
+
+		assert!(cache.pin(a));
+		assert!(cache.pin(a));
+		assert!(cache.pin(b));
+		// The cache is full, but blocks already pinned can be pinned again.
+		assert!(!cache.pin(c));
+		assert!(cache.pin(b));
+		assert_eq!(cache.len(), 2);
+
+		cache.insert_body(&a, Some(vec![1.into()]));
+		cache.insert_body(&c, Some(vec![3.into()]));
+		assert_eq!(cache.body(&a), Some(Some(vec![1.into()])));
+		assert_eq!(cache.body(&b), None);
+		assert_eq!(cache.body(&c), None);
+
+		assert!(!cache.unpin(&a));
+		assert!(cache.contains(&a));
+		assert!(cache.unpin(&a));
+		assert!(!cache.contains(&a));
+		assert_eq!(cache.body(&a), None);
+		assert!(!cache.unpin(&a));
+
+		assert!(cache.pin(c));
+	}
+}
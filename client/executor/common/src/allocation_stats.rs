@@ -0,0 +1,55 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Collection of the allocator statistics of runtime calls.
+//!
+//! The executors report the [`AllocationStats`] of every runtime call executed in wasm through
+//! [`record`]. Running code inside [`collect`] gathers all of these reports on the current
+//! thread, e.g. to find out how much heap the execution of a block needed.
+
+use crate::wasm_runtime::AllocationStats;
+use std::cell::RefCell;
+
+thread_local! {
+	static COLLECTED: RefCell<Option<Vec<(String, AllocationStats)>>> = RefCell::new(None);
+}
+
+/// Run `f` and collect the allocator statistics of all runtime calls it executes on the
+/// current thread.
+///
+/// Returns the result of `f` together with the name of each executed runtime method and its
+/// [`AllocationStats`], in the order of execution. Runtime calls that are executed natively
+/// are not part of the result.
+pub fn collect<R>(f: impl FnOnce() -> R) -> (R, Vec<(String, AllocationStats)>) {
+	let previous = COLLECTED.with(|c| c.borrow_mut().replace(Vec::new()));
+	let result = f();
+	let collected = COLLECTED.with(|c| std::mem::replace(&mut *c.borrow_mut(), previous));
+
+	(result, collected.unwrap_or_default())
+}
+
+/// Record the allocator statistics of the runtime `method` that was just executed.
+///
+/// Does nothing if the current thread is not inside [`collect`].
+pub fn record(method: &str, stats: &AllocationStats) {
+	COLLECTED.with(|c| {
+		if let Some(collected) = c.borrow_mut().as_mut() {
+			collected.push((method.into(), stats.clone()));
+		}
+	})
+}
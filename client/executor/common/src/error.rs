@@ -57,6 +57,9 @@ pub enum Error {
 	#[error("Runtime panicked: {0}")]
 	RuntimePanicked(String),
 
+	#[error("Runtime call was aborted after exceeding its execution timeout")]
+	Timeout,
+
 	#[error("Invalid memory reference")]
 	InvalidMemoryReference,
 
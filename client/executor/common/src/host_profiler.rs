@@ -0,0 +1,185 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Opt-in profiling of host function calls.
+//!
+//! Profiling is enabled for the current thread by running a runtime call inside [`profile`].
+//! While enabled, the executor backends report every host function invocation through
+//! [`record_call`] and every memory transfer between the host and the runtime through
+//! [`record_memory_read`] and [`record_memory_written`]. The transferred bytes are attributed
+//! to the host function that is being executed at that moment.
+//!
+//! When profiling is disabled all of these functions boil down to a thread local lookup.
+
+use std::{
+	cell::RefCell,
+	collections::BTreeMap,
+	time::{Duration, Instant},
+};
+
+/// Statistics collected for a single host function.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HostFunctionStats {
+	/// The number of times the host function was called.
+	pub calls: u64,
+	/// The cumulative time spent in the host function.
+	///
+	/// This includes the time spent in nested host function calls, e.g. when a sandboxed
+	/// instance calls back into the supervisor.
+	pub time: Duration,
+	/// The number of bytes the host function read from the runtime memory.
+	pub bytes_read: u64,
+	/// The number of bytes the host function wrote to the runtime memory.
+	pub bytes_written: u64,
+}
+
+impl HostFunctionStats {
+	/// Merge the statistics of `other` into `self`.
+	pub fn merge(&mut self, other: &Self) {
+		self.calls = self.calls.saturating_add(other.calls);
+		self.time = self.time.saturating_add(other.time);
+		self.bytes_read = self.bytes_read.saturating_add(other.bytes_read);
+		self.bytes_written = self.bytes_written.saturating_add(other.bytes_written);
+	}
+}
+
+/// The statistics of all host functions called while profiling, keyed by the function name.
+pub type HostProfile = BTreeMap<String, HostFunctionStats>;
+
+#[derive(Default)]
+struct Profiler {
+	profile: HostProfile,
+	bytes_read: u64,
+	bytes_written: u64,
+}
+
+thread_local! {
+	static PROFILER: RefCell<Option<Profiler>> = RefCell::new(None);
+}
+
+/// Run `f` with host function profiling enabled on the current thread.
+///
+/// Returns the result of `f` together with the collected [`HostProfile`]. Nested invocations
+/// profile independently of the outer invocation.
+///
+/// Only host functions executed by the wasm executors are profiled. Native runtime calls do not
+/// go through host functions and thus produce an empty profile.
+pub fn profile<R>(f: impl FnOnce() -> R) -> (R, HostProfile) {
+	let previous = PROFILER.with(|p| p.borrow_mut().replace(Profiler::default()));
+	let result = f();
+	let profiler = PROFILER.with(|p| std::mem::replace(&mut *p.borrow_mut(), previous));
+
+	(result, profiler.map(|p| p.profile).unwrap_or_default())
+}
+
+/// Returns `true` if host function profiling is enabled on the current thread.
+pub fn is_profiling() -> bool {
+	PROFILER.with(|p| p.borrow().is_some())
+}
+
+/// Execute the host function `name` by calling `f` and record it in the current profile.
+pub fn record_call<R>(name: &str, f: impl FnOnce() -> R) -> R {
+	if !is_profiling() {
+		return f()
+	}
+
+	// Memory transfers are attributed to the innermost host function, so stash the counters of
+	// the calling host function (if any) and restore them afterwards.
+	let outer = PROFILER.with(|p| {
+		p.borrow_mut()
+			.as_mut()
+			.map(|p| (std::mem::take(&mut p.bytes_read), std::mem::take(&mut p.bytes_written)))
+	});
+
+	let start = Instant::now();
+	let result = f();
+	let elapsed = start.elapsed();
+
+	PROFILER.with(|p| {
+		if let Some(p) = p.borrow_mut().as_mut() {
+			let stats = p.profile.entry(name.into()).or_default();
+			stats.merge(&HostFunctionStats {
+				calls: 1,
+				time: elapsed,
+				bytes_read: p.bytes_read,
+				bytes_written: p.bytes_written,
+			});
+
+			let (bytes_read, bytes_written) = outer.unwrap_or_default();
+			p.bytes_read = bytes_read;
+			p.bytes_written = bytes_written;
+		}
+	});
+
+	result
+}
+
+/// Record that `len` bytes were read from the runtime memory by the host.
+pub fn record_memory_read(len: usize) {
+	PROFILER.with(|p| {
+		if let Some(p) = p.borrow_mut().as_mut() {
+			p.bytes_read = p.bytes_read.saturating_add(len as u64);
+		}
+	})
+}
+
+/// Record that `len` bytes were written to the runtime memory by the host.
+pub fn record_memory_written(len: usize) {
+	PROFILER.with(|p| {
+		if let Some(p) = p.borrow_mut().as_mut() {
+			p.bytes_written = p.bytes_written.saturating_add(len as u64);
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn records_nothing_when_disabled() {
+		assert!(!is_profiling());
+		assert_eq!(record_call("ext_foo", || 42), 42);
+		record_memory_read(10);
+
+		let (_, profile) = profile(|| ());
+		assert!(profile.is_empty());
+	}
+
+	#[test]
+	fn attributes_memory_transfers_to_innermost_call() {
+		let (result, profile) = profile(|| {
+			record_call("ext_outer", || {
+				record_memory_read(4);
+				record_call("ext_inner", || record_memory_written(8));
+				record_memory_read(2);
+			});
+			record_call("ext_inner", || record_memory_written(1));
+			is_profiling()
+		});
+
+		assert!(result);
+		assert!(!is_profiling());
+
+		let outer = profile["ext_outer"];
+		assert_eq!((outer.calls, outer.bytes_read, outer.bytes_written), (1, 6, 0));
+
+		let inner = profile["ext_inner"];
+		assert_eq!((inner.calls, inner.bytes_read, inner.bytes_written), (2, 0, 9));
+	}
+}
@@ -21,7 +21,9 @@
 #![warn(missing_docs)]
 #![deny(unused_crate_dependencies)]
 
+pub mod allocation_stats;
 pub mod error;
+pub mod host_profiler;
 pub mod runtime_blob;
 pub mod sandbox;
 pub mod util;
@@ -20,6 +20,7 @@
 
 use crate::error::Error;
 use sp_wasm_interface::Value;
+use std::time::Duration;
 
 pub use sc_allocator::AllocationStats;
 
@@ -95,6 +96,22 @@ pub trait WasmInstance: Send {
 		data: &[u8],
 	) -> (Result<Vec<u8>, Error>, Option<AllocationStats>);
 
+	/// Call a method on this WASM instance and abort it with [`Error::Timeout`] if it is still
+	/// executing after `timeout`.
+	///
+	/// Before execution, instance is reset.
+	///
+	/// Instances that don't support interrupting a call ignore the `timeout`.
+	fn call_with_timeout(
+		&mut self,
+		method: InvokeMethod,
+		data: &[u8],
+		timeout: Option<Duration>,
+	) -> (Result<Vec<u8>, Error>, Option<AllocationStats>) {
+		let _ = timeout;
+		self.call_with_allocation_stats(method, data)
+	}
+
 	/// Call an exported method on this WASM instance.
 	///
 	/// Before execution, instance is reset.
@@ -22,7 +22,9 @@ mod sandbox;
 
 use codec::{Decode, Encode};
 use hex_literal::hex;
-use sc_executor_common::{error::Error, runtime_blob::RuntimeBlob, wasm_runtime::WasmModule};
+use sc_executor_common::{
+	error::Error, host_profiler, runtime_blob::RuntimeBlob, wasm_runtime::WasmModule,
+};
 use sc_runtime_test::wasm_binary_unwrap;
 use sp_core::{
 	blake2_128, blake2_256, ed25519, map,
@@ -325,6 +327,29 @@ fn storage_should_work(wasm_method: WasmExecutionMethod) {
 	assert_eq!(ext, expected);
 }
 
+test_wasm_execution!(host_function_profiling_should_work);
+fn host_function_profiling_should_work(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+	ext.set_storage(b"foo".to_vec(), b"bar".to_vec());
+
+	let value = vec![7u8; 60];
+	let (output, profile) = host_profiler::profile(|| {
+		call_in_wasm("test_data_in", &value.encode(), wasm_method, &mut ext).unwrap()
+	});
+	assert_eq!(output, b"all ok!".to_vec().encode());
+
+	let set = profile["ext_storage_set_version_1"];
+	assert_eq!(set.calls, 2);
+	// `input` => `value` and `baz` => `bar`.
+	assert_eq!(set.bytes_read, (5 + 60 + 3 + 3) as u64);
+	assert_eq!(set.bytes_written, 0);
+
+	let get = profile["ext_storage_get_version_1"];
+	assert_eq!(get.calls, 1);
+	assert!(get.bytes_written > 0);
+}
+
 test_wasm_execution!(clear_prefix_should_work);
 fn clear_prefix_should_work(wasm_method: WasmExecutionMethod) {
 	let mut ext = TestExternalities::default();
@@ -599,6 +624,7 @@ fn mk_test_runtime(wasm_method: WasmExecutionMethod, pages: u64) -> Arc<dyn Wasm
 		pages,
 		blob,
 		true,
+		false,
 		None,
 	)
 	.expect("failed to instantiate wasm runtime")
@@ -859,6 +885,7 @@ fn memory_is_cleared_between_invocations(wasm_method: WasmExecutionMethod) {
 		1024,
 		RuntimeBlob::uncompress_if_needed(&binary[..]).unwrap(),
 		true,
+		false,
 		None,
 	)
 	.unwrap();
@@ -35,6 +35,7 @@
 mod native_executor;
 #[cfg(test)]
 mod integration_tests;
+mod metrics;
 mod wasm_runtime;
 
 pub use codec::Codec;
@@ -49,7 +50,7 @@ pub use sp_wasm_interface;
 pub use wasm_runtime::{read_embedded_version, WasmExecutionMethod};
 pub use wasmi;
 
-pub use sc_executor_common::{error, sandbox};
+pub use sc_executor_common::{allocation_stats, error, host_profiler, sandbox};
 
 #[cfg(feature = "wasmtime")]
 pub use sc_executor_wasmtime::InstantiationStrategy as WasmtimeInstantiationStrategy;
@@ -94,4 +95,32 @@ mod tests {
 			.unwrap();
 		assert_eq!(res, vec![0u8; 0]);
 	}
+
+	#[test]
+	fn precompile_runtime_requires_compiled_execution_and_cache_path() {
+		let dir = tempfile::tempdir().unwrap();
+		let executor = |method, cache_path| {
+			WasmExecutor::<sp_io::SubstrateHostFunctions>::new(method, Some(8), 8, cache_path, 2)
+		};
+
+		let interpreted = executor(WasmExecutionMethod::Interpreted, Some(dir.path().into()));
+		assert!(!interpreted.precompile_runtime(wasm_binary_unwrap(), None).unwrap());
+
+		#[cfg(feature = "wasmtime")]
+		{
+			let compiled = |cache_path| {
+				executor(
+					WasmExecutionMethod::Compiled {
+						instantiation_strategy: WasmtimeInstantiationStrategy::RecreateInstance,
+					},
+					cache_path,
+				)
+			};
+
+			assert!(!compiled(None).precompile_runtime(wasm_binary_unwrap(), None).unwrap());
+			assert!(compiled(Some(dir.path().into()))
+				.precompile_runtime(wasm_binary_unwrap(), None)
+				.unwrap());
+		}
+	}
 }
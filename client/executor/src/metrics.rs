@@ -0,0 +1,75 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the executor.
+
+use prometheus_endpoint::{
+	exponential_buckets, register, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry,
+};
+use sc_executor_common::wasm_runtime::AllocationStats;
+
+/// Executor metrics.
+pub(crate) struct Metrics {
+	heap_usage: HistogramVec,
+	heap_allocated_peak: HistogramVec,
+}
+
+impl Metrics {
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		// From 64 KiB (a single wasm page) to 128 MiB (the default number of heap pages).
+		let buckets = exponential_buckets(65536.0, 2.0, 12)?;
+		Ok(Self {
+			heap_usage: register(
+				HistogramVec::new(
+					HistogramOpts {
+						common_opts: Opts::new(
+							"substrate_executor_runtime_heap_usage_bytes",
+							"Address space of the heap used by a runtime call",
+						),
+						buckets: buckets.clone(),
+					},
+					&["method"],
+				)?,
+				registry,
+			)?,
+			heap_allocated_peak: register(
+				HistogramVec::new(
+					HistogramOpts {
+						common_opts: Opts::new(
+							"substrate_executor_runtime_heap_allocated_peak_bytes",
+							"Peak number of bytes allocated on the heap by a runtime call",
+						),
+						buckets,
+					},
+					&["method"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Observe the allocator statistics of a runtime call of `method`.
+	pub(crate) fn observe(&self, method: &str, stats: &AllocationStats) {
+		self.heap_usage
+			.with_label_values(&[method])
+			.observe(stats.address_space_used as f64);
+		self.heap_allocated_peak
+			.with_label_values(&[method])
+			.observe(stats.bytes_allocated_peak as f64);
+	}
+}
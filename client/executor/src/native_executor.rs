@@ -17,7 +17,8 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-	error::{Error, Result},
+	error::{Error, Result, WasmError},
+	metrics::Metrics,
 	wasm_runtime::{RuntimeCache, WasmExecutionMethod},
 	RuntimeVersionOf,
 };
@@ -35,12 +36,17 @@ use std::{
 };
 
 use codec::{Decode, Encode};
+use prometheus_endpoint::{PrometheusError, Registry};
 use sc_executor_common::{
+	allocation_stats,
 	runtime_blob::RuntimeBlob,
 	wasm_runtime::{AllocationStats, InvokeMethod, WasmInstance, WasmModule},
 };
 use sp_core::{
-	traits::{CodeExecutor, Externalities, RuntimeCode, RuntimeSpawn, RuntimeSpawnExt},
+	traits::{
+		CodeExecutor, ExecutionTimeoutExt, Externalities, RuntimeCode, RuntimeSpawn,
+		RuntimeSpawnExt,
+	},
 	NativeOrEncoded,
 };
 use sp_externalities::ExternalitiesExt as _;
@@ -51,6 +57,22 @@ use sp_wasm_interface::{ExtendedHostFunctions, HostFunctions};
 /// Default num of pages for the heap
 const DEFAULT_HEAP_PAGES: u64 = 2048;
 
+/// Create the span of a runtime call of `method`.
+///
+/// The allocator statistics are recorded into it once the call was executed in wasm.
+fn runtime_call_span(method: &str) -> tracing::Span {
+	tracing::debug_span!(
+		target: "executor",
+		"runtime_call",
+		%method,
+		heap_usage = tracing::field::Empty,
+		heap_allocated_peak = tracing::field::Empty,
+		allocations = tracing::field::Empty,
+		bytes_wasted = tracing::field::Empty,
+		bytes_free = tracing::field::Empty,
+	)
+}
+
 /// Set up the externalities and safe calling environment to execute runtime calls.
 ///
 /// If the inner closure panics, it will be caught and return an error.
@@ -103,6 +125,8 @@ pub struct WasmExecutor<H> {
 	cache_path: Option<PathBuf>,
 	/// Ignore missing function imports.
 	allow_missing_host_functions: bool,
+	/// Prometheus metrics, if registered.
+	metrics: Option<Arc<Metrics>>,
 	phantom: PhantomData<H>,
 }
 
@@ -114,6 +138,7 @@ impl<H> Clone for WasmExecutor<H> {
 			cache: self.cache.clone(),
 			cache_path: self.cache_path.clone(),
 			allow_missing_host_functions: self.allow_missing_host_functions,
+			metrics: self.metrics.clone(),
 			phantom: self.phantom,
 		}
 	}
@@ -156,6 +181,7 @@ where
 			)),
 			cache_path,
 			allow_missing_host_functions: false,
+			metrics: None,
 			phantom: PhantomData,
 		}
 	}
@@ -165,6 +191,34 @@ where
 		self.allow_missing_host_functions = allow_missing_host_functions
 	}
 
+	/// Register the Prometheus metrics of this executor, e.g. the heap usage of runtime calls.
+	pub fn register_metrics(&mut self, registry: &Registry) -> result::Result<(), PrometheusError> {
+		self.metrics = Some(Arc::new(Metrics::register(registry)?));
+		Ok(())
+	}
+
+	/// Report the allocator statistics of a runtime call of `method` to the current tracing span,
+	/// the metrics and [`allocation_stats::collect`].
+	fn report_allocation_stats(&self, method: &str, stats: Option<AllocationStats>) {
+		let stats = match stats {
+			Some(stats) => stats,
+			None => return,
+		};
+
+		let span = tracing::Span::current();
+		span.record("heap_usage", &stats.address_space_used);
+		span.record("heap_allocated_peak", &stats.bytes_allocated_peak);
+		span.record("allocations", &stats.allocations());
+		span.record("bytes_wasted", &(stats.bytes_wasted_sum as u64));
+		span.record("bytes_free", &stats.bytes_free());
+
+		if let Some(metrics) = &self.metrics {
+			metrics.observe(method, &stats);
+		}
+
+		allocation_stats::record(method, &stats);
+	}
+
 	/// Execute the given closure `f` with the latest runtime (based on `runtime_code`).
 	///
 	/// The closure `f` is expected to return `Err(_)` when there happened a `panic!` in native code
@@ -258,6 +312,25 @@ where
 		(result, allocation_stats)
 	}
 
+	/// Compile the given runtime `code` ahead of time into the artifact cache of this executor, so
+	/// that its first execution doesn't need to compile it. The `heap_pages` must be the ones the
+	/// runtime will be executed with, and default to the ones of this executor.
+	///
+	/// Returns `false` if nothing was compiled because this executor doesn't compile runtimes or
+	/// doesn't have a cache path.
+	pub fn precompile_runtime(
+		&self,
+		code: &[u8],
+		heap_pages: Option<u64>,
+	) -> std::result::Result<bool, WasmError> {
+		crate::wasm_runtime::precompile_wasm_runtime(
+			self.method,
+			heap_pages.unwrap_or(self.default_heap_pages),
+			code,
+			self.cache_path.as_deref(),
+		)
+	}
+
 	fn uncached_call_impl(
 		&self,
 		runtime_blob: RuntimeBlob,
@@ -272,6 +345,7 @@ where
 			self.default_heap_pages,
 			runtime_blob,
 			allow_missing_host_functions,
+			false,
 			self.cache_path.as_deref(),
 		)
 		.map_err(|e| format!("Failed to create module: {}", e))?;
@@ -357,13 +431,21 @@ where
 			"Executing function",
 		);
 
+		let span = runtime_call_span(method);
+		let _enter = span.enter();
+
+		let timeout = ext.extension::<ExecutionTimeoutExt>().map(|timeout| timeout.0);
 		let result = self.with_instance(
 			runtime_code,
 			ext,
 			|module, mut instance, _onchain_version, mut ext| {
 				with_externalities_safe(&mut **ext, move || {
 					preregister_builtin_ext(module.clone());
-					instance.call_export(method, data).map(NativeOrEncoded::Encoded)
+					instance.call_with_timeout(method.into(), data, timeout)
+				})
+				.map(|(result, stats)| {
+					self.report_allocation_stats(method, stats);
+					result.map(NativeOrEncoded::Encoded)
 				})
 			},
 		);
@@ -435,10 +517,32 @@ impl<D: NativeExecutionDispatch> NativeElseWasmExecutor<D> {
 		}
 	}
 
+	/// Create a new instance using the given [`WasmExecutor`], e.g. to make use of a cache path.
+	pub fn new_with_wasm_executor(
+		executor: WasmExecutor<
+			ExtendedHostFunctions<sp_io::SubstrateHostFunctions, D::ExtendHostFunctions>,
+		>,
+	) -> Self {
+		NativeElseWasmExecutor {
+			_dummy: Default::default(),
+			native_version: D::native_version(),
+			wasm: executor,
+		}
+	}
+
 	/// Ignore missing function imports if set true.
 	pub fn allow_missing_host_functions(&mut self, allow_missing_host_functions: bool) {
 		self.wasm.allow_missing_host_functions = allow_missing_host_functions
 	}
+
+	/// Compile the given runtime `code` ahead of time, see [`WasmExecutor::precompile_runtime`].
+	pub fn precompile_runtime(
+		&self,
+		code: &[u8],
+		heap_pages: Option<u64>,
+	) -> std::result::Result<bool, WasmError> {
+		self.wasm.precompile_runtime(code, heap_pages)
+	}
 }
 
 impl<D: NativeExecutionDispatch> RuntimeVersionOf for NativeElseWasmExecutor<D> {
@@ -612,6 +716,10 @@ impl<D: NativeExecutionDispatch + 'static> CodeExecutor for NativeElseWasmExecut
 			"Executing function",
 		);
 
+		let span = runtime_call_span(method);
+		let _enter = span.enter();
+
+		let timeout = ext.extension::<ExecutionTimeoutExt>().map(|timeout| timeout.0);
 		let mut used_native = false;
 		let result = self.wasm.with_instance(
 			runtime_code,
@@ -636,7 +744,11 @@ impl<D: NativeExecutionDispatch + 'static> CodeExecutor for NativeElseWasmExecut
 
 						with_externalities_safe(&mut **ext, move || {
 							preregister_builtin_ext(module.clone());
-							instance.call_export(method, data).map(NativeOrEncoded::Encoded)
+							instance.call_with_timeout(method.into(), data, timeout)
+						})
+						.map(|(result, stats)| {
+							self.wasm.report_allocation_stats(method, stats);
+							result.map(NativeOrEncoded::Encoded)
 						})
 					},
 					(true, true, Some(call)) => {
@@ -746,4 +858,44 @@ mod tests {
 
 		my_interface::say_hello_world("hey");
 	}
+
+	#[test]
+	fn wasm_calls_report_allocation_stats() {
+		let mut executor = WasmExecutor::<sp_io::SubstrateHostFunctions>::new(
+			WasmExecutionMethod::Interpreted,
+			None,
+			8,
+			None,
+			2,
+		);
+		let registry = Registry::new();
+		executor.register_metrics(&registry).unwrap();
+
+		let mut ext = sp_io::TestExternalities::default();
+		let mut ext = ext.ext();
+		let code_fetcher = sp_core::traits::WrappedRuntimeCode(
+			substrate_test_runtime::wasm_binary_unwrap().into(),
+		);
+		let runtime_code =
+			RuntimeCode { code_fetcher: &code_fetcher, heap_pages: None, hash: vec![1] };
+
+		let (result, collected) = allocation_stats::collect(|| {
+			executor
+				.call::<(), fn() -> _>(&mut ext, &runtime_code, "Core_version", &[], false, None)
+				.0
+		});
+		result.unwrap();
+
+		let (_, stats) = collected
+			.iter()
+			.find(|(method, _)| method == "Core_version")
+			.expect("Core_version was executed in wasm");
+		assert!(stats.address_space_used > 0);
+		assert!(stats.allocations() > 0);
+
+		assert!(registry
+			.gather()
+			.iter()
+			.any(|family| family.get_name() == "substrate_executor_runtime_heap_usage_bytes"));
+	}
 }
@@ -29,7 +29,8 @@ use sc_executor_common::{
 	runtime_blob::RuntimeBlob,
 	wasm_runtime::{WasmInstance, WasmModule},
 };
-use sp_core::traits::{Externalities, FetchRuntimeCode, RuntimeCode};
+use sp_core::traits::{ExecutionTimeoutExt, Externalities, FetchRuntimeCode, RuntimeCode};
+use sp_externalities::ExternalitiesExt as _;
 use sp_version::RuntimeVersion;
 use std::{
 	panic::AssertUnwindSafe,
@@ -66,6 +67,8 @@ struct VersionedRuntimeId {
 	wasm_method: WasmExecutionMethod,
 	/// The number of WebAssembly heap pages this instance was created with.
 	heap_pages: u64,
+	/// Whether calls into this runtime can be interrupted once they time out.
+	execution_timeouts: bool,
 }
 
 /// A Wasm runtime object along with its cached runtime version.
@@ -236,9 +239,17 @@ impl RuntimeCache {
 	{
 		let code_hash = &runtime_code.hash;
 		let heap_pages = runtime_code.heap_pages.unwrap_or(default_heap_pages);
-
-		let versioned_runtime_id =
-			VersionedRuntimeId { code_hash: code_hash.clone(), heap_pages, wasm_method };
+		// Supporting timeouts slows down every call, so only calls that have a timeout get a
+		// runtime that supports them.
+		let execution_timeouts = wasm_method != WasmExecutionMethod::Interpreted &&
+			ext.extension::<ExecutionTimeoutExt>().is_some();
+
+		let versioned_runtime_id = VersionedRuntimeId {
+			code_hash: code_hash.clone(),
+			heap_pages,
+			wasm_method,
+			execution_timeouts,
+		};
 
 		let mut runtimes = self.runtimes.lock(); // this must be released prior to calling f
 		let versioned_runtime = if let Some(versioned_runtime) = runtimes.get(&versioned_runtime_id)
@@ -255,6 +266,7 @@ impl RuntimeCache {
 				wasm_method,
 				heap_pages,
 				allow_missing_func_imports,
+				execution_timeouts,
 				self.max_runtime_instances,
 				self.cache_path.as_deref(),
 			);
@@ -289,11 +301,15 @@ impl RuntimeCache {
 }
 
 /// Create a wasm runtime with the given `code`.
+///
+/// `execution_timeouts` enables support for interrupting calls that time out, if `wasm_method`
+/// supports it.
 pub fn create_wasm_runtime_with_code<H>(
 	wasm_method: WasmExecutionMethod,
 	heap_pages: u64,
 	blob: RuntimeBlob,
 	allow_missing_func_imports: bool,
+	execution_timeouts: bool,
 	cache_path: Option<&Path>,
 ) -> Result<Arc<dyn WasmModule>, WasmError>
 where
@@ -305,7 +321,7 @@ where
 			//
 			// We drop the cache_path here to silence warnings that cache_path is not used if
 			// compiling without the `wasmtime` flag.
-			let _ = cache_path;
+			let _ = (cache_path, execution_timeouts);
 
 			sc_executor_wasmi::create_runtime(
 				blob,
@@ -322,20 +338,74 @@ where
 				sc_executor_wasmtime::Config {
 					allow_missing_func_imports,
 					cache_path: cache_path.map(ToOwned::to_owned),
-					semantics: sc_executor_wasmtime::Semantics {
-						extra_heap_pages: heap_pages,
+					semantics: wasmtime_semantics(
 						instantiation_strategy,
-						deterministic_stack_limit: None,
-						canonicalize_nans: false,
-						parallel_compilation: true,
-						max_memory_size: None,
-					},
+						heap_pages,
+						execution_timeouts,
+					),
 				},
 			)
 			.map(|runtime| -> Arc<dyn WasmModule> { Arc::new(runtime) }),
 	}
 }
 
+/// The wasmtime semantics of the runtimes created by [`create_wasm_runtime_with_code`].
+///
+/// Precompiled artifacts are only picked up if they were compiled with the same semantics.
+#[cfg(feature = "wasmtime")]
+fn wasmtime_semantics(
+	instantiation_strategy: sc_executor_wasmtime::InstantiationStrategy,
+	heap_pages: u64,
+	execution_timeouts: bool,
+) -> sc_executor_wasmtime::Semantics {
+	sc_executor_wasmtime::Semantics {
+		extra_heap_pages: heap_pages,
+		instantiation_strategy,
+		deterministic_stack_limit: None,
+		canonicalize_nans: false,
+		parallel_compilation: true,
+		max_memory_size: None,
+		execution_timeouts,
+	}
+}
+
+/// Compile the given `code` ahead of time into the artifact cache at `cache_path`, so that creating
+/// a runtime from it later on with the same `heap_pages` doesn't need to compile it again.
+///
+/// Returns `false` if nothing was compiled because `wasm_method` doesn't compile runtimes or no
+/// `cache_path` was given.
+pub fn precompile_wasm_runtime(
+	wasm_method: WasmExecutionMethod,
+	heap_pages: u64,
+	code: &[u8],
+	cache_path: Option<&Path>,
+) -> Result<bool, WasmError> {
+	match (wasm_method, cache_path) {
+		#[cfg(feature = "wasmtime")]
+		(WasmExecutionMethod::Compiled { instantiation_strategy }, Some(cache_path)) => {
+			let blob = RuntimeBlob::uncompress_if_needed(code)?;
+
+			// This must match the config used by `create_wasm_runtime_with_code`, otherwise the
+			// cached artifact will not be used. Runtimes supporting timeouts are not precompiled,
+			// as they are only used for calls that are not part of importing blocks.
+			sc_executor_wasmtime::precompile_runtime_to_cache(
+				blob,
+				sc_executor_wasmtime::Config {
+					allow_missing_func_imports: false,
+					cache_path: Some(cache_path.to_owned()),
+					semantics: wasmtime_semantics(instantiation_strategy, heap_pages, false),
+				},
+			)?;
+
+			Ok(true)
+		},
+		_ => {
+			let _ = (heap_pages, code);
+			Ok(false)
+		},
+	}
+}
+
 fn decode_version(mut version: &[u8]) -> Result<RuntimeVersion, WasmError> {
 	Decode::decode(&mut version).map_err(|_| {
 		WasmError::Instantiation(
@@ -398,6 +468,7 @@ fn create_versioned_wasm_runtime<H>(
 	wasm_method: WasmExecutionMethod,
 	heap_pages: u64,
 	allow_missing_func_imports: bool,
+	execution_timeouts: bool,
 	max_instances: usize,
 	cache_path: Option<&Path>,
 ) -> Result<VersionedRuntime, WasmError>
@@ -418,6 +489,7 @@ where
 		heap_pages,
 		blob,
 		allow_missing_func_imports,
+		execution_timeouts,
 		cache_path,
 	)?;
 
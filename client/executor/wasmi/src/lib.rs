@@ -32,6 +32,7 @@ use codec::{Decode, Encode};
 use sc_allocator::AllocationStats;
 use sc_executor_common::{
 	error::{Error, MessageWithBacktrace, WasmError},
+	host_profiler,
 	runtime_blob::{DataSegmentsSnapshot, RuntimeBlob},
 	sandbox,
 	util::MemoryTransfer,
@@ -116,10 +117,12 @@ impl<'a> sandbox::SandboxContext for SandboxContext<'a> {
 
 impl FunctionContext for FunctionExecutor {
 	fn read_memory_into(&self, address: Pointer<u8>, dest: &mut [u8]) -> WResult<()> {
+		host_profiler::record_memory_read(dest.len());
 		self.memory.get_into(address.into(), dest).map_err(|e| e.to_string())
 	}
 
 	fn write_memory(&mut self, address: Pointer<u8>, data: &[u8]) -> WResult<()> {
+		host_profiler::record_memory_written(data.len());
 		self.memory.set(address.into(), data).map_err(|e| e.to_string())
 	}
 
@@ -437,8 +440,7 @@ impl wasmi::Externals for FunctionExecutor {
 		let mut args = args.as_ref().iter().copied().map(Into::into);
 
 		if let Some(function) = self.host_functions.clone().get(index) {
-			function
-				.execute(self, &mut args)
+			host_profiler::record_call(function.name(), || function.execute(self, &mut args))
 				.map_err(|msg| Error::FunctionExecution(function.name().to_string(), msg))
 				.map_err(wasmi::Trap::from)
 				.map(|v| v.map(Into::into))
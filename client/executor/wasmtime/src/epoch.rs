@@ -0,0 +1,147 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Interruption of runtime calls that exceed their execution timeout.
+//!
+//! Runtimes created with [`Semantics::execution_timeouts`](crate::Semantics) are compiled with
+//! wasmtime's epoch interruption, i.e. the compiled code checks whether the epoch of its engine
+//! has reached the deadline of its store and traps if so.
+//!
+//! A single background thread increments the epoch of every engine that is currently executing
+//! a call with a timeout once per [`EPOCH_TICK`]. A call with a timeout sets the deadline of its
+//! store to the number of ticks that corresponds to its timeout, all other calls set a deadline
+//! that is never reached.
+
+use crate::runtime::Store;
+use once_cell::sync::Lazy;
+use std::{
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Condvar, Mutex,
+	},
+	time::Duration,
+};
+use wasmtime::Engine;
+
+/// The interval in which the epochs of the engines are incremented.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// The deadline of calls without a timeout.
+///
+/// The deadline is relative to the current epoch, so leave plenty of room to not overflow.
+pub(crate) const NO_DEADLINE: u64 = u64::MAX / 2;
+
+/// The engines that are currently executing calls with a timeout.
+static TICKED_ENGINES: Lazy<Arc<TickedEngines>> = Lazy::new(|| {
+	let engines = Arc::new(TickedEngines::default());
+
+	let ticked = engines.clone();
+	std::thread::Builder::new()
+		.name("wasmtime-epoch-ticker".into())
+		.spawn(move || ticked.run())
+		.expect("failed to spawn the wasmtime epoch ticker thread");
+
+	engines
+});
+
+#[derive(Default)]
+struct TickedEngines {
+	engines: Mutex<Vec<Arc<EpochInterrupter>>>,
+	wakeup: Condvar,
+}
+
+impl TickedEngines {
+	fn register(&self, interrupter: Arc<EpochInterrupter>) {
+		let mut engines = self.engines.lock().expect("the ticker never panics; qed");
+		if !engines.iter().any(|engine| Arc::ptr_eq(engine, &interrupter)) {
+			engines.push(interrupter);
+		}
+		self.wakeup.notify_one();
+	}
+
+	fn run(&self) {
+		let mut engines = self.engines.lock().expect("the ticker never panics; qed");
+		loop {
+			engines.retain(|engine| engine.timed_calls.load(Ordering::Acquire) > 0);
+			if engines.is_empty() {
+				engines = self.wakeup.wait(engines).expect("the ticker never panics; qed");
+				continue
+			}
+
+			engines.iter().for_each(|engine| engine.engine.increment_epoch());
+
+			drop(engines);
+			std::thread::sleep(EPOCH_TICK);
+			engines = self.engines.lock().expect("the ticker never panics; qed");
+		}
+	}
+}
+
+/// Interrupts the calls executed by an engine once they exceed their timeout.
+pub(crate) struct EpochInterrupter {
+	engine: Engine,
+	/// The number of calls with a timeout that are currently executed by the engine.
+	timed_calls: AtomicUsize,
+}
+
+impl EpochInterrupter {
+	/// Create a new interrupter for the given `engine`.
+	///
+	/// The engine must have epoch interruption enabled.
+	pub(crate) fn new(engine: Engine) -> Arc<Self> {
+		Arc::new(Self { engine, timed_calls: AtomicUsize::new(0) })
+	}
+
+	/// Prepare the `store` for a call that is interrupted once it executes for longer than
+	/// `timeout`.
+	///
+	/// The returned guard needs to be kept alive until the call finished.
+	pub(crate) fn start_call(
+		self: &Arc<Self>,
+		store: &mut Store,
+		timeout: Option<Duration>,
+	) -> Option<TimedCall> {
+		let timeout = match timeout {
+			Some(timeout) => timeout,
+			None => {
+				store.set_epoch_deadline(NO_DEADLINE);
+				return None
+			},
+		};
+
+		// The first tick can happen right after setting the deadline, so add one tick to never
+		// interrupt a call before its timeout.
+		let ticks = (timeout.as_nanos() / EPOCH_TICK.as_nanos()) as u64 + 1;
+		store.set_epoch_deadline(ticks.min(NO_DEADLINE));
+
+		if self.timed_calls.fetch_add(1, Ordering::AcqRel) == 0 {
+			TICKED_ENGINES.register(self.clone());
+		}
+
+		Some(TimedCall(self.clone()))
+	}
+}
+
+/// A call with a timeout that is currently executed.
+pub(crate) struct TimedCall(Arc<EpochInterrupter>);
+
+impl Drop for TimedCall {
+	fn drop(&mut self) {
+		self.0.timed_calls.fetch_sub(1, Ordering::AcqRel);
+	}
+}
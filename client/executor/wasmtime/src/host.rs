@@ -26,6 +26,7 @@ use codec::{Decode, Encode};
 use sc_allocator::{AllocationStats, FreeingBumpHeapAllocator};
 use sc_executor_common::{
 	error::Result,
+	host_profiler,
 	sandbox::{self, SupervisorFuncIndex},
 	util::MemoryTransfer,
 };
@@ -117,10 +118,12 @@ impl<'a> sp_wasm_interface::FunctionContext for HostContext<'a> {
 		address: Pointer<u8>,
 		dest: &mut [u8],
 	) -> sp_wasm_interface::Result<()> {
+		host_profiler::record_memory_read(dest.len());
 		util::read_memory_into(&self.caller, address, dest).map_err(|e| e.to_string())
 	}
 
 	fn write_memory(&mut self, address: Pointer<u8>, data: &[u8]) -> sp_wasm_interface::Result<()> {
+		host_profiler::record_memory_written(data.len());
 		util::write_memory_from(&mut self.caller, address, data).map_err(|e| e.to_string())
 	}
 
@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{host::HostContext, runtime::StoreData};
-use sc_executor_common::error::WasmError;
+use sc_executor_common::{error::WasmError, host_profiler};
 use sp_wasm_interface::{FunctionContext, HostFunctions};
 use std::collections::HashMap;
 use wasmtime::{ExternType, FuncType, ImportType, Linker, Module, Trap};
@@ -104,6 +104,14 @@ impl<'a, 'b> sp_wasm_interface::HostFunctionRegistry for Registry<'a, 'b> {
 		callback(&mut HostContext { caller })
 	}
 
+	fn with_named_function_context<R>(
+		caller: wasmtime::Caller<Self::State>,
+		fn_name: &str,
+		callback: impl FnOnce(&mut dyn FunctionContext) -> R,
+	) -> R {
+		host_profiler::record_call(fn_name, || Self::with_function_context(caller, callback))
+	}
+
 	fn register_static<Params, Results>(
 		&mut self,
 		fn_name: &str,
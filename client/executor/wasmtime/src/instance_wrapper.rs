@@ -70,6 +70,10 @@ impl EntryPoint {
 				dispatcher.call(&mut *store, (func, data_ptr, data_len)),
 		}
 		.map_err(|trap| {
+			if trap.trap_code() == Some(wasmtime::TrapCode::Interrupt) {
+				return Error::Timeout
+			}
+
 			let host_state = store
 				.data_mut()
 				.host_state
@@ -173,6 +177,9 @@ pub(crate) fn create_store(engine: &wasmtime::Engine, max_memory_size: Option<us
 	if max_memory_size.is_some() {
 		store.limiter(|s| &mut s.limits);
 	}
+	// Stores start with an epoch deadline that is already reached, which would interrupt any code
+	// executed during instantiation if the engine has epoch interruption enabled.
+	store.set_epoch_deadline(crate::epoch::NO_DEADLINE);
 	store
 }
 
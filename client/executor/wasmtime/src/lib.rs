@@ -27,6 +27,7 @@
 //! | `"jitdump"` | jitdump profiling       |
 //! | other value | No profiling (warning)  |
 
+mod epoch;
 mod host;
 mod imports;
 mod instance_wrapper;
@@ -37,6 +38,6 @@ mod util;
 mod tests;
 
 pub use runtime::{
-	create_runtime, create_runtime_from_artifact, prepare_runtime_artifact, Config,
-	DeterministicStackLimit, InstantiationStrategy, Semantics,
+	create_runtime, create_runtime_from_artifact, precompile_runtime_to_cache,
+	prepare_runtime_artifact, Config, DeterministicStackLimit, InstantiationStrategy, Semantics,
 };
@@ -19,6 +19,7 @@
 //! Defines the compiled Wasm runtime that uses Wasmtime internally.
 
 use crate::{
+	epoch::EpochInterrupter,
 	host::HostState,
 	instance_wrapper::{EntryPoint, InstanceWrapper},
 	util::{self, replace_strategy_if_broken},
@@ -40,6 +41,7 @@ use std::{
 		atomic::{AtomicBool, Ordering},
 		Arc,
 	},
+	time::Duration,
 };
 use wasmtime::{Engine, Memory, StoreLimits, Table};
 
@@ -138,6 +140,8 @@ pub struct WasmtimeRuntime {
 	instance_pre: Arc<wasmtime::InstancePre<StoreData>>,
 	instantiation_strategy: InternalInstantiationStrategy,
 	config: Config,
+	/// Only set if [`Semantics::execution_timeouts`] is enabled.
+	interrupter: Option<Arc<EpochInterrupter>>,
 }
 
 impl WasmModule for WasmtimeRuntime {
@@ -174,7 +178,7 @@ impl WasmModule for WasmtimeRuntime {
 			}),
 		};
 
-		Ok(Box::new(WasmtimeInstance { strategy }))
+		Ok(Box::new(WasmtimeInstance { strategy, interrupter: self.interrupter.clone() }))
 	}
 }
 
@@ -182,6 +186,7 @@ impl WasmModule for WasmtimeRuntime {
 /// to execute the compiled code.
 pub struct WasmtimeInstance {
 	strategy: Strategy,
+	interrupter: Option<Arc<EpochInterrupter>>,
 }
 
 impl WasmtimeInstance {
@@ -189,8 +194,17 @@ impl WasmtimeInstance {
 		&mut self,
 		method: InvokeMethod,
 		data: &[u8],
+		timeout: Option<Duration>,
 		allocation_stats: &mut Option<AllocationStats>,
 	) -> Result<Vec<u8>> {
+		if timeout.is_some() && self.interrupter.is_none() {
+			log::debug!(
+				target: "wasm-runtime",
+				"Ignoring the timeout of a call into a runtime without execution timeouts enabled",
+			);
+		}
+
+		let interrupter = self.interrupter.as_ref();
 		match &mut self.strategy {
 			Strategy::LegacyInstanceReuse {
 				ref mut instance_wrapper,
@@ -210,8 +224,14 @@ impl WasmtimeInstance {
 				globals_snapshot.apply(&mut InstanceGlobals { instance: instance_wrapper });
 				let allocator = FreeingBumpHeapAllocator::new(*heap_base);
 
-				let result =
-					perform_call(data, instance_wrapper, entrypoint, allocator, allocation_stats);
+				let result = perform_call(
+					data,
+					instance_wrapper,
+					entrypoint,
+					allocator,
+					interrupter.map(|interrupter| (interrupter, timeout)),
+					allocation_stats,
+				);
 
 				// Signal to the OS that we are done with the linear memory and that it can be
 				// reclaimed.
@@ -225,7 +245,14 @@ impl WasmtimeInstance {
 				let entrypoint = instance_wrapper.resolve_entrypoint(method)?;
 
 				let allocator = FreeingBumpHeapAllocator::new(heap_base);
-				perform_call(data, &mut instance_wrapper, entrypoint, allocator, allocation_stats)
+				perform_call(
+					data,
+					&mut instance_wrapper,
+					entrypoint,
+					allocator,
+					interrupter.map(|interrupter| (interrupter, timeout)),
+					allocation_stats,
+				)
 			},
 		}
 	}
@@ -236,9 +263,18 @@ impl WasmInstance for WasmtimeInstance {
 		&mut self,
 		method: InvokeMethod,
 		data: &[u8],
+	) -> (Result<Vec<u8>>, Option<AllocationStats>) {
+		self.call_with_timeout(method, data, None)
+	}
+
+	fn call_with_timeout(
+		&mut self,
+		method: InvokeMethod,
+		data: &[u8],
+		timeout: Option<Duration>,
 	) -> (Result<Vec<u8>>, Option<AllocationStats>) {
 		let mut allocation_stats = None;
-		let result = self.call_impl(method, data, &mut allocation_stats);
+		let result = self.call_impl(method, data, timeout, &mut allocation_stats);
 		(result, allocation_stats)
 	}
 
@@ -306,6 +342,7 @@ fn common_config(semantics: &Semantics) -> std::result::Result<wasmtime::Config,
 	let mut config = wasmtime::Config::new();
 	config.cranelift_opt_level(wasmtime::OptLevel::SpeedAndSize);
 	config.cranelift_nan_canonicalization(semantics.canonicalize_nans);
+	config.epoch_interruption(semantics.execution_timeouts);
 
 	let profiler = match std::env::var_os("WASMTIME_PROFILING_STRATEGY") {
 		Some(os_string) if os_string == "jitdump" => wasmtime::ProfilingStrategy::JitDump,
@@ -542,6 +579,13 @@ pub struct Semantics {
 	///
 	/// The default is `None`.
 	pub max_memory_size: Option<usize>,
+
+	/// Enables support for [`WasmInstance::call_with_timeout`] by configuring wasmtime to use
+	/// epoch interruption.
+	///
+	/// This adds a small overhead to every call, so it should only be enabled when timeouts
+	/// are actually needed.
+	pub execution_timeouts: bool,
 }
 
 pub struct Config {
@@ -699,11 +743,17 @@ where
 		.instantiate_pre(&mut store, &module)
 		.map_err(|e| WasmError::Other(format!("cannot preinstantiate module: {:#}", e)))?;
 
+	let interrupter = config
+		.semantics
+		.execution_timeouts
+		.then(|| EpochInterrupter::new(engine.clone()));
+
 	Ok(WasmtimeRuntime {
 		engine,
 		instance_pre: Arc::new(instance_pre),
 		instantiation_strategy,
 		config,
+		interrupter,
 	})
 }
 
@@ -756,11 +806,44 @@ pub fn prepare_runtime_artifact(
 		.map_err(|e| WasmError::Other(format!("cannot precompile module: {:#}", e)))
 }
 
+/// Compiles the given runtime into the wasmtime cache located at the `cache_path` of `config`, so
+/// that a later [`create_runtime`] with the same code and `config` can skip the compilation.
+///
+/// This function performs translation from Wasm to machine code, which can be computationally
+/// heavy, and fails if `config` doesn't specify a `cache_path`.
+pub fn precompile_runtime_to_cache(
+	blob: RuntimeBlob,
+	mut config: Config,
+) -> std::result::Result<(), WasmError> {
+	let cache_path = config
+		.cache_path
+		.as_ref()
+		.ok_or_else(|| WasmError::Other("no cache path to precompile the runtime to".into()))?;
+
+	replace_strategy_if_broken(&mut config.semantics.instantiation_strategy);
+
+	let mut wasmtime_config = common_config(&config.semantics)?;
+	setup_wasmtime_caching(cache_path, &mut wasmtime_config)
+		.map_err(|e| WasmError::Other(format!("failed to setup wasmtime cache: {}", e)))?;
+
+	let engine = Engine::new(&wasmtime_config)
+		.map_err(|e| WasmError::Other(format!("cannot create the wasmtime engine: {:#}", e)))?;
+
+	// The module is cached by `wasmtime` when it is compiled, the same way it is when the runtime
+	// is created with `create_runtime`.
+	let blob = prepare_blob_for_compilation(blob, &config.semantics)?;
+	wasmtime::Module::new(&engine, &blob.serialize())
+		.map_err(|e| WasmError::Other(format!("cannot create module: {:#}", e)))?;
+
+	Ok(())
+}
+
 fn perform_call(
 	data: &[u8],
 	instance_wrapper: &mut InstanceWrapper,
 	entrypoint: EntryPoint,
 	mut allocator: FreeingBumpHeapAllocator,
+	timeout: Option<(&Arc<EpochInterrupter>, Option<Duration>)>,
 	allocation_stats: &mut Option<AllocationStats>,
 ) -> Result<Vec<u8>> {
 	let (data_ptr, data_len) = inject_input_data(instance_wrapper, &mut allocator, data)?;
@@ -770,6 +853,11 @@ fn perform_call(
 	// Set the host state before calling into wasm.
 	instance_wrapper.store_mut().data_mut().host_state = Some(host_state);
 
+	// Keep the guard alive until the call returned.
+	let _timed_call = timeout.and_then(|(interrupter, timeout)| {
+		interrupter.start_call(instance_wrapper.store_mut(), timeout)
+	});
+
 	let ret = entrypoint
 		.call(instance_wrapper.store_mut(), data_ptr, data_len)
 		.map(unpack_ptr_and_len);
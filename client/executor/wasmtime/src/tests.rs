@@ -79,6 +79,7 @@ struct RuntimeBuilder {
 	deterministic_stack: bool,
 	extra_heap_pages: u64,
 	max_memory_size: Option<usize>,
+	execution_timeouts: bool,
 	precompile_runtime: bool,
 	tmpdir: Option<tempfile::TempDir>,
 }
@@ -92,6 +93,7 @@ impl RuntimeBuilder {
 			deterministic_stack: false,
 			extra_heap_pages: 1024,
 			max_memory_size: None,
+			execution_timeouts: false,
 			precompile_runtime: false,
 			tmpdir: None,
 		}
@@ -122,6 +124,11 @@ impl RuntimeBuilder {
 		self
 	}
 
+	fn execution_timeouts(mut self, execution_timeouts: bool) -> Self {
+		self.execution_timeouts = execution_timeouts;
+		self
+	}
+
 	fn build(&mut self) -> impl WasmModule + '_ {
 		let blob = {
 			let wasm: Vec<u8>;
@@ -154,6 +161,7 @@ impl RuntimeBuilder {
 				parallel_compilation: true,
 				extra_heap_pages: self.extra_heap_pages,
 				max_memory_size: self.max_memory_size,
+				execution_timeouts: self.execution_timeouts,
 			},
 		};
 
@@ -253,6 +261,43 @@ fn test_consume_over_1mb_of_stack_does_trap(instantiation_strategy: Instantiatio
 	}
 }
 
+test_wasm_execution!(test_execution_timeout_interrupts_call);
+fn test_execution_timeout_interrupts_call(instantiation_strategy: InstantiationStrategy) {
+	let wat = r#"
+		(module
+		  (memory $0 32)
+		  (export "memory" (memory $0))
+		  (global (export "__heap_base") i32 (i32.const 0))
+
+		  (func (export "main")
+		    (param i32 i32) (result i64)
+		    (loop $label$1 (br $label$1))
+		    (i64.const 0)
+		  )
+
+		  (func (export "empty")
+		    (param i32 i32) (result i64)
+		    (i64.const 0)
+		  )
+		)
+	"#;
+	let mut builder = RuntimeBuilder::new(instantiation_strategy)
+		.use_wat(wat.to_string())
+		.execution_timeouts(true);
+	let runtime = builder.build();
+	let mut instance = runtime.new_instance().expect("failed to instantiate a runtime");
+
+	let (result, _) =
+		instance.call_with_timeout("main".into(), &[], Some(std::time::Duration::from_millis(50)));
+	match result.unwrap_err() {
+		Error::Timeout => {},
+		error => panic!("unexpected error: {:?}", error),
+	}
+
+	// Calls without a timeout are never interrupted, also not after a call that timed out.
+	instance.call_export("empty", &[]).unwrap();
+}
+
 test_wasm_execution!(test_nan_canonicalization);
 fn test_nan_canonicalization(instantiation_strategy: InstantiationStrategy) {
 	let mut builder = RuntimeBuilder::new(instantiation_strategy).canonicalize_nans(true);
@@ -540,6 +585,7 @@ fn test_instances_without_reuse_are_not_leaked() {
 				parallel_compilation: true,
 				extra_heap_pages: 2048,
 				max_memory_size: None,
+				execution_timeouts: false,
 			},
 		},
 	)
@@ -555,3 +601,48 @@ fn test_instances_without_reuse_are_not_leaked() {
 		instance.call_export("test_empty_return", &[0]).unwrap();
 	}
 }
+
+#[test]
+fn precompiling_runtime_to_cache_works() {
+	let dir = tempfile::tempdir().unwrap();
+	let config = || crate::Config {
+		allow_missing_func_imports: true,
+		cache_path: Some(dir.path().to_path_buf()),
+		semantics: crate::Semantics {
+			instantiation_strategy: InstantiationStrategy::RecreateInstance,
+			deterministic_stack_limit: None,
+			canonicalize_nans: false,
+			parallel_compilation: true,
+			extra_heap_pages: 2048,
+			max_memory_size: None,
+			execution_timeouts: false,
+		},
+	};
+	let blob = || RuntimeBlob::uncompress_if_needed(wasm_binary_unwrap()).unwrap();
+
+	fn count_files(path: &std::path::Path) -> usize {
+		std::fs::read_dir(path)
+			.unwrap()
+			.map(|entry| {
+				let path = entry.unwrap().path();
+				if path.is_dir() {
+					count_files(&path)
+				} else {
+					1
+				}
+			})
+			.sum()
+	}
+
+	crate::precompile_runtime_to_cache(blob(), config()).unwrap();
+
+	// besides the cache config, the cache now contains the compiled module
+	assert!(count_files(dir.path()) > 1);
+
+	let runtime = crate::create_runtime::<HostFunctions>(blob(), config()).unwrap();
+	runtime.new_instance().unwrap().call_export("test_empty_return", &[0]).unwrap();
+
+	// the cache path is required
+	let config = crate::Config { cache_path: None, ..config() };
+	assert!(crate::precompile_runtime_to_cache(blob(), config).is_err());
+}
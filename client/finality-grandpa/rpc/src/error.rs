@@ -36,6 +36,9 @@ pub enum Error {
 	/// GRANDPA prove finality failed.
 	#[error("GRANDPA prove finality rpc failed: {0}")]
 	ProveFinalityFailed(#[from] sc_finality_grandpa::FinalityProofError),
+	/// The requested range is too large to be proven in a single call.
+	#[error("Requested range spans more than {0} blocks")]
+	RangeTooLarge(u32),
 }
 
 /// The error codes returned by jsonrpc.
@@ -48,6 +51,8 @@ pub enum ErrorCode {
 	VoterStateTooLarge,
 	/// Failed to prove finality.
 	ProveFinality,
+	/// The requested range is too large.
+	RangeTooLarge,
 }
 
 impl From<Error> for ErrorCode {
@@ -57,6 +62,7 @@ impl From<Error> for ErrorCode {
 			Error::AuthoritySetIdReportedAsUnreasonablyLarge => ErrorCode::AuthoritySetTooLarge,
 			Error::VoterStateReportsUnreasonablyLargeNumbers => ErrorCode::VoterStateTooLarge,
 			Error::ProveFinalityFailed(_) => ErrorCode::ProveFinality,
+			Error::RangeTooLarge(_) => ErrorCode::RangeTooLarge,
 		}
 	}
 }
@@ -32,6 +32,14 @@ pub trait RpcFinalityProofProvider<Block: BlockT> {
 		&self,
 		block: NumberFor<Block>,
 	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError>;
+
+	/// Prove finality of the blocks in `(begin; end]` by returning an encoded
+	/// [`sc_finality_grandpa::RangeFinalityProof`].
+	fn rpc_prove_finality_range(
+		&self,
+		begin: NumberFor<Block>,
+		end: NumberFor<Block>,
+	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError>;
 }
 
 impl<B, Block> RpcFinalityProofProvider<Block> for FinalityProofProvider<B, Block>
@@ -46,4 +54,13 @@ where
 	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError> {
 		self.prove_finality(block).map(|x| x.map(|y| EncodedFinalityProof(y.into())))
 	}
+
+	fn rpc_prove_finality_range(
+		&self,
+		begin: NumberFor<Block>,
+		end: NumberFor<Block>,
+	) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError> {
+		self.prove_finality_range(begin, end)
+			.map(|x| x.map(|y| EncodedFinalityProof(y.into())))
+	}
 }
@@ -36,12 +36,15 @@ mod notification;
 mod report;
 
 use sc_finality_grandpa::GrandpaJustificationStream;
-use sc_rpc::SubscriptionTaskExecutor;
-use sp_runtime::traits::{Block as BlockT, NumberFor};
+use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
+use sp_runtime::traits::{Block as BlockT, NumberFor, Saturating};
 
 use finality::{EncodedFinalityProof, RpcFinalityProofProvider};
 use notification::JustificationNotification;
-use report::{ReportAuthoritySet, ReportVoterState, ReportedRoundStates};
+use report::{ReportAuthoritySet, ReportVoterState, ReportedParticipation, ReportedRoundStates};
+
+/// Maximum number of blocks a single `grandpa_proveFinalityRange` call may cover.
+const MAX_PROVE_FINALITY_RANGE: u32 = 16_384;
 
 /// Provides RPC methods for interacting with GRANDPA.
 #[rpc(client, server)]
@@ -51,6 +54,12 @@ pub trait GrandpaApi<Notification, Hash, Number> {
 	#[method(name = "grandpa_roundState")]
 	async fn round_state(&self) -> RpcResult<ReportedRoundStates>;
 
+	/// Returns the participation of the current authorities in the last `rounds` completed rounds
+	/// (or in all the rounds tracked by the voter), as well as the equivocations detected by the
+	/// voter and whether they were reported.
+	#[method(name = "grandpa_participation")]
+	async fn participation(&self, rounds: Option<u32>) -> RpcResult<ReportedParticipation>;
+
 	/// Returns the block most recently finalized by Grandpa, alongside
 	/// side its justification.
 	#[subscription(
@@ -64,6 +73,18 @@ pub trait GrandpaApi<Notification, Hash, Number> {
 	/// in the set and all the intermediary headers to link them together.
 	#[method(name = "grandpa_proveFinality")]
 	async fn prove_finality(&self, block: Number) -> RpcResult<Option<EncodedFinalityProof>>;
+
+	/// Prove finality of the blocks after `begin` up to `end` by returning the Justifications for
+	/// the last block of every authority set ending in the range, followed by the Justification for
+	/// `end` or its earliest descendant having one.
+	///
+	/// The range can't span more than 16384 blocks. This is an unsafe RPC.
+	#[method(name = "grandpa_proveFinalityRange")]
+	async fn prove_finality_range(
+		&self,
+		begin: Number,
+		end: Number,
+	) -> RpcResult<Option<EncodedFinalityProof>>;
 }
 
 /// Provides RPC methods for interacting with GRANDPA.
@@ -73,6 +94,7 @@ pub struct Grandpa<AuthoritySet, VoterState, Block: BlockT, ProofProvider> {
 	voter_state: VoterState,
 	justification_stream: GrandpaJustificationStream<Block>,
 	finality_proof_provider: Arc<ProofProvider>,
+	deny_unsafe: DenyUnsafe,
 }
 impl<AuthoritySet, VoterState, Block: BlockT, ProofProvider>
 	Grandpa<AuthoritySet, VoterState, Block, ProofProvider>
@@ -84,8 +106,16 @@ impl<AuthoritySet, VoterState, Block: BlockT, ProofProvider>
 		voter_state: VoterState,
 		justification_stream: GrandpaJustificationStream<Block>,
 		finality_proof_provider: Arc<ProofProvider>,
+		deny_unsafe: DenyUnsafe,
 	) -> Self {
-		Self { executor, authority_set, voter_state, justification_stream, finality_proof_provider }
+		Self {
+			executor,
+			authority_set,
+			voter_state,
+			justification_stream,
+			finality_proof_provider,
+			deny_unsafe,
+		}
 	}
 }
 
@@ -103,6 +133,11 @@ where
 		ReportedRoundStates::from(&self.authority_set, &self.voter_state).map_err(Into::into)
 	}
 
+	async fn participation(&self, rounds: Option<u32>) -> RpcResult<ReportedParticipation> {
+		ReportedParticipation::from(&self.authority_set, &self.voter_state, rounds)
+			.map_err(Into::into)
+	}
+
 	fn subscribe_justifications(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
 		let stream = self.justification_stream.subscribe().map(
 			|x: sc_finality_grandpa::GrandpaJustification<Block>| {
@@ -130,6 +165,26 @@ where
 			})
 			.map_err(Into::into)
 	}
+
+	async fn prove_finality_range(
+		&self,
+		begin: NumberFor<Block>,
+		end: NumberFor<Block>,
+	) -> RpcResult<Option<EncodedFinalityProof>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		if end.saturating_sub(begin) > MAX_PROVE_FINALITY_RANGE.into() {
+			return Err(error::Error::RangeTooLarge(MAX_PROVE_FINALITY_RANGE).into())
+		}
+
+		self.finality_proof_provider
+			.rpc_prove_finality_range(begin, end)
+			.map_err(|e| {
+				warn!("Error proving finality of range: {}", e);
+				error::Error::ProveFinalityFailed(e)
+			})
+			.map_err(Into::into)
+	}
 }
 
 #[cfg(test)]
@@ -144,7 +199,9 @@ mod tests {
 	use parity_scale_codec::{Decode, Encode};
 	use sc_block_builder::{BlockBuilder, RecordProof};
 	use sc_finality_grandpa::{
-		report, AuthorityId, FinalityProof, GrandpaJustification, GrandpaJustificationSender,
+		report, AuthorityId, DetectedEquivocation, EquivocationKind, EquivocationReport,
+		FinalityProof, GrandpaJustification, GrandpaJustificationSender, RangeFinalityProof,
+		RoundParticipation, VoterHistory,
 	};
 	use sp_blockchain::HeaderBackend;
 	use sp_core::{crypto::ByteArray, testing::TaskExecutor};
@@ -180,6 +237,10 @@ mod tests {
 		fn get(&self) -> Option<report::VoterState<AuthorityId>> {
 			None
 		}
+
+		fn history(&self) -> VoterHistory {
+			VoterHistory::default()
+		}
 	}
 
 	fn header(number: u64) -> Header {
@@ -209,6 +270,22 @@ mod tests {
 					.into(),
 			)))
 		}
+
+		fn rpc_prove_finality_range(
+			&self,
+			_begin: NumberFor<Block>,
+			_end: NumberFor<Block>,
+		) -> Result<Option<EncodedFinalityProof>, sc_finality_grandpa::FinalityProofError> {
+			let finality_proof = self
+				.finality_proof
+				.as_ref()
+				.expect("Don't call rpc_prove_finality_range without setting the FinalityProof");
+			Ok(Some(EncodedFinalityProof(
+				RangeFinalityProof { justifications: vec![finality_proof.justification.clone()] }
+					.encode()
+					.into(),
+			)))
+		}
 	}
 
 	impl ReportVoterState for TestVoterState {
@@ -238,6 +315,40 @@ mod tests {
 
 			Some(report::VoterState { background_rounds, best_round: (2, best_round_state) })
 		}
+
+		fn history(&self) -> VoterHistory {
+			let voter_id_1 = AuthorityId::from_slice(&[1; 32]).unwrap();
+			let voter_id_2 = AuthorityId::from_slice(&[2; 32]).unwrap();
+
+			let mut history = VoterHistory::default();
+			// a round of a previous set, which isn't reported
+			history.note_participation(RoundParticipation {
+				set_id: 0,
+				round: 5,
+				prevotes: Default::default(),
+				precommits: Default::default(),
+			});
+			history.note_participation(RoundParticipation {
+				set_id: 1,
+				round: 1,
+				prevotes: voters().into_iter().collect(),
+				precommits: vec![voter_id_1.clone()].into_iter().collect(),
+			});
+			history.note_participation(RoundParticipation {
+				set_id: 1,
+				round: 2,
+				prevotes: vec![voter_id_1.clone()].into_iter().collect(),
+				precommits: vec![voter_id_1].into_iter().collect(),
+			});
+			history.note_equivocation(DetectedEquivocation {
+				set_id: 1,
+				round: 2,
+				offender: voter_id_2,
+				kind: EquivocationKind::Precommit,
+				report: EquivocationReport::Failed("runtime error".into()),
+			});
+			history
+		}
 	}
 
 	fn setup_io_handler<VoterState>(
@@ -249,12 +360,13 @@ mod tests {
 	where
 		VoterState: ReportVoterState + Send + Sync + 'static,
 	{
-		setup_io_handler_with_finality_proofs(voter_state, None)
+		setup_io_handler_with_finality_proofs(voter_state, None, DenyUnsafe::No)
 	}
 
 	fn setup_io_handler_with_finality_proofs<VoterState>(
 		voter_state: VoterState,
 		finality_proof: Option<FinalityProof<Header>>,
+		deny_unsafe: DenyUnsafe,
 	) -> (
 		RpcModule<Grandpa<TestAuthoritySet, VoterState, Block, TestFinalityProofProvider>>,
 		GrandpaJustificationSender<Block>,
@@ -272,6 +384,7 @@ mod tests {
 			voter_state,
 			justification_stream,
 			finality_proof_provider,
+			deny_unsafe,
 		)
 		.into_rpc();
 
@@ -310,6 +423,43 @@ mod tests {
 		assert_eq!(expected_response, response.result);
 	}
 
+	#[tokio::test]
+	async fn participation_rpc_handler() {
+		let (rpc, _) = setup_io_handler(TestVoterState);
+		let expected_response = "{\"jsonrpc\":\"2.0\",\"result\":{\
+			\"setId\":1,\
+			\"rounds\":[{\
+				\"round\":2,\
+				\"missingPrevotes\":[\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"],\
+				\"missingPrecommits\":[\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"]\
+			}],\
+			\"authorities\":[{\
+				\"id\":\"5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT\",\
+				\"prevotes\":1,\"precommits\":1,\"missedPrevotes\":0,\"missedPrecommits\":0\
+			},{\
+				\"id\":\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\",\
+				\"prevotes\":0,\"precommits\":0,\"missedPrevotes\":1,\"missedPrecommits\":1\
+			}],\
+			\"equivocations\":[{\
+				\"setId\":1,\"round\":2,\
+				\"offender\":\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\",\
+				\"vote\":\"precommit\",\"report\":\"failed\",\"reportError\":\"runtime error\"\
+			}]\
+		},\"id\":0}"
+			.to_string();
+
+		let request = r#"{"jsonrpc":"2.0","method":"grandpa_participation","params":[1],"id":0}"#;
+		let (response, _) = rpc.raw_json_request(&request).await.unwrap();
+		assert_eq!(expected_response, response.result);
+
+		// all the tracked rounds of the current set are reported by default
+		let participation: serde_json::Value =
+			rpc.call("grandpa_participation", EmptyParams::new()).await.unwrap();
+		assert_eq!(participation["rounds"].as_array().unwrap().len(), 2);
+		assert_eq!(participation["authorities"][1]["prevotes"], 1);
+		assert_eq!(participation["authorities"][1]["missedPrecommits"], 2);
+	}
+
 	#[tokio::test]
 	async fn subscribe_and_unsubscribe_with_wrong_id() {
 		let (rpc, _) = setup_io_handler(TestVoterState);
@@ -415,11 +565,60 @@ mod tests {
 			justification: create_justification().encode(),
 			unknown_headers: vec![header(2)],
 		};
-		let (rpc, _) =
-			setup_io_handler_with_finality_proofs(TestVoterState, Some(finality_proof.clone()));
+		let (rpc, _) = setup_io_handler_with_finality_proofs(
+			TestVoterState,
+			Some(finality_proof.clone()),
+			DenyUnsafe::No,
+		);
 
 		let bytes: sp_core::Bytes = rpc.call("grandpa_proveFinality", [42]).await.unwrap();
 		let finality_proof_rpc: FinalityProof<Header> = Decode::decode(&mut &bytes[..]).unwrap();
 		assert_eq!(finality_proof_rpc, finality_proof);
 	}
+
+	#[tokio::test]
+	async fn prove_finality_range_with_test_finality_proof_provider() {
+		let justification = create_justification();
+		let finality_proof = FinalityProof {
+			block: header(42).hash(),
+			justification: justification.encode(),
+			unknown_headers: vec![],
+		};
+		let (rpc, _) = setup_io_handler_with_finality_proofs(
+			TestVoterState,
+			Some(finality_proof),
+			DenyUnsafe::No,
+		);
+
+		let bytes: sp_core::Bytes = rpc.call("grandpa_proveFinalityRange", [10, 42]).await.unwrap();
+		let range_proof: RangeFinalityProof = Decode::decode(&mut &bytes[..]).unwrap();
+		assert_eq!(
+			range_proof,
+			RangeFinalityProof { justifications: vec![justification.encode()] }
+		);
+	}
+
+	#[tokio::test]
+	async fn prove_finality_range_is_unsafe() {
+		let (rpc, _) = setup_io_handler_with_finality_proofs(TestVoterState, None, DenyUnsafe::Yes);
+
+		let expected_response = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"RPC call is unsafe to be called externally"},"id":0}"#;
+		let request =
+			r#"{"jsonrpc":"2.0","method":"grandpa_proveFinalityRange","params":[10,42],"id":0}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+
+		assert_eq!(expected_response, response.result);
+	}
+
+	#[tokio::test]
+	async fn prove_finality_range_rejects_large_ranges() {
+		let (rpc, _) = setup_io_handler(TestVoterState);
+
+		let expected_response = r#"{"jsonrpc":"2.0","error":{"code":5,"message":"Requested range spans more than 16384 blocks"},"id":0}"#;
+		let request =
+			r#"{"jsonrpc":"2.0","method":"grandpa_proveFinalityRange","params":[10,16395],"id":0}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+
+		assert_eq!(expected_response, response.result);
+	}
 }
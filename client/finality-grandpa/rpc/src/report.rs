@@ -24,7 +24,9 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use sc_finality_grandpa::{report, AuthorityId, SharedAuthoritySet, SharedVoterState};
+use sc_finality_grandpa::{
+	report, AuthorityId, EquivocationReport, SharedAuthoritySet, SharedVoterState, VoterHistory,
+};
 
 use crate::error::Error;
 
@@ -36,6 +38,9 @@ pub trait ReportAuthoritySet {
 /// Utility trait to get reporting data for the current GRANDPA voter state.
 pub trait ReportVoterState {
 	fn get(&self) -> Option<report::VoterState<AuthorityId>>;
+
+	/// The recent activity of the voter.
+	fn history(&self) -> VoterHistory;
 }
 
 impl<H, N> ReportAuthoritySet for SharedAuthoritySet<H, N>
@@ -55,6 +60,10 @@ impl ReportVoterState for SharedVoterState {
 	fn get(&self) -> Option<report::VoterState<AuthorityId>> {
 		self.voter_state()
 	}
+
+	fn history(&self) -> VoterHistory {
+		self.voter_history()
+	}
 }
 
 #[derive(Serialize, Deserialize)]
@@ -148,3 +157,121 @@ impl ReportedRoundStates {
 		Ok(Self { set_id, best, background })
 	}
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoundParticipation {
+	round: u32,
+	missing_prevotes: BTreeSet<AuthorityId>,
+	missing_precommits: BTreeSet<AuthorityId>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorityParticipation {
+	id: AuthorityId,
+	prevotes: u32,
+	precommits: u32,
+	missed_prevotes: u32,
+	missed_precommits: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Equivocation {
+	set_id: u32,
+	round: u32,
+	offender: AuthorityId,
+	vote: String,
+	report: String,
+	report_error: Option<String>,
+}
+
+/// The participation of the current authorities in the last completed rounds, as well as the
+/// equivocations detected by the voter in a form suitable for serialization.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportedParticipation {
+	set_id: u32,
+	rounds: Vec<RoundParticipation>,
+	authorities: Vec<AuthorityParticipation>,
+	equivocations: Vec<Equivocation>,
+}
+
+impl ReportedParticipation {
+	/// Report the participation in the last `rounds` completed rounds of the current set, or in all
+	/// the tracked ones if `None`.
+	pub fn from<AuthoritySet, VoterState>(
+		authority_set: &AuthoritySet,
+		voter_state: &VoterState,
+		rounds: Option<u32>,
+	) -> Result<Self, Error>
+	where
+		AuthoritySet: ReportAuthoritySet,
+		VoterState: ReportVoterState,
+	{
+		let history = voter_state.history();
+
+		let (set_id, current_voters) = authority_set.get();
+		let current_voters: BTreeSet<_> = current_voters.into_iter().collect();
+
+		let set_rounds = history.rounds().filter(|r| r.set_id == set_id).collect::<Vec<_>>();
+		let skip = match rounds {
+			Some(rounds) => set_rounds.len().saturating_sub(rounds as usize),
+			None => 0,
+		};
+		let set_rounds = &set_rounds[skip..];
+
+		let authorities = current_voters
+			.iter()
+			.map(|id| {
+				let prevotes = set_rounds.iter().filter(|r| r.prevotes.contains(id)).count();
+				let precommits = set_rounds.iter().filter(|r| r.precommits.contains(id)).count();
+
+				Ok(AuthorityParticipation {
+					id: id.clone(),
+					prevotes: prevotes.try_into()?,
+					precommits: precommits.try_into()?,
+					missed_prevotes: (set_rounds.len() - prevotes).try_into()?,
+					missed_precommits: (set_rounds.len() - precommits).try_into()?,
+				})
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		let rounds = set_rounds
+			.iter()
+			.map(|r| {
+				Ok(RoundParticipation {
+					round: r.round.try_into()?,
+					missing_prevotes: current_voters.difference(&r.prevotes).cloned().collect(),
+					missing_precommits: current_voters.difference(&r.precommits).cloned().collect(),
+				})
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		let equivocations = history
+			.equivocations()
+			.map(|e| {
+				Ok(Equivocation {
+					set_id: e
+						.set_id
+						.try_into()
+						.map_err(|_| Error::AuthoritySetIdReportedAsUnreasonablyLarge)?,
+					round: e.round.try_into()?,
+					offender: e.offender.clone(),
+					vote: e.kind.as_str().into(),
+					report: e.report.as_str().into(),
+					report_error: match &e.report {
+						EquivocationReport::Failed(err) => Some(err.clone()),
+						_ => None,
+					},
+				})
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		let set_id =
+			u32::try_from(set_id).map_err(|_| Error::AuthoritySetIdReportedAsUnreasonablyLarge)?;
+
+		Ok(Self { set_id, rounds, authorities, equivocations })
+	}
+}
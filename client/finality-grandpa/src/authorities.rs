@@ -743,6 +743,11 @@ impl<N: Ord + Clone> AuthoritySetChanges<N> {
 		}
 	}
 
+	/// Returns the number of the last block of the set with the given id, if known.
+	pub(crate) fn last_block_of_set(&self, set_id: SetId) -> Option<N> {
+		self.0.iter().find(|(id, _)| *id == set_id).map(|(_, number)| number.clone())
+	}
+
 	pub(crate) fn insert(&mut self, block_number: N) {
 		let idx = self
 			.0
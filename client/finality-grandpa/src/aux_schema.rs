@@ -46,6 +46,7 @@ const SET_STATE_KEY: &[u8] = b"grandpa_completed_round";
 const CONCLUDED_ROUNDS: &[u8] = b"grandpa_concluded_rounds";
 const AUTHORITY_SET_KEY: &[u8] = b"grandpa_voters";
 const BEST_JUSTIFICATION: &[u8] = b"grandpa_best_justification";
+const ARCHIVED_JUSTIFICATION_PREFIX: &[u8] = b"grandpa_archived_justification";
 
 const CURRENT_VERSION: u32 = 3;
 
@@ -468,6 +469,38 @@ where
 	load_decode::<_, GrandpaJustification<Block>>(backend, BEST_JUSTIFICATION)
 }
 
+fn archived_justification_key<N: Encode>(number: N) -> Vec<u8> {
+	(ARCHIVED_JUSTIFICATION_PREFIX, number).encode()
+}
+
+/// Store the justification of a finalized block in the justification archive.
+///
+/// Unlike the justifications stored with the blocks, archived justifications are never replaced
+/// nor pruned. They are keyed by the number of the block they finalize.
+pub(crate) fn archive_justification<Block: BlockT, F, R>(
+	justification: &GrandpaJustification<Block>,
+	write_aux: F,
+) -> R
+where
+	F: FnOnce(&[(&[u8], &[u8])]) -> R,
+{
+	let key = archived_justification_key(justification.target().0);
+	let encoded_justification = justification.encode();
+	write_aux(&[(&key[..], &encoded_justification[..])])
+}
+
+/// Fetch the archived justification for the given finalized block number, if any.
+pub fn archived_justification<B, Block>(
+	backend: &B,
+	number: NumberFor<Block>,
+) -> ClientResult<Option<GrandpaJustification<Block>>>
+where
+	B: AuxStore,
+	Block: BlockT,
+{
+	load_decode::<_, GrandpaJustification<Block>>(backend, &archived_justification_key(number))
+}
+
 /// Write voter set state.
 pub(crate) fn write_voter_set_state<Block: BlockT, B: AuxStore>(
 	backend: &B,
@@ -488,11 +521,18 @@ pub(crate) fn write_concluded_round<Block: BlockT, B: AuxStore>(
 	backend.insert_aux(&[(&key[..], round_data.encode().as_slice())], &[])
 }
 
+/// Load the authority set persisted by the voter, if any.
+pub(crate) fn load_authority_set<B: AuxStore, H: Decode, N: Decode + Clone + Ord>(
+	backend: &B,
+) -> ClientResult<Option<AuthoritySet<H, N>>> {
+	load_decode::<_, AuthoritySet<H, N>>(backend, AUTHORITY_SET_KEY)
+}
+
 #[cfg(test)]
 pub(crate) fn load_authorities<B: AuxStore, H: Decode, N: Decode + Clone + Ord>(
 	backend: &B,
 ) -> Option<AuthoritySet<H, N>> {
-	load_decode::<_, AuthoritySet<H, N>>(backend, AUTHORITY_SET_KEY).expect("backend error")
+	load_authority_set(backend).expect("backend error")
 }
 
 #[cfg(test)]
@@ -740,7 +780,9 @@ mod test {
 			substrate_test_runtime_client::runtime::Block,
 			_,
 			_,
-		>(&client, H256::random(), 0, || unreachable!())
+		>(
+			&client, H256::random(), 0, || unreachable!()
+		)
 		.unwrap();
 
 		assert_eq!(
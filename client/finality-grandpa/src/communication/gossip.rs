@@ -1659,6 +1659,7 @@ mod tests {
 		crate::Config {
 			gossip_duration: Duration::from_millis(10),
 			justification_period: 256,
+			justification_archive_period: None,
 			keystore: None,
 			name: None,
 			local_role: Role::Authority,
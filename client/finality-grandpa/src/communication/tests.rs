@@ -227,6 +227,7 @@ fn config() -> crate::Config {
 	crate::Config {
 		gossip_duration: std::time::Duration::from_millis(10),
 		justification_period: 256,
+		justification_archive_period: None,
 		keystore: None,
 		name: None,
 		local_role: Role::Authority,
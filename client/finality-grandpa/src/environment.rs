@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::{
-	collections::{BTreeMap, HashMap},
+	collections::{BTreeMap, BTreeSet, HashMap},
 	iter::FromIterator,
 	marker::PhantomData,
 	pin::Pin,
@@ -33,7 +33,10 @@ use futures_timer::Delay;
 use log::{debug, warn};
 use parity_scale_codec::{Decode, Encode};
 use parking_lot::RwLock;
-use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, U64};
+use prometheus_endpoint::{
+	exponential_buckets, register, Counter, CounterVec, Gauge, Histogram, HistogramOpts, Opts,
+	PrometheusError, U64,
+};
 
 use sc_client_api::{
 	backend::{apply_aux, Backend as BackendT},
@@ -58,6 +61,10 @@ use crate::{
 	local_authority_id,
 	notification::GrandpaJustificationSender,
 	until_imported::UntilVoteTargetImported,
+	voter_history::{
+		DetectedEquivocation, EquivocationKind, EquivocationReport, RoundParticipation,
+		SharedVoterHistory,
+	},
 	voting_rule::VotingRule as VotingRuleT,
 	ClientForGrandpa, CommandOrError, Commit, Config, Error, NewAuthoritySet, Precommit, Prevote,
 	PrimaryPropose, SignedMessage, VoterCommand,
@@ -396,6 +403,9 @@ pub(crate) struct Metrics {
 	finality_grandpa_round: Gauge<U64>,
 	finality_grandpa_prevotes: Counter<U64>,
 	finality_grandpa_precommits: Counter<U64>,
+	finality_grandpa_missed_votes: CounterVec<U64>,
+	finality_grandpa_equivocations: CounterVec<U64>,
+	finality_grandpa_time_to_finality: Histogram,
 }
 
 impl Metrics {
@@ -421,6 +431,39 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			finality_grandpa_missed_votes: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_finality_grandpa_missed_votes_total",
+						"Number of votes of the given kind missing from the authorities in \
+						 completed GRANDPA rounds.",
+					),
+					&["vote"],
+				)?,
+				registry,
+			)?,
+			finality_grandpa_equivocations: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_finality_grandpa_equivocations_total",
+						"Number of GRANDPA equivocations detected, by kind of vote and outcome \
+						 of the equivocation report.",
+					),
+					&["vote", "report"],
+				)?,
+				registry,
+			)?,
+			finality_grandpa_time_to_finality: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"substrate_finality_grandpa_time_to_finality_seconds",
+						"Time between the start of a GRANDPA round and the finalization of a \
+						 block in it.",
+					)
+					.buckets(exponential_buckets(0.25, 2.0, 10)?),
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -437,6 +480,7 @@ pub(crate) struct Environment<Backend, Block: BlockT, C, N: NetworkT<Block>, SC,
 	pub(crate) voter_set_state: SharedVoterSetState<Block>,
 	pub(crate) voting_rule: VR,
 	pub(crate) metrics: Option<Metrics>,
+	pub(crate) voter_history: SharedVoterHistory,
 	pub(crate) justification_sender: Option<GrandpaJustificationSender<Block>>,
 	pub(crate) telemetry: Option<TelemetryHandle>,
 	pub(crate) _phantom: PhantomData<Backend>,
@@ -470,6 +514,74 @@ impl<BE, Block: BlockT, C, N: NetworkT<Block>, SC, VR> Environment<BE, Block, C,
 			Ok(())
 		})
 	}
+
+	/// Notes which authorities voted in the given round. The missed votes are only reported to
+	/// prometheus when the round is `completed`, as the votes are noted again if it concludes.
+	fn note_participation(
+		&self,
+		round: RoundNumber,
+		historical_votes: &HistoricalVotes<Block>,
+		completed: bool,
+	) {
+		let mut prevotes = BTreeSet::new();
+		let mut precommits = BTreeSet::new();
+		for vote in historical_votes.seen() {
+			match vote.message {
+				finality_grandpa::Message::Prevote(_) => prevotes.insert(vote.id.clone()),
+				finality_grandpa::Message::Precommit(_) => precommits.insert(vote.id.clone()),
+				finality_grandpa::Message::PrimaryPropose(_) => false,
+			};
+		}
+
+		// the missed votes of each authority are available through the voter history, as
+		// labelling the metric by authority would make its cardinality unbounded.
+		if completed {
+			if let Some(metrics) = self.metrics.as_ref() {
+				let missed = |votes: &BTreeSet<AuthorityId>| {
+					self.voters.iter().filter(|(id, _)| !votes.contains(*id)).count() as u64
+				};
+				metrics
+					.finality_grandpa_missed_votes
+					.with_label_values(&["prevote"])
+					.inc_by(missed(&prevotes));
+				metrics
+					.finality_grandpa_missed_votes
+					.with_label_values(&["precommit"])
+					.inc_by(missed(&precommits));
+			}
+		}
+
+		self.voter_history.write().note_participation(RoundParticipation {
+			set_id: self.set_id,
+			round,
+			prevotes,
+			precommits,
+		});
+	}
+
+	/// Notes a detected equivocation along with the outcome of its report.
+	fn note_equivocation(
+		&self,
+		round: RoundNumber,
+		offender: AuthorityId,
+		kind: EquivocationKind,
+		report: EquivocationReport,
+	) {
+		if let Some(metrics) = self.metrics.as_ref() {
+			metrics
+				.finality_grandpa_equivocations
+				.with_label_values(&[kind.as_str(), report.as_str()])
+				.inc();
+		}
+
+		self.voter_history.write().note_equivocation(DetectedEquivocation {
+			set_id: self.set_id,
+			round,
+			offender,
+			kind,
+			report,
+		});
+	}
 }
 
 impl<BE, Block, C, N, SC, VR> Environment<BE, Block, C, N, SC, VR>
@@ -486,10 +598,11 @@ where
 	/// extrinsic to report the equivocation. In particular, the session membership
 	/// proof must be generated at the block at which the given set was active which
 	/// isn't necessarily the best block if there are pending authority set changes.
+	/// Returns whether a report was submitted.
 	pub(crate) fn report_equivocation(
 		&self,
 		equivocation: Equivocation<Block::Hash, NumberFor<Block>>,
-	) -> Result<(), Error> {
+	) -> Result<bool, Error> {
 		if let Some(local_id) = self.voter_set_state.voting_on(equivocation.round_number()) {
 			if *equivocation.offender() == local_id {
 				return Err(Error::Safety(
@@ -552,7 +665,7 @@ where
 			Some(proof) => proof,
 			None => {
 				debug!(target: "afg", "Equivocation offender is not part of the authority set.");
-				return Ok(())
+				return Ok(false)
 			},
 		};
 
@@ -568,7 +681,27 @@ where
 			)
 			.map_err(Error::RuntimeApi)?;
 
-		Ok(())
+		Ok(true)
+	}
+
+	/// Reports the given equivocation and notes it in the voter history.
+	fn handle_equivocation(
+		&self,
+		round: RoundNumber,
+		kind: EquivocationKind,
+		equivocation: Equivocation<Block::Hash, NumberFor<Block>>,
+	) {
+		let offender = equivocation.offender().clone();
+		let report = match self.report_equivocation(equivocation) {
+			Ok(true) => EquivocationReport::Submitted,
+			Ok(false) => EquivocationReport::NotSubmitted,
+			Err(err) => {
+				warn!(target: "afg", "Error reporting {} equivocation: {}", kind.as_str(), err);
+				EquivocationReport::Failed(err.to_string())
+			},
+		};
+
+		self.note_equivocation(round, offender, kind, report);
 	}
 }
 
@@ -727,6 +860,8 @@ where
 			self.voter_set_state.started_voting_on(round, id.clone());
 		}
 
+		self.voter_history.write().note_round_started(self.set_id, round);
+
 		// we can only sign when we have a local key in the authority set
 		// and we have a reference to the keystore.
 		let keystore = match (local_id.as_ref(), self.config.keystore.as_ref()) {
@@ -998,6 +1133,8 @@ where
 			Ok(Some(set_state))
 		})?;
 
+		self.note_participation(round, historical_votes, true);
+
 		// clear any cached local authority id associated with this round
 		self.voter_set_state.finished_voting_on(round);
 
@@ -1057,6 +1194,8 @@ where
 			Ok(Some(set_state))
 		})?;
 
+		self.note_participation(round, historical_votes, false);
+
 		Ok(())
 	}
 
@@ -1067,10 +1206,19 @@ where
 		round: RoundNumber,
 		commit: Commit<Block>,
 	) -> Result<(), Self::Error> {
+		if let Some(metrics) = self.metrics.as_ref() {
+			if let Some(started) = self.voter_history.read().round_started(self.set_id, round) {
+				metrics
+					.finality_grandpa_time_to_finality
+					.observe(started.elapsed().as_secs_f64());
+			}
+		}
+
 		finalize_block(
 			self.client.clone(),
 			&self.authority_set,
 			Some(self.config.justification_period.into()),
+			self.config.justification_archive_period.map(Into::into),
 			hash,
 			number,
 			(round, commit).into(),
@@ -1091,24 +1239,20 @@ where
 
 	fn prevote_equivocation(
 		&self,
-		_round: RoundNumber,
+		round: RoundNumber,
 		equivocation: finality_grandpa::Equivocation<Self::Id, Prevote<Block>, Self::Signature>,
 	) {
 		warn!(target: "afg", "Detected prevote equivocation in the finality worker: {:?}", equivocation);
-		if let Err(err) = self.report_equivocation(equivocation.into()) {
-			warn!(target: "afg", "Error reporting prevote equivocation: {}", err);
-		}
+		self.handle_equivocation(round, EquivocationKind::Prevote, equivocation.into());
 	}
 
 	fn precommit_equivocation(
 		&self,
-		_round: RoundNumber,
+		round: RoundNumber,
 		equivocation: finality_grandpa::Equivocation<Self::Id, Precommit<Block>, Self::Signature>,
 	) {
 		warn!(target: "afg", "Detected precommit equivocation in the finality worker: {:?}", equivocation);
-		if let Err(err) = self.report_equivocation(equivocation.into()) {
-			warn!(target: "afg", "Error reporting precommit equivocation: {}", err);
-		}
+		self.handle_equivocation(round, EquivocationKind::Precommit, equivocation.into());
 	}
 }
 
@@ -1238,6 +1382,7 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 	client: Arc<Client>,
 	authority_set: &SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
 	justification_period: Option<NumberFor<Block>>,
+	justification_archive_period: Option<NumberFor<Block>>,
 	hash: Block::Hash,
 	number: NumberFor<Block>,
 	justification_or_commit: JustificationOrCommit<Block>,
@@ -1302,6 +1447,8 @@ where
 		// `N+1`. this assumption is required to make sure we store
 		// justifications for transition blocks which will be requested by
 		// syncing clients.
+		let last_finalized_number = client.info().finalized_number;
+
 		let (justification_required, justification) = match justification_or_commit {
 			JustificationOrCommit::Justification(justification) => (true, justification),
 			JustificationOrCommit::Commit((round_number, commit)) => {
@@ -1314,7 +1461,6 @@ where
 				// finalization to remote nodes
 				if !justification_required {
 					if let Some(justification_period) = justification_period {
-						let last_finalized_number = client.info().finalized_number;
 						justification_required = (!last_finalized_number.is_zero() ||
 							number - last_finalized_number == justification_period) &&
 							(last_finalized_number / justification_period !=
@@ -1359,6 +1505,17 @@ where
 			apply_aux(import_op, insert, &[])
 		})?;
 
+		// archive a justification every N blocks, these are kept regardless of the ones stored
+		// with the blocks.
+		let archive_required = justification_archive_period
+			.filter(|period| !period.is_zero())
+			.map_or(false, |period| last_finalized_number / period != number / period);
+		if archive_required {
+			crate::aux_schema::archive_justification(&justification, |insert| {
+				apply_aux(import_op, insert, &[])
+			})?;
+		}
+
 		let new_authorities = if let Some((canon_hash, canon_number)) = status.new_set_block {
 			// the authority set has changed.
 			let (new_id, set_ref) = authority_set.current();
@@ -52,6 +52,7 @@ use crate::{
 	authorities::{AuthoritySetChangeId, AuthoritySetChanges},
 	best_justification,
 	justification::GrandpaJustification,
+	justification_archive::stored_justification,
 	SharedAuthoritySet,
 };
 
@@ -116,6 +117,25 @@ where
 
 		prove_finality(&*self.backend, authority_set_changes, block)
 	}
+
+	/// Prove finality of the blocks in `(begin; end]` by returning a [`RangeFinalityProof`].
+	pub fn prove_finality_range(
+		&self,
+		begin: NumberFor<Block>,
+		end: NumberFor<Block>,
+	) -> Result<Option<Vec<u8>>, FinalityProofError> {
+		let authority_set_changes = if let Some(changes) = self
+			.shared_authority_set
+			.as_ref()
+			.map(SharedAuthoritySet::authority_set_changes)
+		{
+			changes
+		} else {
+			return Ok(None)
+		};
+
+		prove_finality_range(&*self.backend, authority_set_changes, begin, end)
+	}
 }
 
 /// Finality for block B is proved by providing:
@@ -131,6 +151,19 @@ pub struct FinalityProof<Header: HeaderT> {
 	pub unknown_headers: Vec<Header>,
 }
 
+/// Finality of the blocks in the range (B; E] is proved by providing, in order:
+/// 1) the justification for the last block of each authority set ending in the range;
+/// 2) the justification for E, or for the earliest descendant of E it is known for.
+///
+/// Each justification is signed by the authority set enacted by the previous one, the first one
+/// by the authority set of block B. Unlike [`FinalityProof`] no headers are included, the proof
+/// only contains the justifications.
+#[derive(Debug, PartialEq, Encode, Decode, Clone)]
+pub struct RangeFinalityProof {
+	/// Encoded GRANDPA justifications, ordered by block number.
+	pub justifications: Vec<Vec<u8>>,
+}
+
 /// Errors occurring when trying to prove finality
 #[derive(Debug, thiserror::Error)]
 pub enum FinalityProofError {
@@ -141,6 +174,9 @@ pub enum FinalityProofError {
 	/// in the latest authority set, and the subscription API is more appropriate.
 	#[error("Block not covered by authority set changes")]
 	BlockNotInAuthoritySetChanges,
+	/// The end of the requested range is lower than its beginning.
+	#[error("Invalid block range")]
+	InvalidRange,
 	/// Errors originating from the client.
 	#[error(transparent)]
 	Client(#[from] sp_blockchain::Error),
@@ -236,8 +272,99 @@ where
 	))
 }
 
+fn prove_finality_range<Block, B>(
+	backend: &B,
+	authority_set_changes: AuthoritySetChanges<NumberFor<Block>>,
+	begin: NumberFor<Block>,
+	end: NumberFor<Block>,
+) -> Result<Option<Vec<u8>>, FinalityProofError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	if end <= begin {
+		return Err(FinalityProofError::InvalidRange)
+	}
+
+	let info = backend.blockchain().info();
+	if info.finalized_number < end {
+		trace!(
+			target: "afg",
+			"Requested finality proof for range ending at #{} while we only have finalized #{}.",
+			end,
+			info.finalized_number,
+		);
+		return Err(FinalityProofError::BlockNotYetFinalized)
+	}
+
+	let changes = authority_set_changes.iter_from(begin).ok_or_else(|| {
+		warn!(
+			target: "afg",
+			"AuthoritySetChanges does not cover the requested block #{} due to missing data. \
+			 You need to resync to populate AuthoritySetChanges properly.",
+			begin,
+		);
+		FinalityProofError::BlockNotInAuthoritySetChanges
+	})?;
+
+	// The last blocks of the sets ending in the range come with a justification, unless the set
+	// ended with a forced change, in which case the range can't be proven.
+	let mut justifications = Vec::new();
+	let mut last_block_of_end_set = None;
+	for (_, last_block_for_set) in changes {
+		if *last_block_for_set >= end {
+			last_block_of_end_set = Some(*last_block_for_set);
+			break
+		}
+
+		match stored_justification(backend, *last_block_for_set)? {
+			Some(justification) => justifications.push(justification),
+			None => {
+				trace!(
+					target: "afg",
+					"No justification found for the last block #{} of a set. Returning empty proof.",
+					last_block_for_set,
+				);
+				return Ok(None)
+			},
+		}
+	}
+
+	// Find the earliest justification finalizing the end of the range, within the set of `end`.
+	let limit = last_block_of_end_set.unwrap_or(info.finalized_number);
+	let mut current = end;
+	let mut end_justification = None;
+	while current <= limit {
+		end_justification = stored_justification(backend, current)?;
+		if end_justification.is_some() {
+			break
+		}
+		current += One::one();
+	}
+
+	if end_justification.is_none() && last_block_of_end_set.is_none() {
+		end_justification = best_justification(backend)?
+			.filter(|j: &GrandpaJustification<Block>| j.target().0 >= end)
+			.map(|j| j.encode());
+	}
+
+	match end_justification {
+		Some(justification) => justifications.push(justification),
+		None => {
+			trace!(
+				target: "afg",
+				"No justification found for block #{} or its descendants. Returning empty proof.",
+				end,
+			);
+			return Ok(None)
+		},
+	}
+
+	Ok(Some(RangeFinalityProof { justifications }.encode()))
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
 	use super::*;
 	use crate::{authorities::AuthoritySetChanges, BlockNumberOps, ClientError, SetId};
 	use futures::executor::block_on;
@@ -293,7 +420,7 @@ mod tests {
 		)
 	}
 
-	fn test_blockchain(
+	pub(crate) fn test_blockchain(
 		number_of_blocks: u64,
 		to_finalize: &[u64],
 	) -> (Arc<TestClient>, Arc<TestBackend>, Vec<Block>) {
@@ -397,7 +524,7 @@ mod tests {
 		.unwrap_err();
 	}
 
-	fn create_commit<S, Id>(
+	pub(crate) fn create_commit<S, Id>(
 		block: Block,
 		round: u64,
 		set_id: SetId,
@@ -548,4 +675,56 @@ mod tests {
 			}
 		);
 	}
+
+	#[test]
+	fn finality_proof_range_works() {
+		let (client, backend, blocks) = test_blockchain(8, &[2]);
+		let justification = |number: usize| {
+			let round = number as u64;
+			let commit =
+				create_commit(blocks[number - 1].clone(), round, 0, &[Ed25519Keyring::Alice]);
+			GrandpaJustification::<Block>::from_commit(&client, round, commit).unwrap()
+		};
+		let prove = |begin, end| {
+			let mut authority_set_changes = AuthoritySetChanges::empty();
+			authority_set_changes.append(0, 3);
+			authority_set_changes.append(1, 5);
+			prove_finality_range(&*backend, authority_set_changes, begin, end)
+				.map(|proof| proof.map(|p| RangeFinalityProof::decode(&mut &p[..]).unwrap()))
+		};
+		let proof = |numbers: &[usize]| RangeFinalityProof {
+			justifications: numbers.iter().map(|n| justification(*n).encode()).collect(),
+		};
+
+		// Sets end at blocks 3 and 5, which are finalized with a justification, block 7 has an
+		// archived justification and block 8 is finalized without justification.
+		client
+			.finalize_block(BlockId::Number(3), Some((ID, justification(3).encode())))
+			.unwrap();
+		client
+			.finalize_block(BlockId::Number(5), Some((ID, justification(5).encode())))
+			.unwrap();
+		client
+			.lock_import_and_run(|import_op| {
+				crate::aux_schema::archive_justification(&justification(7), |insert| {
+					apply_aux(import_op, insert, &[])
+				})
+			})
+			.unwrap();
+		client.finalize_block(BlockId::Number(8), None).unwrap();
+
+		// Both set changes and the earliest justification of the last set are needed.
+		assert_eq!(prove(1, 6).unwrap(), Some(proof(&[3, 5, 7])));
+
+		// The end of the range is finalized by the last block of its set.
+		assert_eq!(prove(3, 4).unwrap(), Some(proof(&[5])));
+
+		// No justification of block 8 is known until the best justification is.
+		assert_eq!(prove(5, 8).unwrap(), None);
+		store_best_justification(&client, &justification(8));
+		assert_eq!(prove(5, 8).unwrap(), Some(proof(&[8])));
+
+		assert!(matches!(prove(4, 4), Err(FinalityProofError::InvalidRange)));
+		assert!(matches!(prove(1, 9), Err(FinalityProofError::BlockNotYetFinalized)));
+	}
 }
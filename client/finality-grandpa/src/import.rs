@@ -791,6 +791,7 @@ where
 			self.inner.clone(),
 			&self.authority_set,
 			None,
+			None,
 			hash,
 			number,
 			justification.into(),
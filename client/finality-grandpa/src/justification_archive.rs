@@ -0,0 +1,372 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export and import of the GRANDPA justifications stored by a node.
+//!
+//! A node stores the justifications of the blocks enacting authority set changes and of some
+//! other blocks with the blocks themselves, and optionally archives more of them (see
+//! [`crate::Config::justification_archive_period`]). The justifications of a block range can be
+//! exported to a file and imported into the archive of another node, e.g. to serve range finality
+//! proofs without having to resync.
+
+use log::debug;
+use parity_scale_codec::{Decode, Encode};
+use std::io;
+
+use sc_client_api::backend::Backend;
+use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
+use sp_finality_grandpa::{AuthorityList, SetId, GRANDPA_ENGINE_ID};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, NumberFor, One},
+};
+
+use crate::{
+	authorities::{AuthoritySet, AuthoritySetChangeId},
+	aux_schema::{archive_justification, archived_justification, load_authority_set},
+	find_scheduled_change,
+	justification::GrandpaJustification,
+	GenesisAuthoritySetProvider,
+};
+
+/// Errors occurring when exporting or importing justifications.
+#[derive(Debug, thiserror::Error)]
+pub enum JustificationArchiveError {
+	/// The requested range is not finalized yet.
+	#[error("Block #{0} is not finalized yet")]
+	BlockNotYetFinalized(String),
+	/// An imported justification is for a block that is not in the local finalized chain.
+	#[error("Justification for unknown block #{0}")]
+	UnknownBlock(String),
+	/// The authority set that should have signed an imported justification is not known.
+	#[error("Unknown authority set for block #{0}")]
+	UnknownAuthoritySet(String),
+	/// An imported justification is not signed by the authority set of its block.
+	#[error("Invalid justification for block #{0}: {1}")]
+	InvalidJustification(String, #[source] sp_blockchain::Error),
+	/// Failed to decode the justifications.
+	#[error("Failed to decode justifications: {0}")]
+	Decode(#[from] parity_scale_codec::Error),
+	/// Failed to read or write the justifications.
+	#[error(transparent)]
+	Io(#[from] io::Error),
+	/// Errors originating from the client.
+	#[error(transparent)]
+	Client(#[from] sp_blockchain::Error),
+}
+
+/// Fetch the encoded GRANDPA justification of the finalized block `number`, if the node stores
+/// one, either with the block or in the archive.
+pub(crate) fn stored_justification<Block, B>(
+	backend: &B,
+	number: NumberFor<Block>,
+) -> sp_blockchain::Result<Option<Vec<u8>>>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	let stored = backend
+		.blockchain()
+		.justifications(BlockId::Number(number))?
+		.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID));
+	if stored.is_some() {
+		return Ok(stored)
+	}
+
+	Ok(archived_justification::<_, Block>(backend, number)?.map(|j| j.encode()))
+}
+
+/// Export all the GRANDPA justifications stored for the finalized blocks in `[from, to]`.
+///
+/// The justifications are written to `output` as a SCALE-encoded list of encoded
+/// justifications, ordered by block number. Returns the number of exported justifications.
+pub fn export_justifications<Block, B>(
+	backend: &B,
+	from: NumberFor<Block>,
+	to: NumberFor<Block>,
+	mut output: impl io::Write,
+) -> Result<usize, JustificationArchiveError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	let finalized_number = backend.blockchain().info().finalized_number;
+	if finalized_number < to {
+		return Err(JustificationArchiveError::BlockNotYetFinalized(to.to_string()))
+	}
+
+	let mut justifications = Vec::new();
+	let mut current = from;
+	while current <= to {
+		if let Some(justification) = stored_justification(backend, current)? {
+			justifications.push(justification);
+		}
+		current += One::one();
+	}
+
+	output.write_all(&justifications.encode())?;
+	Ok(justifications.len())
+}
+
+/// Fetch the authorities of the set `set_id`, knowing the current authority set of the node.
+///
+/// The authorities of a past set are taken from the standard change signalled by the last block
+/// of the set preceding it, so sets enacted by a forced change are not known.
+fn set_authorities<Block, B>(
+	backend: &B,
+	authority_set: &AuthoritySet<Block::Hash, NumberFor<Block>>,
+	genesis_authorities_provider: &dyn GenesisAuthoritySetProvider<Block>,
+	set_id: SetId,
+) -> Result<Option<AuthorityList>, JustificationArchiveError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	if set_id == authority_set.set_id {
+		return Ok(Some(authority_set.current_authorities.clone()))
+	}
+	if set_id == 0 {
+		return Ok(Some(genesis_authorities_provider.get()?))
+	}
+
+	let last_block_of_previous_set =
+		match authority_set.authority_set_changes.last_block_of_set(set_id - 1) {
+			Some(number) => number,
+			None => return Ok(None),
+		};
+	let header = backend.blockchain().header(BlockId::Number(last_block_of_previous_set))?;
+	Ok(header
+		.and_then(|header| find_scheduled_change::<Block>(&header))
+		.map(|change| change.next_authorities))
+}
+
+/// Import justifications exported by [`export_justifications`] into the justification archive.
+///
+/// The justifications must be for blocks of the local finalized chain and are verified against
+/// the authority set of the block they finalize, which must be known to the node. Nothing is
+/// imported if any of them is invalid. Returns the number of imported justifications.
+pub fn import_justifications<Block, B>(
+	backend: &B,
+	genesis_authorities_provider: &dyn GenesisAuthoritySetProvider<Block>,
+	mut input: impl io::Read,
+) -> Result<usize, JustificationArchiveError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+	NumberFor<Block>: finality_grandpa::BlockNumberOps,
+{
+	let mut encoded = Vec::new();
+	input.read_to_end(&mut encoded)?;
+	let justifications = Vec::<Vec<u8>>::decode(&mut &encoded[..])?
+		.into_iter()
+		.map(|encoded| GrandpaJustification::<Block>::decode(&mut &encoded[..]))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let authority_set = load_authority_set::<_, Block::Hash, NumberFor<Block>>(backend)?
+		.ok_or_else(|| sp_blockchain::Error::Backend("GRANDPA authority set not found".into()))?;
+	let finalized_number = backend.blockchain().info().finalized_number;
+	let mut authorities: Option<(SetId, AuthorityList)> = None;
+	for justification in &justifications {
+		let (number, hash) = justification.target();
+
+		if number > finalized_number {
+			return Err(JustificationArchiveError::BlockNotYetFinalized(number.to_string()))
+		}
+		if backend.blockchain().hash(number)? != Some(hash) {
+			return Err(JustificationArchiveError::UnknownBlock(number.to_string()))
+		}
+
+		let set_id = match authority_set.authority_set_changes.get_set_id(number) {
+			AuthoritySetChangeId::Latest => authority_set.set_id,
+			AuthoritySetChangeId::Set(set_id, _) => set_id,
+			// no authority set change was recorded yet
+			AuthoritySetChangeId::Unknown if authority_set.set_id == 0 => 0,
+			AuthoritySetChangeId::Unknown =>
+				return Err(JustificationArchiveError::UnknownAuthoritySet(number.to_string())),
+		};
+		if authorities.as_ref().map_or(true, |(id, _)| *id != set_id) {
+			let set_authorities =
+				set_authorities(backend, &authority_set, genesis_authorities_provider, set_id)?
+					.ok_or_else(|| {
+						JustificationArchiveError::UnknownAuthoritySet(number.to_string())
+					})?;
+			authorities = Some((set_id, set_authorities));
+		}
+		let (set_id, set_authorities) = authorities.as_ref().expect("set above; qed");
+
+		justification.verify(*set_id, set_authorities).map_err(|error| {
+			JustificationArchiveError::InvalidJustification(number.to_string(), error)
+		})?;
+	}
+
+	for justification in &justifications {
+		let (number, hash) = justification.target();
+		debug!(target: "afg", "Importing justification for block #{} ({:?})", number, hash);
+		archive_justification(justification, |insert| backend.insert_aux(insert, &[]))?;
+	}
+
+	Ok(justifications.len())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		authorities::AuthoritySetChanges,
+		aux_schema::update_authority_set,
+		finality_proof::tests::{create_commit, test_blockchain},
+	};
+	use fork_tree::ForkTree;
+	use sc_client_api::{apply_aux, LockImportRun};
+	use sp_blockchain::Error as ClientError;
+	use sp_keyring::Ed25519Keyring;
+	use sp_runtime::traits::Block as _;
+	use substrate_test_runtime_client::{runtime::Block, ClientExt};
+
+	struct GenesisAuthorities(AuthorityList);
+
+	impl GenesisAuthoritySetProvider<Block> for GenesisAuthorities {
+		fn get(&self) -> Result<AuthorityList, ClientError> {
+			Ok(self.0.clone())
+		}
+	}
+
+	fn authorities(keyring: Ed25519Keyring) -> AuthorityList {
+		vec![(keyring.public().into(), 1)]
+	}
+
+	fn store_authority_set<B: Backend<Block>>(
+		backend: &B,
+		set: AuthoritySet<<Block as BlockT>::Hash, NumberFor<Block>>,
+	) {
+		update_authority_set::<Block, _, _>(&set, None, |insert| backend.insert_aux(insert, &[]))
+			.unwrap();
+	}
+
+	#[test]
+	fn justifications_roundtrip_through_export_and_import() {
+		let (client, backend, blocks) = test_blockchain(8, &[5]);
+		let justification = |number: usize| {
+			let commit = create_commit(blocks[number - 1].clone(), 1, 0, &[Ed25519Keyring::Alice]);
+			GrandpaJustification::<Block>::from_commit(&client, 1, commit).unwrap()
+		};
+		let genesis_authorities = GenesisAuthorities(authorities(Ed25519Keyring::Alice));
+
+		// the justification of block 6 is archived, the one of block 8 is stored with the block
+		client
+			.lock_import_and_run(|import_op| {
+				archive_justification(&justification(6), |insert| apply_aux(import_op, insert, &[]))
+			})
+			.unwrap();
+		client
+			.finalize_block(
+				BlockId::Number(8),
+				Some((GRANDPA_ENGINE_ID, justification(8).encode())),
+			)
+			.unwrap();
+
+		let mut exported = Vec::new();
+		assert_eq!(export_justifications(&*backend, 1, 8, &mut exported).unwrap(), 2);
+		assert_eq!(
+			Vec::<Vec<u8>>::decode(&mut &exported[..]).unwrap(),
+			vec![justification(6).encode(), justification(8).encode()],
+		);
+
+		// the same blocks are built on another node, up to block 7 being finalized
+		let (other_client, other_backend, _) = test_blockchain(8, &[7]);
+		assert_eq!(other_client.hash(6).unwrap(), Some(blocks[5].hash()));
+		store_authority_set(
+			&*other_backend,
+			AuthoritySet::genesis(authorities(Ed25519Keyring::Alice)).unwrap(),
+		);
+
+		// unfinalized blocks can't be exported nor imported
+		assert!(matches!(
+			export_justifications(&*other_backend, 1, 8, &mut Vec::new()),
+			Err(JustificationArchiveError::BlockNotYetFinalized(_)),
+		));
+		assert!(matches!(
+			import_justifications(&*other_backend, &genesis_authorities, &exported[..]),
+			Err(JustificationArchiveError::BlockNotYetFinalized(_)),
+		));
+
+		other_client.finalize_block(BlockId::Number(8), None).unwrap();
+		assert_eq!(
+			import_justifications(&*other_backend, &genesis_authorities, &exported[..]).unwrap(),
+			2,
+		);
+		assert_eq!(
+			archived_justification::<_, Block>(&*other_backend, 6).unwrap(),
+			Some(justification(6)),
+		);
+		assert_eq!(
+			stored_justification(&*other_backend, 8).unwrap(),
+			Some(justification(8).encode()),
+		);
+		assert_eq!(stored_justification(&*other_backend, 7).unwrap(), None);
+	}
+
+	#[test]
+	fn justifications_are_verified_against_the_authority_set_of_their_block() {
+		let (client, backend, blocks) = test_blockchain(8, &[8]);
+		let justification = |number: usize, set_id, keyring| {
+			let commit = create_commit(blocks[number - 1].clone(), 1, set_id, &[keyring]);
+			GrandpaJustification::<Block>::from_commit(&client, 1, commit).unwrap().encode()
+		};
+		let genesis_authorities = GenesisAuthorities(authorities(Ed25519Keyring::Alice));
+		let import = |justifications: Vec<Vec<u8>>| {
+			import_justifications(&*backend, &genesis_authorities, &justifications.encode()[..])
+		};
+
+		// Alice is the genesis authority, Bob the one of the current set starting after block 4
+		store_authority_set(
+			&*backend,
+			AuthoritySet::new(
+				authorities(Ed25519Keyring::Bob),
+				1,
+				ForkTree::new(),
+				Vec::new(),
+				AuthoritySetChanges::from(vec![(0, 4)]),
+			)
+			.unwrap(),
+		);
+
+		// justifications signed by another authority set are forged, and nothing is imported
+		// alongside them
+		let valid = vec![
+			justification(3, 0, Ed25519Keyring::Alice),
+			justification(6, 1, Ed25519Keyring::Bob),
+		];
+		for forged in [
+			justification(6, 1, Ed25519Keyring::Alice),
+			justification(6, 0, Ed25519Keyring::Alice),
+			justification(3, 0, Ed25519Keyring::Bob),
+			justification(3, 1, Ed25519Keyring::Bob),
+		] {
+			assert!(matches!(
+				import(vec![valid[0].clone(), forged]),
+				Err(JustificationArchiveError::InvalidJustification(_, _)),
+			));
+		}
+		assert_eq!(archived_justification::<_, Block>(&*backend, 3).unwrap(), None);
+
+		assert_eq!(import(valid.clone()).unwrap(), 2);
+		assert_eq!(stored_justification(&*backend, 3).unwrap(), Some(valid[0].clone()));
+		assert_eq!(stored_justification(&*backend, 6).unwrap(), Some(valid[1].clone()));
+	}
+}
@@ -117,21 +117,31 @@ mod environment;
 mod finality_proof;
 mod import;
 mod justification;
+mod justification_archive;
 mod notification;
 mod observer;
 mod until_imported;
+mod voter_history;
 mod voting_rule;
 pub mod warp_proof;
 
 pub use authorities::{AuthoritySet, AuthoritySetChanges, SharedAuthoritySet};
-pub use aux_schema::best_justification;
+pub use aux_schema::{archived_justification, best_justification};
 pub use communication::grandpa_protocol_name::standard_name as protocol_standard_name;
 pub use finality_grandpa::voter::report;
-pub use finality_proof::{FinalityProof, FinalityProofError, FinalityProofProvider};
+pub use finality_proof::{
+	FinalityProof, FinalityProofError, FinalityProofProvider, RangeFinalityProof,
+};
 pub use import::{find_forced_change, find_scheduled_change, GrandpaBlockImport};
 pub use justification::GrandpaJustification;
+pub use justification_archive::{
+	export_justifications, import_justifications, JustificationArchiveError,
+};
 pub use notification::{GrandpaJustificationSender, GrandpaJustificationStream};
 pub use observer::run_grandpa_observer;
+pub use voter_history::{
+	DetectedEquivocation, EquivocationKind, EquivocationReport, RoundParticipation, VoterHistory,
+};
 pub use voting_rule::{
 	BeforeBestBlockBy, ThreeQuartersOfTheUnfinalizedChain, VotingRule, VotingRuleResult,
 	VotingRulesBuilder,
@@ -142,6 +152,7 @@ use communication::{Network as NetworkT, NetworkBridge};
 use environment::{Environment, VoterSetState};
 use sp_finality_grandpa::{AuthorityList, AuthoritySignature, SetId};
 use until_imported::UntilGlobalMessageBlocksImported;
+use voter_history::SharedVoterHistory;
 
 // Re-export these two because it's just so damn convenient.
 pub use sp_finality_grandpa::{AuthorityId, AuthorityPair, GrandpaApi, ScheduledChange};
@@ -214,12 +225,13 @@ type CommunicationOutH<Block, H> =
 /// Shared voter state for querying.
 pub struct SharedVoterState {
 	inner: Arc<RwLock<Option<Box<dyn voter::VoterState<AuthorityId> + Sync + Send>>>>,
+	history: SharedVoterHistory,
 }
 
 impl SharedVoterState {
 	/// Create a new empty `SharedVoterState` instance.
 	pub fn empty() -> Self {
-		Self { inner: Arc::new(RwLock::new(None)) }
+		Self { inner: Arc::new(RwLock::new(None)), history: Default::default() }
 	}
 
 	fn reset(
@@ -236,11 +248,17 @@ impl SharedVoterState {
 	pub fn voter_state(&self) -> Option<voter::report::VoterState<AuthorityId>> {
 		self.inner.read().as_ref().map(|vs| vs.get())
 	}
+
+	/// Get the recent activity of the voter, i.e. the participation of the authorities in the last
+	/// completed rounds and the detected equivocations.
+	pub fn voter_history(&self) -> VoterHistory {
+		self.history.read().clone()
+	}
 }
 
 impl Clone for SharedVoterState {
 	fn clone(&self) -> Self {
-		SharedVoterState { inner: self.inner.clone() }
+		SharedVoterState { inner: self.inner.clone(), history: self.history.clone() }
 	}
 }
 
@@ -253,6 +271,11 @@ pub struct Config {
 	/// at least every justification_period blocks. There are some other events which might cause
 	/// justification generation.
 	pub justification_period: u32,
+	/// Justification archive period (in blocks). If set, GRANDPA stores the justification of a
+	/// finalized block at least every `justification_archive_period` blocks in its archive, where
+	/// it is kept regardless of the justifications stored with the blocks. Archived
+	/// justifications can be exported and served as range finality proofs.
+	pub justification_archive_period: Option<u32>,
 	/// Whether the GRANDPA observer protocol is live on the network and thereby
 	/// a full-node not running as a validator is running the GRANDPA observer
 	/// protocol (we will only issue catch-up requests to authorities when the
@@ -926,6 +949,7 @@ where
 			authority_set: persistent_data.authority_set.clone(),
 			voter_set_state: persistent_data.set_state,
 			metrics: metrics.as_ref().map(|m| m.environment.clone()),
+			voter_history: shared_voter_state.history.clone(),
 			justification_sender: Some(justification_sender),
 			telemetry: telemetry.clone(),
 			_phantom: PhantomData,
@@ -1071,6 +1095,7 @@ where
 					network: self.env.network.clone(),
 					voting_rule: self.env.voting_rule.clone(),
 					metrics: self.env.metrics.clone(),
+					voter_history: self.env.voter_history.clone(),
 					justification_sender: self.env.justification_sender.clone(),
 					telemetry: self.telemetry.clone(),
 					_phantom: PhantomData,
@@ -125,6 +125,7 @@ where
 				client.clone(),
 				&authority_set,
 				None,
+				None,
 				finalized_hash,
 				finalized_number,
 				(round, commit).into(),
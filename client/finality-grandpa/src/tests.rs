@@ -256,6 +256,7 @@ fn initialize_grandpa(
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
+				justification_archive_period: None,
 				keystore: Some(keystore),
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
@@ -406,6 +407,7 @@ fn finalize_3_voters_1_full_observer() {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
+				justification_archive_period: None,
 				keystore: None,
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
@@ -497,6 +499,7 @@ fn transition_3_voters_twice_1_full_observer() {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
+				justification_archive_period: None,
 				keystore: Some(keystore),
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
@@ -956,6 +959,7 @@ fn voter_persists_its_votes() {
 		let config = Config {
 			gossip_duration: TEST_GOSSIP_DURATION,
 			justification_period: 32,
+			justification_archive_period: None,
 			keystore: Some(bob_keystore.clone()),
 			name: Some(format!("peer#{}", 1)),
 			local_role: Role::Authority,
@@ -996,6 +1000,7 @@ fn voter_persists_its_votes() {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
+				justification_archive_period: None,
 				keystore: Some(keystore),
 				name: Some(format!("peer#{}", 0)),
 				local_role: Role::Authority,
@@ -1037,6 +1042,7 @@ fn voter_persists_its_votes() {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
+				justification_archive_period: None,
 				keystore: Some(keystore),
 				name: Some(format!("peer#{}", 0)),
 				local_role: Role::Authority,
@@ -1201,6 +1207,7 @@ fn finalize_3_voters_1_light_observer() {
 		Config {
 			gossip_duration: TEST_GOSSIP_DURATION,
 			justification_period: 32,
+			justification_archive_period: None,
 			keystore: None,
 			name: Some("observer".to_string()),
 			local_role: Role::Full,
@@ -1248,6 +1255,7 @@ fn voter_catches_up_to_latest_round_when_behind() {
 			config: Config {
 				gossip_duration: TEST_GOSSIP_DURATION,
 				justification_period: 32,
+				justification_archive_period: None,
 				keystore,
 				name: Some(format!("peer#{}", peer_id)),
 				local_role: Role::Authority,
@@ -1366,6 +1374,7 @@ where
 	let config = Config {
 		gossip_duration: TEST_GOSSIP_DURATION,
 		justification_period: 32,
+		justification_archive_period: None,
 		keystore,
 		name: None,
 		local_role: Role::Authority,
@@ -1388,6 +1397,7 @@ where
 		network,
 		voting_rule,
 		metrics: None,
+		voter_history: Default::default(),
 		justification_sender: None,
 		telemetry: None,
 		_phantom: PhantomData,
@@ -1739,6 +1749,73 @@ fn grandpa_environment_doesnt_send_equivocation_reports_for_itself() {
 	assert!(environment.report_equivocation(equivocation_proof).is_ok());
 }
 
+#[test]
+fn grandpa_environment_notes_equivocations_and_participation() {
+	use finality_grandpa::voter::Environment;
+
+	let alice = Ed25519Keyring::Alice;
+	let bob = Ed25519Keyring::Bob;
+	let voters = make_ids(&[alice, bob]);
+
+	let mut net = GrandpaTestNet::new(TestApi::new(voters), 1, 0);
+	let peer = net.peer(0);
+	let network_service = peer.network_service().clone();
+	let link = peer.data.lock().take().unwrap();
+	let (keystore, _keystore_path) = create_keystore(alice);
+	let environment = test_environment(&link, Some(keystore), network_service.clone(), ());
+
+	environment.round_data(1);
+
+	let prevote = |target_number| {
+		let prevote = finality_grandpa::Prevote { target_hash: H256::random(), target_number };
+		(prevote, bob.sign(&[]).into())
+	};
+	let equivocation = finality_grandpa::Equivocation {
+		round_number: 1,
+		identity: bob.public().into(),
+		first: prevote(1),
+		second: prevote(2),
+	};
+
+	// the equivocation of bob is noted, no report is submitted since the test runtime doesn't
+	// provide key ownership proofs
+	environment.prevote_equivocation(1, equivocation);
+	let history = environment.voter_history.read().clone();
+	assert_eq!(
+		history.equivocations().cloned().collect::<Vec<_>>(),
+		vec![DetectedEquivocation {
+			set_id: 0,
+			round: 1,
+			offender: bob.public().into(),
+			kind: EquivocationKind::Prevote,
+			report: EquivocationReport::NotSubmitted,
+		}],
+	);
+
+	// only the prevote of alice is seen when the round completes
+	let mut historical_votes = finality_grandpa::HistoricalVotes::new();
+	historical_votes.push_vote(finality_grandpa::SignedMessage {
+		message: finality_grandpa::Message::Prevote(prevote(1).0),
+		signature: alice.sign(&[]).into(),
+		id: alice.public().into(),
+	});
+
+	let genesis = (link.client.info().genesis_hash, 0);
+	let round_state = finality_grandpa::round::State::genesis(genesis);
+	environment.completed(1, round_state, genesis, &historical_votes).unwrap();
+
+	let history = environment.voter_history.read().clone();
+	assert_eq!(
+		history.rounds().cloned().collect::<Vec<_>>(),
+		vec![RoundParticipation {
+			set_id: 0,
+			round: 1,
+			prevotes: vec![alice.public().into()].into_iter().collect(),
+			precommits: Default::default(),
+		}],
+	);
+}
+
 #[test]
 fn revert_prunes_authority_changes() {
 	sp_tracing::try_init_simple();
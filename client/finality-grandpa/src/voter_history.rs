@@ -0,0 +1,208 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bounded history of the recent activity of the GRANDPA voter, used for introspection.
+//!
+//! The voter environment records which authorities voted in each of the last rounds it
+//! completed, as well as the equivocations it detected and whether they were reported to the
+//! runtime. The history is shared with the RPC layer through [`crate::SharedVoterState`].
+
+use std::{
+	collections::{BTreeMap, BTreeSet, VecDeque},
+	sync::Arc,
+	time::Instant,
+};
+
+use parking_lot::RwLock;
+use sp_finality_grandpa::{AuthorityId, RoundNumber, SetId};
+
+/// The maximum number of rounds and equivocations kept in the history.
+pub(crate) const MAX_HISTORY_LENGTH: usize = 256;
+
+/// Voter history shared between the voter environment and its consumers.
+pub(crate) type SharedVoterHistory = Arc<RwLock<VoterHistory>>;
+
+/// The authorities that participated in a completed round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundParticipation {
+	/// The authority set id of the round.
+	pub set_id: SetId,
+	/// The round number.
+	pub round: RoundNumber,
+	/// The authorities whose prevote was seen in the round.
+	pub prevotes: BTreeSet<AuthorityId>,
+	/// The authorities whose precommit was seen in the round.
+	pub precommits: BTreeSet<AuthorityId>,
+}
+
+/// The kind of vote an authority equivocated on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquivocationKind {
+	/// Two different prevotes in the same round.
+	Prevote,
+	/// Two different precommits in the same round.
+	Precommit,
+}
+
+impl EquivocationKind {
+	/// A label identifying the kind of vote, e.g. for metrics.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			EquivocationKind::Prevote => "prevote",
+			EquivocationKind::Precommit => "precommit",
+		}
+	}
+}
+
+/// The outcome of reporting an equivocation to the runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquivocationReport {
+	/// The report was submitted with `submit_report_equivocation_unsigned_extrinsic`.
+	Submitted,
+	/// The offender is not part of the authority set according to the runtime, no report was
+	/// submitted.
+	NotSubmitted,
+	/// Reporting the equivocation failed.
+	Failed(String),
+}
+
+impl EquivocationReport {
+	/// A label identifying the outcome, e.g. for metrics.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			EquivocationReport::Submitted => "submitted",
+			EquivocationReport::NotSubmitted => "not_submitted",
+			EquivocationReport::Failed(_) => "failed",
+		}
+	}
+}
+
+/// An equivocation detected by the voter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedEquivocation {
+	/// The authority set id of the round the equivocation happened in.
+	pub set_id: SetId,
+	/// The round the equivocation happened in.
+	pub round: RoundNumber,
+	/// The equivocating authority.
+	pub offender: AuthorityId,
+	/// The kind of vote the offender equivocated on.
+	pub kind: EquivocationKind,
+	/// The outcome of reporting the equivocation.
+	pub report: EquivocationReport,
+}
+
+/// The recent activity of the GRANDPA voter.
+#[derive(Debug, Clone, Default)]
+pub struct VoterHistory {
+	rounds: VecDeque<RoundParticipation>,
+	equivocations: VecDeque<DetectedEquivocation>,
+	round_starts: BTreeMap<(SetId, RoundNumber), Instant>,
+}
+
+impl VoterHistory {
+	/// The participation of the authorities in the last completed rounds, oldest first.
+	pub fn rounds(&self) -> impl Iterator<Item = &RoundParticipation> {
+		self.rounds.iter()
+	}
+
+	/// The last detected equivocations, oldest first.
+	pub fn equivocations(&self) -> impl Iterator<Item = &DetectedEquivocation> {
+		self.equivocations.iter()
+	}
+
+	/// Note that the voter started the given round.
+	pub(crate) fn note_round_started(&mut self, set_id: SetId, round: RoundNumber) {
+		self.round_starts.entry((set_id, round)).or_insert_with(Instant::now);
+
+		while self.round_starts.len() > MAX_HISTORY_LENGTH {
+			let oldest = *self.round_starts.keys().next().expect("map is not empty; qed");
+			self.round_starts.remove(&oldest);
+		}
+	}
+
+	/// The time at which the voter started the given round, if it is still known.
+	pub(crate) fn round_started(&self, set_id: SetId, round: RoundNumber) -> Option<Instant> {
+		self.round_starts.get(&(set_id, round)).copied()
+	}
+
+	/// Note the participation in a round, replacing any previous participation noted for it.
+	pub fn note_participation(&mut self, participation: RoundParticipation) {
+		if let Some(existing) = self
+			.rounds
+			.iter_mut()
+			.find(|r| r.set_id == participation.set_id && r.round == participation.round)
+		{
+			*existing = participation;
+			return
+		}
+
+		self.rounds.push_back(participation);
+		if self.rounds.len() > MAX_HISTORY_LENGTH {
+			self.rounds.pop_front();
+		}
+	}
+
+	/// Note a detected equivocation.
+	pub fn note_equivocation(&mut self, equivocation: DetectedEquivocation) {
+		self.equivocations.push_back(equivocation);
+		if self.equivocations.len() > MAX_HISTORY_LENGTH {
+			self.equivocations.pop_front();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::crypto::ByteArray;
+
+	fn participation(round: RoundNumber, voters: &[u8]) -> RoundParticipation {
+		let ids = voters
+			.iter()
+			.map(|v| AuthorityId::from_slice(&[*v; 32]).unwrap())
+			.collect::<BTreeSet<_>>();
+		RoundParticipation { set_id: 0, round, prevotes: ids.clone(), precommits: ids }
+	}
+
+	#[test]
+	fn participation_is_replaced_and_bounded() {
+		let mut history = VoterHistory::default();
+
+		history.note_participation(participation(1, &[1]));
+		history.note_participation(participation(1, &[1, 2]));
+		assert_eq!(history.rounds().collect::<Vec<_>>(), vec![&participation(1, &[1, 2])]);
+
+		for round in 2..=MAX_HISTORY_LENGTH as u64 + 1 {
+			history.note_participation(participation(round, &[1]));
+		}
+		assert_eq!(history.rounds().count(), MAX_HISTORY_LENGTH);
+		assert_eq!(history.rounds().next().map(|r| r.round), Some(2));
+	}
+
+	#[test]
+	fn round_starts_are_bounded() {
+		let mut history = VoterHistory::default();
+
+		for round in 1..=MAX_HISTORY_LENGTH as u64 + 1 {
+			history.note_round_started(0, round);
+		}
+		assert!(history.round_started(0, 1).is_none());
+		assert!(history.round_started(0, MAX_HISTORY_LENGTH as u64 + 1).is_some());
+	}
+}
@@ -50,8 +50,8 @@ use sc_consensus::{
 pub use sc_network::config::EmptyTransactionPool;
 use sc_network::{
 	config::{
-		NetworkConfiguration, NonDefaultSetConfig, NonReservedPeerMode, Role, SyncMode,
-		TransportConfig,
+		NetworkConfiguration, NonDefaultSetConfig, NonReservedPeerMode, RequestResponseConfig,
+		Role, SyncMode, TransportConfig,
 	},
 	Multiaddr, NetworkService, NetworkWorker,
 };
@@ -683,6 +683,8 @@ pub struct FullPeerConfig {
 	pub block_announce_validator: Option<Box<dyn BlockAnnounceValidator<Block> + Send + Sync>>,
 	/// List of notification protocols that the network must support.
 	pub notifications_protocols: Vec<ProtocolName>,
+	/// List of request-response protocols that the network must support.
+	pub request_response_protocols: Vec<RequestResponseConfig>,
 	/// The indices of the peers the peer should be connected to.
 	///
 	/// If `None`, it will be connected to all other peers.
@@ -795,6 +797,7 @@ where
 				set_config: Default::default(),
 			})
 			.collect();
+		network_config.request_response_protocols = config.request_response_protocols;
 		if let Some(connect_to) = config.connect_to_peers {
 			let addrs = connect_to
 				.iter()
@@ -21,7 +21,7 @@ use std::{collections::HashSet, str::FromStr, sync::Arc, thread::sleep};
 use crate::NetworkProvider;
 use codec::{Decode, Encode};
 use futures::Future;
-pub use http::SharedClient;
+pub use http::{HttpConfig, SharedClient};
 use libp2p::{Multiaddr, PeerId};
 use sp_core::{
 	offchain::{
@@ -33,6 +33,7 @@ use sp_core::{
 pub use sp_offchain::STORAGE_PREFIX;
 
 mod http;
+mod proxy;
 
 mod timestamp;
 
@@ -138,6 +139,32 @@ impl<Storage: OffchainStorage> offchain::DbExternalities for Db<Storage> {
 		);
 		result
 	}
+
+	fn local_storage_keys(
+		&mut self,
+		kind: StorageKind,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: u32,
+	) -> Vec<Vec<u8>> {
+		let result = match kind {
+			StorageKind::PERSISTENT => self
+				.persistent
+				.keys(STORAGE_PREFIX, prefix, start_key, count as usize)
+				.unwrap_or_default(),
+			StorageKind::LOCAL => unavailable_yet(LOCAL_DB),
+		};
+		tracing::debug!(
+			target: "offchain-worker::storage",
+			?kind,
+			prefix = ?hex::encode(prefix),
+			start_key = ?start_key.map(hex::encode),
+			count,
+			found = result.len(),
+			"Keys",
+		);
+		result
+	}
 }
 
 /// Asynchronous offchain API.
@@ -424,7 +451,7 @@ mod tests {
 	fn offchain_api() -> (Api, AsyncApi) {
 		sp_tracing::try_init_simple();
 		let mock = Arc::new(TestNetwork());
-		let shared_client = SharedClient::new();
+		let shared_client = SharedClient::with_config(Default::default());
 
 		AsyncApi::new(mock, false, shared_client)
 	}
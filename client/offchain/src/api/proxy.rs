@@ -0,0 +1,178 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Connector that establishes the connections of the HTTP client of the offchain workers,
+//! optionally through an HTTP proxy.
+//!
+//! Plain HTTP requests are sent to the proxy in absolute form. HTTPS requests are tunneled through
+//! the proxy with an HTTP `CONNECT` request, so that TLS is still established end-to-end with the
+//! destination.
+
+use futures::prelude::*;
+use hyper::{
+	client::connect::{Connected, Connection, HttpConnector},
+	service::Service,
+	Uri,
+};
+use std::{
+	io,
+	pin::Pin,
+	task::{Context, Poll},
+};
+use tokio::{
+	io::{AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _, ReadBuf},
+	net::TcpStream,
+};
+
+/// Maximum size of the response of a proxy to a `CONNECT` request.
+const MAX_CONNECT_RESPONSE_SIZE: usize = 8 * 1024;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Connects to the destination of a request, either directly or through a proxy.
+#[derive(Clone)]
+pub(crate) struct ProxyConnector {
+	http: HttpConnector,
+	http_proxy: Option<Uri>,
+	https_proxy: Option<Uri>,
+}
+
+impl ProxyConnector {
+	/// Create a new connector that sends plain HTTP requests through `http_proxy` and tunnels
+	/// HTTPS requests through `https_proxy`.
+	pub(crate) fn new(http_proxy: Option<Uri>, https_proxy: Option<Uri>) -> Self {
+		let mut http = HttpConnector::new();
+		// The TLS layer on top of this connector takes care of `https` URIs.
+		http.enforce_http(false);
+		Self { http, http_proxy, https_proxy }
+	}
+}
+
+impl Service<Uri> for ProxyConnector {
+	type Response = ProxyStream;
+	type Error = BoxError;
+	type Future = Pin<Box<dyn Future<Output = Result<ProxyStream, BoxError>> + Send>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.http.poll_ready(cx).map_err(Into::into)
+	}
+
+	fn call(&mut self, dst: Uri) -> Self::Future {
+		let is_https = dst.scheme() == Some(&hyper::http::uri::Scheme::HTTPS);
+		let proxy = if is_https { self.https_proxy.clone() } else { self.http_proxy.clone() };
+		let mut http = self.http.clone();
+
+		Box::pin(async move {
+			let proxy = match proxy {
+				Some(proxy) => proxy,
+				None => return Ok(ProxyStream { stream: http.call(dst).await?, proxied: false }),
+			};
+
+			let mut stream = http.call(proxy).await?;
+			if is_https {
+				tunnel(&mut stream, &dst).await?;
+			}
+
+			// Requests sent through a tunnel look exactly like requests sent to the destination.
+			Ok(ProxyStream { stream, proxied: !is_https })
+		})
+	}
+}
+
+/// Ask the proxy connected to by `stream` to open a tunnel to `dst`.
+async fn tunnel(stream: &mut TcpStream, dst: &Uri) -> io::Result<()> {
+	let host = dst
+		.host()
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "URI without host"))?;
+	let port = dst.port_u16().unwrap_or(443);
+
+	let request = format!("CONNECT {0}:{1} HTTP/1.1\r\nHost: {0}:{1}\r\n\r\n", host, port);
+	stream.write_all(request.as_bytes()).await?;
+
+	// The destination doesn't send anything before the TLS handshake was started, so we can't
+	// accidentally read past the response of the proxy.
+	let mut response = Vec::new();
+	let mut buffer = [0; 1024];
+	while !response.windows(4).any(|window| window == b"\r\n\r\n") {
+		if response.len() > MAX_CONNECT_RESPONSE_SIZE {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "proxy response too large"))
+		}
+
+		let read = stream.read(&mut buffer).await?;
+		if read == 0 {
+			return Err(io::ErrorKind::UnexpectedEof.into())
+		}
+		response.extend_from_slice(&buffer[..read]);
+	}
+
+	let status = response.split(|byte| *byte == b' ').nth(1);
+	if status == Some(b"200") {
+		Ok(())
+	} else {
+		Err(io::Error::new(
+			io::ErrorKind::Other,
+			format!(
+				"proxy refused to open a tunnel: {}",
+				String::from_utf8_lossy(
+					response.split(|byte| *byte == b'\r').next().unwrap_or(&[])
+				)
+			),
+		))
+	}
+}
+
+/// A connection to the destination of a request or to a proxy.
+pub(crate) struct ProxyStream {
+	stream: TcpStream,
+	/// Whether requests have to be sent in absolute form, because the connection is to a proxy.
+	proxied: bool,
+}
+
+impl Connection for ProxyStream {
+	fn connected(&self) -> Connected {
+		self.stream.connected().proxy(self.proxied)
+	}
+}
+
+impl AsyncRead for ProxyStream {
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.stream).poll_read(cx, buf)
+	}
+}
+
+impl AsyncWrite for ProxyStream {
+	fn poll_write(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.stream).poll_write(cx, buf)
+	}
+
+	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.stream).poll_flush(cx)
+	}
+
+	fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.stream).poll_shutdown(cx)
+	}
+}
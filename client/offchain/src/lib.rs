@@ -35,25 +35,36 @@
 
 #![warn(missing_docs)]
 
-use std::{fmt, marker::PhantomData, sync::Arc};
+use std::{
+	fmt,
+	marker::PhantomData,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	time::Instant,
+};
 
 use futures::{
 	future::{ready, Future},
 	prelude::*,
 };
 use parking_lot::Mutex;
+use prometheus_endpoint::Registry;
 use sc_network_common::service::{NetworkPeers, NetworkStateInfo};
 use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
 use sp_core::{offchain, traits::SpawnNamed, ExecutionContext};
 use sp_runtime::{
 	generic::BlockId,
-	traits::{self, Header},
+	traits::{self, Header, Zero},
 };
 use threadpool::ThreadPool;
 
 mod api;
+mod metrics;
 
-pub use api::Db as OffchainDb;
+pub use api::{Db as OffchainDb, HttpConfig as OffchainHttpConfig};
 pub use sp_offchain::{OffchainWorkerApi, STORAGE_PREFIX};
 
 const LOG_TARGET: &str = "offchain-worker";
@@ -64,12 +75,65 @@ pub trait NetworkProvider: NetworkStateInfo + NetworkPeers {}
 
 impl<T> NetworkProvider for T where T: NetworkStateInfo + NetworkPeers {}
 
+/// Blocks the offchain workers are run for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffchainWorkerSchedule {
+	/// Run for every imported block that becomes the new best block.
+	BestBlocks,
+	/// Run for every finalized block.
+	///
+	/// If many blocks are finalized at once, only the most recent ones are run for, up to the
+	/// maximum number of concurrent offchain workers.
+	FinalizedBlocks,
+}
+
+impl Default for OffchainWorkerSchedule {
+	fn default() -> Self {
+		Self::BestBlocks
+	}
+}
+
+/// Scheduling of the offchain workers.
+#[derive(Debug, Clone)]
+pub struct OffchainWorkerScheduling {
+	/// Blocks the offchain workers are run for.
+	pub schedule: OffchainWorkerSchedule,
+	/// Only run for blocks whose number is a multiple of the interval.
+	///
+	/// An interval of `0` or `1` runs the offchain workers for every scheduled block.
+	pub interval: u32,
+	/// Maximum number of offchain workers that run concurrently.
+	///
+	/// Defaults to the number of CPUs.
+	pub max_concurrent_workers: Option<usize>,
+	/// Skip blocks while `max_concurrent_workers` offchain workers are running, instead of
+	/// queueing them until a worker finished.
+	pub skip_if_busy: bool,
+}
+
+impl Default for OffchainWorkerScheduling {
+	fn default() -> Self {
+		Self {
+			schedule: Default::default(),
+			interval: 1,
+			max_concurrent_workers: None,
+			skip_if_busy: false,
+		}
+	}
+}
+
 /// Options for [`OffchainWorkers`]
 pub struct OffchainWorkerOptions {
 	/// Enable http requests from offchain workers?
 	///
 	/// If not enabled, any http request will panic.
 	pub enable_http_requests: bool,
+	/// Configuration of the HTTP client that executes the http requests.
+	pub http_config: OffchainHttpConfig,
+	/// Scheduling of the offchain workers.
+	pub scheduling: OffchainWorkerScheduling,
+	/// Registry to report the metrics of the offchain workers to.
+	pub prometheus_registry: Option<Registry>,
 }
 
 /// An offchain workers manager.
@@ -79,27 +143,85 @@ pub struct OffchainWorkers<Client, Block: traits::Block> {
 	thread_pool: Mutex<ThreadPool>,
 	shared_http_client: api::SharedClient,
 	enable_http: bool,
+	scheduling: OffchainWorkerScheduling,
+	max_concurrent_workers: usize,
+	/// Number of offchain workers that are running or queued in the thread pool.
+	running_workers: Arc<AtomicUsize>,
+	metrics: Option<Arc<metrics::Metrics>>,
 }
 
 impl<Client, Block: traits::Block> OffchainWorkers<Client, Block> {
 	/// Creates new [`OffchainWorkers`].
 	pub fn new(client: Arc<Client>) -> Self {
-		Self::new_with_options(client, OffchainWorkerOptions { enable_http_requests: true })
+		Self::new_with_options(
+			client,
+			OffchainWorkerOptions {
+				enable_http_requests: true,
+				http_config: Default::default(),
+				scheduling: Default::default(),
+				prometheus_registry: None,
+			},
+		)
 	}
 
 	/// Creates new [`OffchainWorkers`] using the given `options`.
 	pub fn new_with_options(client: Arc<Client>, options: OffchainWorkerOptions) -> Self {
+		let max_concurrent_workers =
+			options.scheduling.max_concurrent_workers.unwrap_or_else(num_cpus::get).max(1);
+		let metrics = options.prometheus_registry.as_ref().and_then(|registry| {
+			metrics::Metrics::register(registry)
+				.map_err(|err| {
+					tracing::warn!(
+						target: LOG_TARGET,
+						"Failed to register offchain worker metrics: {}",
+						err,
+					)
+				})
+				.ok()
+		});
+
 		Self {
 			client,
 			_block: PhantomData,
 			thread_pool: Mutex::new(ThreadPool::with_name(
 				"offchain-worker".into(),
-				num_cpus::get(),
+				max_concurrent_workers,
 			)),
-			shared_http_client: api::SharedClient::new(),
+			shared_http_client: api::SharedClient::with_config(options.http_config),
 			enable_http: options.enable_http_requests,
+			scheduling: options.scheduling,
+			max_concurrent_workers,
+			running_workers: Default::default(),
+			metrics: metrics.map(Arc::new),
 		}
 	}
+
+	/// Returns `true` if the offchain workers are scheduled to run for the given block number.
+	fn is_scheduled(&self, number: traits::NumberFor<Block>) -> bool {
+		let interval = self.scheduling.interval;
+		interval <= 1 || (number % traits::NumberFor::<Block>::from(interval)).is_zero()
+	}
+
+	/// Returns `true` if the offchain workers at `at` should be skipped because the maximum
+	/// number of offchain workers is already running.
+	fn is_busy(&self, at: &BlockId<Block>) -> bool {
+		if !self.scheduling.skip_if_busy ||
+			self.running_workers.load(Ordering::Acquire) < self.max_concurrent_workers
+		{
+			return false
+		}
+
+		tracing::debug!(
+			target: LOG_TARGET,
+			"Skipping offchain workers at {:?}: {} workers are still running",
+			at,
+			self.max_concurrent_workers,
+		);
+		if let Some(metrics) = &self.metrics {
+			metrics.skipped_runs.inc();
+		}
+		true
+	}
 }
 
 impl<Client, Block: traits::Block> fmt::Debug for OffchainWorkers<Client, Block> {
@@ -147,7 +269,7 @@ where
 			at,
 			version
 		);
-		let process = (version > 0).then(|| {
+		let process = (version > 0 && !self.is_busy(&at)).then(|| {
 			let (api, runner) =
 				api::AsyncApi::new(network_provider, is_validator, self.shared_http_client.clone());
 			tracing::debug!(target: LOG_TARGET, "Spawning offchain workers at {:?}", at);
@@ -157,7 +279,9 @@ where
 			let mut capabilities = offchain::Capabilities::all();
 
 			capabilities.set(offchain::Capabilities::HTTP, self.enable_http);
+			let metrics = self.metrics.clone();
 			self.spawn_worker(move || {
+				let started = Instant::now();
 				let runtime = client.runtime_api();
 				let api = Box::new(api);
 				tracing::debug!(target: LOG_TARGET, "Running offchain workers at {:?}", at);
@@ -181,6 +305,9 @@ where
 						e
 					);
 				}
+				if let Some(metrics) = metrics {
+					metrics.run_duration.observe(started.elapsed().as_secs_f64());
+				}
 			});
 
 			runner.process()
@@ -200,11 +327,39 @@ where
 	/// Note that we should avoid that if we switch to future-based runtime in the future,
 	/// alternatively:
 	fn spawn_worker(&self, f: impl FnOnce() -> () + Send + 'static) {
-		self.thread_pool.lock().execute(f);
+		let running = self.running_workers.fetch_add(1, Ordering::AcqRel) + 1;
+		if let Some(metrics) = &self.metrics {
+			metrics.running_workers.set(running as u64);
+		}
+		let guard = RunningWorkerGuard {
+			running_workers: self.running_workers.clone(),
+			metrics: self.metrics.clone(),
+		};
+
+		self.thread_pool.lock().execute(move || {
+			let _guard = guard;
+			f();
+		});
 	}
 }
 
-/// Inform the offchain worker about new imported blocks
+/// Stops counting an offchain worker as running when dropped, also if the worker panicked.
+struct RunningWorkerGuard {
+	running_workers: Arc<AtomicUsize>,
+	metrics: Option<Arc<metrics::Metrics>>,
+}
+
+impl Drop for RunningWorkerGuard {
+	fn drop(&mut self) {
+		let running = self.running_workers.fetch_sub(1, Ordering::AcqRel) - 1;
+		if let Some(metrics) = &self.metrics {
+			metrics.running_workers.set(running as u64);
+		}
+	}
+}
+
+/// Inform the offchain worker about new imported or finalized blocks, depending on the
+/// [`OffchainWorkerSchedule`].
 pub async fn notification_future<Client, Block, Spawner>(
 	is_validator: bool,
 	client: Arc<Client>,
@@ -213,28 +368,84 @@ pub async fn notification_future<Client, Block, Spawner>(
 	network_provider: Arc<dyn NetworkProvider + Send + Sync>,
 ) where
 	Block: traits::Block,
-	Client:
-		ProvideRuntimeApi<Block> + sc_client_api::BlockchainEvents<Block> + Send + Sync + 'static,
+	Client: ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockchainEvents<Block>
+		+ HeaderBackend<Block>
+		+ Send
+		+ Sync
+		+ 'static,
 	Client::Api: OffchainWorkerApi<Block>,
 	Spawner: SpawnNamed,
 {
-	client
-		.import_notification_stream()
-		.for_each(move |n| {
-			if n.is_new_best {
+	let headers = match offchain.scheduling.schedule {
+		OffchainWorkerSchedule::BestBlocks => client
+			.import_notification_stream()
+			.filter_map(|n| {
+				if n.is_new_best {
+					ready(Some(vec![n.header]))
+				} else {
+					tracing::debug!(
+						target: LOG_TARGET,
+						"Skipping offchain workers for non-canon block: {:?}",
+						n.header,
+					);
+					ready(None)
+				}
+			})
+			.boxed(),
+		OffchainWorkerSchedule::FinalizedBlocks => {
+			let finality_notifications = client.finality_notification_stream();
+			let offchain = offchain.clone();
+			finality_notifications
+				.map(move |n| {
+					// Also run for the implicitly finalized blocks, oldest first. Running for more
+					// blocks than workers run concurrently would only queue up outdated work.
+					let mut headers = n
+						.tree_route
+						.iter()
+						.rev()
+						.filter_map(|hash| match client.header(BlockId::hash(*hash)) {
+							Ok(header) => header,
+							Err(e) => {
+								tracing::error!(
+									target: LOG_TARGET,
+									"Error fetching finalized header {:?}: {}",
+									hash,
+									e,
+								);
+								None
+							},
+						})
+						.filter(|header| offchain.is_scheduled(*header.number()))
+						.take(offchain.max_concurrent_workers - 1)
+						.collect::<Vec<_>>();
+					headers.reverse();
+					headers.push(n.header);
+					headers
+				})
+				.boxed()
+		},
+	};
+
+	headers
+		.for_each(move |headers| {
+			for header in headers {
+				if !offchain.is_scheduled(*header.number()) {
+					tracing::trace!(
+						target: LOG_TARGET,
+						"Offchain workers are not scheduled for block: {:?}",
+						header,
+					);
+					continue
+				}
+
 				spawner.spawn(
 					"offchain-on-block",
 					Some("offchain-worker"),
 					offchain
-						.on_block_imported(&n.header, network_provider.clone(), is_validator)
+						.on_block_imported(&header, network_provider.clone(), is_validator)
 						.boxed(),
 				);
-			} else {
-				tracing::debug!(
-					target: LOG_TARGET,
-					"Skipping offchain workers for non-canon block: {:?}",
-					n.header,
-				)
 			}
 
 			ready(())
@@ -386,6 +597,80 @@ mod tests {
 		assert_eq!(pool.0.ready().next().unwrap().is_propagable(), false);
 	}
 
+	#[test]
+	fn should_skip_offchain_workers_when_busy() {
+		sp_tracing::try_init_simple();
+
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = TestPool(BasicPool::new_full(
+			Default::default(),
+			true.into(),
+			None,
+			spawner,
+			client.clone(),
+		));
+		let network = Arc::new(TestNetwork());
+		let header = client.header(&BlockId::number(0)).unwrap().unwrap();
+		let registry = Registry::new();
+
+		let offchain = OffchainWorkers::new_with_options(
+			client,
+			OffchainWorkerOptions {
+				enable_http_requests: true,
+				http_config: Default::default(),
+				scheduling: OffchainWorkerScheduling {
+					max_concurrent_workers: Some(1),
+					skip_if_busy: true,
+					..Default::default()
+				},
+				prometheus_registry: Some(registry.clone()),
+			},
+		);
+
+		// when the only worker slot is taken
+		offchain.running_workers.store(1, Ordering::Release);
+		block_on(offchain.on_block_imported(&header, network.clone(), false));
+
+		// then
+		assert_eq!(pool.0.status().ready, 0);
+		assert_eq!(offchain.metrics.as_ref().unwrap().skipped_runs.get(), 1);
+
+		// when the slot is freed
+		offchain.running_workers.store(0, Ordering::Release);
+		block_on(offchain.on_block_imported(&header, network, false));
+
+		// then
+		assert_eq!(pool.0.status().ready, 1);
+	}
+
+	#[test]
+	fn should_only_schedule_blocks_at_interval() {
+		let offchain = OffchainWorkers::<_, Block>::new_with_options(
+			Arc::new(substrate_test_runtime_client::new()),
+			OffchainWorkerOptions {
+				enable_http_requests: true,
+				http_config: Default::default(),
+				scheduling: OffchainWorkerScheduling { interval: 3, ..Default::default() },
+				prometheus_registry: None,
+			},
+		);
+
+		let scheduled = (0..7).filter(|n| offchain.is_scheduled(*n)).collect::<Vec<_>>();
+		assert_eq!(scheduled, vec![0, 3, 6]);
+	}
+
+	#[test]
+	fn panicking_worker_is_no_longer_counted_as_running() {
+		let offchain =
+			OffchainWorkers::<_, Block>::new(Arc::new(substrate_test_runtime_client::new()));
+
+		offchain.spawn_worker(|| panic!("offchain worker panicked"));
+		offchain.thread_pool.lock().join();
+
+		assert_eq!(offchain.running_workers.load(Ordering::Acquire), 0);
+	}
+
 	#[test]
 	fn offchain_index_set_and_clear_works() {
 		use sp_core::offchain::OffchainStorage;
@@ -0,0 +1,62 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Offchain workers Prometheus metrics.
+
+use prometheus_endpoint::{
+	exponential_buckets, register, Counter, Gauge, Histogram, HistogramOpts, PrometheusError,
+	Registry, U64,
+};
+
+/// Offchain workers Prometheus metrics.
+pub struct Metrics {
+	pub run_duration: Histogram,
+	pub skipped_runs: Counter<U64>,
+	pub running_workers: Gauge<U64>,
+}
+
+impl Metrics {
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			run_duration: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"substrate_offchain_worker_run_duration_seconds",
+						"Time it took to run the offchain workers for a block",
+					)
+					.buckets(exponential_buckets(0.001, 4.0, 9)?),
+				)?,
+				registry,
+			)?,
+			skipped_runs: register(
+				Counter::new(
+					"substrate_offchain_worker_skipped_runs",
+					"Number of blocks the offchain workers were skipped for because they were busy",
+				)?,
+				registry,
+			)?,
+			running_workers: register(
+				Gauge::new(
+					"substrate_offchain_workers_running",
+					"Number of offchain workers that are running or waiting for a thread",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
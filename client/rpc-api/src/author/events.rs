@@ -0,0 +1,114 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Types reported by the author RPC module when watching an extrinsic with its events.
+
+use sc_transaction_pool_api::TransactionStatus;
+use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
+
+/// Dispatch outcome of an included extrinsic, as reported by `frame_system`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DispatchOutcome {
+	/// The extrinsic emitted `ExtrinsicSuccess`.
+	Success,
+	/// The extrinsic emitted `ExtrinsicFailed`.
+	Failed,
+}
+
+/// An event emitted while applying an extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicEvent {
+	/// Name of the pallet that emitted the event.
+	pub pallet: String,
+	/// Name of the event variant.
+	pub variant: String,
+	/// The SCALE encoded event, including the pallet and variant indices.
+	pub data: Bytes,
+}
+
+/// Where and with which result an extrinsic was included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicInclusion<BlockHash> {
+	/// Hash of the block the extrinsic was included in.
+	pub block: BlockHash,
+	/// Index of the extrinsic in the block body.
+	///
+	/// `None` if the block body is not available.
+	pub index: Option<u32>,
+	/// Dispatch outcome of the extrinsic.
+	///
+	/// `None` if the runtime does not deposit `frame_system` events or they could not be decoded.
+	pub outcome: Option<DispatchOutcome>,
+	/// Events emitted while applying the extrinsic, in the order they were deposited.
+	pub events: Vec<ExtrinsicEvent>,
+}
+
+/// Status of a watched extrinsic, with inclusion details on `InBlock` and `Finalized`.
+///
+/// Mirrors [`TransactionStatus`] otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtrinsicStatus<Hash, BlockHash> {
+	/// Transaction is part of the future queue.
+	Future,
+	/// Transaction is part of the ready queue.
+	Ready,
+	/// The transaction has been broadcast to the given peers.
+	Broadcast(Vec<String>),
+	/// Transaction has been included in a block.
+	InBlock(ExtrinsicInclusion<BlockHash>),
+	/// The block this transaction was included in has been retracted.
+	Retracted(BlockHash),
+	/// Maximum number of finality watchers has been reached,
+	/// old watchers are being removed.
+	FinalityTimeout(BlockHash),
+	/// Transaction has been finalized by a finality-gadget.
+	Finalized(ExtrinsicInclusion<BlockHash>),
+	/// Transaction has been replaced in the pool, by another transaction
+	/// that provides the same tags.
+	Usurped(Hash),
+	/// Transaction has been dropped from the pool because of the limit.
+	Dropped,
+	/// Transaction is no longer valid in the current state.
+	Invalid,
+}
+
+impl<Hash, BlockHash> ExtrinsicStatus<Hash, BlockHash> {
+	/// Convert a [`TransactionStatus`], resolving the inclusion details with `inclusion`.
+	pub fn from_status(
+		status: TransactionStatus<Hash, BlockHash>,
+		inclusion: impl FnOnce(BlockHash) -> ExtrinsicInclusion<BlockHash>,
+	) -> Self {
+		match status {
+			TransactionStatus::Future => Self::Future,
+			TransactionStatus::Ready => Self::Ready,
+			TransactionStatus::Broadcast(peers) => Self::Broadcast(peers),
+			TransactionStatus::InBlock(block) => Self::InBlock(inclusion(block)),
+			TransactionStatus::Retracted(block) => Self::Retracted(block),
+			TransactionStatus::FinalityTimeout(block) => Self::FinalityTimeout(block),
+			TransactionStatus::Finalized(block) => Self::Finalized(inclusion(block)),
+			TransactionStatus::Usurped(hash) => Self::Usurped(hash),
+			TransactionStatus::Dropped => Self::Dropped,
+			TransactionStatus::Invalid => Self::Invalid,
+		}
+	}
+}
@@ -23,6 +23,7 @@ use sc_transaction_pool_api::TransactionStatus;
 use sp_core::Bytes;
 
 pub mod error;
+pub mod events;
 pub mod hash;
 
 /// Substrate authoring RPC API
@@ -75,4 +76,16 @@ pub trait AuthorApi<Hash, BlockHash> {
 		item = TransactionStatus<Hash, BlockHash>,
 	)]
 	fn watch_extrinsic(&self, bytes: Bytes);
+
+	/// Submit an extrinsic to watch, reporting its index, dispatch outcome and events once it
+	/// is included in a block or finalized.
+	///
+	/// The events are read from the `frame_system` events storage of the including block and
+	/// decoded using the runtime metadata.
+	#[subscription(
+		name = "author_submitAndWatchExtrinsicWithEvents" => "author_extrinsicUpdateWithEvents",
+		unsubscribe = "author_unwatchExtrinsicWithEvents",
+		item = events::ExtrinsicStatus<Hash, BlockHash>,
+	)]
+	fn watch_extrinsic_with_events(&self, bytes: Bytes);
 }
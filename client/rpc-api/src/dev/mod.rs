@@ -27,6 +27,24 @@ use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 
+/// Statistics of the runtime heap while executing a block returned by the
+/// `dev_getBlockHeapStats` RPC.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, Debug, TypeInfo, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapStats {
+	/// The amount of heap address space in bytes that was used.
+	///
+	/// This is what needs to fit into the heap pages the runtime is executed with.
+	pub usage: u64,
+	/// The peak number of bytes that were allocated at the same time.
+	pub allocated_peak: u64,
+	/// The number of bytes wasted by rounding allocations up to their size class.
+	pub wasted: u64,
+	/// The number of allocations per size class, as pairs of the size of the class in bytes
+	/// and the number of allocations.
+	pub allocations_per_size_class: Vec<(u32, u64)>,
+}
+
 /// Statistics of a block returned by the `dev_getBlockStats` RPC.
 #[derive(Eq, PartialEq, Clone, Copy, Encode, Decode, Debug, TypeInfo, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -60,4 +78,12 @@ pub trait DevApi<Hash> {
 	/// this function will return `None`.
 	#[method(name = "dev_getBlockStats")]
 	fn block_stats(&self, block_hash: Hash) -> RpcResult<Option<BlockStats>>;
+
+	/// Reexecute the specified `block_hash` and gather statistics of the runtime heap while
+	/// doing so.
+	///
+	/// This function has the same requirements as `dev_getBlockStats`. It also returns `None`
+	/// if the block was not executed in wasm.
+	#[method(name = "dev_getBlockHeapStats")]
+	fn block_heap_stats(&self, block_hash: Hash) -> RpcResult<Option<HeapStats>>;
 }
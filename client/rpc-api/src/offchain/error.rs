@@ -32,6 +32,17 @@ pub enum Error {
 	/// Unavailable storage kind error.
 	#[error("This storage kind is not available yet.")]
 	UnavailableStorageKind,
+	/// Provided count exceeds maximum value.
+	#[error("count exceeds maximum value. value: {}, max: {}", .value, .max)]
+	InvalidCount {
+		/// Provided value
+		value: u32,
+		/// Maximum allowed value
+		max: u32,
+	},
+	/// The keys of the storage can't be iterated over.
+	#[error("Iterating over the keys of the local storage is not supported by the database.")]
+	KeysUnsupported,
 	/// Call to an unsafe RPC was denied.
 	#[error(transparent)]
 	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
@@ -49,6 +60,12 @@ impl From<Error> for JsonRpseeError {
 				None::<()>,
 			))
 			.into(),
+			Error::InvalidCount { .. } =>
+				CallError::Custom(ErrorObject::owned(BASE_ERROR + 2, e.to_string(), None::<()>))
+					.into(),
+			Error::KeysUnsupported =>
+				CallError::Custom(ErrorObject::owned(BASE_ERROR + 3, e.to_string(), None::<()>))
+					.into(),
 			Error::UnsafeRpcCalled(e) => e.into(),
 		}
 	}
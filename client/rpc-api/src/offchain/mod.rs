@@ -19,10 +19,21 @@
 //! Substrate offchain API.
 
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
 use sp_core::{offchain::StorageKind, Bytes};
 
 pub mod error;
 
+/// Change made to a key through offchain indexing.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OffchainIndexChange {
+	/// The indexed key
+	pub key: Bytes,
+	/// The value written under the key, `None` if the key was removed
+	pub value: Option<Bytes>,
+}
+
 /// Substrate offchain RPC API
 #[rpc(client, server)]
 pub trait OffchainApi {
@@ -33,4 +44,40 @@ pub trait OffchainApi {
 	/// Get offchain local storage under given key and prefix.
 	#[method(name = "offchain_localStorageGet")]
 	fn get_local_storage(&self, kind: StorageKind, key: Bytes) -> RpcResult<Option<Bytes>>;
+
+	/// Returns the keys of offchain local storage with given prefix with pagination support.
+	///
+	/// Up to `count` keys will be returned.
+	/// If `start_key` is passed, return next keys in storage in lexicographic order.
+	#[method(name = "offchain_localStorageKeys")]
+	fn local_storage_keys(
+		&self,
+		kind: StorageKind,
+		prefix: Bytes,
+		count: u32,
+		start_key: Option<Bytes>,
+	) -> RpcResult<Vec<Bytes>>;
+
+	/// Remove all entries of offchain local storage with given prefix.
+	///
+	/// Returns the number of removed entries.
+	#[method(name = "offchain_localStorageClearPrefix")]
+	fn clear_local_storage_prefix(&self, kind: StorageKind, prefix: Bytes) -> RpcResult<u32>;
+}
+
+/// Substrate offchain indexing RPC API
+#[rpc(client, server)]
+pub trait OffchainIndexApi<Hash> {
+	/// Get the value written through offchain indexing under given key.
+	///
+	/// If `at` is given, returns the value written by that block, `None` if the block didn't
+	/// write the key or if its changes were pruned. Otherwise returns the current value.
+	#[method(name = "offchain_indexGet")]
+	fn index_get(&self, key: Bytes, at: Option<Hash>) -> RpcResult<Option<Bytes>>;
+
+	/// Get all changes made through offchain indexing by given block.
+	///
+	/// Returns an empty list if the block didn't index anything or if its changes were pruned.
+	#[method(name = "offchain_indexChanges")]
+	fn index_changes(&self, at: Hash) -> RpcResult<Vec<OffchainIndexChange>>;
 }
@@ -19,7 +19,10 @@
 //! Substrate state API helpers.
 
 use serde::{Deserialize, Serialize};
-use sp_core::Bytes;
+use sp_core::{
+	storage::{PrefixedStorageKey, StorageData, StorageKey},
+	Bytes,
+};
 
 /// ReadProof struct returned by the RPC
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -30,3 +33,204 @@ pub struct ReadProof<Hash> {
 	/// A proof used to prove that storage entries are included in the storage trie
 	pub proof: Vec<Bytes>,
 }
+
+/// Statistics of a single host function, part of [`BlockProfile`].
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostFunctionProfile {
+	/// Name of the host function, e.g. `ext_storage_get_version_1`.
+	pub name: String,
+	/// Number of times the host function was called.
+	pub calls: u64,
+	/// Cumulative time spent in the host function in nanoseconds.
+	pub time_nanos: u64,
+	/// Number of bytes the host function read from the runtime memory.
+	pub bytes_read: u64,
+	/// Number of bytes the host function wrote to the runtime memory.
+	pub bytes_written: u64,
+}
+
+/// Host function profile of a block execution returned by the RPC
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockProfile<Hash> {
+	/// Hash of the profiled block
+	pub block: Hash,
+	/// Total time it took to execute the block in nanoseconds
+	pub execution_nanos: u64,
+	/// Statistics of all host functions called during the execution, sorted by the time spent
+	/// in them in descending order
+	pub host_functions: Vec<HostFunctionProfile>,
+}
+
+/// Kind of change of a storage entry, part of [`StorageDiff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageDiffKind {
+	/// The key did not exist in the state of the first block.
+	Added,
+	/// The value of the key changed.
+	Modified,
+	/// The key does not exist in the state of the second block.
+	Removed,
+}
+
+/// A storage entry that differs between the states of two blocks returned by the RPC
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDiff {
+	/// Prefixed storage key of the child trie holding the entry, absent for the main trie
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub child_trie: Option<PrefixedStorageKey>,
+	/// Key of the entry
+	pub key: StorageKey,
+	/// How the entry changed
+	pub kind: StorageDiffKind,
+	/// Value of the entry in the state of the second block, `None` if it was removed
+	pub value: Option<StorageData>,
+}
+
+/// Blocks to query with `state_queryStorageWithProof`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryBlocks<Hash> {
+	/// The given blocks, in the given order
+	List(Vec<Hash>),
+	/// All blocks from `from` up to and including `to`, or the best block if `to` is `None`
+	Range {
+		/// First block of the range
+		from: Hash,
+		/// Last block of the range
+		to: Option<Hash>,
+	},
+}
+
+/// Storage values of a block together with a proof of them returned by the RPC
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageWithProof<Hash> {
+	/// Block hash the values were read at
+	pub at: Hash,
+	/// The queried keys with their values, in the order of the query
+	pub values: Vec<(StorageKey, Option<StorageData>)>,
+	/// SCALE encoded `sp_trie::CompactProof` of all values against the state root of the block
+	pub proof: Bytes,
+}
+
+/// Runtime APIs implemented by the runtime of a block returned by the RPC
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeApis {
+	/// The implemented runtime APIs, in the order of the runtime version
+	pub apis: Vec<RuntimeApi>,
+	/// SCALE encoded `scale_info::PortableRegistry` the type ids of the methods refer to, empty
+	/// if the runtime does not provide the metadata of its runtime APIs
+	pub types: Bytes,
+}
+
+/// A runtime API implemented by the runtime, part of [`RuntimeApis`].
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeApi {
+	/// Id of the runtime API as found in the runtime version
+	pub id: Bytes,
+	/// Name of the runtime API trait, `None` if the runtime does not provide its metadata
+	pub name: Option<String>,
+	/// Version of the runtime API implemented by the runtime
+	pub version: u32,
+	/// Methods of the runtime API
+	pub methods: Vec<RuntimeApiMethod>,
+	/// Documentation of the runtime API trait
+	pub docs: Vec<String>,
+}
+
+/// A method of a runtime API, part of [`RuntimeApi`].
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeApiMethod {
+	/// Name of the method
+	pub name: String,
+	/// Name to use with `state_call`, e.g. `Core_version`
+	pub call_name: String,
+	/// Parameters of the method
+	pub inputs: Vec<RuntimeApiParam>,
+	/// Return type of the method
+	pub output: RuntimeApiType,
+	/// Documentation of the method
+	pub docs: Vec<String>,
+}
+
+/// A parameter of a runtime API method, part of [`RuntimeApiMethod`].
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeApiParam {
+	/// Name of the parameter
+	pub name: String,
+	/// Type of the parameter
+	pub ty: RuntimeApiType,
+}
+
+/// Type of a parameter or return value, part of [`RuntimeApiMethod`].
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeApiType {
+	/// The type as written in the runtime
+	pub name: String,
+	/// Id of the type in [`RuntimeApis::types`], `None` if the type has no type information
+	pub id: Option<u32>,
+}
+
+/// Filter of `state_subscribeEvents`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+	/// Only report events of the pallet with this name
+	#[serde(default)]
+	pub pallet: Option<String>,
+	/// Only report events with this name
+	#[serde(default)]
+	pub event: Option<String>,
+	/// Report the events of finalized blocks instead of new best blocks
+	#[serde(default)]
+	pub finalized: bool,
+}
+
+/// Phase of the block execution an event was deposited in, part of [`RuntimeEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventPhase {
+	/// Applying the extrinsic with the given index
+	ApplyExtrinsic(u32),
+	/// Finalizing the block
+	Finalization,
+	/// Initializing the block
+	Initialization,
+}
+
+/// A runtime event, part of [`BlockEvents`].
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeEvent<Hash> {
+	/// Phase the event was deposited in
+	pub phase: EventPhase,
+	/// Name of the pallet that deposited the event
+	pub pallet: String,
+	/// Name of the event
+	pub event: String,
+	/// Topics of the event
+	pub topics: Vec<Hash>,
+	/// SCALE encoded runtime event, starting with the index of the pallet
+	pub data: Bytes,
+	/// Fields of the event decoded using the runtime metadata
+	pub fields: serde_json::Value,
+}
+
+/// Events of a block returned by `state_subscribeEvents`
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockEvents<Hash> {
+	/// Hash of the block
+	pub block: Hash,
+	/// Events of the block matching the filter, in the order they were deposited
+	pub events: Vec<RuntimeEvent<Hash>>,
+}
@@ -20,7 +20,7 @@
 
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use sp_core::{
-	storage::{StorageChangeSet, StorageData, StorageKey},
+	storage::{PrefixedStorageKey, StorageChangeSet, StorageData, StorageKey},
 	Bytes,
 };
 use sp_version::RuntimeVersion;
@@ -28,7 +28,11 @@ use sp_version::RuntimeVersion;
 pub mod error;
 pub mod helpers;
 
-pub use self::helpers::ReadProof;
+pub use self::helpers::{
+	BlockEvents, BlockProfile, EventFilter, EventPhase, HostFunctionProfile, QueryBlocks,
+	ReadProof, RuntimeApi, RuntimeApiMethod, RuntimeApiParam, RuntimeApiType, RuntimeApis,
+	RuntimeEvent, StorageDiff, StorageDiffKind, StorageWithProof,
+};
 
 /// Substrate state API
 #[rpc(client, server)]
@@ -82,6 +86,15 @@ pub trait StateApi<Hash> {
 	#[method(name = "state_getRuntimeVersion", aliases = ["chain_getRuntimeVersion"], blocking)]
 	fn runtime_version(&self, hash: Option<Hash>) -> RpcResult<RuntimeVersion>;
 
+	/// Returns the runtime APIs implemented by the runtime at a block together with the
+	/// signatures of their methods.
+	///
+	/// Names and methods are only available if the runtime appends the metadata of its runtime
+	/// APIs to its metadata, otherwise only the ids and versions from the runtime version are
+	/// returned.
+	#[method(name = "state_getRuntimeApis", blocking)]
+	fn runtime_apis(&self, hash: Option<Hash>) -> RpcResult<RuntimeApis>;
+
 	/// Query historical storage entries (by key) starting from a block given as the second
 	/// parameter.
 	///
@@ -107,6 +120,47 @@ pub trait StateApi<Hash> {
 	#[method(name = "state_getReadProof", blocking)]
 	fn read_proof(&self, keys: Vec<StorageKey>, hash: Option<Hash>) -> RpcResult<ReadProof<Hash>>;
 
+	/// Returns the values of storage entries at each of the given blocks, together with a
+	/// compact proof of them per block.
+	///
+	/// The proof of a block covers all queried keys and is checked against the state root of
+	/// that block.
+	///
+	/// ## Limits
+	///
+	/// At most 256 blocks and 1000 keys can be queried at once.
+	#[method(name = "state_queryStorageWithProof", blocking)]
+	fn query_storage_with_proof(
+		&self,
+		keys: Vec<StorageKey>,
+		blocks: QueryBlocks<Hash>,
+	) -> RpcResult<Vec<StorageWithProof<Hash>>>;
+
+	/// Returns the storage entries that differ between the states of the blocks `from` and `to`.
+	///
+	/// Only keys starting with `prefix` are compared. Entries of a child trie are included when
+	/// the root of the child trie changed and its prefixed storage key starts with `prefix`,
+	/// they follow the entry of the root of the child trie.
+	///
+	/// At most `count` entries are returned. The entries following the last returned entry are
+	/// retrieved by passing its key as `start_key` and its child trie as `start_child_trie`.
+	/// Passing only `start_child_trie` starts with the entries of that child trie.
+	///
+	/// ## Node requirements
+	///
+	/// - The state of both blocks must be available, i.e. the blocks must not be pruned.
+	/// - The RPC is only available when the node runs with `--rpc-methods=Unsafe`.
+	#[method(name = "state_getStorageDiff", blocking)]
+	fn storage_diff(
+		&self,
+		from: Hash,
+		to: Hash,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		start_child_trie: Option<PrefixedStorageKey>,
+	) -> RpcResult<Vec<StorageDiff>>;
+
 	/// New runtime version subscription
 	#[subscription(
 		name = "state_subscribeRuntimeVersion" => "state_runtimeVersion",
@@ -125,6 +179,20 @@ pub trait StateApi<Hash> {
 	)]
 	fn subscribe_storage(&self, keys: Option<Vec<StorageKey>>);
 
+	/// Subscribe to the events deposited in new best blocks, or in finalized blocks if
+	/// `filter.finalized` is set.
+	///
+	/// A notification is sent for every block, containing the events that match the pallet and
+	/// event names of the filter. The events are decoded using the metadata of the runtime of
+	/// the block. Blocks enacted by a re-org and blocks finalized implicitly are reported as
+	/// well, in ascending order.
+	#[subscription(
+		name = "state_subscribeEvents" => "state_events",
+		unsubscribe = "state_unsubscribeEvents",
+		item = BlockEvents<Hash>,
+	)]
+	fn subscribe_events(&self, filter: Option<EventFilter>);
+
 	/// The `traceBlock` RPC provides a way to trace the re-execution of a single
 	/// block, collecting Spans and Events from both the client and the relevant WASM runtime.
 	/// The Spans and Events are conceptually equivalent to those from the [Tracing][1] crate.
@@ -285,4 +353,18 @@ pub trait StateApi<Hash> {
 		storage_keys: Option<String>,
 		methods: Option<String>,
 	) -> RpcResult<sp_rpc::tracing::TraceBlockResponse>;
+
+	/// Re-execute the given block using the wasm executor and return how often each host
+	/// function was called, the time spent in it and the number of bytes it transferred
+	/// between the host and the runtime.
+	///
+	/// The block is always executed in wasm, even if a native runtime is available, as native
+	/// execution does not go through host functions.
+	///
+	/// ## Node requirements
+	///
+	/// - The state of the parent block must be available, i.e. the block must not be pruned.
+	/// - The RPC is only available when the node runs with `--rpc-methods=Unsafe`.
+	#[method(name = "state_profileBlock", blocking)]
+	fn profile_block(&self, block: Hash) -> RpcResult<BlockProfile<Hash>>;
 }
@@ -25,9 +25,12 @@ use jsonrpsee::{
 	ws_server::{WsServerBuilder, WsServerHandle},
 	RpcModule,
 };
-use std::{error::Error as StdError, net::SocketAddr};
+use std::{error::Error as StdError, net::SocketAddr, sync::Arc};
 
-pub use crate::middleware::{RpcMetrics, RpcMiddleware};
+pub use crate::{
+	limits::{Caller, RateLimitMiddleware, RateLimiter, RpcLimits},
+	middleware::{RpcMetrics, RpcMiddleware},
+};
 pub use jsonrpsee::core::{
 	id_providers::{RandomIntegerIdProvider, RandomStringIdProvider},
 	traits::IdProvider,
@@ -44,6 +47,7 @@ const WS_MAX_CONNECTIONS: usize = 100;
 /// Default maximum number subscriptions per connection for WS RPC servers.
 const WS_MAX_SUBS_PER_CONN: usize = 1024;
 
+pub mod limits;
 pub mod middleware;
 
 /// Type alias for http server
@@ -84,6 +88,7 @@ pub async fn start_http<M: Send + Sync + 'static>(
 	max_payload_in_mb: Option<usize>,
 	max_payload_out_mb: Option<usize>,
 	metrics: Option<RpcMetrics>,
+	limits: &RpcLimits,
 	rpc_api: RpcModule<M>,
 	rt: tokio::runtime::Handle,
 ) -> Result<HttpServerHandle, Box<dyn StdError + Send + Sync>> {
@@ -106,14 +111,16 @@ pub async fn start_http<M: Send + Sync + 'static>(
 		.health_api("/health", "system_health")?
 		.custom_tokio_runtime(rt);
 
-	let rpc_api = build_rpc_api(rpc_api);
+	let (rpc_api, limiter) = build_rpc_api(rpc_api, limits, max_payload_out);
+	let limits_middleware = RateLimitMiddleware::new(limiter);
 	let (handle, addr) = if let Some(metrics) = metrics {
-		let middleware = RpcMiddleware::new(metrics, "http".into());
+		let middleware = (RpcMiddleware::new(metrics, "http".into()), limits_middleware);
 		let builder = builder.set_middleware(middleware);
 		let server = builder.build(&addrs[..]).await?;
 		let addr = server.local_addr();
 		(server.start(rpc_api)?, addr)
 	} else {
+		let builder = builder.set_middleware(limits_middleware);
 		let server = builder.build(&addrs[..]).await?;
 		let addr = server.local_addr();
 		(server.start(rpc_api)?, addr)
@@ -134,6 +141,7 @@ pub async fn start_ws<M: Send + Sync + 'static>(
 	cors: Option<&Vec<String>>,
 	ws_config: WsConfig,
 	metrics: Option<RpcMetrics>,
+	limits: &RpcLimits,
 	rpc_api: RpcModule<M>,
 	rt: tokio::runtime::Handle,
 	id_provider: Option<Box<dyn IdProvider>>,
@@ -165,14 +173,16 @@ pub async fn start_ws<M: Send + Sync + 'static>(
 		builder = builder.set_id_provider(RandomStringIdProvider::new(16));
 	};
 
-	let rpc_api = build_rpc_api(rpc_api);
+	let (rpc_api, limiter) = build_rpc_api(rpc_api, limits, max_payload_out as usize);
+	let limits_middleware = RateLimitMiddleware::new(limiter);
 	let (handle, addr) = if let Some(metrics) = metrics {
-		let middleware = RpcMiddleware::new(metrics, "ws".into());
+		let middleware = (RpcMiddleware::new(metrics, "ws".into()), limits_middleware);
 		let builder = builder.set_middleware(middleware);
 		let server = builder.build(&addrs[..]).await?;
 		let addr = server.local_addr();
 		(server.start(rpc_api)?, addr)
 	} else {
+		let builder = builder.set_middleware(limits_middleware);
 		let server = builder.build(&addrs[..]).await?;
 		let addr = server.local_addr();
 		(server.start(rpc_api)?, addr)
@@ -196,9 +206,13 @@ fn format_allowed_hosts(addrs: &[SocketAddr]) -> Vec<String> {
 	hosts
 }
 
-fn build_rpc_api<M: Send + Sync + 'static>(mut rpc_api: RpcModule<M>) -> RpcModule<M> {
-	let mut available_methods = rpc_api.method_names().collect::<Vec<_>>();
-	available_methods.sort_unstable();
+fn build_rpc_api<M: Send + Sync + 'static>(
+	rpc_api: RpcModule<M>,
+	limits: &RpcLimits,
+	max_response_size: usize,
+) -> (RpcModule<M>, Option<Arc<RateLimiter>>) {
+	let (mut rpc_api, available_methods, limiter) =
+		limits::apply_limits(rpc_api, limits, max_response_size);
 
 	rpc_api
 		.register_method("rpc_methods", move |_, _| {
@@ -209,7 +223,7 @@ fn build_rpc_api<M: Send + Sync + 'static>(mut rpc_api: RpcModule<M>) -> RpcModu
 		})
 		.expect("infallible all other methods have their own address space; qed");
 
-	rpc_api
+	(rpc_api, limiter)
 }
 
 fn payload_size_or_default(size_mb: Option<usize>) -> usize {
@@ -0,0 +1,598 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rate limiting and filtering of RPC method calls.
+//!
+//! The middleware of the RPC servers can't reject calls, so the limits are applied by rewriting
+//! the [`RpcModule`] before the server is started: methods that may not be called are replaced
+//! by a method returning an error and, when a rate limit is configured, every other method call
+//! is forwarded to the original method only if the [`RateLimiter`] accepts it.
+//!
+//! The methods of an [`RpcModule`] are not told which connection a call was made on. The
+//! [`RateLimitMiddleware`] is cloned by the servers for every connection, it records the peer of
+//! the connection and charges the cost of every call to the rate limits of the peer right before
+//! the method is called. Subscriptions can't be forwarded and thus can't be rejected, but their
+//! cost is charged all the same, such that a peer subscribing too often has its calls rejected.
+
+use jsonrpsee::{
+	core::{
+		middleware::{Headers, HttpMiddleware, MethodKind as CallKind, WsMiddleware},
+		server::rpc_module::MethodKind,
+		Error as JsonRpseeError,
+	},
+	types::{
+		error::{CallError, ErrorObject},
+		Id, Params,
+	},
+	RpcModule,
+};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::{
+	cell::Cell,
+	collections::HashMap,
+	hash::Hash,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	num::NonZeroU32,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	time::{Duration, Instant},
+};
+
+/// Cost of a method call that has no cost configured.
+pub const DEFAULT_METHOD_COST: u32 = 1;
+
+/// Error code returned for calls of methods that are not allowed.
+///
+/// Distinct from the "method not found" error, such that callers can tell a denied method from
+/// an unknown one.
+pub const METHOD_NOT_ALLOWED: i32 = -32030;
+
+/// Error code returned for calls exceeding the rate limit.
+pub const RATE_LIMIT_EXCEEDED: i32 = -32029;
+
+/// Buckets that were not used for this long are full again and are dropped.
+const IDLE_BUCKET: Duration = Duration::from_secs(1);
+
+/// Limits applied to the calls of an RPC server.
+#[derive(Debug, Clone, Default)]
+pub struct RpcLimits {
+	/// Maximum total cost of the calls accepted per second on a single connection, `None` if
+	/// unlimited.
+	pub max_cost_per_sec: Option<NonZeroU32>,
+	/// Maximum total cost of the calls accepted per second from a single IP address, `None` if
+	/// unlimited.
+	pub max_cost_per_sec_per_ip: Option<NonZeroU32>,
+	/// Cost of calling a method, [`DEFAULT_METHOD_COST`] for methods that are not listed.
+	pub method_costs: HashMap<String, u32>,
+	/// Methods that may be called, `None` if all methods may be called.
+	pub allowed_methods: Option<Vec<String>>,
+	/// Methods that may not be called, takes precedence over `allowed_methods`.
+	pub denied_methods: Vec<String>,
+}
+
+impl RpcLimits {
+	/// Returns `true` if `method` may be called.
+	pub fn is_allowed(&self, method: &str) -> bool {
+		!self.denied_methods.iter().any(|m| m == method) &&
+			self.allowed_methods
+				.as_ref()
+				.map_or(true, |allowed| allowed.iter().any(|m| m == method))
+	}
+
+	/// Returns the cost of calling `method`.
+	pub fn cost(&self, method: &str) -> u32 {
+		self.method_costs.get(method).copied().unwrap_or(DEFAULT_METHOD_COST)
+	}
+
+	/// Returns `true` if the calls are rate limited.
+	pub fn is_rate_limited(&self) -> bool {
+		self.max_cost_per_sec.is_some() || self.max_cost_per_sec_per_ip.is_some()
+	}
+}
+
+/// The peer a method call was received from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caller {
+	/// Identifies the connection the call was received on.
+	pub connection_id: usize,
+	/// Address of the peer.
+	pub remote_addr: SocketAddr,
+}
+
+impl Caller {
+	/// The caller of calls that were not received by a server, e.g. calls made in tests.
+	fn local() -> Self {
+		Self { connection_id: 0, remote_addr: (Ipv4Addr::LOCALHOST, 0).into() }
+	}
+}
+
+/// Rate limits of the callers of a server.
+///
+/// Every connection and every IP address has a token bucket refilled at a constant rate,
+/// allowing bursts of up to one second worth of calls. A call is accepted if both the bucket of
+/// its connection and the bucket of its IP address can pay for it.
+pub struct RateLimiter {
+	limits: RpcLimits,
+	buckets: Mutex<CallerBuckets>,
+}
+
+struct CallerBuckets {
+	per_connection: Option<Buckets<usize>>,
+	per_ip: Option<Buckets<IpAddr>>,
+}
+
+struct Buckets<K> {
+	capacity: u32,
+	buckets: HashMap<K, Bucket>,
+	evicted_at: Instant,
+}
+
+struct Bucket {
+	tokens: f64,
+	updated_at: Instant,
+}
+
+impl<K: Hash + Eq> Buckets<K> {
+	fn new(max_cost_per_sec: NonZeroU32, now: Instant) -> Self {
+		Self { capacity: max_cost_per_sec.get(), buckets: HashMap::new(), evicted_at: now }
+	}
+
+	/// Returns the bucket of `key` refilled up to `now`, and what `cost` takes from it.
+	fn refilled(&mut self, key: K, cost: u32, now: Instant) -> (&mut Bucket, f64) {
+		// An idle bucket is no different from a new one.
+		if now.saturating_duration_since(self.evicted_at) >= IDLE_BUCKET {
+			self.buckets
+				.retain(|_, bucket| now.saturating_duration_since(bucket.updated_at) < IDLE_BUCKET);
+			self.evicted_at = now;
+		}
+
+		let capacity = self.capacity;
+		let bucket = self
+			.buckets
+			.entry(key)
+			.or_insert_with(|| Bucket { tokens: capacity as f64, updated_at: now });
+		let elapsed = now.saturating_duration_since(bucket.updated_at);
+		bucket.tokens = (bucket.tokens + refill(elapsed, capacity)).min(capacity as f64);
+		bucket.updated_at = now;
+
+		// A call costing more than the capacity would never be accepted otherwise.
+		(bucket, cost.min(capacity) as f64)
+	}
+}
+
+impl RateLimiter {
+	/// Create a new [`RateLimiter`] applying the rate limits of `limits`.
+	pub fn new(limits: RpcLimits) -> Self {
+		let now = Instant::now();
+		let buckets = CallerBuckets {
+			per_connection: limits.max_cost_per_sec.map(|max| Buckets::new(max, now)),
+			per_ip: limits.max_cost_per_sec_per_ip.map(|max| Buckets::new(max, now)),
+		};
+		Self { limits, buckets: Mutex::new(buckets) }
+	}
+
+	/// Try to take the cost of calling `method` from the buckets of `caller`, returns `false` if
+	/// the call exceeds the rate limit.
+	pub fn try_acquire(&self, caller: &Caller, method: &str) -> bool {
+		self.try_acquire_at(caller, method, Instant::now())
+	}
+
+	fn try_acquire_at(&self, caller: &Caller, method: &str, now: Instant) -> bool {
+		let cost = self.limits.cost(method);
+		let mut buckets = self.buckets.lock();
+		let CallerBuckets { per_connection, per_ip } = &mut *buckets;
+
+		let buckets = [
+			per_connection
+				.as_mut()
+				.map(|buckets| buckets.refilled(caller.connection_id, cost, now)),
+			per_ip
+				.as_mut()
+				.map(|buckets| buckets.refilled(caller.remote_addr.ip(), cost, now)),
+		];
+		if buckets.iter().flatten().any(|(bucket, cost)| bucket.tokens < *cost) {
+			return false
+		}
+		for (bucket, cost) in buckets.into_iter().flatten() {
+			bucket.tokens -= cost;
+		}
+		true
+	}
+}
+
+fn refill(elapsed: Duration, per_sec: u32) -> f64 {
+	elapsed.as_secs_f64() * per_sec as f64
+}
+
+/// A method call about to be executed, see [`RateLimitMiddleware`].
+#[derive(Debug, Clone, Copy)]
+struct CurrentCall {
+	caller: Caller,
+	/// Whether the rate limiter accepted the call.
+	accepted: bool,
+}
+
+thread_local! {
+	/// The call made right after the middleware was notified of it, on the same thread.
+	static CURRENT_CALL: Cell<Option<CurrentCall>> = Cell::new(None);
+}
+
+/// Source of the connection ids of [`Caller`]s, `0` is the id of [`Caller::local`].
+static NEXT_CONNECTION_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Middleware charging the calls to the rate limits of their callers.
+///
+/// Every clone of the middleware tracks a connection of its own, the servers clone the
+/// middleware for every connection they accept.
+pub struct RateLimitMiddleware {
+	limiter: Option<Arc<RateLimiter>>,
+	caller: Mutex<Option<Caller>>,
+}
+
+impl RateLimitMiddleware {
+	/// Create a new [`RateLimitMiddleware`] charging the calls to `limiter`, if any.
+	pub fn new(limiter: Option<Arc<RateLimiter>>) -> Self {
+		Self { limiter, caller: Mutex::new(None) }
+	}
+
+	/// Records the peer of the connection, a different peer is a new connection.
+	fn connect(&self, remote_addr: SocketAddr) {
+		let mut caller = self.caller.lock();
+		if caller.map_or(true, |caller| caller.remote_addr != remote_addr) {
+			let connection_id = NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed);
+			*caller = Some(Caller { connection_id, remote_addr });
+		}
+	}
+
+	/// Charges the call to the caller, the method learns the outcome from [`CURRENT_CALL`].
+	fn on_call(&self, name: &str, kind: CallKind) {
+		let caller = self.caller.lock().unwrap_or_else(Caller::local);
+		let accepted =
+			self.limiter.as_ref().map_or(true, |limiter| limiter.try_acquire(&caller, name));
+
+		if !accepted && matches!(kind, CallKind::Subscription) {
+			log::debug!(
+				target: "rpc",
+				"Subscription {} of {:?} exceeds the rate limit",
+				name,
+				caller.remote_addr,
+			);
+		}
+		CURRENT_CALL.with(|call| call.set(Some(CurrentCall { caller, accepted })));
+	}
+}
+
+impl Clone for RateLimitMiddleware {
+	fn clone(&self) -> Self {
+		Self::new(self.limiter.clone())
+	}
+}
+
+impl WsMiddleware for RateLimitMiddleware {
+	type Instant = ();
+
+	fn on_connect(&self, remote_addr: SocketAddr, _headers: &Headers) {
+		self.connect(remote_addr)
+	}
+
+	fn on_request(&self) -> Self::Instant {}
+
+	fn on_call(&self, name: &str, _params: Params, kind: CallKind) {
+		self.on_call(name, kind)
+	}
+
+	fn on_result(&self, _name: &str, _success: bool, _started_at: Self::Instant) {}
+
+	fn on_response(&self, _result: &str, _started_at: Self::Instant) {}
+
+	fn on_disconnect(&self, _remote_addr: SocketAddr) {}
+}
+
+impl HttpMiddleware for RateLimitMiddleware {
+	type Instant = ();
+
+	fn on_request(&self, remote_addr: SocketAddr, _headers: &Headers) -> Self::Instant {
+		self.connect(remote_addr)
+	}
+
+	fn on_call(&self, name: &str, _params: Params, kind: CallKind) {
+		self.on_call(name, kind)
+	}
+
+	fn on_result(&self, _name: &str, _success: bool, _started_at: Self::Instant) {}
+
+	fn on_response(&self, _result: &str, _started_at: Self::Instant) {}
+}
+
+/// Apply `limits` to the methods of `rpc_api`.
+///
+/// Returns the modified module, the sorted names of the methods that may be called and the rate
+/// limiter that must be passed to the [`RateLimitMiddleware`] of the server, if the calls are
+/// rate limited.
+pub(crate) fn apply_limits<M: Send + Sync + 'static>(
+	mut rpc_api: RpcModule<M>,
+	limits: &RpcLimits,
+	max_response_size: usize,
+) -> (RpcModule<M>, Vec<&'static str>, Option<Arc<RateLimiter>>) {
+	let names = rpc_api.method_names().collect::<Vec<_>>();
+	let (mut allowed, denied): (Vec<_>, Vec<_>) =
+		names.into_iter().partition(|name| limits.is_allowed(name));
+	allowed.sort_unstable();
+
+	for name in denied {
+		rpc_api.remove_method(name);
+		rpc_api
+			.register_method(name, move |_, _| {
+				Err::<(), _>(call_error(
+					METHOD_NOT_ALLOWED,
+					format!("RPC method {} is not allowed", name),
+				))
+			})
+			.expect("method was removed above; qed");
+	}
+
+	if !limits.is_rate_limited() {
+		return (rpc_api, allowed, None)
+	}
+
+	let limiter = Arc::new(RateLimiter::new(limits.clone()));
+	let inner = rpc_api.clone();
+
+	// Subscriptions can't be forwarded, their cost is only charged by the middleware.
+	let methods = allowed.iter().copied().filter(|name| {
+		inner.method(name).map_or(false, |method| {
+			matches!(method.inner(), MethodKind::Sync(_) | MethodKind::Async(_))
+		})
+	});
+
+	for name in methods.collect::<Vec<_>>() {
+		let inner = inner.clone();
+		let limiter = limiter.clone();
+		rpc_api.remove_method(name);
+		rpc_api
+			.register_async_method(name, move |params, _| {
+				// Calls that didn't go through the middleware are charged here.
+				let call = CURRENT_CALL.with(|call| call.take()).unwrap_or_else(|| {
+					let caller = Caller::local();
+					CurrentCall { caller, accepted: limiter.try_acquire(&caller, name) }
+				});
+				let inner = inner.clone();
+				async move {
+					if !call.accepted {
+						return Err(call_error(
+							RATE_LIMIT_EXCEEDED,
+							"RPC rate limit exceeded, try again later".into(),
+						))
+					}
+					forward_call(&inner, name, params, call.caller.connection_id, max_response_size)
+						.await
+				}
+			})
+			.expect("method was removed above; qed");
+	}
+
+	(rpc_api, allowed, Some(limiter))
+}
+
+/// Response of a forwarded call, borrowing the result from the serialized response.
+#[derive(Deserialize)]
+struct ForwardedResponse<'a> {
+	#[serde(borrow)]
+	result: Option<&'a RawValue>,
+	#[serde(borrow)]
+	error: Option<ErrorObject<'a>>,
+}
+
+/// Call `method` of `rpc_api` with `params` and return the result of the call.
+///
+/// The callback of the method is invoked directly and its serialized result is passed through
+/// as is, it is not deserialized and serialized again. The id of the request is not known to the
+/// methods of a module, the server puts it in the response to the caller.
+async fn forward_call<M: Send + Sync + 'static>(
+	rpc_api: &RpcModule<M>,
+	method: &'static str,
+	params: Params<'static>,
+	connection_id: usize,
+	max_response_size: usize,
+) -> Result<Box<RawValue>, JsonRpseeError> {
+	let response = match rpc_api.method(method).map(|method| method.inner()) {
+		Some(MethodKind::Sync(callback)) => callback(Id::Null, params, max_response_size),
+		Some(MethodKind::Async(callback)) =>
+			callback(Id::Null, params, connection_id, max_response_size, None).await,
+		_ => return Err(JsonRpseeError::MethodNotFound(method.into())),
+	};
+
+	let response: ForwardedResponse = serde_json::from_str(&response.result)?;
+	match (response.result, response.error) {
+		(_, Some(error)) => Err(CallError::Custom(error.into_owned()).into()),
+		(Some(result), None) => Ok(result.to_owned()),
+		(None, None) => Ok(RawValue::from_string("null".into())?),
+	}
+}
+
+fn call_error(code: i32, message: String) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(code, message, None::<()>)).into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+
+	fn limits(max_cost_per_sec: u32) -> RpcLimits {
+		RpcLimits {
+			max_cost_per_sec: NonZeroU32::new(max_cost_per_sec),
+			method_costs: [("expensive".to_string(), 5)].into_iter().collect(),
+			..Default::default()
+		}
+	}
+
+	fn caller(connection_id: usize, ip: [u8; 4]) -> Caller {
+		Caller { connection_id, remote_addr: (ip, 30333).into() }
+	}
+
+	fn call(module: &RpcModule<()>, request: &str) -> String {
+		block_on(module.raw_json_request(request)).unwrap().0.result
+	}
+
+	#[test]
+	fn rate_limiter_applies_method_costs() {
+		let limiter = RateLimiter::new(limits(10));
+		let caller = Caller::local();
+		let now = Instant::now();
+
+		assert!(limiter.try_acquire_at(&caller, "expensive", now));
+		assert!(limiter.try_acquire_at(&caller, "expensive", now));
+		assert!(!limiter.try_acquire_at(&caller, "cheap", now));
+
+		// Refilled at 10 per second.
+		let now = now + Duration::from_millis(100);
+		assert!(limiter.try_acquire_at(&caller, "cheap", now));
+		assert!(!limiter.try_acquire_at(&caller, "cheap", now));
+
+		// Never more than one second worth of calls.
+		let now = now + Duration::from_secs(10);
+		for _ in 0..10 {
+			assert!(limiter.try_acquire_at(&caller, "cheap", now));
+		}
+		assert!(!limiter.try_acquire_at(&caller, "cheap", now));
+	}
+
+	#[test]
+	fn rate_limiter_applies_limits_per_connection_and_ip() {
+		let limiter = RateLimiter::new(RpcLimits {
+			max_cost_per_sec_per_ip: NonZeroU32::new(15),
+			..limits(10)
+		});
+		let (first, second, other) =
+			(caller(1, [10, 0, 0, 1]), caller(2, [10, 0, 0, 1]), caller(3, [10, 0, 0, 2]));
+		let now = Instant::now();
+
+		assert!(limiter.try_acquire_at(&first, "expensive", now));
+		assert!(limiter.try_acquire_at(&first, "expensive", now));
+		assert!(!limiter.try_acquire_at(&first, "cheap", now));
+
+		// Another connection from the same address shares the limit of the address.
+		for _ in 0..5 {
+			assert!(limiter.try_acquire_at(&second, "cheap", now));
+		}
+		assert!(!limiter.try_acquire_at(&second, "cheap", now));
+
+		// A rejected call isn't charged to the connection nor to the address.
+		assert!(limiter.try_acquire_at(&other, "expensive", now));
+		assert!(limiter.try_acquire_at(&other, "expensive", now));
+		assert!(!limiter.try_acquire_at(&other, "expensive", now));
+		assert!(limiter.try_acquire_at(&caller(4, [10, 0, 0, 2]), "expensive", now));
+
+		// Idle buckets are dropped.
+		let now = now + IDLE_BUCKET;
+		assert!(limiter.try_acquire_at(&first, "cheap", now));
+		let buckets = limiter.buckets.lock();
+		assert_eq!(buckets.per_connection.as_ref().unwrap().buckets.len(), 1);
+		assert_eq!(buckets.per_ip.as_ref().unwrap().buckets.len(), 1);
+	}
+
+	#[test]
+	fn denied_methods_return_error() {
+		let mut module = RpcModule::new(());
+		module.register_method("allowed", |_, _| Ok("allowed")).unwrap();
+		module.register_method("denied", |_, _| Ok("denied")).unwrap();
+
+		let limits = RpcLimits { denied_methods: vec!["denied".into()], ..Default::default() };
+		let (module, allowed, limiter) = apply_limits(module, &limits, usize::MAX);
+		assert_eq!(allowed, vec!["allowed"]);
+		assert!(limiter.is_none());
+
+		let request = r#"{"jsonrpc":"2.0","method":"denied","id":1}"#;
+		let (response, _) = block_on(module.raw_json_request(request)).unwrap();
+		assert_eq!(
+			response.result,
+			r#"{"jsonrpc":"2.0","error":{"code":-32030,"message":"RPC method denied is not allowed"},"id":1}"#
+		);
+
+		let request = r#"{"jsonrpc":"2.0","method":"allowed","id":1}"#;
+		let (response, _) = block_on(module.raw_json_request(request)).unwrap();
+		assert_eq!(response.result, r#"{"jsonrpc":"2.0","result":"allowed","id":1}"#);
+	}
+
+	#[test]
+	fn rate_limited_calls_return_error() {
+		let mut module = RpcModule::new(());
+		module
+			.register_method("add", |params, _| params.parse::<(u32, u32)>().map(|(a, b)| a + b))
+			.unwrap();
+
+		let (module, _, _) = apply_limits(module, &limits(1), usize::MAX);
+
+		let request = r#"{"jsonrpc":"2.0","method":"add","params":[1,2],"id":1}"#;
+		assert_eq!(call(&module, request), r#"{"jsonrpc":"2.0","result":3,"id":1}"#);
+		assert_eq!(
+			call(&module, request),
+			r#"{"jsonrpc":"2.0","error":{"code":-32029,"message":"RPC rate limit exceeded, try again later"},"id":1}"#
+		);
+	}
+
+	#[test]
+	fn rate_limits_of_clients_are_independent() {
+		let mut module = RpcModule::new(());
+		module.register_method("hello", |_, _| Ok("hello")).unwrap();
+		let (module, _, limiter) = apply_limits(module, &limits(1), usize::MAX);
+
+		// The server clones the middleware for every connection.
+		let middleware = RateLimitMiddleware::new(limiter);
+		let (first, second) = (middleware.clone(), middleware.clone());
+		WsMiddleware::on_connect(&first, ([10, 0, 0, 1], 30333).into(), &Headers::new());
+		WsMiddleware::on_connect(&second, ([10, 0, 0, 2], 30333).into(), &Headers::new());
+
+		let request = r#"{"jsonrpc":"2.0","method":"hello","id":1}"#;
+		let call_on = |middleware: &RateLimitMiddleware| {
+			WsMiddleware::on_call(middleware, "hello", Params::new(None), CallKind::MethodCall);
+			call(&module, request)
+		};
+		let limited = r#"{"jsonrpc":"2.0","error":{"code":-32029,"message":"RPC rate limit exceeded, try again later"},"id":1}"#;
+
+		assert_eq!(call_on(&first), r#"{"jsonrpc":"2.0","result":"hello","id":1}"#);
+		assert_eq!(call_on(&first), limited);
+		assert_eq!(call_on(&second), r#"{"jsonrpc":"2.0","result":"hello","id":1}"#);
+		assert_eq!(call_on(&second), limited);
+	}
+
+	#[test]
+	fn subscriptions_are_charged() {
+		let limiter = Arc::new(RateLimiter::new(limits(5)));
+		let middleware = RateLimitMiddleware::new(Some(limiter.clone()));
+		let remote_addr = SocketAddr::from(([10, 0, 0, 1], 30333));
+		HttpMiddleware::on_request(&middleware, remote_addr, &Headers::new());
+
+		for _ in 0..5 {
+			HttpMiddleware::on_call(
+				&middleware,
+				"subscribe",
+				Params::new(None),
+				CallKind::Subscription,
+			);
+		}
+		let caller = middleware.caller.lock().unwrap();
+		assert_eq!(caller.remote_addr, remote_addr);
+		assert!(!limiter.try_acquire(&caller, "cheap"));
+	}
+}
@@ -0,0 +1,67 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API trait of the archive methods.
+
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use sp_core::Bytes;
+
+/// Substrate archive RPC API
+#[rpc(client, server)]
+pub trait ArchiveApi<Hash> {
+	/// Retrieves the SCALE encoded extrinsics of a block.
+	///
+	/// Returns `None` if the block is unknown or its body is not available.
+	#[method(name = "archive_unstable_body")]
+	fn body(&self, hash: Hash) -> RpcResult<Option<Vec<Bytes>>>;
+
+	/// Retrieves the SCALE encoded header of a block.
+	///
+	/// Returns `None` if the block is unknown.
+	#[method(name = "archive_unstable_header")]
+	fn header(&self, hash: Hash) -> RpcResult<Option<Bytes>>;
+
+	/// Retrieves the hash of the genesis block.
+	#[method(name = "archive_unstable_genesisHash")]
+	fn genesis_hash(&self) -> RpcResult<Hash>;
+
+	/// Retrieves the height of the latest finalized block.
+	#[method(name = "archive_unstable_finalizedHeight")]
+	fn finalized_height(&self) -> RpcResult<u64>;
+
+	/// Retrieves the hashes of all known blocks at the given height.
+	///
+	/// Heights up to the latest finalized block contain at most one block.
+	#[method(name = "archive_unstable_hashByHeight")]
+	fn hash_by_height(&self, height: u64) -> RpcResult<Vec<Hash>>;
+
+	/// Returns the storage entry at the given key in the state of a block.
+	///
+	/// If `child_trie` is given, the key is looked up in that child trie instead.
+	#[method(name = "archive_unstable_storage")]
+	fn storage(
+		&self,
+		hash: Hash,
+		key: Bytes,
+		child_trie: Option<Bytes>,
+	) -> RpcResult<Option<Bytes>>;
+
+	/// Calls the given runtime function on top of a block.
+	#[method(name = "archive_unstable_call")]
+	fn call(&self, hash: Hash, function: String, call_parameters: Bytes) -> RpcResult<Bytes>;
+}
@@ -0,0 +1,159 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API implementation for `archive`.
+
+use crate::archive::{api::ArchiveApiServer, error::Error as ArchiveRpcError};
+use codec::Encode;
+use jsonrpsee::core::RpcResult;
+use sc_client_api::{
+	Backend, BlockBackend, CallExecutor, ChildInfo, ExecutorProvider, StorageKey, StorageProvider,
+};
+use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
+use sp_core::Bytes;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header, NumberFor, UniqueSaturatedInto},
+};
+use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+
+/// An API for archive RPC calls.
+pub struct Archive<BE, Block: BlockT, Client> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Backend of the chain.
+	backend: Arc<BE>,
+	/// The hash of the genesis block.
+	genesis_hash: Block::Hash,
+	/// Phantom member to pin the block type.
+	_phantom: PhantomData<Block>,
+}
+
+impl<BE, Block: BlockT, Client> Archive<BE, Block, Client> {
+	/// Create a new [`Archive`].
+	pub fn new(client: Arc<Client>, backend: Arc<BE>, genesis_hash: Block::Hash) -> Self {
+		Self { client, backend, genesis_hash, _phantom: PhantomData }
+	}
+}
+
+impl<BE, Block, Client> ArchiveApiServer<Block::Hash> for Archive<BE, Block, Client>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: BlockBackend<Block>
+		+ ExecutorProvider<Block>
+		+ HeaderBackend<Block>
+		+ StorageProvider<Block, BE>
+		+ Send
+		+ Sync
+		+ 'static,
+{
+	fn body(&self, hash: Block::Hash) -> RpcResult<Option<Vec<Bytes>>> {
+		let body = self.client.block_body(&BlockId::Hash(hash)).map_err(ArchiveRpcError::Client)?;
+		Ok(body.map(|extrinsics| extrinsics.iter().map(|ext| ext.encode().into()).collect()))
+	}
+
+	fn header(&self, hash: Block::Hash) -> RpcResult<Option<Bytes>> {
+		let header = self.client.header(BlockId::Hash(hash)).map_err(ArchiveRpcError::Client)?;
+		Ok(header.map(|header| header.encode().into()))
+	}
+
+	fn genesis_hash(&self) -> RpcResult<Block::Hash> {
+		Ok(self.genesis_hash)
+	}
+
+	fn finalized_height(&self) -> RpcResult<u64> {
+		Ok(self.client.info().finalized_number.unique_saturated_into())
+	}
+
+	fn hash_by_height(&self, height: u64) -> RpcResult<Vec<Block::Hash>> {
+		let height: NumberFor<Block> = height.try_into().map_err(|_| {
+			ArchiveRpcError::InvalidParam(format!("Height {} is out of range", height))
+		})?;
+
+		// Blocks up to the finalized height are canonical.
+		if height <= self.client.info().finalized_number {
+			let hash = self.client.hash(height).map_err(ArchiveRpcError::Client)?;
+			return Ok(hash.into_iter().collect())
+		}
+
+		// Walk back from every leaf to the requested height.
+		let blockchain = self.backend.blockchain();
+		let leaves = blockchain.leaves().map_err(ArchiveRpcError::Client)?;
+		let mut hashes = Vec::new();
+		let mut visited = HashSet::new();
+		for leaf in leaves {
+			let mut current =
+				blockchain.header(BlockId::Hash(leaf)).map_err(ArchiveRpcError::Client)?;
+			while let Some(header) = current {
+				let number = *header.number();
+				if number < height || !visited.insert(header.hash()) {
+					break
+				}
+				if number == height {
+					hashes.push(header.hash());
+					break
+				}
+				current = blockchain
+					.header(BlockId::Hash(*header.parent_hash()))
+					.map_err(ArchiveRpcError::Client)?;
+			}
+		}
+
+		Ok(hashes)
+	}
+
+	fn storage(
+		&self,
+		hash: Block::Hash,
+		key: Bytes,
+		child_trie: Option<Bytes>,
+	) -> RpcResult<Option<Bytes>> {
+		let key = StorageKey(key.0);
+		let value = match child_trie {
+			Some(child_trie) => self.client.child_storage(
+				&BlockId::Hash(hash),
+				&ChildInfo::new_default(&child_trie),
+				&key,
+			),
+			None => self.client.storage(&BlockId::Hash(hash), &key),
+		}
+		.map_err(ArchiveRpcError::Client)?;
+
+		Ok(value.map(|data| data.0.into()))
+	}
+
+	fn call(
+		&self,
+		hash: Block::Hash,
+		function: String,
+		call_parameters: Bytes,
+	) -> RpcResult<Bytes> {
+		self.client
+			.executor()
+			.call(
+				&BlockId::Hash(hash),
+				&function,
+				&call_parameters,
+				self.client.execution_extensions().strategies().other,
+				None,
+			)
+			.map(Into::into)
+			.map_err(|err| ArchiveRpcError::RuntimeCall(err.to_string()).into())
+	}
+}
@@ -0,0 +1,59 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error helpers for `archive` RPC module.
+
+use crate::chain_head::error::{json_rpc_spec, rpc_spec_v2};
+use jsonrpsee::{
+	core::Error as JsonRpseeError,
+	types::error::{CallError, ErrorObject},
+};
+
+/// Archive RPC errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// Wrong request parameter.
+	#[error("Invalid parameter: {0}")]
+	InvalidParam(String),
+	/// Runtime call failed.
+	#[error("Runtime call failed: {0}")]
+	RuntimeCall(String),
+	/// Client error.
+	#[error("Client error: {0}")]
+	Client(#[from] sp_blockchain::Error),
+}
+
+impl From<Error> for ErrorObject<'static> {
+	fn from(e: Error) -> Self {
+		let msg = e.to_string();
+
+		match e {
+			Error::InvalidParam(_) =>
+				ErrorObject::owned(json_rpc_spec::INVALID_PARAM_ERROR, msg, None::<()>),
+			Error::RuntimeCall(_) =>
+				ErrorObject::owned(rpc_spec_v2::RUNTIME_CALL_ERROR, msg, None::<()>),
+			Error::Client(_) => ErrorObject::owned(json_rpc_spec::INTERNAL_ERROR, msg, None::<()>),
+		}
+	}
+}
+
+impl From<Error> for JsonRpseeError {
+	fn from(e: Error) -> Self {
+		CallError::Custom(e.into()).into()
+	}
+}
@@ -0,0 +1,113 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use codec::Encode;
+use jsonrpsee::{types::EmptyParams, RpcModule};
+use sc_block_builder::BlockBuilderProvider;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_core::{storage::well_known_keys::CODE, Bytes};
+use sp_runtime::generic::BlockId;
+use std::sync::Arc;
+use substrate_test_runtime_client::{
+	prelude::*,
+	runtime::{Block, Hash},
+	Backend, Client,
+};
+
+fn setup_api() -> (Arc<Client<Backend>>, RpcModule<Archive<Backend, Block, Client<Backend>>>) {
+	let builder = TestClientBuilder::new();
+	let backend = builder.backend();
+	let client = Arc::new(builder.build());
+
+	let api = Archive::new(client.clone(), backend, client.genesis_hash()).into_rpc();
+	(client, api)
+}
+
+#[tokio::test]
+async fn archive_block_queries() {
+	let (mut client, api) = setup_api();
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_hash = block.hash();
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let header: Option<Bytes> = api.call("archive_unstable_header", [block_hash]).await.unwrap();
+	assert_eq!(header.unwrap().0, block.header.encode());
+
+	let body: Option<Vec<Bytes>> = api.call("archive_unstable_body", [block_hash]).await.unwrap();
+	assert_eq!(body, Some(vec![]));
+
+	let unknown: Option<Bytes> =
+		api.call("archive_unstable_header", [Hash::repeat_byte(1)]).await.unwrap();
+	assert!(unknown.is_none());
+
+	let genesis: Hash = api.call("archive_unstable_genesisHash", EmptyParams::new()).await.unwrap();
+	assert_eq!(genesis, client.genesis_hash());
+
+	let code: Option<Bytes> = api
+		.call("archive_unstable_storage", (block_hash, Bytes(CODE.to_vec()), None::<Bytes>))
+		.await
+		.unwrap();
+	assert!(code.is_some());
+
+	let version: Bytes = api
+		.call("archive_unstable_call", (block_hash, "Core_version", Bytes(vec![])))
+		.await
+		.unwrap();
+	assert!(!version.0.is_empty());
+}
+
+#[tokio::test]
+async fn archive_hash_by_height() {
+	let (mut client, api) = setup_api();
+
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_1 = block.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_2 = block.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	// Fork of block 2 on top of block 1.
+	let mut builder =
+		client.new_block_at(&BlockId::Hash(block_1), Default::default(), false).unwrap();
+	builder.push_storage_change(vec![1], Some(vec![2])).unwrap();
+	let block = builder.build().unwrap().block;
+	let fork_2 = block.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	client.finalize_block(BlockId::Hash(block_1), None).unwrap();
+
+	let height: u64 =
+		api.call("archive_unstable_finalizedHeight", EmptyParams::new()).await.unwrap();
+	assert_eq!(height, 1);
+
+	let hashes: Vec<Hash> = api.call("archive_unstable_hashByHeight", [1]).await.unwrap();
+	assert_eq!(hashes, vec![block_1]);
+
+	let mut hashes: Vec<Hash> = api.call("archive_unstable_hashByHeight", [2]).await.unwrap();
+	hashes.sort();
+	let mut expected = vec![block_2, fork_2];
+	expected.sort();
+	assert_eq!(hashes, expected);
+
+	let hashes: Vec<Hash> = api.call("archive_unstable_hashByHeight", [3]).await.unwrap();
+	assert!(hashes.is_empty());
+	assert_eq!(client.info().best_number, 2);
+}
@@ -0,0 +1,82 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API trait of the chain head.
+
+use crate::chain_head::event::FollowEvent;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use sp_core::Bytes;
+
+/// Substrate chain head RPC API
+#[rpc(client, server)]
+pub trait ChainHeadApi<Hash> {
+	/// Track the state of the head of the chain: the finalized, non-finalized, and best blocks.
+	///
+	/// Every reported block is pinned by the subscription until it is unpinned with
+	/// `chainHead_unstable_unpin` or until the subscription stops. If `runtime_updates` is
+	/// `true`, the events carry the runtime of the reported blocks whenever it changed.
+	#[subscription(
+		name = "chainHead_unstable_follow" => "chainHead_unstable_followEvent",
+		unsubscribe = "chainHead_unstable_unfollow",
+		item = FollowEvent<Hash>,
+	)]
+	fn follow(&self, runtime_updates: bool);
+
+	/// Retrieves the SCALE encoded extrinsics of a block pinned by the follow subscription.
+	///
+	/// Returns `None` if the body of the block is not available.
+	#[method(name = "chainHead_unstable_body")]
+	fn body(&self, follow_subscription: String, hash: Hash) -> RpcResult<Option<Vec<Bytes>>>;
+
+	/// Retrieves the SCALE encoded header of a block pinned by the follow subscription.
+	#[method(name = "chainHead_unstable_header")]
+	fn header(&self, follow_subscription: String, hash: Hash) -> RpcResult<Option<Bytes>>;
+
+	/// Returns the storage entry at the given key in the state of a block pinned by the follow
+	/// subscription.
+	///
+	/// If `child_trie` is given, the key is looked up in that child trie instead.
+	#[method(name = "chainHead_unstable_storage")]
+	fn storage(
+		&self,
+		follow_subscription: String,
+		hash: Hash,
+		key: Bytes,
+		child_trie: Option<Bytes>,
+	) -> RpcResult<Option<Bytes>>;
+
+	/// Calls the given runtime function on top of a block pinned by the follow subscription.
+	#[method(name = "chainHead_unstable_call")]
+	fn call(
+		&self,
+		follow_subscription: String,
+		hash: Hash,
+		function: String,
+		call_parameters: Bytes,
+	) -> RpcResult<Bytes>;
+
+	/// Unpins a block reported by the follow subscription.
+	///
+	/// The block can no longer be queried through the subscription afterwards.
+	#[method(name = "chainHead_unstable_unpin")]
+	fn unpin(&self, follow_subscription: String, hash: Hash) -> RpcResult<()>;
+
+	/// Retrieves the hash of the genesis block.
+	#[method(name = "chainHead_unstable_genesisHash")]
+	fn genesis_hash(&self) -> RpcResult<Hash>;
+}
@@ -0,0 +1,541 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API implementation for `chainHead`.
+
+use crate::{
+	chain_head::{
+		api::ChainHeadApiServer,
+		error::Error as ChainHeadRpcError,
+		event::{
+			BestBlockChanged, ErrorEvent, Finalized, FollowEvent, Initialized, NewBlock,
+			RuntimeEvent, RuntimeVersionEvent,
+		},
+		subscription::{SubscriptionError, SubscriptionLimits, SubscriptionManagement},
+	},
+	SubscriptionTaskExecutor,
+};
+use codec::Encode;
+use futures::{
+	future::{self, FutureExt},
+	stream::{self, StreamExt},
+};
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	types::{SubscriptionId, SubscriptionResult},
+	SubscriptionSink,
+};
+use sc_client_api::{
+	Backend, BlockBackend, BlockImportNotification, BlockchainEvents, CallExecutor, ChildInfo,
+	ExecutorProvider, FinalityNotification, StorageKey, StorageProvider,
+};
+use sp_api::CallApiAt;
+use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
+use sp_core::Bytes;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header, NumberFor},
+};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+/// Maximum number of blocks a single follow subscription can pin.
+pub const MAX_PINNED_BLOCKS: usize = 512;
+
+/// Maximum number of distinct blocks all follow subscriptions together can pin.
+pub const GLOBAL_MAX_PINNED_BLOCKS: usize = 1024;
+
+/// Maximum number of follow subscriptions.
+pub const MAX_FOLLOW_SUBSCRIPTIONS: usize = 16;
+
+impl Default for SubscriptionLimits {
+	fn default() -> Self {
+		Self {
+			max_pinned_blocks: MAX_PINNED_BLOCKS,
+			global_max_pinned_blocks: GLOBAL_MAX_PINNED_BLOCKS,
+			max_subscriptions: MAX_FOLLOW_SUBSCRIPTIONS,
+		}
+	}
+}
+
+/// An API for chain head RPC calls.
+pub struct ChainHead<BE: Backend<Block>, Block: BlockT, Client> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Backend of the chain.
+	backend: Arc<BE>,
+	/// Executor to spawn subscriptions.
+	executor: SubscriptionTaskExecutor,
+	/// Keep track of the pinned blocks for each subscription.
+	subscriptions: SubscriptionManagement<Block, BE>,
+	/// The hash of the genesis block.
+	genesis_hash: Block::Hash,
+}
+
+impl<BE: Backend<Block>, Block: BlockT, Client> ChainHead<BE, Block, Client> {
+	/// Create a new [`ChainHead`].
+	///
+	/// A follow subscription is stopped once it exceeds the number of blocks it may pin, once
+	/// all follow subscriptions together exceed the number of blocks they may pin, or once the
+	/// backend fails to pin a block. New follow subscriptions are stopped right away when there
+	/// are too many follow subscriptions already.
+	pub fn new(
+		client: Arc<Client>,
+		backend: Arc<BE>,
+		executor: SubscriptionTaskExecutor,
+		genesis_hash: Block::Hash,
+		limits: SubscriptionLimits,
+	) -> Self {
+		Self {
+			client,
+			backend: backend.clone(),
+			executor,
+			subscriptions: SubscriptionManagement::new(limits, backend),
+			genesis_hash,
+		}
+	}
+}
+
+/// Converts the error of the subscription management into an RPC error.
+fn subscription_error(err: SubscriptionError) -> ChainHeadRpcError {
+	match err {
+		SubscriptionError::InvalidSubscription => ChainHeadRpcError::InvalidFollowSubscription,
+		SubscriptionError::InvalidBlock |
+		SubscriptionError::ExceededLimits |
+		SubscriptionError::PinFailed(_) => ChainHeadRpcError::InvalidBlock,
+	}
+}
+
+/// Accepts the subscription and returns its ID.
+///
+/// Returns `None` if the subscription could not be accepted, e.g. because the client
+/// disconnected already.
+fn accept_subscription(sink: &mut SubscriptionSink) -> Option<String> {
+	// The subscription must be accepted before it can provide a valid subscription ID.
+	sink.accept().ok()?;
+
+	match sink.subscription_id()? {
+		SubscriptionId::Num(num) => Some(num.to_string()),
+		SubscriptionId::Str(id) => Some(id.into_owned()),
+	}
+}
+
+/// Notification that a follow subscription reacts to.
+enum Notification<Block: BlockT> {
+	Import(BlockImportNotification<Block>),
+	Finality(FinalityNotification<Block>),
+}
+
+/// The state of a single follow subscription, producing its events.
+struct Follow<BE: Backend<Block>, Block: BlockT, Client> {
+	client: Arc<Client>,
+	backend: Arc<BE>,
+	subscriptions: SubscriptionManagement<Block, BE>,
+	sub_id: String,
+	runtime_updates: bool,
+	/// Non-finalized blocks reported by the subscription, with their number and parent hash.
+	announced: HashMap<Block::Hash, (NumberFor<Block>, Block::Hash)>,
+	/// The last reported best block.
+	best_block: Option<Block::Hash>,
+}
+
+impl<BE, Block, Client> Follow<BE, Block, Client>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: HeaderBackend<Block> + CallApiAt<Block> + Send + Sync + 'static,
+{
+	/// Pins the block for the subscription, returns `false` if the subscription must stop.
+	fn pin(&self, hash: Block::Hash) -> bool {
+		match self.subscriptions.pin_block(&self.sub_id, hash) {
+			Ok(()) => true,
+			Err(err) => {
+				log::debug!(
+					target: "rpc-spec-v2",
+					"[follow][id={:?}] Stopping subscription, failed to pin block {:?}: {:?}",
+					self.sub_id,
+					hash,
+					err,
+				);
+				false
+			},
+		}
+	}
+
+	/// Returns the runtime of the block if the subscription requested runtime updates and the
+	/// runtime changed compared to the parent block, if any.
+	fn runtime_event(
+		&self,
+		hash: Block::Hash,
+		parent: Option<Block::Hash>,
+	) -> Option<RuntimeEvent> {
+		if !self.runtime_updates {
+			return None
+		}
+
+		let version = match self.client.runtime_version_at(&BlockId::Hash(hash)) {
+			Ok(version) => version,
+			Err(err) => return Some(RuntimeEvent::Invalid(ErrorEvent { error: err.to_string() })),
+		};
+
+		let parent_version =
+			parent.and_then(|parent| self.client.runtime_version_at(&BlockId::Hash(parent)).ok());
+		if parent_version.as_ref() == Some(&version) {
+			return None
+		}
+
+		Some(RuntimeEvent::Valid(RuntimeVersionEvent { spec: version }))
+	}
+
+	/// Announces a new non-finalized block, returns `false` if the subscription must stop.
+	fn new_block(
+		&mut self,
+		events: &mut Vec<FollowEvent<Block::Hash>>,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		parent: Block::Hash,
+	) -> bool {
+		if !self.pin(hash) {
+			return false
+		}
+
+		self.announced.insert(hash, (number, parent));
+		events.push(FollowEvent::NewBlock(NewBlock {
+			block_hash: hash,
+			parent_block_hash: parent,
+			new_runtime: self.runtime_event(hash, Some(parent)),
+		}));
+		true
+	}
+
+	/// Reports the given block as the new best block, if it isn't already.
+	fn best_block(&mut self, events: &mut Vec<FollowEvent<Block::Hash>>, hash: Block::Hash) {
+		if self.best_block != Some(hash) {
+			self.best_block = Some(hash);
+			events.push(FollowEvent::BestBlockChanged(BestBlockChanged { best_block_hash: hash }));
+		}
+	}
+
+	/// Generates the `Initialized` event, followed by the known non-finalized blocks and the
+	/// best block.
+	fn initial_events(&mut self) -> Vec<FollowEvent<Block::Hash>> {
+		let info = self.client.info();
+		let mut events = Vec::new();
+
+		if !self.pin(info.finalized_hash) {
+			return vec![FollowEvent::Stop]
+		}
+		events.push(FollowEvent::Initialized(Initialized {
+			finalized_block_hash: info.finalized_hash,
+			finalized_block_runtime: self.runtime_event(info.finalized_hash, None),
+		}));
+		self.best_block = Some(info.finalized_hash);
+
+		// Report the descendants of the finalized block, parents first.
+		let mut to_visit = vec![info.finalized_hash];
+		while let Some(parent) = to_visit.pop() {
+			let children = match self.backend.blockchain().children(parent) {
+				Ok(children) => children,
+				Err(err) => {
+					log::debug!(
+						target: "rpc-spec-v2",
+						"[follow][id={:?}] Failed to fetch children of {:?}: {}",
+						self.sub_id,
+						parent,
+						err,
+					);
+					continue
+				},
+			};
+
+			for child in children {
+				let number = match self.client.number(child) {
+					Ok(Some(number)) => number,
+					_ => continue,
+				};
+				if !self.new_block(&mut events, child, number, parent) {
+					events.push(FollowEvent::Stop);
+					return events
+				}
+				to_visit.push(child);
+			}
+		}
+
+		self.best_block(&mut events, info.best_hash);
+		events
+	}
+
+	/// Generates the events of a block import.
+	fn on_import(
+		&mut self,
+		notification: BlockImportNotification<Block>,
+	) -> Vec<FollowEvent<Block::Hash>> {
+		let mut events = Vec::new();
+
+		// The block might have been reported by the initial events already.
+		if !self.announced.contains_key(&notification.hash) {
+			let number = *notification.header.number();
+			let parent = *notification.header.parent_hash();
+			if !self.new_block(&mut events, notification.hash, number, parent) {
+				events.push(FollowEvent::Stop);
+				return events
+			}
+		}
+
+		if notification.is_new_best {
+			self.best_block(&mut events, notification.hash);
+		}
+		events
+	}
+
+	/// Generates the events of a finalization.
+	fn on_finality(
+		&mut self,
+		notification: FinalityNotification<Block>,
+	) -> Vec<FollowEvent<Block::Hash>> {
+		let mut events = Vec::new();
+		let finalized_number = *notification.header.number();
+
+		let mut finalized_block_hashes = notification.tree_route.to_vec();
+		finalized_block_hashes.push(notification.hash);
+
+		// Blocks that were finalized without being imported while the subscription was active
+		// are announced first.
+		for hash in &finalized_block_hashes {
+			if self.announced.contains_key(hash) {
+				continue
+			}
+
+			let header = match self.client.header(BlockId::Hash(*hash)) {
+				Ok(Some(header)) => header,
+				_ => continue,
+			};
+			if !self.new_block(&mut events, *hash, *header.number(), *header.parent_hash()) {
+				events.push(FollowEvent::Stop);
+				return events
+			}
+		}
+
+		for hash in &finalized_block_hashes {
+			self.announced.remove(hash);
+		}
+
+		// Every remaining block at or below the finalized height is on a pruned fork, as is
+		// every descendant of such a block.
+		let mut remaining = self
+			.announced
+			.iter()
+			.map(|(hash, (number, parent))| (*number, *hash, *parent))
+			.collect::<Vec<_>>();
+		remaining.sort();
+		let mut pruned = HashSet::new();
+		let mut pruned_block_hashes = Vec::new();
+		for (number, hash, parent) in remaining {
+			if number <= finalized_number || pruned.contains(&parent) {
+				pruned.insert(hash);
+				pruned_block_hashes.push(hash);
+				self.announced.remove(&hash);
+			}
+		}
+
+		// The best block must not be pruned.
+		if self.best_block.map_or(false, |best| pruned.contains(&best)) {
+			let best_hash = self.client.info().best_hash;
+			self.best_block(&mut events, best_hash);
+		}
+		events.push(FollowEvent::Finalized(Finalized {
+			finalized_block_hashes,
+			pruned_block_hashes,
+		}));
+		events
+	}
+
+	/// Generates the events of the given notification.
+	fn on_notification(
+		&mut self,
+		notification: Notification<Block>,
+	) -> Vec<FollowEvent<Block::Hash>> {
+		match notification {
+			Notification::Import(notification) => self.on_import(notification),
+			Notification::Finality(notification) => self.on_finality(notification),
+		}
+	}
+}
+
+#[async_trait]
+impl<BE, Block, Client> ChainHeadApiServer<Block::Hash> for ChainHead<BE, Block, Client>
+where
+	Block: BlockT + 'static,
+	Block::Header: Unpin,
+	BE: Backend<Block> + 'static,
+	Client: BlockBackend<Block>
+		+ ExecutorProvider<Block>
+		+ HeaderBackend<Block>
+		+ BlockchainEvents<Block>
+		+ CallApiAt<Block>
+		+ StorageProvider<Block, BE>
+		+ Send
+		+ Sync
+		+ 'static,
+{
+	fn follow(&self, mut sink: SubscriptionSink, runtime_updates: bool) -> SubscriptionResult {
+		let sub_id = match accept_subscription(&mut sink) {
+			Some(sub_id) => sub_id,
+			None => return Ok(()),
+		};
+
+		if let Err(err) = self.subscriptions.insert_subscription(sub_id.clone()) {
+			log::debug!(
+				target: "rpc-spec-v2",
+				"[follow][id={:?}] Stopping subscription: {:?}",
+				sub_id,
+				err,
+			);
+			let _ = sink.send(&FollowEvent::<Block::Hash>::Stop);
+			return Ok(())
+		}
+
+		// Register for the notifications before generating the initial events, such that no
+		// block is missed. Blocks reported twice are filtered out.
+		let notifications = stream::select(
+			self.client.import_notification_stream().map(Notification::Import),
+			self.client.finality_notification_stream().map(Notification::Finality),
+		);
+
+		let mut follow = Follow {
+			client: self.client.clone(),
+			backend: self.backend.clone(),
+			subscriptions: self.subscriptions.clone(),
+			sub_id: sub_id.clone(),
+			runtime_updates,
+			announced: HashMap::new(),
+			best_block: None,
+		};
+		let initial_events = follow.initial_events();
+
+		let events = stream::iter(initial_events)
+			.chain(
+				notifications
+					.map(move |notification| stream::iter(follow.on_notification(notification)))
+					.flatten(),
+			)
+			// Nothing is reported after the `Stop` event.
+			.scan(false, |stopped, event| {
+				if *stopped {
+					return future::ready(None)
+				}
+				*stopped = matches!(event, FollowEvent::Stop);
+				future::ready(Some(event))
+			});
+
+		let subscriptions = self.subscriptions.clone();
+		let fut = async move {
+			sink.pipe_from_stream(events.boxed()).await;
+			subscriptions.remove_subscription(&sub_id);
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+
+	fn body(
+		&self,
+		follow_subscription: String,
+		hash: Block::Hash,
+	) -> RpcResult<Option<Vec<Bytes>>> {
+		self.subscriptions
+			.ensure_pinned(&follow_subscription, &hash)
+			.map_err(subscription_error)?;
+
+		let body = self
+			.client
+			.block_body(&BlockId::Hash(hash))
+			.map_err(ChainHeadRpcError::Client)?;
+		Ok(body.map(|extrinsics| extrinsics.iter().map(|ext| ext.encode().into()).collect()))
+	}
+
+	fn header(&self, follow_subscription: String, hash: Block::Hash) -> RpcResult<Option<Bytes>> {
+		self.subscriptions
+			.ensure_pinned(&follow_subscription, &hash)
+			.map_err(subscription_error)?;
+
+		let header = self.client.header(BlockId::Hash(hash)).map_err(ChainHeadRpcError::Client)?;
+		Ok(header.map(|header| header.encode().into()))
+	}
+
+	fn storage(
+		&self,
+		follow_subscription: String,
+		hash: Block::Hash,
+		key: Bytes,
+		child_trie: Option<Bytes>,
+	) -> RpcResult<Option<Bytes>> {
+		self.subscriptions
+			.ensure_pinned(&follow_subscription, &hash)
+			.map_err(subscription_error)?;
+
+		let key = StorageKey(key.0);
+		let value = match child_trie {
+			Some(child_trie) => self.client.child_storage(
+				&BlockId::Hash(hash),
+				&ChildInfo::new_default(&child_trie),
+				&key,
+			),
+			None => self.client.storage(&BlockId::Hash(hash), &key),
+		}
+		.map_err(ChainHeadRpcError::Client)?;
+
+		Ok(value.map(|data| data.0.into()))
+	}
+
+	fn call(
+		&self,
+		follow_subscription: String,
+		hash: Block::Hash,
+		function: String,
+		call_parameters: Bytes,
+	) -> RpcResult<Bytes> {
+		self.subscriptions
+			.ensure_pinned(&follow_subscription, &hash)
+			.map_err(subscription_error)?;
+
+		self.client
+			.executor()
+			.call(
+				&BlockId::Hash(hash),
+				&function,
+				&call_parameters,
+				self.client.execution_extensions().strategies().other,
+				Some(self.client.execution_extensions().rpc_call_extensions()),
+			)
+			.map(Into::into)
+			.map_err(|err| ChainHeadRpcError::RuntimeCall(err.to_string()).into())
+	}
+
+	fn unpin(&self, follow_subscription: String, hash: Block::Hash) -> RpcResult<()> {
+		self.subscriptions
+			.unpin_block(&follow_subscription, &hash)
+			.map_err(|err| subscription_error(err).into())
+	}
+
+	fn genesis_hash(&self) -> RpcResult<Block::Hash> {
+		Ok(self.genesis_hash)
+	}
+}
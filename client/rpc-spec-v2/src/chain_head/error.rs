@@ -0,0 +1,88 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error helpers for `chainHead` RPC module.
+
+use jsonrpsee::{
+	core::Error as JsonRpseeError,
+	types::error::{CallError, ErrorObject},
+};
+
+/// ChainHead RPC errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// The provided block hash is invalid or not pinned by the follow subscription.
+	#[error("Invalid block hash")]
+	InvalidBlock,
+	/// The follow subscription is unknown.
+	#[error("Invalid follow subscription")]
+	InvalidFollowSubscription,
+	/// Wrong request parameter.
+	#[error("Invalid parameter: {0}")]
+	InvalidParam(String),
+	/// Runtime call failed.
+	#[error("Runtime call failed: {0}")]
+	RuntimeCall(String),
+	/// Client error.
+	#[error("Client error: {0}")]
+	Client(#[from] sp_blockchain::Error),
+}
+
+/// Errors for `chainHead` RPC module, as defined in
+/// <https://github.com/paritytech/json-rpc-interface-spec>.
+pub mod rpc_spec_v2 {
+	/// The provided block hash is invalid.
+	pub const INVALID_BLOCK_ERROR: i32 = -32801;
+	/// The follow subscription is unknown.
+	pub const INVALID_FOLLOW_SUBSCRIPTION_ERROR: i32 = -32802;
+	/// The runtime call failed.
+	pub const RUNTIME_CALL_ERROR: i32 = -32803;
+}
+
+/// General purpose errors, as defined in
+/// <https://www.jsonrpc.org/specification#error_object>.
+pub mod json_rpc_spec {
+	/// Invalid parameter error.
+	pub const INVALID_PARAM_ERROR: i32 = -32602;
+	/// Internal error.
+	pub const INTERNAL_ERROR: i32 = -32603;
+}
+
+impl From<Error> for ErrorObject<'static> {
+	fn from(e: Error) -> Self {
+		let msg = e.to_string();
+
+		match e {
+			Error::InvalidBlock =>
+				ErrorObject::owned(rpc_spec_v2::INVALID_BLOCK_ERROR, msg, None::<()>),
+			Error::InvalidFollowSubscription =>
+				ErrorObject::owned(rpc_spec_v2::INVALID_FOLLOW_SUBSCRIPTION_ERROR, msg, None::<()>),
+			Error::InvalidParam(_) =>
+				ErrorObject::owned(json_rpc_spec::INVALID_PARAM_ERROR, msg, None::<()>),
+			Error::RuntimeCall(_) =>
+				ErrorObject::owned(rpc_spec_v2::RUNTIME_CALL_ERROR, msg, None::<()>),
+			Error::Client(_) => ErrorObject::owned(json_rpc_spec::INTERNAL_ERROR, msg, None::<()>),
+		}
+	}
+}
+
+impl From<Error> for JsonRpseeError {
+	fn from(e: Error) -> Self {
+		CallError::Custom(e.into()).into()
+	}
+}
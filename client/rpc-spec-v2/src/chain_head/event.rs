@@ -0,0 +1,185 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The chain head's event returned as json compatible object.
+
+use serde::{Deserialize, Serialize};
+use sp_version::RuntimeVersion;
+
+/// The runtime specification of the current block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeVersionEvent {
+	/// The runtime version.
+	pub spec: RuntimeVersion,
+}
+
+/// The runtime could not be obtained.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorEvent {
+	/// Reason of the error.
+	pub error: String,
+}
+
+/// The runtime event generated if the `follow` subscription
+/// has set the `runtime_updates` flag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum RuntimeEvent {
+	/// The runtime version of this block.
+	Valid(RuntimeVersionEvent),
+	/// The runtime could not be obtained due to an error.
+	Invalid(ErrorEvent),
+}
+
+/// Contain information about the latest finalized block.
+///
+/// # Note
+///
+/// This is the first event generated by the `follow` subscription
+/// and is submitted only once.
+///
+/// If the `runtime_updates` flag is set, then this event contains
+/// the `RuntimeEvent`, otherwise the `RuntimeEvent` is not present.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Initialized<Hash> {
+	/// The hash of the latest finalized block.
+	pub finalized_block_hash: Hash,
+	/// The runtime version of the finalized block.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub finalized_block_runtime: Option<RuntimeEvent>,
+}
+
+/// Indicate a new non-finalized block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewBlock<Hash> {
+	/// The hash of the new block.
+	pub block_hash: Hash,
+	/// The parent hash of the new block.
+	pub parent_block_hash: Hash,
+	/// The runtime version of the new block, if it changed compared to its parent.
+	///
+	/// Always `None` if the `runtime_updates` flag is not set.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub new_runtime: Option<RuntimeEvent>,
+}
+
+/// Indicate the block hash of the new best block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BestBlockChanged<Hash> {
+	/// The block hash of the new best block.
+	pub best_block_hash: Hash,
+}
+
+/// Indicate the finalized and pruned block hashes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finalized<Hash> {
+	/// Block hashes that are finalized, oldest first.
+	pub finalized_block_hashes: Vec<Hash>,
+	/// Block hashes that are pruned (removed).
+	pub pruned_block_hashes: Vec<Hash>,
+}
+
+/// The event generated by the `follow` method.
+///
+/// The events are generated in the following order:
+/// 1. Initialized - generated only once to signal the latest finalized block
+/// 2. NewBlock - a new block was added.
+/// 3. BestBlockChanged - indicate that the best block is now the one from this event. The block was
+///    announced priorly with the `NewBlock` event.
+/// 4. Finalized - State the finalized and pruned blocks.
+///
+/// The following events are related to operations:
+/// - Stop - the subscription stopped, e.g. because it pinned too many blocks. No more events are
+///   generated and the pinned blocks are released.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "event")]
+pub enum FollowEvent<Hash> {
+	/// The latest finalized block.
+	///
+	/// This event is generated only once.
+	Initialized(Initialized<Hash>),
+	/// A new non-finalized block was added.
+	NewBlock(NewBlock<Hash>),
+	/// The best block of the chain.
+	BestBlockChanged(BestBlockChanged<Hash>),
+	/// A list of finalized and pruned blocks.
+	Finalized(Finalized<Hash>),
+	/// The subscription is dropped and no further events
+	/// will be generated.
+	Stop,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn follow_initialized_event_no_updates() {
+		let event: FollowEvent<String> = FollowEvent::Initialized(Initialized {
+			finalized_block_hash: "0x1".into(),
+			finalized_block_runtime: None,
+		});
+
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp = r#"{"event":"initialized","finalizedBlockHash":"0x1"}"#;
+		assert_eq!(ser, exp);
+
+		let event_dec: FollowEvent<String> = serde_json::from_str(exp).unwrap();
+		assert_eq!(event_dec, event);
+	}
+
+	#[test]
+	fn follow_new_block_event() {
+		let event: FollowEvent<String> = FollowEvent::NewBlock(NewBlock {
+			block_hash: "0x1".into(),
+			parent_block_hash: "0x2".into(),
+			new_runtime: Some(RuntimeEvent::Invalid(ErrorEvent { error: "Bad".into() })),
+		});
+
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp = r#"{"event":"newBlock","blockHash":"0x1","parentBlockHash":"0x2","newRuntime":{"type":"invalid","error":"Bad"}}"#;
+		assert_eq!(ser, exp);
+
+		let event_dec: FollowEvent<String> = serde_json::from_str(exp).unwrap();
+		assert_eq!(event_dec, event);
+	}
+
+	#[test]
+	fn follow_finalized_and_stop_events() {
+		let event: FollowEvent<String> = FollowEvent::Finalized(Finalized {
+			finalized_block_hashes: vec!["0x1".into()],
+			pruned_block_hashes: vec!["0x2".into()],
+		});
+
+		let ser = serde_json::to_string(&event).unwrap();
+		let exp =
+			r#"{"event":"finalized","finalizedBlockHashes":["0x1"],"prunedBlockHashes":["0x2"]}"#;
+		assert_eq!(ser, exp);
+
+		let ser = serde_json::to_string(&FollowEvent::<String>::Stop).unwrap();
+		assert_eq!(ser, r#"{"event":"stop"}"#);
+	}
+}
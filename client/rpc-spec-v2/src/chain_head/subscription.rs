@@ -0,0 +1,347 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Subscription management for tracking the blocks pinned by follow subscriptions.
+
+use parking_lot::RwLock;
+use sc_client_api::Backend;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	collections::{hash_map::Entry, HashMap, HashSet},
+	sync::Arc,
+};
+
+/// Subscription management error.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SubscriptionError {
+	/// The follow subscription is unknown.
+	InvalidSubscription,
+	/// The block is not pinned by the follow subscription.
+	InvalidBlock,
+	/// The follow subscription or all follow subscriptions together exceeded the maximum number
+	/// of pinned blocks, or there are too many follow subscriptions.
+	ExceededLimits,
+	/// The backend failed to pin the block.
+	PinFailed(String),
+}
+
+/// The state of a single follow subscription.
+///
+/// The blocks pinned by the subscription are unpinned in the backend once the state is dropped.
+struct SubscriptionState<Block: BlockT, BE: Backend<Block>> {
+	/// The blocks pinned by the subscription.
+	pinned_blocks: HashSet<Block::Hash>,
+	/// Backend holding the pinned blocks.
+	backend: Arc<BE>,
+}
+
+impl<Block: BlockT, BE: Backend<Block>> Drop for SubscriptionState<Block, BE> {
+	fn drop(&mut self) {
+		for hash in self.pinned_blocks.drain() {
+			self.backend.unpin_block(&hash);
+		}
+	}
+}
+
+/// Limits of the follow subscriptions.
+#[derive(Debug, Clone, Copy)]
+pub struct SubscriptionLimits {
+	/// Maximum number of blocks a single subscription can pin.
+	pub max_pinned_blocks: usize,
+	/// Maximum number of distinct blocks all subscriptions together can pin.
+	pub global_max_pinned_blocks: usize,
+	/// Maximum number of subscriptions.
+	pub max_subscriptions: usize,
+}
+
+/// The follow subscriptions and the blocks they pin.
+struct Subscriptions<Block: BlockT, BE: Backend<Block>> {
+	subscriptions: HashMap<String, SubscriptionState<Block, BE>>,
+	/// The number of subscriptions pinning each block.
+	global_pins: HashMap<Block::Hash, usize>,
+}
+
+impl<Block: BlockT, BE: Backend<Block>> Subscriptions<Block, BE> {
+	/// Releases a block pinned by a subscription.
+	fn release(&mut self, hash: &Block::Hash) {
+		if let Entry::Occupied(mut entry) = self.global_pins.entry(*hash) {
+			*entry.get_mut() -= 1;
+			if *entry.get() == 0 {
+				entry.remove();
+			}
+		}
+	}
+}
+
+/// Keeps track of the blocks pinned by the follow subscriptions.
+///
+/// The subscription manager is shared between the `follow` subscriptions and the methods that
+/// query the pinned blocks. Every block pinned by a subscription is also pinned in the backend,
+/// such that it stays available until the subscription unpins it or ends. The blocks pinned by
+/// all subscriptions together are limited, such that the subscriptions can't exhaust the blocks
+/// the backend can pin.
+pub struct SubscriptionManagement<Block: BlockT, BE: Backend<Block>> {
+	inner: Arc<RwLock<Subscriptions<Block, BE>>>,
+	/// Limits of the subscriptions.
+	limits: SubscriptionLimits,
+	/// Backend of the chain.
+	backend: Arc<BE>,
+}
+
+impl<Block: BlockT, BE: Backend<Block>> Clone for SubscriptionManagement<Block, BE> {
+	fn clone(&self) -> Self {
+		Self { inner: self.inner.clone(), limits: self.limits, backend: self.backend.clone() }
+	}
+}
+
+impl<Block: BlockT, BE: Backend<Block>> SubscriptionManagement<Block, BE> {
+	/// Constructs a new [`SubscriptionManagement`].
+	pub fn new(limits: SubscriptionLimits, backend: Arc<BE>) -> Self {
+		let inner = Subscriptions { subscriptions: HashMap::new(), global_pins: HashMap::new() };
+		Self { inner: Arc::new(RwLock::new(inner)), limits, backend }
+	}
+
+	/// Inserts a new follow subscription.
+	///
+	/// Fails with [`SubscriptionError::ExceededLimits`] if there are too many subscriptions
+	/// already, and with [`SubscriptionError::InvalidSubscription`] if a subscription with the
+	/// given ID already exists.
+	pub fn insert_subscription(&self, sub_id: String) -> Result<(), SubscriptionError> {
+		let mut inner = self.inner.write();
+		if inner.subscriptions.len() >= self.limits.max_subscriptions {
+			return Err(SubscriptionError::ExceededLimits)
+		}
+
+		match inner.subscriptions.entry(sub_id) {
+			Entry::Vacant(entry) => {
+				entry.insert(SubscriptionState {
+					pinned_blocks: Default::default(),
+					backend: self.backend.clone(),
+				});
+				Ok(())
+			},
+			Entry::Occupied(_) => Err(SubscriptionError::InvalidSubscription),
+		}
+	}
+
+	/// Removes the follow subscription, releasing all of its pinned blocks.
+	pub fn remove_subscription(&self, sub_id: &str) {
+		let mut inner = self.inner.write();
+		if let Some(subscription) = inner.subscriptions.remove(sub_id) {
+			for hash in &subscription.pinned_blocks {
+				inner.release(hash);
+			}
+		}
+	}
+
+	/// Pins the block for the given subscription.
+	pub fn pin_block(&self, sub_id: &str, hash: Block::Hash) -> Result<(), SubscriptionError> {
+		let mut inner = self.inner.write();
+		let Subscriptions { subscriptions, global_pins } = &mut *inner;
+		let subscription =
+			subscriptions.get_mut(sub_id).ok_or(SubscriptionError::InvalidSubscription)?;
+
+		if subscription.pinned_blocks.contains(&hash) {
+			return Ok(())
+		}
+
+		if subscription.pinned_blocks.len() >= self.limits.max_pinned_blocks ||
+			(!global_pins.contains_key(&hash) &&
+				global_pins.len() >= self.limits.global_max_pinned_blocks)
+		{
+			return Err(SubscriptionError::ExceededLimits)
+		}
+
+		self.backend
+			.pin_block(&hash)
+			.map_err(|err| SubscriptionError::PinFailed(err.to_string()))?;
+		subscription.pinned_blocks.insert(hash);
+		*global_pins.entry(hash).or_default() += 1;
+		Ok(())
+	}
+
+	/// Unpins the block for the given subscription.
+	pub fn unpin_block(&self, sub_id: &str, hash: &Block::Hash) -> Result<(), SubscriptionError> {
+		let mut inner = self.inner.write();
+		let subscription = inner
+			.subscriptions
+			.get_mut(sub_id)
+			.ok_or(SubscriptionError::InvalidSubscription)?;
+
+		if subscription.pinned_blocks.remove(hash) {
+			self.backend.unpin_block(hash);
+			inner.release(hash);
+			Ok(())
+		} else {
+			Err(SubscriptionError::InvalidBlock)
+		}
+	}
+
+	/// Ensures the block is pinned by the given subscription.
+	pub fn ensure_pinned(&self, sub_id: &str, hash: &Block::Hash) -> Result<(), SubscriptionError> {
+		let inner = self.inner.read();
+		let subscription =
+			inner.subscriptions.get(sub_id).ok_or(SubscriptionError::InvalidSubscription)?;
+
+		if subscription.pinned_blocks.contains(hash) {
+			Ok(())
+		} else {
+			Err(SubscriptionError::InvalidBlock)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_client_api::in_mem::Backend as InMemoryBackend;
+	use sp_core::H256;
+	use substrate_test_runtime_client::{
+		runtime::{Block, Hash},
+		ClientExt, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
+	};
+
+	fn pinned_blocks(backend: &substrate_test_runtime_client::Backend) -> u64 {
+		backend.usage_info().unwrap().memory.pinned_blocks
+	}
+
+	fn limits(max_pinned_blocks: usize) -> SubscriptionLimits {
+		SubscriptionLimits {
+			max_pinned_blocks,
+			global_max_pinned_blocks: 1024,
+			max_subscriptions: 16,
+		}
+	}
+
+	#[test]
+	fn pin_and_unpin_blocks() {
+		let backend = Arc::new(InMemoryBackend::<Block>::new());
+		let subs = SubscriptionManagement::new(limits(2), backend);
+		let id = "abc".to_string();
+		let hash = |n| H256::repeat_byte(n);
+
+		assert_eq!(subs.pin_block(&id, hash(1)), Err(SubscriptionError::InvalidSubscription));
+		assert_eq!(subs.insert_subscription(id.clone()), Ok(()));
+		assert_eq!(
+			subs.insert_subscription(id.clone()),
+			Err(SubscriptionError::InvalidSubscription)
+		);
+
+		subs.pin_block(&id, hash(1)).unwrap();
+		subs.pin_block(&id, hash(2)).unwrap();
+		// Pinning an already pinned block doesn't count against the limit.
+		subs.pin_block(&id, hash(2)).unwrap();
+		assert_eq!(subs.pin_block(&id, hash(3)), Err(SubscriptionError::ExceededLimits));
+
+		assert_eq!(subs.ensure_pinned(&id, &hash(1)), Ok(()));
+		subs.unpin_block(&id, &hash(1)).unwrap();
+		assert_eq!(subs.ensure_pinned(&id, &hash(1)), Err(SubscriptionError::InvalidBlock));
+		assert_eq!(subs.unpin_block(&id, &hash(1)), Err(SubscriptionError::InvalidBlock));
+		subs.pin_block(&id, hash(3)).unwrap();
+
+		subs.remove_subscription(&id);
+		assert_eq!(subs.ensure_pinned(&id, &hash(3)), Err(SubscriptionError::InvalidSubscription));
+	}
+
+	#[test]
+	fn blocks_are_pinned_in_the_backend() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let client = builder.build();
+		let genesis: Hash = client.genesis_hash();
+		let subs = SubscriptionManagement::new(limits(2), backend.clone());
+
+		let (first, second) = ("abc".to_string(), "def".to_string());
+		subs.insert_subscription(first.clone()).unwrap();
+		subs.insert_subscription(second.clone()).unwrap();
+
+		subs.pin_block(&first, genesis).unwrap();
+		subs.pin_block(&second, genesis).unwrap();
+		assert_eq!(pinned_blocks(&backend), 1);
+
+		// The block stays pinned in the backend while any subscription pins it.
+		subs.unpin_block(&first, &genesis).unwrap();
+		assert_eq!(pinned_blocks(&backend), 1);
+
+		// Removing the subscription unpins its remaining blocks.
+		subs.remove_subscription(&second);
+		assert_eq!(pinned_blocks(&backend), 0);
+
+		// So does dropping the subscription management.
+		subs.pin_block(&first, genesis).unwrap();
+		assert_eq!(pinned_blocks(&backend), 1);
+		drop(subs);
+		assert_eq!(pinned_blocks(&backend), 0);
+
+		// Unknown blocks can't be pinned.
+		let subs = SubscriptionManagement::new(limits(2), backend);
+		subs.insert_subscription(first.clone()).unwrap();
+		assert!(matches!(
+			subs.pin_block(&first, H256::repeat_byte(1)),
+			Err(SubscriptionError::PinFailed(_))
+		));
+	}
+
+	#[test]
+	fn pinned_blocks_are_limited_across_subscriptions() {
+		let backend = Arc::new(InMemoryBackend::<Block>::new());
+		let limits = SubscriptionLimits {
+			max_pinned_blocks: 3,
+			global_max_pinned_blocks: 3,
+			max_subscriptions: 16,
+		};
+		let subs = SubscriptionManagement::new(limits, backend);
+		let (first, second) = ("abc".to_string(), "def".to_string());
+		let hash = |n| H256::repeat_byte(n);
+
+		subs.insert_subscription(first.clone()).unwrap();
+		subs.insert_subscription(second.clone()).unwrap();
+		subs.pin_block(&first, hash(1)).unwrap();
+		subs.pin_block(&first, hash(2)).unwrap();
+		subs.pin_block(&second, hash(3)).unwrap();
+
+		// Blocks pinned by another subscription don't count against the global limit.
+		subs.pin_block(&second, hash(1)).unwrap();
+		assert_eq!(subs.pin_block(&second, hash(4)), Err(SubscriptionError::ExceededLimits));
+
+		// The block is still pinned by the first subscription.
+		subs.unpin_block(&second, &hash(1)).unwrap();
+		assert_eq!(subs.pin_block(&second, hash(4)), Err(SubscriptionError::ExceededLimits));
+
+		// Removing a subscription releases its blocks.
+		subs.remove_subscription(&first);
+		subs.pin_block(&second, hash(4)).unwrap();
+	}
+
+	#[test]
+	fn subscriptions_are_limited() {
+		let backend = Arc::new(InMemoryBackend::<Block>::new());
+		let limits = SubscriptionLimits {
+			max_pinned_blocks: 2,
+			global_max_pinned_blocks: 2,
+			max_subscriptions: 1,
+		};
+		let subs = SubscriptionManagement::new(limits, backend);
+
+		subs.insert_subscription("abc".into()).unwrap();
+		assert_eq!(subs.insert_subscription("def".into()), Err(SubscriptionError::ExceededLimits));
+
+		subs.remove_subscription("abc");
+		subs.insert_subscription("def".into()).unwrap();
+	}
+}
@@ -0,0 +1,254 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use assert_matches::assert_matches;
+use codec::Encode;
+use jsonrpsee::{
+	core::Error,
+	types::{error::CallError, EmptyParams, SubscriptionId},
+	RpcModule, Subscription,
+};
+use sc_block_builder::BlockBuilderProvider;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_core::{storage::well_known_keys::CODE, Bytes};
+use sp_runtime::generic::BlockId;
+use std::{future::Future, sync::Arc, time::Duration};
+use substrate_test_runtime_client::{
+	prelude::*,
+	runtime::{Block, Hash},
+	Backend, Client,
+};
+
+type Event = FollowEvent<String>;
+
+fn timeout_secs<I, F: Future<Output = I>>(s: u64, f: F) -> tokio::time::Timeout<F> {
+	tokio::time::timeout(Duration::from_secs(s), f)
+}
+
+fn setup_api(
+	limits: SubscriptionLimits,
+) -> (Arc<Client<Backend>>, RpcModule<ChainHead<Backend, Block, Client<Backend>>>) {
+	let builder = TestClientBuilder::new();
+	let backend = builder.backend();
+	let client = Arc::new(builder.build());
+
+	let api = ChainHead::new(
+		client.clone(),
+		backend,
+		Arc::new(sp_core::testing::TaskExecutor::default()),
+		client.genesis_hash(),
+		limits,
+	)
+	.into_rpc();
+
+	(client, api)
+}
+
+async fn next_event(sub: &mut Subscription<Event>) -> Event {
+	timeout_secs(10, sub.next()).await.unwrap().unwrap().unwrap().0
+}
+
+fn sub_id(sub: &Subscription<Event>) -> String {
+	match sub.subscription_id() {
+		SubscriptionId::Num(num) => num.to_string(),
+		SubscriptionId::Str(id) => id.to_string(),
+	}
+}
+
+fn hex(hash: Hash) -> String {
+	format!("{:?}", hash)
+}
+
+#[tokio::test]
+async fn follow_reports_imported_and_finalized_blocks() {
+	let (mut client, api) = setup_api(Default::default());
+	let mut sub = api.subscribe("chainHead_unstable_follow", [false]).await.unwrap();
+
+	assert_eq!(
+		next_event(&mut sub).await,
+		Event::Initialized(Initialized {
+			finalized_block_hash: hex(client.genesis_hash()),
+			finalized_block_runtime: None,
+		})
+	);
+
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let block_hash = block.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	assert_eq!(
+		next_event(&mut sub).await,
+		Event::NewBlock(NewBlock {
+			block_hash: hex(block_hash),
+			parent_block_hash: hex(client.genesis_hash()),
+			new_runtime: None,
+		})
+	);
+	assert_eq!(
+		next_event(&mut sub).await,
+		Event::BestBlockChanged(BestBlockChanged { best_block_hash: hex(block_hash) })
+	);
+
+	client.finalize_block(BlockId::Hash(block_hash), None).unwrap();
+	assert_eq!(
+		next_event(&mut sub).await,
+		Event::Finalized(Finalized {
+			finalized_block_hashes: vec![hex(block_hash)],
+			pruned_block_hashes: vec![],
+		})
+	);
+}
+
+#[tokio::test]
+async fn follow_reports_runtime_of_finalized_block() {
+	let (_client, api) = setup_api(Default::default());
+	let mut sub = api.subscribe("chainHead_unstable_follow", [true]).await.unwrap();
+
+	assert_matches!(
+		next_event(&mut sub).await,
+		Event::Initialized(Initialized {
+			finalized_block_runtime: Some(RuntimeEvent::Valid(RuntimeVersionEvent { spec })),
+			..
+		}) if spec.spec_name == "test".into()
+	);
+}
+
+#[tokio::test]
+async fn queries_are_bound_to_pinned_blocks() {
+	let (client, api) = setup_api(Default::default());
+	let genesis = client.genesis_hash();
+	let mut sub = api.subscribe("chainHead_unstable_follow", [false]).await.unwrap();
+	let _ = next_event(&mut sub).await;
+	let sub_id = sub_id(&sub);
+
+	// Unknown follow subscription.
+	assert_matches!(
+		api.call::<_, Option<Bytes>>("chainHead_unstable_header", ["invalid".to_string(), hex(genesis)])
+			.await,
+		Err(Error::Call(CallError::Custom(err))) if err.code() == -32802
+	);
+
+	let header: Option<Bytes> = api
+		.call("chainHead_unstable_header", [sub_id.clone(), hex(genesis)])
+		.await
+		.unwrap();
+	let expected = client.header(&BlockId::Hash(genesis)).unwrap().unwrap();
+	assert_eq!(header.unwrap().0, expected.encode());
+
+	let body: Option<Vec<Bytes>> = api
+		.call("chainHead_unstable_body", [sub_id.clone(), hex(genesis)])
+		.await
+		.unwrap();
+	assert_eq!(body, Some(vec![]));
+
+	let code: Option<Bytes> = api
+		.call(
+			"chainHead_unstable_storage",
+			(sub_id.clone(), hex(genesis), Bytes(CODE.to_vec()), None::<Bytes>),
+		)
+		.await
+		.unwrap();
+	assert!(code.is_some());
+
+	let version: Bytes = api
+		.call(
+			"chainHead_unstable_call",
+			(sub_id.clone(), hex(genesis), "Core_version", Bytes(vec![])),
+		)
+		.await
+		.unwrap();
+	assert!(!version.0.is_empty());
+
+	let _: () = api
+		.call("chainHead_unstable_unpin", [sub_id.clone(), hex(genesis)])
+		.await
+		.unwrap();
+
+	// The block is no longer pinned.
+	assert_matches!(
+		api.call::<_, Option<Bytes>>("chainHead_unstable_header", [sub_id.clone(), hex(genesis)])
+			.await,
+		Err(Error::Call(CallError::Custom(err))) if err.code() == -32801
+	);
+	assert_matches!(
+		api.call::<_, ()>("chainHead_unstable_unpin", [sub_id, hex(genesis)]).await,
+		Err(Error::Call(CallError::Custom(err))) if err.code() == -32801
+	);
+}
+
+#[tokio::test]
+async fn follow_stops_when_exceeding_pinned_blocks() {
+	let (mut client, api) =
+		setup_api(SubscriptionLimits { max_pinned_blocks: 2, ..Default::default() });
+	let mut sub = api.subscribe("chainHead_unstable_follow", [false]).await.unwrap();
+	assert_matches!(next_event(&mut sub).await, Event::Initialized(_));
+
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, block).await.unwrap();
+	assert_matches!(next_event(&mut sub).await, Event::NewBlock(_));
+	assert_matches!(next_event(&mut sub).await, Event::BestBlockChanged(_));
+
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, block).await.unwrap();
+	assert_eq!(next_event(&mut sub).await, Event::Stop);
+}
+
+#[tokio::test]
+async fn follow_stops_when_exceeding_global_pinned_blocks() {
+	let (mut client, api) =
+		setup_api(SubscriptionLimits { global_max_pinned_blocks: 2, ..Default::default() });
+	let mut first = api.subscribe("chainHead_unstable_follow", [false]).await.unwrap();
+	assert_matches!(next_event(&mut first).await, Event::Initialized(_));
+	let mut second = api.subscribe("chainHead_unstable_follow", [false]).await.unwrap();
+	assert_matches!(next_event(&mut second).await, Event::Initialized(_));
+
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, block).await.unwrap();
+	assert_matches!(next_event(&mut first).await, Event::NewBlock(_));
+	assert_matches!(next_event(&mut second).await, Event::NewBlock(_));
+
+	// Both subscriptions pin the same two blocks, a third one exceeds the limit.
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, block).await.unwrap();
+	assert_matches!(next_event(&mut first).await, Event::BestBlockChanged(_));
+	assert_eq!(next_event(&mut first).await, Event::Stop);
+	assert_matches!(next_event(&mut second).await, Event::BestBlockChanged(_));
+	assert_eq!(next_event(&mut second).await, Event::Stop);
+}
+
+#[tokio::test]
+async fn follow_stops_when_exceeding_subscriptions() {
+	let (_client, api) =
+		setup_api(SubscriptionLimits { max_subscriptions: 1, ..Default::default() });
+	let mut first = api.subscribe("chainHead_unstable_follow", [false]).await.unwrap();
+	assert_matches!(next_event(&mut first).await, Event::Initialized(_));
+
+	let mut second = api.subscribe("chainHead_unstable_follow", [false]).await.unwrap();
+	assert_eq!(next_event(&mut second).await, Event::Stop);
+}
+
+#[tokio::test]
+async fn genesis_hash() {
+	let (client, api) = setup_api(Default::default());
+
+	let genesis: String =
+		api.call("chainHead_unstable_genesisHash", EmptyParams::new()).await.unwrap();
+	assert_eq!(genesis, hex(client.genesis_hash()));
+}
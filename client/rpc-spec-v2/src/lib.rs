@@ -0,0 +1,35 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate JSON-RPC interface v2.
+//!
+//! Implementation of the new JSON-RPC interface
+//! [specification](https://github.com/paritytech/json-rpc-interface-spec/).
+//!
+//! The `chainHead` methods are bound to a follow subscription which pins the blocks it reports,
+//! the `archive` methods give access to the finalized history of the chain and the
+//! `transaction` methods submit and watch transactions.
+
+#![warn(missing_docs)]
+
+pub mod archive;
+pub mod chain_head;
+pub mod transaction;
+
+/// Task executor that is being used by RPC subscriptions.
+pub type SubscriptionTaskExecutor = std::sync::Arc<dyn sp_core::traits::SpawnNamed>;
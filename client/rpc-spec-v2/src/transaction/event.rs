@@ -0,0 +1,156 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The transaction's event returned as json compatible object.
+
+use serde::{Deserialize, Serialize};
+
+/// The transaction was broadcasted to a number of peers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionBroadcasted {
+	/// The number of peers the transaction was broadcasted to.
+	pub num_peers: usize,
+}
+
+/// The block a transaction was included in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionBlock<Hash> {
+	/// The hash of the block the transaction was included into.
+	pub hash: Hash,
+	/// The index (zero-based) of the transaction within the body of the block.
+	pub index: usize,
+}
+
+/// The transaction was included in a block of the best chain, or is no longer part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BestChainBlockIncluded<Hash> {
+	/// The block of the best chain that includes the transaction.
+	///
+	/// `None` if the block previously reported was retracted from the best chain.
+	pub block: Option<TransactionBlock<Hash>>,
+}
+
+/// The transaction was included in a finalized block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finalized<Hash> {
+	/// The finalized block that includes the transaction.
+	pub block: TransactionBlock<Hash>,
+}
+
+/// The transaction could not be processed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionError {
+	/// Reason of the error.
+	pub error: String,
+}
+
+/// The transaction was dropped from the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionDropped {
+	/// `true` if the transaction was broadcasted to other peers before being dropped.
+	pub broadcasted: bool,
+	/// Reason of the transaction being dropped.
+	pub error: String,
+}
+
+/// The event generated by the `submitAndWatch` method.
+///
+/// `finalized`, `error`, `invalid` and `dropped` are final events: no further events are
+/// generated after them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "event")]
+pub enum TransactionEvent<Hash> {
+	/// The transaction was validated and entered the pool.
+	Validated,
+	/// The transaction was broadcasted to other peers.
+	Broadcasted(TransactionBroadcasted),
+	/// The transaction was included in, or retracted from, a block of the best chain.
+	BestChainBlockIncluded(BestChainBlockIncluded<Hash>),
+	/// The transaction was included in a finalized block.
+	Finalized(Finalized<Hash>),
+	/// An error occurred while processing the transaction.
+	Error(TransactionError),
+	/// The transaction is invalid.
+	Invalid(TransactionError),
+	/// The transaction was dropped from the pool.
+	Dropped(TransactionDropped),
+}
+
+impl<Hash> TransactionEvent<Hash> {
+	/// Returns `true` if no further events are generated after this one.
+	pub fn is_final(&self) -> bool {
+		matches!(self, Self::Finalized(_) | Self::Error(_) | Self::Invalid(_) | Self::Dropped(_))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn validated_event() {
+		let event: TransactionEvent<()> = TransactionEvent::Validated;
+
+		let ser = serde_json::to_string(&event).unwrap();
+		assert_eq!(ser, r#"{"event":"validated"}"#);
+
+		let parsed: TransactionEvent<()> = serde_json::from_str(&ser).unwrap();
+		assert_eq!(parsed, event);
+	}
+
+	#[test]
+	fn best_chain_block_included_event() {
+		let event: TransactionEvent<String> =
+			TransactionEvent::BestChainBlockIncluded(BestChainBlockIncluded {
+				block: Some(TransactionBlock { hash: "0x1".into(), index: 2 }),
+			});
+
+		let ser = serde_json::to_string(&event).unwrap();
+		assert_eq!(ser, r#"{"event":"bestChainBlockIncluded","block":{"hash":"0x1","index":2}}"#);
+
+		let parsed: TransactionEvent<String> = serde_json::from_str(&ser).unwrap();
+		assert_eq!(parsed, event);
+
+		let event: TransactionEvent<String> =
+			TransactionEvent::BestChainBlockIncluded(BestChainBlockIncluded { block: None });
+
+		let ser = serde_json::to_string(&event).unwrap();
+		assert_eq!(ser, r#"{"event":"bestChainBlockIncluded","block":null}"#);
+	}
+
+	#[test]
+	fn dropped_event() {
+		let event: TransactionEvent<()> = TransactionEvent::Dropped(TransactionDropped {
+			broadcasted: true,
+			error: "msg".into(),
+		});
+
+		let ser = serde_json::to_string(&event).unwrap();
+		assert_eq!(ser, r#"{"event":"dropped","broadcasted":true,"error":"msg"}"#);
+
+		let parsed: TransactionEvent<()> = serde_json::from_str(&ser).unwrap();
+		assert_eq!(parsed, event);
+	}
+}
@@ -0,0 +1,96 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use assert_matches::assert_matches;
+use codec::Encode;
+use jsonrpsee::{RpcModule, Subscription};
+use sc_transaction_pool::{BasicPool, FullChainApi};
+use sp_core::Bytes;
+use std::{future::Future, sync::Arc, time::Duration};
+use substrate_test_runtime_client::{
+	prelude::*,
+	runtime::{Block, Extrinsic, Hash, Transfer},
+	Backend, Client,
+};
+
+type FullTransactionPool = BasicPool<FullChainApi<Client<Backend>, Block>, Block>;
+
+fn timeout_secs<I, F: Future<Output = I>>(s: u64, f: F) -> tokio::time::Timeout<F> {
+	tokio::time::timeout(Duration::from_secs(s), f)
+}
+
+fn uxt(sender: AccountKeyring, nonce: u64) -> Extrinsic {
+	let tx = Transfer {
+		amount: Default::default(),
+		nonce,
+		from: sender.into(),
+		to: AccountKeyring::Bob.into(),
+	};
+	tx.into_signed_tx()
+}
+
+fn setup_api() -> RpcModule<Transaction<FullTransactionPool, Client<Backend>>> {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let spawner = sp_core::testing::TaskExecutor::new();
+	let pool = BasicPool::new_full(Default::default(), true.into(), None, spawner, client.clone());
+
+	Transaction::new(client, pool, Arc::new(sp_core::testing::TaskExecutor::default())).into_rpc()
+}
+
+async fn next_event(sub: &mut Subscription<TransactionEvent<Hash>>) -> TransactionEvent<Hash> {
+	timeout_secs(10, sub.next()).await.unwrap().unwrap().unwrap().0
+}
+
+#[tokio::test]
+async fn submit_and_watch_reports_validated() {
+	let api = setup_api();
+	let xt: Bytes = uxt(AccountKeyring::Alice, 0).encode().into();
+
+	let mut sub = api.subscribe("transaction_unstable_submitAndWatch", [xt]).await.unwrap();
+	assert_eq!(next_event(&mut sub).await, TransactionEvent::Validated);
+}
+
+#[tokio::test]
+async fn submit_and_watch_reports_invalid_extrinsic() {
+	let api = setup_api();
+
+	let mut sub = api
+		.subscribe("transaction_unstable_submitAndWatch", [Bytes(vec![1, 2, 3])])
+		.await
+		.unwrap();
+	assert_matches!(next_event(&mut sub).await, TransactionEvent::Invalid(_));
+	// No further events are generated after the final event.
+	assert_matches!(timeout_secs(10, sub.next::<TransactionEvent<Hash>>()).await, Ok(None));
+}
+
+#[tokio::test]
+async fn submit_and_watch_reports_rejected_extrinsic() {
+	let api = setup_api();
+	let xt: Bytes = uxt(AccountKeyring::Alice, 0).encode().into();
+
+	let mut sub = api
+		.subscribe("transaction_unstable_submitAndWatch", [xt.clone()])
+		.await
+		.unwrap();
+	assert_eq!(next_event(&mut sub).await, TransactionEvent::Validated);
+
+	// The same extrinsic is already in the pool.
+	let mut sub = api.subscribe("transaction_unstable_submitAndWatch", [xt]).await.unwrap();
+	assert_matches!(next_event(&mut sub).await, TransactionEvent::Invalid(_));
+}
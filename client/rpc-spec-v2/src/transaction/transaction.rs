@@ -0,0 +1,223 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API implementation for submitting transactions.
+
+use crate::{
+	transaction::{
+		api::TransactionApiServer,
+		event::{
+			BestChainBlockIncluded, Finalized, TransactionBlock, TransactionBroadcasted,
+			TransactionDropped, TransactionError, TransactionEvent,
+		},
+	},
+	SubscriptionTaskExecutor,
+};
+use codec::Decode;
+use futures::{
+	future::{self, FutureExt},
+	stream::{self, StreamExt},
+};
+use jsonrpsee::{types::SubscriptionResult, SubscriptionSink};
+use sc_client_api::BlockBackend;
+use sc_transaction_pool_api::{
+	error::IntoPoolError, BlockHash, TransactionFor, TransactionPool, TransactionSource,
+	TransactionStatus, TxHash,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+/// The transaction source of transactions submitted through this API.
+const TX_SOURCE: TransactionSource = TransactionSource::External;
+
+/// An API for transaction RPC calls.
+pub struct Transaction<Pool, Client> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Transactions pool.
+	pool: Arc<Pool>,
+	/// Executor to spawn subscriptions.
+	executor: SubscriptionTaskExecutor,
+}
+
+impl<Pool, Client> Transaction<Pool, Client> {
+	/// Create a new [`Transaction`].
+	pub fn new(client: Arc<Client>, pool: Arc<Pool>, executor: SubscriptionTaskExecutor) -> Self {
+		Transaction { client, pool, executor }
+	}
+}
+
+impl<Pool, Client> TransactionApiServer<BlockHash<Pool>> for Transaction<Pool, Client>
+where
+	Pool: TransactionPool + Sync + Send + 'static,
+	Pool::Hash: Unpin,
+	<Pool::Block as BlockT>::Hash: Unpin,
+	Client: HeaderBackend<Pool::Block> + BlockBackend<Pool::Block> + Send + Sync + 'static,
+{
+	fn submit_and_watch(&self, mut sink: SubscriptionSink, xt: Bytes) -> SubscriptionResult {
+		let best_block_hash = self.client.info().best_hash;
+		let decoded = match TransactionFor::<Pool>::decode(&mut &xt[..]) {
+			Ok(decoded) => decoded,
+			Err(e) => {
+				let event = TransactionEvent::<BlockHash<Pool>>::Invalid(TransactionError {
+					error: format!("Extrinsic has invalid format: {}", e),
+				});
+				let fut = async move {
+					sink.pipe_from_stream(stream::iter(vec![event])).await;
+				};
+				self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+				return Ok(())
+			},
+		};
+
+		let tx_hash = self.pool.hash_of(&decoded);
+		let submit =
+			self.pool.submit_and_watch(&BlockId::hash(best_block_hash), TX_SOURCE, decoded);
+		let client = self.client.clone();
+		let pool = self.pool.clone();
+
+		let fut = async move {
+			match submit.await {
+				Ok(stream) => {
+					let mut state = TransactionState::new(tx_hash);
+					let stream = stream
+						.map(move |status| state.handle_event(&*client, &*pool, status))
+						.filter_map(future::ready)
+						// Stop the subscription right after the final event.
+						.scan(false, |done, event| {
+							if *done {
+								return future::ready(None)
+							}
+							*done = event.is_final();
+							future::ready(Some(event))
+						});
+					sink.pipe_from_stream(stream.boxed()).await;
+				},
+				Err(err) => {
+					let event = match err.into_pool_error() {
+						Ok(pool_error) =>
+							TransactionEvent::<BlockHash<Pool>>::Invalid(TransactionError {
+								error: pool_error.to_string(),
+							}),
+						Err(err) => TransactionEvent::<BlockHash<Pool>>::Error(TransactionError {
+							error: err.to_string(),
+						}),
+					};
+					sink.pipe_from_stream(stream::iter(vec![event])).await;
+				},
+			}
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
+
+/// Tracks the events already reported for a transaction.
+struct TransactionState<Hash> {
+	/// The hash of the watched transaction.
+	tx_hash: Hash,
+	/// The `validated` event was already generated.
+	validated: bool,
+	/// The transaction was broadcasted to other peers.
+	broadcasted: bool,
+}
+
+impl<Hash: PartialEq> TransactionState<Hash> {
+	fn new(tx_hash: Hash) -> Self {
+		TransactionState { tx_hash, validated: false, broadcasted: false }
+	}
+
+	/// Convert the transaction pool status into an RPC event.
+	///
+	/// Returns `None` for statuses that do not produce a new event.
+	fn handle_event<Pool, Client>(
+		&mut self,
+		client: &Client,
+		pool: &Pool,
+		status: TransactionStatus<TxHash<Pool>, BlockHash<Pool>>,
+	) -> Option<TransactionEvent<BlockHash<Pool>>>
+	where
+		Pool: TransactionPool<Hash = Hash>,
+		Client: BlockBackend<Pool::Block>,
+	{
+		let event = match status {
+			TransactionStatus::Future | TransactionStatus::Ready => {
+				if std::mem::replace(&mut self.validated, true) {
+					return None
+				}
+				TransactionEvent::Validated
+			},
+			TransactionStatus::Broadcast(peers) => {
+				self.broadcasted = true;
+				TransactionEvent::Broadcasted(TransactionBroadcasted { num_peers: peers.len() })
+			},
+			TransactionStatus::InBlock(hash) =>
+				TransactionEvent::BestChainBlockIncluded(BestChainBlockIncluded {
+					block: Some(self.transaction_block(client, pool, hash)),
+				}),
+			TransactionStatus::Retracted(_) =>
+				TransactionEvent::BestChainBlockIncluded(BestChainBlockIncluded { block: None }),
+			TransactionStatus::FinalityTimeout(_) =>
+				TransactionEvent::Dropped(TransactionDropped {
+					broadcasted: self.broadcasted,
+					error: "Maximum number of finality watchers has been exceeded".into(),
+				}),
+			TransactionStatus::Finalized(hash) => TransactionEvent::Finalized(Finalized {
+				block: self.transaction_block(client, pool, hash),
+			}),
+			TransactionStatus::Usurped(_) => TransactionEvent::Invalid(TransactionError {
+				error: "Extrinsic was rendered invalid by another extrinsic".into(),
+			}),
+			TransactionStatus::Dropped => TransactionEvent::Dropped(TransactionDropped {
+				broadcasted: self.broadcasted,
+				error: "Extrinsic dropped from the pool due to exceeding limits".into(),
+			}),
+			TransactionStatus::Invalid => TransactionEvent::Invalid(TransactionError {
+				error: "Extrinsic marked as invalid".into(),
+			}),
+		};
+
+		Some(event)
+	}
+
+	/// Find the position of the watched transaction within the body of the given block.
+	///
+	/// Falls back to index `0` if the body is not available.
+	fn transaction_block<Pool, Client>(
+		&self,
+		client: &Client,
+		pool: &Pool,
+		hash: BlockHash<Pool>,
+	) -> TransactionBlock<BlockHash<Pool>>
+	where
+		Pool: TransactionPool<Hash = Hash>,
+		Client: BlockBackend<Pool::Block>,
+	{
+		let index = client
+			.block_body(&BlockId::hash(hash))
+			.ok()
+			.flatten()
+			.and_then(|body| body.iter().position(|xt| pool.hash_of(xt) == self.tx_hash))
+			.unwrap_or_default();
+
+		TransactionBlock { hash, index }
+	}
+}
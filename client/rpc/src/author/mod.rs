@@ -23,25 +23,36 @@ mod tests;
 
 use std::sync::Arc;
 
-use crate::SubscriptionTaskExecutor;
+use crate::{
+	system_events::{self, ExtrinsicEvents, MetadataCache},
+	SubscriptionTaskExecutor,
+};
 
 use codec::{Decode, Encode};
-use futures::{FutureExt, TryFutureExt};
+use futures::{channel::oneshot, FutureExt, StreamExt, TryFutureExt};
 use jsonrpsee::{
 	core::{async_trait, Error as JsonRpseeError, RpcResult},
 	types::SubscriptionResult,
 	SubscriptionSink,
 };
-use sc_rpc_api::DenyUnsafe;
+use parking_lot::Mutex;
+use sc_client_api::BlockBackend;
+use sc_rpc_api::{
+	author::events::{ExtrinsicInclusion, ExtrinsicStatus},
+	DenyUnsafe,
+};
 use sc_transaction_pool_api::{
 	error::IntoPoolError, BlockHash, InPoolTransaction, TransactionFor, TransactionPool,
-	TransactionSource, TxHash,
+	TransactionSource, TransactionStatus, TxHash,
 };
-use sp_api::ProvideRuntimeApi;
+use sp_api::{CallApiAt, Metadata, ProvideRuntimeApi, StateBackend};
 use sp_blockchain::HeaderBackend;
 use sp_core::Bytes;
 use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
-use sp_runtime::{generic, traits::Block as BlockT};
+use sp_runtime::{
+	generic::{self, BlockId},
+	traits::Block as BlockT,
+};
 use sp_session::SessionKeys;
 
 use self::error::{Error, Result};
@@ -60,6 +71,8 @@ pub struct Author<P, Client> {
 	deny_unsafe: DenyUnsafe,
 	/// Executor to spawn subscriptions.
 	executor: SubscriptionTaskExecutor,
+	/// Decoded metadata of the runtime, used to decode the events of watched extrinsics.
+	metadata: Arc<Mutex<MetadataCache>>,
 }
 
 impl<P, Client> Author<P, Client> {
@@ -71,7 +84,7 @@ impl<P, Client> Author<P, Client> {
 		deny_unsafe: DenyUnsafe,
 		executor: SubscriptionTaskExecutor,
 	) -> Self {
-		Author { client, pool, keystore, deny_unsafe, executor }
+		Author { client, pool, keystore, deny_unsafe, executor, metadata: Default::default() }
 	}
 }
 
@@ -86,8 +99,14 @@ const TX_SOURCE: TransactionSource = TransactionSource::External;
 impl<P, Client> AuthorApiServer<TxHash<P>, BlockHash<P>> for Author<P, Client>
 where
 	P: TransactionPool + Sync + Send + 'static,
-	Client: HeaderBackend<P::Block> + ProvideRuntimeApi<P::Block> + Send + Sync + 'static,
-	Client::Api: SessionKeys<P::Block>,
+	Client: HeaderBackend<P::Block>
+		+ ProvideRuntimeApi<P::Block>
+		+ BlockBackend<P::Block>
+		+ CallApiAt<P::Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: SessionKeys<P::Block> + Metadata<P::Block>,
 	P::Hash: Unpin,
 	<P::Block as BlockT>::Hash: Unpin,
 {
@@ -211,4 +230,157 @@ where
 		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
 		Ok(())
 	}
+
+	fn watch_extrinsic_with_events(
+		&self,
+		mut sink: SubscriptionSink,
+		xt: Bytes,
+	) -> SubscriptionResult {
+		let best_block_hash = self.client.info().best_hash;
+		let dxt = match TransactionFor::<P>::decode(&mut &xt[..]).map_err(|e| Error::from(e)) {
+			Ok(dxt) => dxt,
+			Err(e) => {
+				let _ = sink.reject(JsonRpseeError::from(e));
+				return Ok(())
+			},
+		};
+
+		let tx_hash = self.pool.hash_of(&dxt);
+		let submit = self
+			.pool
+			.submit_and_watch(&generic::BlockId::hash(best_block_hash), TX_SOURCE, dxt)
+			.map_err(|e| {
+				e.into_pool_error()
+					.map(error::Error::from)
+					.unwrap_or_else(|e| error::Error::Verification(Box::new(e)))
+			});
+
+		let client = self.client.clone();
+		let pool = self.pool.clone();
+		let metadata = self.metadata.clone();
+		let executor = self.executor.clone();
+		let fut = async move {
+			let stream = match submit.await {
+				Ok(stream) => stream,
+				Err(err) => {
+					let _ = sink.reject(JsonRpseeError::from(err));
+					return
+				},
+			};
+
+			// Reading the block body and decoding the events may take a while, so the inclusion
+			// details are looked up on a blocking task.
+			let stream = stream.then(move |status| {
+				let (tx, rx) = oneshot::channel();
+				if let TransactionStatus::InBlock(block) | TransactionStatus::Finalized(block) =
+					&status
+				{
+					let block = *block;
+					let (client, pool, metadata) = (client.clone(), pool.clone(), metadata.clone());
+					let tx_hash = tx_hash.clone();
+					let task = async move {
+						let _ = tx.send(extrinsic_inclusion(
+							&*client, &*pool, &metadata, block, &tx_hash,
+						));
+					};
+					executor.spawn_blocking(
+						"substrate-rpc-extrinsic-inclusion",
+						Some("rpc"),
+						task.boxed(),
+					);
+				}
+
+				rx.map(move |inclusion| {
+					ExtrinsicStatus::from_status(status, |block| {
+						inclusion.unwrap_or_else(|_| ExtrinsicInclusion {
+							block,
+							index: None,
+							outcome: None,
+							events: Vec::new(),
+						})
+					})
+				})
+			});
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
+
+/// Look up the index, dispatch outcome and events of the extrinsic with `tx_hash` in `block`.
+///
+/// Inclusion details that cannot be determined are left empty, the block hash is always reported.
+fn extrinsic_inclusion<P, Client>(
+	client: &Client,
+	pool: &P,
+	metadata: &Mutex<MetadataCache>,
+	block: BlockHash<P>,
+	tx_hash: &TxHash<P>,
+) -> ExtrinsicInclusion<BlockHash<P>>
+where
+	P: TransactionPool,
+	Client: BlockBackend<P::Block> + CallApiAt<P::Block> + ProvideRuntimeApi<P::Block>,
+	Client::Api: Metadata<P::Block>,
+{
+	let at = BlockId::Hash(block);
+	let mut inclusion =
+		ExtrinsicInclusion { block, index: None, outcome: None, events: Vec::new() };
+
+	let index = match client.block_body(&at) {
+		Ok(Some(body)) => body.iter().position(|ext| pool.hash_of(ext) == *tx_hash),
+		_ => None,
+	};
+	let index = match index {
+		Some(index) => index as u32,
+		None => return inclusion,
+	};
+	inclusion.index = Some(index);
+
+	match extrinsic_events(client, metadata, &at, index) {
+		Ok(ExtrinsicEvents { outcome, events }) => {
+			inclusion.outcome = outcome;
+			inclusion.events = events;
+		},
+		Err(err) => log::debug!(
+			target: "rpc",
+			"Failed to decode events of extrinsic {} in block {:?}: {}",
+			index,
+			block,
+			err,
+		),
+	}
+
+	inclusion
+}
+
+/// Read the `frame_system` events at `at` and decode those of the extrinsic at `index`.
+///
+/// The decoded metadata is cached in `metadata`.
+fn extrinsic_events<Block, Client>(
+	client: &Client,
+	metadata: &Mutex<MetadataCache>,
+	at: &BlockId<Block>,
+	index: u32,
+) -> std::result::Result<ExtrinsicEvents, String>
+where
+	Block: BlockT,
+	Client: CallApiAt<Block> + ProvideRuntimeApi<Block>,
+	Client::Api: Metadata<Block>,
+{
+	let state = client.state_at(at).map_err(|e| e.to_string())?;
+	let raw = match state.storage(&system_events::system_events_key()).map_err(|e| e.to_string())? {
+		Some(raw) => raw,
+		None => return Ok(Default::default()),
+	};
+
+	let spec_version = client.runtime_version_at(at).map_err(|e| e.to_string())?.spec_version;
+	let mut metadata = metadata.lock();
+	let (types, pallets) = metadata.get_or_decode(spec_version, || {
+		client.runtime_api().metadata(at).map_err(|e| e.to_string())
+	})?;
+	let hash_len = Block::Hash::default().as_ref().len();
+
+	system_events::decode_extrinsic_events(types, pallets, hash_len, &raw, index)
 }
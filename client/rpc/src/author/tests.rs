@@ -26,8 +26,11 @@ use jsonrpsee::{
 	types::{error::CallError, EmptyParams},
 	RpcModule,
 };
+use sc_block_builder::BlockBuilderProvider;
+use sc_rpc_api::author::events::{DispatchOutcome, ExtrinsicEvent};
 use sc_transaction_pool::{BasicPool, FullChainApi};
 use sc_transaction_pool_api::TransactionStatus;
+use sp_consensus::BlockOrigin;
 use sp_core::{
 	blake2_256,
 	bytes::to_hex,
@@ -41,7 +44,8 @@ use std::sync::Arc;
 use substrate_test_runtime_client::{
 	self,
 	runtime::{Block, Extrinsic, SessionKeys, Transfer},
-	AccountKeyring, Backend, Client, DefaultTestClientBuilderExt, TestClientBuilderExt,
+	AccountKeyring, Backend, Client, ClientBlockImportExt, DefaultTestClientBuilderExt,
+	TestClientBuilderExt,
 };
 
 fn uxt(sender: AccountKeyring, nonce: u64) -> Extrinsic {
@@ -83,6 +87,7 @@ impl TestSetup {
 			keystore: self.keystore.clone(),
 			deny_unsafe: DenyUnsafe::No,
 			executor: test_executor(),
+			metadata: Default::default(),
 		}
 	}
 
@@ -163,6 +168,118 @@ async fn author_should_return_watch_validation_error() {
 	);
 }
 
+#[tokio::test]
+async fn author_should_report_extrinsic_inclusion() {
+	let setup = TestSetup::default();
+	let mut client = setup.client.clone();
+	let metadata = Mutex::new(MetadataCache::default());
+	let xt = uxt(AccountKeyring::Alice, 0);
+
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder.push(uxt(AccountKeyring::Bob, 0)).unwrap();
+	builder.push(xt.clone()).unwrap();
+	let block = builder.build().unwrap().block;
+	let hash = block.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	// The test runtime does not deposit `frame_system` events, only the index is known.
+	let tx_hash = setup.pool.hash_of(&xt);
+	assert_eq!(
+		extrinsic_inclusion(&*client, &*setup.pool, &metadata, hash, &tx_hash),
+		ExtrinsicInclusion { block: hash, index: Some(1), outcome: None, events: vec![] },
+	);
+	let tx_hash = setup.pool.hash_of(&uxt(AccountKeyring::Alice, 1));
+	assert_eq!(
+		extrinsic_inclusion(&*client, &*setup.pool, &metadata, hash, &tx_hash),
+		ExtrinsicInclusion { block: hash, index: None, outcome: None, events: vec![] },
+	);
+}
+
+#[test]
+fn should_decode_extrinsic_events() {
+	use frame_metadata::{PalletEventMetadata, PalletMetadata};
+	use scale_info::{form::PortableForm, meta_type, PortableRegistry, Registry, TypeInfo};
+	use system_events::{decode_extrinsic_events, Phase};
+
+	#[derive(Clone, Encode, TypeInfo)]
+	enum SystemEvent {
+		ExtrinsicSuccess { weight: u64 },
+		ExtrinsicFailed { error: Vec<u8>, weight: u64 },
+	}
+
+	#[derive(Clone, Encode, TypeInfo)]
+	enum BalancesEvent {
+		Transfer {
+			from: [u8; 32],
+			to: [u8; 32],
+			#[codec(compact)]
+			amount: u128,
+		},
+	}
+
+	#[derive(Clone, Encode)]
+	enum RuntimeEvent {
+		#[codec(index = 0)]
+		System(SystemEvent),
+		#[codec(index = 5)]
+		Balances(BalancesEvent),
+	}
+
+	let mut registry = Registry::new();
+	let pallet = |name: &str, index, ty| PalletMetadata::<PortableForm> {
+		name: name.into(),
+		storage: None,
+		calls: None,
+		event: Some(PalletEventMetadata { ty }),
+		constants: vec![],
+		error: None,
+		index,
+	};
+	let pallets = vec![
+		pallet("System", 0, registry.register_type(&meta_type::<SystemEvent>())),
+		pallet("Balances", 5, registry.register_type(&meta_type::<BalancesEvent>())),
+	];
+	let types: PortableRegistry = registry.into();
+
+	let success = RuntimeEvent::System(SystemEvent::ExtrinsicSuccess { weight: 1 });
+	let transfer =
+		RuntimeEvent::Balances(BalancesEvent::Transfer { from: [1; 32], to: [2; 32], amount: 10 });
+	let failed =
+		RuntimeEvent::System(SystemEvent::ExtrinsicFailed { error: vec![1, 2], weight: 2 });
+	let records = vec![
+		(Phase::ApplyExtrinsic(0), success.clone(), Vec::<H256>::new()),
+		(Phase::ApplyExtrinsic(1), transfer.clone(), vec![H256::repeat_byte(1)]),
+		(Phase::ApplyExtrinsic(1), failed.clone(), vec![]),
+		(Phase::Finalization, transfer, vec![]),
+	]
+	.encode();
+
+	let events = decode_extrinsic_events(&types, &pallets, 32, &records, 0).unwrap();
+	assert_eq!(events.outcome, Some(DispatchOutcome::Success));
+	assert_eq!(
+		events.events,
+		vec![ExtrinsicEvent {
+			pallet: "System".into(),
+			variant: "ExtrinsicSuccess".into(),
+			data: success.encode().into(),
+		}],
+	);
+
+	let events = decode_extrinsic_events(&types, &pallets, 32, &records, 1).unwrap();
+	assert_eq!(events.outcome, Some(DispatchOutcome::Failed));
+	assert_eq!(events.events.len(), 2);
+	assert_eq!(events.events[0].variant, "Transfer");
+	assert_eq!(events.events[1].data, Bytes::from(failed.encode()));
+
+	let events = decode_extrinsic_events(&types, &pallets, 32, &records, 2).unwrap();
+	assert_eq!(events.outcome, None);
+	assert!(events.events.is_empty());
+
+	assert!(
+		decode_extrinsic_events(&types, &pallets, 32, &records[..records.len() - 1], 0).is_err()
+	);
+}
+
 #[tokio::test]
 async fn author_should_return_pending_extrinsics() {
 	let api = TestSetup::into_rpc();
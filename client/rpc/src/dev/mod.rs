@@ -24,6 +24,7 @@ mod tests;
 
 use jsonrpsee::core::RpcResult;
 use sc_client_api::{BlockBackend, HeaderBackend};
+use sc_executor_common::allocation_stats;
 use sc_rpc_api::{dev::error::Error, DenyUnsafe};
 use sp_api::{ApiExt, Core, ProvideRuntimeApi};
 use sp_core::Encode;
@@ -36,7 +37,7 @@ use std::{
 	sync::Arc,
 };
 
-pub use sc_rpc_api::dev::{BlockStats, DevApiServer};
+pub use sc_rpc_api::dev::{BlockStats, DevApiServer, HeapStats};
 
 type HasherOf<Block> = <<Block as BlockT>::Header as Header>::Hashing;
 
@@ -54,20 +55,16 @@ impl<Block: BlockT, Client> Dev<Block, Client> {
 	}
 }
 
-impl<Block, Client> DevApiServer<Block::Hash> for Dev<Block, Client>
+impl<Block, Client> Dev<Block, Client>
 where
-	Block: BlockT + 'static,
-	Client: BlockBackend<Block>
-		+ HeaderBackend<Block>
-		+ ProvideRuntimeApi<Block>
-		+ Send
-		+ Sync
-		+ 'static,
-	Client::Api: Core<Block>,
+	Block: BlockT,
+	Client: BlockBackend<Block> + HeaderBackend<Block>,
 {
-	fn block_stats(&self, hash: Block::Hash) -> RpcResult<Option<BlockStats>> {
-		self.deny_unsafe.check_if_safe()?;
-
+	/// Fetch the block `hash` prepared for reexecution, together with the header of its parent.
+	fn block_with_parent_header(
+		&self,
+		hash: Block::Hash,
+	) -> RpcResult<Option<(Block, Block::Header)>> {
 		let block = {
 			let block = self
 				.client
@@ -95,6 +92,28 @@ where
 				return Ok(None)
 			}
 		};
+		Ok(Some((block, parent_header)))
+	}
+}
+
+impl<Block, Client> DevApiServer<Block::Hash> for Dev<Block, Client>
+where
+	Block: BlockT + 'static,
+	Client: BlockBackend<Block>
+		+ HeaderBackend<Block>
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: Core<Block>,
+{
+	fn block_stats(&self, hash: Block::Hash) -> RpcResult<Option<BlockStats>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let (block, parent_header) = match self.block_with_parent_header(hash)? {
+			Some(block_with_parent_header) => block_with_parent_header,
+			None => return Ok(None),
+		};
 		let block_len = block.encoded_size() as u64;
 		let num_extrinsics = block.extrinsics().len() as u64;
 		let pre_root = *parent_header.state_root();
@@ -113,4 +132,29 @@ where
 			.encoded_size() as u64;
 		Ok(Some(BlockStats { witness_len, witness_compact_len, block_len, num_extrinsics }))
 	}
+
+	fn block_heap_stats(&self, hash: Block::Hash) -> RpcResult<Option<HeapStats>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let (block, parent_header) = match self.block_with_parent_header(hash)? {
+			Some(block_with_parent_header) => block_with_parent_header,
+			None => return Ok(None),
+		};
+		let (result, allocation_stats) = allocation_stats::collect(|| {
+			self.client
+				.runtime_api()
+				.execute_block(&BlockId::Hash(parent_header.hash()), block)
+		});
+		result.map_err(|_| Error::BlockExecutionFailed)?;
+		Ok(allocation_stats
+			.into_iter()
+			.rev()
+			.find(|(method, _)| method == "Core_execute_block")
+			.map(|(_, stats)| HeapStats {
+				usage: stats.address_space_used.into(),
+				allocated_peak: stats.bytes_allocated_peak.into(),
+				wasted: stats.bytes_wasted_sum as u64,
+				allocations_per_size_class: stats.allocations_per_size_class().collect(),
+			}))
+	}
 }
@@ -20,7 +20,7 @@ use super::*;
 use sc_block_builder::BlockBuilderProvider;
 use sp_blockchain::HeaderBackend;
 use sp_consensus::BlockOrigin;
-use substrate_test_runtime_client::{prelude::*, runtime::Block};
+use substrate_test_runtime_client::{prelude::*, runtime::Block, ExecutionStrategy};
 
 #[tokio::test]
 async fn block_stats_work() {
@@ -51,6 +51,29 @@ async fn block_stats_work() {
 	);
 }
 
+#[tokio::test]
+async fn block_heap_stats_work() {
+	let mut client = Arc::new(
+		TestClientBuilder::new()
+			.set_execution_strategy(ExecutionStrategy::AlwaysWasm)
+			.build(),
+	);
+	let api = <Dev<Block, _>>::new(client.clone(), DenyUnsafe::No).into_rpc();
+
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	let heap = api
+		.call::<_, Option<HeapStats>>("dev_getBlockHeapStats", [client.info().best_hash])
+		.await
+		.unwrap()
+		.expect("The block was executed in wasm");
+
+	assert!(heap.usage >= heap.allocated_peak);
+	assert!(heap.allocated_peak > 0);
+	assert!(!heap.allocations_per_size_class.is_empty());
+}
+
 #[tokio::test]
 async fn deny_unsafe_works() {
 	let mut client = Arc::new(substrate_test_runtime_client::new());
@@ -38,6 +38,8 @@ pub mod offchain;
 pub mod state;
 pub mod system;
 
+mod system_events;
+
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod testing;
 
@@ -22,8 +22,10 @@
 mod tests;
 
 use self::error::Error;
+use codec::Decode;
 use jsonrpsee::core::{async_trait, Error as JsonRpseeError, RpcResult};
 use parking_lot::RwLock;
+use sc_client_api::backend::{OffchainIndexJournal, OFFCHAIN_INDEX_JOURNAL_PREFIX};
 /// Re-export the API for backward compatibility.
 pub use sc_rpc_api::offchain::*;
 use sc_rpc_api::DenyUnsafe;
@@ -31,7 +33,11 @@ use sp_core::{
 	offchain::{OffchainStorage, StorageKind},
 	Bytes,
 };
-use std::sync::Arc;
+use sp_runtime::traits::Block as BlockT;
+use std::{marker::PhantomData, sync::Arc};
+
+/// Maximum number of keys returned by a single `offchain_localStorageKeys` call.
+const LOCAL_STORAGE_KEYS_MAX_COUNT: u32 = 1000;
 
 /// Offchain API
 #[derive(Debug)]
@@ -71,4 +77,124 @@ impl<T: OffchainStorage + 'static> OffchainApiServer for Offchain<T> {
 
 		Ok(self.storage.read().get(prefix, &key).map(Into::into))
 	}
+
+	fn local_storage_keys(
+		&self,
+		kind: StorageKind,
+		prefix: Bytes,
+		count: u32,
+		start_key: Option<Bytes>,
+	) -> RpcResult<Vec<Bytes>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		if count > LOCAL_STORAGE_KEYS_MAX_COUNT {
+			return Err(JsonRpseeError::from(Error::InvalidCount {
+				value: count,
+				max: LOCAL_STORAGE_KEYS_MAX_COUNT,
+			}))
+		}
+
+		let db_prefix = match kind {
+			StorageKind::PERSISTENT => sp_offchain::STORAGE_PREFIX,
+			StorageKind::LOCAL => return Err(JsonRpseeError::from(Error::UnavailableStorageKind)),
+		};
+
+		let keys = self
+			.storage
+			.read()
+			.keys(db_prefix, &prefix, start_key.as_deref(), count as usize)
+			.ok_or(Error::KeysUnsupported)?;
+
+		Ok(keys.into_iter().map(Into::into).collect())
+	}
+
+	fn clear_local_storage_prefix(&self, kind: StorageKind, prefix: Bytes) -> RpcResult<u32> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let db_prefix = match kind {
+			StorageKind::PERSISTENT => sp_offchain::STORAGE_PREFIX,
+			StorageKind::LOCAL => return Err(JsonRpseeError::from(Error::UnavailableStorageKind)),
+		};
+
+		let mut storage = self.storage.write();
+		let mut removed = 0;
+		loop {
+			let keys = storage
+				.keys(db_prefix, &prefix, None, LOCAL_STORAGE_KEYS_MAX_COUNT as usize)
+				.ok_or(Error::KeysUnsupported)?;
+			if keys.is_empty() {
+				return Ok(removed)
+			}
+
+			for key in keys {
+				storage.remove(db_prefix, &key);
+				removed += 1;
+			}
+		}
+	}
+}
+
+/// Offchain indexing API
+#[derive(Debug)]
+pub struct OffchainIndex<T: OffchainStorage, Block> {
+	/// Offchain storage
+	storage: T,
+	deny_unsafe: DenyUnsafe,
+	_phantom: PhantomData<Block>,
+}
+
+impl<T: OffchainStorage, Block: BlockT> OffchainIndex<T, Block> {
+	/// Create new instance of Offchain indexing API.
+	pub fn new(storage: T, deny_unsafe: DenyUnsafe) -> Self {
+		OffchainIndex { storage, deny_unsafe, _phantom: PhantomData }
+	}
+
+	/// Returns the offchain indexing changes of the given block, stored by the backend.
+	fn journal(&self, at: &Block::Hash) -> OffchainIndexJournal {
+		let journal = match self.storage.get(OFFCHAIN_INDEX_JOURNAL_PREFIX, at.as_ref()) {
+			Some(journal) => journal,
+			None => return Vec::new(),
+		};
+
+		OffchainIndexJournal::decode(&mut &journal[..]).unwrap_or_else(|err| {
+			log::warn!(target: "rpc", "Error decoding offchain indexing journal: {}", err);
+			Vec::new()
+		})
+	}
+}
+
+#[async_trait]
+impl<T, Block> OffchainIndexApiServer<Block::Hash> for OffchainIndex<T, Block>
+where
+	T: OffchainStorage + 'static,
+	Block: BlockT + 'static,
+{
+	fn index_get(&self, key: Bytes, at: Option<Block::Hash>) -> RpcResult<Option<Bytes>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let at = match at {
+			Some(at) => at,
+			None => return Ok(self.storage.get(sp_offchain::STORAGE_PREFIX, &key).map(Into::into)),
+		};
+
+		Ok(self
+			.journal(&at)
+			.into_iter()
+			.find(|((prefix, k), _)| prefix == sp_offchain::STORAGE_PREFIX && k == &key[..])
+			.and_then(|(_, value)| value.map(Into::into)))
+	}
+
+	fn index_changes(&self, at: Block::Hash) -> RpcResult<Vec<OffchainIndexChange>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		Ok(self
+			.journal(&at)
+			.into_iter()
+			.filter(|((prefix, _), _)| prefix == sp_offchain::STORAGE_PREFIX)
+			.map(|((_, key), value)| OffchainIndexChange {
+				key: key.into(),
+				value: value.map(Into::into),
+			})
+			.collect())
+	}
 }
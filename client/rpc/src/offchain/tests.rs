@@ -37,6 +37,51 @@ fn local_storage_should_work() {
 	);
 }
 
+#[test]
+fn local_storage_keys_and_clear_prefix_should_work() {
+	let storage = InMemOffchainStorage::default();
+	let offchain = Offchain::new(storage, DenyUnsafe::No);
+	for key in [&b"prefix_b"[..], b"prefix_a", b"prefix_c", b"other"] {
+		offchain
+			.set_local_storage(
+				StorageKind::PERSISTENT,
+				Bytes(key.to_vec()),
+				Bytes(b"value".to_vec()),
+			)
+			.unwrap();
+	}
+
+	let keys = |count, start_key: Option<&[u8]>| {
+		offchain
+			.local_storage_keys(
+				StorageKind::PERSISTENT,
+				Bytes(b"prefix_".to_vec()),
+				count,
+				start_key.map(|key| Bytes(key.to_vec())),
+			)
+			.unwrap()
+	};
+	assert_eq!(
+		keys(10, None),
+		vec![Bytes(b"prefix_a".to_vec()), Bytes(b"prefix_b".to_vec()), Bytes(b"prefix_c".to_vec())]
+	);
+	assert_eq!(keys(1, Some(b"prefix_a")), vec![Bytes(b"prefix_b".to_vec())]);
+	assert_matches!(
+		offchain.local_storage_keys(StorageKind::PERSISTENT, Bytes(vec![]), 1001, None),
+		Err(JsonRpseeError::Call(_))
+	);
+
+	assert_matches!(
+		offchain.clear_local_storage_prefix(StorageKind::PERSISTENT, Bytes(b"prefix_".to_vec())),
+		Ok(3)
+	);
+	assert!(keys(10, None).is_empty());
+	assert_matches!(
+		offchain.get_local_storage(StorageKind::PERSISTENT, Bytes(b"other".to_vec())),
+		Ok(Some(_))
+	);
+}
+
 #[test]
 fn offchain_calls_considered_unsafe() {
 	use jsonrpsee::types::error::CallError;
@@ -52,9 +97,60 @@ fn offchain_calls_considered_unsafe() {
 		}
 	);
 	assert_matches!(
-		offchain.get_local_storage(StorageKind::PERSISTENT, key),
+		offchain.get_local_storage(StorageKind::PERSISTENT, key.clone()),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+	assert_matches!(
+		offchain.local_storage_keys(StorageKind::PERSISTENT, key.clone(), 10, None),
 		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
 			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
 		}
 	);
+	assert_matches!(
+		offchain.clear_local_storage_prefix(StorageKind::PERSISTENT, key),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+}
+
+#[test]
+fn offchain_index_should_work() {
+	use codec::Encode;
+	use sp_core::H256;
+	use substrate_test_runtime_client::runtime::Block;
+
+	let mut storage = InMemOffchainStorage::default();
+	let at = H256::repeat_byte(1);
+	let journal: OffchainIndexJournal = vec![
+		((sp_offchain::STORAGE_PREFIX.to_vec(), b"a".to_vec()), Some(b"1".to_vec())),
+		((sp_offchain::STORAGE_PREFIX.to_vec(), b"b".to_vec()), None),
+	];
+	storage.set(OFFCHAIN_INDEX_JOURNAL_PREFIX, at.as_ref(), &journal.encode());
+	storage.set(sp_offchain::STORAGE_PREFIX, b"a", b"2");
+	let offchain = OffchainIndex::<_, Block>::new(storage, DenyUnsafe::No);
+
+	assert_matches!(offchain.index_get(Bytes(b"a".to_vec()), None), Ok(Some(v)) if v.0 == b"2");
+	assert_matches!(offchain.index_get(Bytes(b"a".to_vec()), Some(at)), Ok(Some(v)) if v.0 == b"1");
+	assert_matches!(offchain.index_get(Bytes(b"b".to_vec()), Some(at)), Ok(None));
+	assert_matches!(offchain.index_get(Bytes(b"a".to_vec()), Some(H256::repeat_byte(2))), Ok(None));
+	assert_eq!(
+		offchain.index_changes(at).unwrap(),
+		vec![
+			OffchainIndexChange { key: Bytes(b"a".to_vec()), value: Some(Bytes(b"1".to_vec())) },
+			OffchainIndexChange { key: Bytes(b"b".to_vec()), value: None },
+		]
+	);
+}
+
+#[test]
+fn offchain_index_calls_considered_unsafe() {
+	use substrate_test_runtime_client::runtime::Block;
+
+	let offchain = OffchainIndex::<_, Block>::new(InMemOffchainStorage::default(), DenyUnsafe::Yes);
+
+	assert_matches!(offchain.index_get(Bytes(b"a".to_vec()), None), Err(JsonRpseeError::Call(_)));
+	assert_matches!(offchain.index_changes(Default::default()), Err(JsonRpseeError::Call(_)));
 }
@@ -33,7 +33,10 @@ use jsonrpsee::{
 	ws_server::SubscriptionSink,
 };
 
-use sc_rpc_api::{state::ReadProof, DenyUnsafe};
+use sc_rpc_api::{
+	state::{ReadProof, StorageDiff},
+	DenyUnsafe,
+};
 use sp_core::{
 	storage::{PrefixedStorageKey, StorageChangeSet, StorageData, StorageKey},
 	Bytes,
@@ -52,6 +55,12 @@ pub use sc_rpc_api::{child_state::*, state::*};
 use sp_blockchain::{HeaderBackend, HeaderMetadata};
 
 const STORAGE_KEYS_PAGED_MAX_COUNT: u32 = 1000;
+/// Maximum number of entries that can be returned by `state_getStorageDiff`.
+const STORAGE_DIFF_MAX_COUNT: u32 = 1000;
+/// Maximum number of blocks that can be queried with `state_queryStorageWithProof`.
+const QUERY_STORAGE_WITH_PROOF_MAX_BLOCKS: u32 = 256;
+/// Maximum number of keys that can be queried with `state_queryStorageWithProof`.
+const QUERY_STORAGE_WITH_PROOF_MAX_KEYS: u32 = 1000;
 
 /// State backend API.
 pub trait StateBackend<Block: BlockT, Client>: Send + Sync + 'static
@@ -120,6 +129,9 @@ where
 	/// Get the runtime version.
 	fn runtime_version(&self, block: Option<Block::Hash>) -> Result<RuntimeVersion, Error>;
 
+	/// Get the runtime APIs implemented by the runtime together with their metadata.
+	fn runtime_apis(&self, block: Option<Block::Hash>) -> Result<RuntimeApis, Error>;
+
 	/// Query historical storage entries (by key) starting from a block given as the second
 	/// parameter.
 	///
@@ -146,6 +158,24 @@ where
 		keys: Vec<StorageKey>,
 	) -> Result<ReadProof<Block::Hash>, Error>;
 
+	/// Returns the values of storage entries and a compact proof of them at each of the blocks.
+	fn query_storage_with_proof(
+		&self,
+		keys: Vec<StorageKey>,
+		blocks: QueryBlocks<Block::Hash>,
+	) -> Result<Vec<StorageWithProof<Block::Hash>>, Error>;
+
+	/// Returns the storage entries that differ between the states of two blocks.
+	fn storage_diff(
+		&self,
+		from: Block::Hash,
+		to: Block::Hash,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		start_child_trie: Option<PrefixedStorageKey>,
+	) -> Result<Vec<StorageDiff>, Error>;
+
 	/// Trace storage changes for block
 	fn trace_block(
 		&self,
@@ -155,11 +185,17 @@ where
 		methods: Option<String>,
 	) -> Result<sp_rpc::tracing::TraceBlockResponse, Error>;
 
+	/// Profile the host functions called while re-executing the block
+	fn profile_block(&self, block: Block::Hash) -> Result<BlockProfile<Block::Hash>, Error>;
+
 	/// New runtime version subscription
 	fn subscribe_runtime_version(&self, sink: SubscriptionSink);
 
 	/// New storage subscription
 	fn subscribe_storage(&self, sink: SubscriptionSink, keys: Option<Vec<StorageKey>>);
+
+	/// New events subscription
+	fn subscribe_events(&self, sink: SubscriptionSink, filter: EventFilter);
 }
 
 /// Create new state API that works on full node.
@@ -275,6 +311,10 @@ where
 		self.backend.runtime_version(at).map_err(Into::into)
 	}
 
+	fn runtime_apis(&self, at: Option<Block::Hash>) -> RpcResult<RuntimeApis> {
+		self.backend.runtime_apis(at).map_err(Into::into)
+	}
+
 	fn query_storage(
 		&self,
 		keys: Vec<StorageKey>,
@@ -301,6 +341,49 @@ where
 		self.backend.read_proof(block, keys).map_err(Into::into)
 	}
 
+	fn query_storage_with_proof(
+		&self,
+		keys: Vec<StorageKey>,
+		blocks: QueryBlocks<Block::Hash>,
+	) -> RpcResult<Vec<StorageWithProof<Block::Hash>>> {
+		if keys.len() > QUERY_STORAGE_WITH_PROOF_MAX_KEYS as usize {
+			return Err(JsonRpseeError::from(Error::InvalidCount {
+				value: keys.len().try_into().unwrap_or(u32::MAX),
+				max: QUERY_STORAGE_WITH_PROOF_MAX_KEYS,
+			}))
+		}
+		if let QueryBlocks::List(hashes) = &blocks {
+			if hashes.len() > QUERY_STORAGE_WITH_PROOF_MAX_BLOCKS as usize {
+				return Err(JsonRpseeError::from(Error::InvalidCount {
+					value: hashes.len().try_into().unwrap_or(u32::MAX),
+					max: QUERY_STORAGE_WITH_PROOF_MAX_BLOCKS,
+				}))
+			}
+		}
+		self.backend.query_storage_with_proof(keys, blocks).map_err(Into::into)
+	}
+
+	fn storage_diff(
+		&self,
+		from: Block::Hash,
+		to: Block::Hash,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		start_child_trie: Option<PrefixedStorageKey>,
+	) -> RpcResult<Vec<StorageDiff>> {
+		self.deny_unsafe.check_if_safe()?;
+		if count > STORAGE_DIFF_MAX_COUNT {
+			return Err(JsonRpseeError::from(Error::InvalidCount {
+				value: count,
+				max: STORAGE_DIFF_MAX_COUNT,
+			}))
+		}
+		self.backend
+			.storage_diff(from, to, prefix, count, start_key, start_child_trie)
+			.map_err(Into::into)
+	}
+
 	/// Re-execute the given block with the tracing targets given in `targets`
 	/// and capture all state changes.
 	///
@@ -319,6 +402,14 @@ where
 			.map_err(Into::into)
 	}
 
+	/// Re-execute the given block in wasm and profile the host functions it calls.
+	///
+	/// Note: requires the node to run with `--rpc-methods=Unsafe`.
+	fn profile_block(&self, block: Block::Hash) -> RpcResult<BlockProfile<Block::Hash>> {
+		self.deny_unsafe.check_if_safe()?;
+		self.backend.profile_block(block).map_err(Into::into)
+	}
+
 	fn subscribe_runtime_version(&self, sink: SubscriptionSink) -> SubscriptionResult {
 		self.backend.subscribe_runtime_version(sink);
 		Ok(())
@@ -339,6 +430,15 @@ where
 		self.backend.subscribe_storage(sink, keys);
 		Ok(())
 	}
+
+	fn subscribe_events(
+		&self,
+		sink: SubscriptionSink,
+		filter: Option<EventFilter>,
+	) -> SubscriptionResult {
+		self.backend.subscribe_events(sink, filter.unwrap_or_default());
+		Ok(())
+	}
 }
 
 /// Child state backend API.
@@ -18,34 +18,55 @@
 
 //! State API backend for full nodes.
 
-use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{collections::HashMap, marker::PhantomData, sync::Arc, time::Instant};
 
 use super::{
 	client_err,
 	error::{Error, Result},
-	ChildStateBackend, StateBackend,
+	ChildStateBackend, StateBackend, QUERY_STORAGE_WITH_PROOF_MAX_BLOCKS,
+};
+use crate::{
+	system_events::{self, MetadataCache, Phase},
+	SubscriptionTaskExecutor,
 };
-use crate::SubscriptionTaskExecutor;
 
-use futures::{future, stream, FutureExt, StreamExt};
-use jsonrpsee::{core::Error as JsonRpseeError, SubscriptionSink};
+use codec::{Decode, Encode};
+use futures::{channel::oneshot, future, stream, FutureExt, StreamExt};
+use jsonrpsee::{
+	core::{error::SubscriptionClosed, Error as JsonRpseeError},
+	SubscriptionSink,
+};
+use parking_lot::Mutex;
 use sc_client_api::{
-	Backend, BlockBackend, BlockchainEvents, CallExecutor, ExecutorProvider, ProofProvider,
-	StorageProvider,
+	Backend, BlockBackend, BlockchainEvents, CallExecutor, ExecutionStrategy, ExecutorProvider,
+	ProofProvider, StorageProvider,
+};
+use sc_executor_common::host_profiler;
+use sc_rpc_api::state::{
+	BlockEvents, BlockProfile, EventFilter, EventPhase, HostFunctionProfile, QueryBlocks,
+	ReadProof, RuntimeApi, RuntimeApiMethod, RuntimeApiParam, RuntimeApiType, RuntimeApis,
+	RuntimeEvent, StorageDiff, StorageDiffKind, StorageWithProof,
+};
+use scale_info::form::PortableForm;
+use sp_api::{
+	metadata::{extract_runtime_apis_metadata, RuntimeApiTypeMetadata},
+	CallApiAt, Metadata, ProvideRuntimeApi, RuntimeApiInfo,
 };
-use sc_rpc_api::state::ReadProof;
-use sp_api::{CallApiAt, Metadata, ProvideRuntimeApi};
 use sp_blockchain::{
 	CachedHeaderMetadata, Error as ClientError, HeaderBackend, HeaderMetadata,
 	Result as ClientResult,
 };
 use sp_core::{
+	hashing::blake2_64,
 	storage::{
 		ChildInfo, ChildType, PrefixedStorageKey, StorageChangeSet, StorageData, StorageKey,
 	},
 	Bytes,
 };
-use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, HashFor, Header as HeaderT, SaturatedConversion},
+};
 use sp_version::RuntimeVersion;
 
 /// Ranges to query in state_queryStorage.
@@ -201,7 +222,7 @@ where
 						&method,
 						&call_data,
 						self.client.execution_extensions().strategies().other,
-						None,
+						Some(self.client.execution_extensions().rpc_call_extensions()),
 					)
 					.map(Into::into)
 			})
@@ -317,6 +338,75 @@ where
 		})
 	}
 
+	fn runtime_apis(&self, block: Option<Block::Hash>) -> std::result::Result<RuntimeApis, Error> {
+		let at = BlockId::Hash(self.block_or_best(block).map_err(client_err)?);
+		let version =
+			self.client.runtime_version_at(&at).map_err(|e| Error::Client(Box::new(e)))?;
+
+		let metadata =
+			if version.has_api_with(&<dyn Metadata<Block> as RuntimeApiInfo>::ID, |_| true) {
+				let metadata = self
+					.client
+					.runtime_api()
+					.metadata(&at)
+					.map_err(|e| Error::Client(Box::new(e)))?;
+				extract_runtime_apis_metadata(&metadata)
+					.transpose()
+					.map_err(|e| Error::Client(Box::new(e)))?
+			} else {
+				None
+			};
+
+		let to_type = |ty: RuntimeApiTypeMetadata<PortableForm>| RuntimeApiType {
+			name: ty.name,
+			id: ty.ty.map(|ty| ty.id()),
+		};
+		let apis = version
+			.apis
+			.iter()
+			.map(|(id, api_version)| {
+				let api = metadata.as_ref().and_then(|metadata| {
+					metadata.apis.iter().find(|api| &blake2_64(api.name.as_bytes()) == id)
+				});
+				let methods = api
+					.map(|api| {
+						api.methods
+							.iter()
+							.cloned()
+							.map(|method| RuntimeApiMethod {
+								call_name: format!("{}_{}", api.name, method.name),
+								name: method.name,
+								inputs: method
+									.inputs
+									.into_iter()
+									.map(|param| RuntimeApiParam {
+										name: param.name,
+										ty: to_type(param.ty),
+									})
+									.collect(),
+								output: to_type(method.output),
+								docs: method.docs,
+							})
+							.collect()
+					})
+					.unwrap_or_default();
+
+				RuntimeApi {
+					id: id.to_vec().into(),
+					name: api.map(|api| api.name.clone()),
+					version: *api_version,
+					methods,
+					docs: api.map(|api| api.docs.clone()).unwrap_or_default(),
+				}
+			})
+			.collect();
+
+		Ok(RuntimeApis {
+			apis,
+			types: metadata.map(|metadata| metadata.types.encode()).unwrap_or_default().into(),
+		})
+	}
+
 	fn query_storage(
 		&self,
 		from: Block::Hash,
@@ -357,6 +447,120 @@ where
 			.map_err(client_err)
 	}
 
+	fn query_storage_with_proof(
+		&self,
+		keys: Vec<StorageKey>,
+		blocks: QueryBlocks<Block::Hash>,
+	) -> std::result::Result<Vec<StorageWithProof<Block::Hash>>, Error> {
+		let hashes = match blocks {
+			QueryBlocks::List(hashes) => hashes,
+			QueryBlocks::Range { from, to } => {
+				let to = self
+					.block_or_best(to)
+					.map_err(|e| invalid_block::<Block>(from, to, e.to_string()))?;
+				let invalid_block_err =
+					|e: ClientError| invalid_block::<Block>(from, Some(to), e.to_string());
+				let from_number =
+					self.client.header_metadata(from).map_err(invalid_block_err)?.number;
+				let to_number = self.client.header_metadata(to).map_err(invalid_block_err)?.number;
+
+				// Check the size before walking the range, it is only bounded by the chain length.
+				let count =
+					to_number.saturating_sub(from_number).saturated_into::<u32>().saturating_add(1);
+				if count > QUERY_STORAGE_WITH_PROOF_MAX_BLOCKS {
+					return Err(Error::InvalidCount {
+						value: count,
+						max: QUERY_STORAGE_WITH_PROOF_MAX_BLOCKS,
+					})
+				}
+
+				self.query_storage_range(from, Some(to))?.hashes
+			},
+		};
+
+		hashes
+			.into_iter()
+			.map(|block| {
+				let id = BlockId::Hash(block);
+				let state_root = *self
+					.client
+					.header(id)
+					.map_err(client_err)?
+					.ok_or_else(|| client_err(ClientError::UnknownBlock(format!("{:?}", block))))?
+					.state_root();
+
+				let values = keys
+					.iter()
+					.map(|key| {
+						Ok((key.clone(), self.client.storage(&id, key).map_err(client_err)?))
+					})
+					.collect::<Result<Vec<_>>>()?;
+
+				let proof = self
+					.client
+					.read_proof(&id, &mut keys.iter().map(|key| key.0.as_ref()))
+					.map_err(client_err)?
+					.into_compact_proof::<HashFor<Block>>(state_root)
+					.map_err(|e| {
+						Error::Client(format!("Failed to compact proof: {:?}", e).into())
+					})?;
+
+				Ok(StorageWithProof { at: block, values, proof: proof.encode().into() })
+			})
+			.collect()
+	}
+
+	fn storage_diff(
+		&self,
+		from: Block::Hash,
+		to: Block::Hash,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		start_child_trie: Option<PrefixedStorageKey>,
+	) -> std::result::Result<Vec<StorageDiff>, Error> {
+		let start_child_info = match start_child_trie {
+			Some(storage_key) => match ChildType::from_prefixed_key(&storage_key) {
+				Some((ChildType::ParentKeyId, storage_key)) =>
+					Some(ChildInfo::new_default(storage_key)),
+				None => return Err(client_err(sp_blockchain::Error::InvalidChildStorageKey)),
+			},
+			None => None,
+		};
+		// Without a key, the diff resumes after the entry of the root of the child trie.
+		let (start_child_info, start_key) = match (start_child_info, start_key) {
+			(Some(child_info), None) =>
+				(None, Some(StorageKey(child_info.prefixed_storage_key().into_inner()))),
+			start => start,
+		};
+		let start_at = start_key.as_ref().map(|key| (start_child_info.as_ref(), key));
+
+		let diff = self
+			.client
+			.storage_diff(
+				&BlockId::Hash(from),
+				&BlockId::Hash(to),
+				prefix.as_ref(),
+				start_at,
+				count as usize,
+			)
+			.map_err(client_err)?;
+
+		Ok(diff
+			.into_iter()
+			.map(|entry| StorageDiff {
+				child_trie: entry.child_info.map(|child_info| child_info.prefixed_storage_key()),
+				key: StorageKey(entry.key),
+				kind: match (&entry.old_value, &entry.new_value) {
+					(None, _) => StorageDiffKind::Added,
+					(_, None) => StorageDiffKind::Removed,
+					_ => StorageDiffKind::Modified,
+				},
+				value: entry.new_value.map(StorageData),
+			})
+			.collect())
+	}
+
 	fn subscribe_runtime_version(&self, mut sink: SubscriptionSink) {
 		let client = self.client.clone();
 
@@ -446,6 +650,65 @@ where
 		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
 	}
 
+	fn subscribe_events(&self, mut sink: SubscriptionSink, filter: EventFilter) {
+		let client = self.client.clone();
+		let executor = self.executor.clone();
+
+		let blocks = if filter.finalized {
+			client
+				.finality_notification_stream()
+				.flat_map(|n| {
+					// Blocks finalized implicitly come first, in ascending order.
+					let blocks = n.tree_route.iter().copied().chain(Some(n.hash));
+					stream::iter(blocks.collect::<Vec<_>>())
+				})
+				.boxed()
+		} else {
+			client
+				.import_notification_stream()
+				.filter(|n| future::ready(n.is_new_best))
+				.flat_map(|n| {
+					// On a re-org, the blocks of the new best chain imported before are enacted
+					// before the new best block.
+					let enacted = n.tree_route.iter().flat_map(|route| route.enacted());
+					let blocks = enacted.map(|block| block.hash).chain(Some(n.hash));
+					stream::iter(blocks.collect::<Vec<_>>())
+				})
+				.boxed()
+		};
+
+		// Reading the events and decoding them may take a while, so the events of each block are
+		// looked up on a blocking task.
+		let filter = Arc::new(filter);
+		let metadata = Arc::new(Mutex::new(MetadataCache::default()));
+		let stream = blocks.then(move |block| {
+			let (tx, rx) = oneshot::channel();
+			let (client, filter, metadata) = (client.clone(), filter.clone(), metadata.clone());
+			let task = async move {
+				let events =
+					block_events::<BE, Block, _>(&*client, block, &filter, &mut metadata.lock());
+				let _ = tx.send(events);
+			};
+			executor.spawn_blocking("substrate-rpc-block-events", Some("rpc"), task.boxed());
+
+			rx.map(move |events| {
+				events
+					.unwrap_or_else(|_| Err("Lookup was cancelled".into()))
+					.map_err(|e| format!("Failed to decode events of {}: {}", block, e))
+			})
+		});
+
+		let fut = async move {
+			// Events that cannot be decoded end the subscription with an error, instead of
+			// silently skipping blocks.
+			if let SubscriptionClosed::Failed(err) = sink.pipe_from_try_stream(stream).await {
+				sink.close(err);
+			}
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+	}
+
 	fn trace_block(
 		&self,
 		block: Block::Hash,
@@ -464,6 +727,50 @@ where
 		.trace_block()
 		.map_err(|e| invalid_block::<Block>(block, None, e.to_string()))
 	}
+
+	fn profile_block(
+		&self,
+		block: Block::Hash,
+	) -> std::result::Result<BlockProfile<Block::Hash>, Error> {
+		let signed_block = self
+			.client
+			.block(&BlockId::Hash(block))
+			.map_err(client_err)?
+			.ok_or_else(|| invalid_block::<Block>(block, None, "Block not found".into()))?;
+		let (mut header, extrinsics) = signed_block.block.deconstruct();
+		let parent_id = BlockId::Hash(*header.parent_hash());
+		// Remove all `Seal`s as they are added by the consensus engines after building the block.
+		header.digest_mut().logs.retain(|d| d.as_seal().is_none());
+		let encoded_block = Block::new(header, extrinsics).encode();
+
+		let start = Instant::now();
+		// Native execution doesn't go through the host functions, hence always use wasm.
+		let (result, profile) = host_profiler::profile(|| {
+			self.client.executor().call(
+				&parent_id,
+				"Core_execute_block",
+				&encoded_block,
+				ExecutionStrategy::AlwaysWasm,
+				None,
+			)
+		});
+		let execution_nanos = start.elapsed().as_nanos() as u64;
+		result.map_err(|e| invalid_block::<Block>(block, None, e.to_string()))?;
+
+		let mut host_functions = profile
+			.into_iter()
+			.map(|(name, stats)| HostFunctionProfile {
+				name,
+				calls: stats.calls,
+				time_nanos: stats.time.as_nanos() as u64,
+				bytes_read: stats.bytes_read,
+				bytes_written: stats.bytes_written,
+			})
+			.collect::<Vec<_>>();
+		host_functions.sort_by(|a, b| b.time_nanos.cmp(&a.time_nanos));
+
+		Ok(BlockProfile { block, execution_nanos, host_functions })
+	}
 }
 
 impl<BE, Block, Client> ChildStateBackend<Block, Client> for FullState<BE, Block, Client>
@@ -629,3 +936,64 @@ fn invalid_block_range<B: BlockT>(
 fn invalid_block<B: BlockT>(from: B::Hash, to: Option<B::Hash>, details: String) -> Error {
 	Error::InvalidBlockRange { from: format!("{:?}", from), to: format!("{:?}", to), details }
 }
+
+/// Read and decode the events of `block` that match `filter`.
+///
+/// The decoded metadata is cached in `metadata`.
+fn block_events<BE, Block, Client>(
+	client: &Client,
+	block: Block::Hash,
+	filter: &EventFilter,
+	metadata: &mut MetadataCache,
+) -> std::result::Result<BlockEvents<Block::Hash>, String>
+where
+	Block: BlockT,
+	BE: Backend<Block>,
+	Client: StorageProvider<Block, BE> + CallApiAt<Block> + ProvideRuntimeApi<Block>,
+	Client::Api: Metadata<Block>,
+{
+	let at = BlockId::Hash(block);
+	let raw = match client
+		.storage(&at, &StorageKey(system_events::system_events_key()))
+		.map_err(|e| e.to_string())?
+	{
+		Some(raw) => raw.0,
+		None => return Ok(BlockEvents { block, events: Vec::new() }),
+	};
+
+	let spec_version = client.runtime_version_at(&at).map_err(|e| e.to_string())?.spec_version;
+	let (types, pallets) = metadata.get_or_decode(spec_version, || {
+		client.runtime_api().metadata(&at).map_err(|e| e.to_string())
+	})?;
+
+	let hash_len = Block::Hash::default().as_ref().len();
+	let events = system_events::decode_events(types, pallets, hash_len, &raw)?
+		.into_iter()
+		.filter(|record| filter.pallet.as_ref().map_or(true, |pallet| *pallet == record.pallet))
+		.filter(|record| filter.event.as_ref().map_or(true, |event| *event == record.variant))
+		.map(|record| {
+			let topics = record
+				.topics
+				.iter()
+				.map(|topic| Block::Hash::decode(&mut &topic[..]))
+				.collect::<std::result::Result<_, _>>()
+				.map_err(|e| e.to_string())?;
+			let phase = match record.phase {
+				Phase::ApplyExtrinsic(index) => EventPhase::ApplyExtrinsic(index),
+				Phase::Finalization => EventPhase::Finalization,
+				Phase::Initialization => EventPhase::Initialization,
+			};
+
+			Ok(RuntimeEvent {
+				phase,
+				pallet: record.pallet,
+				event: record.variant,
+				topics,
+				data: record.data.into(),
+				fields: record.fields,
+			})
+		})
+		.collect::<std::result::Result<_, String>>()?;
+
+	Ok(BlockEvents { block, events })
+}
@@ -20,18 +20,24 @@ use self::error::Error;
 use super::*;
 use crate::testing::{test_executor, timeout_secs};
 use assert_matches::assert_matches;
+use codec::Decode;
 use futures::executor;
 use jsonrpsee::{
 	core::Error as RpcError,
 	types::{error::CallError as RpcCallError, EmptyParams, ErrorObject},
 };
 use sc_block_builder::BlockBuilderProvider;
-use sc_rpc_api::DenyUnsafe;
+use sc_rpc_api::{
+	state::{BlockEvents, EventFilter, QueryBlocks, StorageDiff, StorageDiffKind},
+	DenyUnsafe,
+};
 use sp_consensus::BlockOrigin;
 use sp_core::{hash::H256, storage::ChildInfo};
-use sp_io::hashing::blake2_256;
-use std::sync::Arc;
-use substrate_test_runtime_client::{prelude::*, runtime};
+use sp_io::hashing::{blake2_256, blake2_64};
+use sp_runtime::{generic::BlockId, traits::BlakeTwo256};
+use sp_trie::CompactProof;
+use std::{sync::Arc, time::Duration};
+use substrate_test_runtime_client::{prelude::*, runtime, ExecutionStrategy, ExecutionTimeouts};
 
 const STORAGE_KEY: &[u8] = b"child";
 
@@ -203,6 +209,36 @@ async fn should_call_contract() {
 	)
 }
 
+#[tokio::test]
+async fn should_abort_calls_exceeding_the_execution_timeout() {
+	let executor = NativeElseWasmExecutor::new(
+		WasmExecutionMethod::Compiled {
+			instantiation_strategy: sc_executor::WasmtimeInstantiationStrategy::RecreateInstance,
+		},
+		None,
+		8,
+		2,
+	);
+	let client: TestClient = TestClientBuilder::new()
+		.set_execution_strategy(ExecutionStrategy::AlwaysWasm)
+		.set_execution_timeouts(ExecutionTimeouts {
+			rpc: Some(Duration::from_millis(100)),
+			..Default::default()
+		})
+		.build_with_native_executor(executor)
+		.0;
+	let (client, _child) = new_full(Arc::new(client), test_executor(), DenyUnsafe::No, None);
+
+	match client.call("TestAPI_run_forever".into(), Bytes(Vec::new()), None) {
+		Err(RpcError::Call(RpcCallError::Failed(error))) => assert!(
+			error.to_string().contains("exceeding its execution timeout"),
+			"unexpected error: {}",
+			error,
+		),
+		result => panic!("unexpected result: {:?}", result),
+	}
+}
+
 #[tokio::test]
 async fn should_notify_about_storage_changes() {
 	let mut sub = {
@@ -235,6 +271,116 @@ async fn should_notify_about_storage_changes() {
 	assert_matches!(timeout_secs(1, sub.next::<StorageChangeSet<H256>>()).await, Ok(None));
 }
 
+#[tokio::test]
+async fn should_notify_about_block_events() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+
+	let api_rpc = api.into_rpc();
+	let mut sub = api_rpc.subscribe("state_subscribeEvents", EmptyParams::new()).await.unwrap();
+
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let hash = block.header.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	// The test runtime does not deposit any events.
+	let (events, _) = timeout_secs(10, sub.next::<BlockEvents<H256>>())
+		.await
+		.unwrap()
+		.unwrap()
+		.unwrap();
+	assert_eq!(events, BlockEvents { block: hash, events: vec![] });
+}
+
+#[tokio::test]
+async fn should_close_events_subscription_on_decode_failure() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+
+	let api_rpc = api.into_rpc();
+	let mut sub = api_rpc.subscribe("state_subscribeEvents", EmptyParams::new()).await.unwrap();
+
+	// The metadata of the test runtime does not describe any events.
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder
+		.push_storage_change(crate::system_events::system_events_key(), Some(vec![1, 2, 3]))
+		.unwrap();
+	let block = builder.build().unwrap().block;
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	// The subscription is closed with the error instead of skipping the block.
+	assert_matches!(
+		timeout_secs(10, sub.next::<BlockEvents<H256>>()).await,
+		Ok(None) | Ok(Some(Err(_)))
+	);
+}
+
+#[tokio::test]
+async fn should_notify_about_events_of_enacted_blocks() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+
+	let api_rpc = api.into_rpc();
+	let mut sub = api_rpc.subscribe("state_subscribeEvents", EmptyParams::new()).await.unwrap();
+
+	let a1 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	let a1_hash = a1.header.hash();
+	client.import(BlockOrigin::Own, a1).await.unwrap();
+
+	// A fork of the same height does not become the new best block.
+	let mut builder = client.new_block_at(&BlockId::Number(0), Default::default(), false).unwrap();
+	builder.push_storage_change(vec![1], None).unwrap();
+	let b1 = builder.build().unwrap().block;
+	let b1_hash = b1.header.hash();
+	client.import(BlockOrigin::Own, b1).await.unwrap();
+
+	// Until it is extended.
+	let b2 = client
+		.new_block_at(&BlockId::Hash(b1_hash), Default::default(), false)
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	let b2_hash = b2.header.hash();
+	client.import(BlockOrigin::Own, b2).await.unwrap();
+
+	for hash in [a1_hash, b1_hash, b2_hash] {
+		let (events, _) = timeout_secs(10, sub.next::<BlockEvents<H256>>())
+			.await
+			.unwrap()
+			.unwrap()
+			.unwrap();
+		assert_eq!(events.block, hash);
+	}
+}
+
+#[tokio::test]
+async fn should_notify_about_events_of_implicitly_finalized_blocks() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+
+	let api_rpc = api.into_rpc();
+	let filter = EventFilter { finalized: true, ..Default::default() };
+	let mut sub = api_rpc.subscribe("state_subscribeEvents", [filter]).await.unwrap();
+
+	let mut hashes = Vec::new();
+	for _ in 0..2 {
+		let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+		hashes.push(block.header.hash());
+		client.import(BlockOrigin::Own, block).await.unwrap();
+	}
+	client.finalize_block(BlockId::Hash(hashes[1]), None).unwrap();
+
+	for hash in hashes {
+		let (events, _) = timeout_secs(10, sub.next::<BlockEvents<H256>>())
+			.await
+			.unwrap()
+			.unwrap()
+			.unwrap();
+		assert_eq!(events.block, hash);
+	}
+}
+
 #[tokio::test]
 async fn should_send_initial_storage_changes_and_notifications() {
 	let mut sub = {
@@ -481,9 +627,10 @@ async fn should_return_runtime_version() {
 
 	let result = "{\"specName\":\"test\",\"implName\":\"parity-test\",\"authoringVersion\":1,\
 		\"specVersion\":2,\"implVersion\":2,\"apis\":[[\"0xdf6acb689907609b\",4],\
-		[\"0x37e397fc7c91f5e4\",1],[\"0xd2bc9897eed08f15\",3],[\"0x40fe3ad401f8959a\",6],\
-		[\"0xc6e9a76309f39b09\",1],[\"0xdd718d5cc53262d4\",1],[\"0xcbca25e39f142387\",2],\
-		[\"0xf78b278be53f454c\",2],[\"0xab3c0572291feb8b\",1],[\"0xbc9d89904f5b923f\",1]],\
+		[\"0x37e397fc7c91f5e4\",1],[\"0xe8f630104acdc6a2\",1],[\"0xd2bc9897eed08f15\",3],\
+		[\"0x40fe3ad401f8959a\",6],[\"0xc6e9a76309f39b09\",1],[\"0xdd718d5cc53262d4\",1],\
+		[\"0xcbca25e39f142387\",2],[\"0xf78b278be53f454c\",2],[\"0xab3c0572291feb8b\",1],\
+		[\"0xbc9d89904f5b923f\",1]],\
 		\"transactionVersion\":1,\"stateVersion\":1}";
 
 	let runtime_version = api.runtime_version(None.into()).unwrap();
@@ -494,6 +641,41 @@ async fn should_return_runtime_version() {
 	assert_eq!(deserialized, runtime_version);
 }
 
+#[tokio::test]
+async fn should_return_runtime_apis() {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+
+	let runtime_apis = api.runtime_apis(None).unwrap();
+	let runtime_version = api.runtime_version(None).unwrap();
+	assert_eq!(runtime_apis.apis.len(), runtime_version.apis.len());
+	assert!(runtime_apis.apis.iter().all(|api| api.name.is_some()));
+	assert!(!runtime_apis.types.is_empty());
+
+	let core = runtime_apis
+		.apis
+		.iter()
+		.find(|api| api.name.as_deref() == Some("Core"))
+		.unwrap();
+	assert_eq!(core.id, Bytes::from(blake2_64(b"Core").to_vec()));
+	assert_eq!(core.version, 4);
+	assert_eq!(
+		core.docs,
+		vec![" The `Core` runtime api that every Substrate runtime needs to implement."]
+	);
+
+	let version = core.methods.iter().find(|method| method.name == "version").unwrap();
+	assert_eq!(version.call_name, "Core_version");
+	assert!(version.inputs.is_empty());
+	assert_eq!(version.output.name, "RuntimeVersion");
+	assert_eq!(version.docs, vec![" Returns the version of the runtime."]);
+
+	let execute_block = core.methods.iter().find(|method| method.name == "execute_block").unwrap();
+	assert_eq!(execute_block.inputs.len(), 1);
+	assert_eq!(execute_block.inputs[0].name, "block");
+	assert_eq!(execute_block.output.name, "()");
+}
+
 #[tokio::test]
 async fn should_notify_on_runtime_version_initially() {
 	let mut sub = {
@@ -545,3 +727,182 @@ async fn concrete_storage_subscriptions_are_rpc_safe() {
 
 	assert!(sub.is_ok());
 }
+
+#[tokio::test]
+async fn should_profile_block() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let mut builder = client.new_block(Default::default()).unwrap();
+	builder
+		.push_transfer(runtime::Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Ferdie.into(),
+			amount: 42,
+			nonce: 0,
+		})
+		.unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = block.hash();
+	client.import(BlockOrigin::Own, block).await.unwrap();
+
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+	let profile = api.profile_block(block_hash).unwrap();
+
+	assert_eq!(profile.block, block_hash);
+	assert!(profile
+		.host_functions
+		.iter()
+		.any(|f| f.name == "ext_storage_get_version_1" && f.calls > 0 && f.bytes_written > 0));
+	assert!(profile.host_functions.windows(2).all(|w| w[0].time_nanos >= w[1].time_nanos));
+
+	let (api, _child) = new_full(client, test_executor(), DenyUnsafe::Yes, None);
+	assert_matches!(
+		api.profile_block(block_hash),
+		Err(RpcError::Call(RpcCallError::Custom(e))) if e.message() == "RPC call is unsafe to be called externally"
+	);
+}
+
+#[tokio::test]
+async fn should_return_storage_diff() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+
+	let mut add_block = |nonce| {
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_storage_change(vec![2], Some(vec![2])).unwrap();
+		builder
+			.push_storage_change(vec![3], if nonce == 0 { Some(vec![3]) } else { None })
+			.unwrap();
+		builder
+			.push_storage_change(vec![4], if nonce == 0 { None } else { Some(vec![4]) })
+			.unwrap();
+		builder.push_storage_change(vec![5], Some(vec![nonce as u8])).unwrap();
+		let block = builder.build().unwrap().block;
+		let hash = block.header.hash();
+		executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+		hash
+	};
+	let block1_hash = add_block(0);
+	let block2_hash = add_block(1);
+
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+	let diff = api
+		.storage_diff(block1_hash, block2_hash, None, 1000, None, None)
+		.unwrap()
+		.into_iter()
+		.filter(|entry| entry.key.0.len() == 1)
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		diff,
+		vec![
+			StorageDiff {
+				child_trie: None,
+				key: StorageKey(vec![3]),
+				kind: StorageDiffKind::Removed,
+				value: None,
+			},
+			StorageDiff {
+				child_trie: None,
+				key: StorageKey(vec![4]),
+				kind: StorageDiffKind::Added,
+				value: Some(StorageData(vec![4])),
+			},
+			StorageDiff {
+				child_trie: None,
+				key: StorageKey(vec![5]),
+				kind: StorageDiffKind::Modified,
+				value: Some(StorageData(vec![1])),
+			},
+		],
+	);
+
+	// Only keys below the prefix are compared.
+	assert_eq!(
+		api.storage_diff(block1_hash, block2_hash, Some(StorageKey(vec![5])), 1000, None, None)
+			.unwrap(),
+		vec![StorageDiff {
+			child_trie: None,
+			key: StorageKey(vec![5]),
+			kind: StorageDiffKind::Modified,
+			value: Some(StorageData(vec![1])),
+		}],
+	);
+
+	// The diff can be retrieved in pages.
+	let keys = |count, start_key| {
+		api.storage_diff(block1_hash, block2_hash, None, count, start_key, None)
+			.unwrap()
+			.into_iter()
+			.map(|entry| entry.key)
+			.collect::<Vec<_>>()
+	};
+	let all = keys(1000, None);
+	let first = keys(2, None);
+	assert_eq!(first, all[..2]);
+	assert_eq!(keys(1000, Some(first[1].clone())), all[2..]);
+	assert_matches!(
+		api.storage_diff(block1_hash, block2_hash, None, 1001, None, None),
+		Err(RpcError::Call(RpcCallError::Custom(e))) if e.message() == "count exceeds maximum value. value: 1001, max: 1000"
+	);
+
+	let (api, _child) = new_full(client, test_executor(), DenyUnsafe::Yes, None);
+	assert_matches!(
+		api.storage_diff(block1_hash, block2_hash, None, 1000, None, None),
+		Err(RpcError::Call(RpcCallError::Custom(e))) if e.message() == "RPC call is unsafe to be called externally"
+	);
+}
+
+#[tokio::test]
+async fn should_query_storage_with_proof() {
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let genesis_hash = client.genesis_hash();
+
+	let mut add_block = |nonce: u8| {
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_storage_change(vec![5], Some(vec![nonce])).unwrap();
+		let block = builder.build().unwrap().block;
+		let hash = block.header.hash();
+		executor::block_on(client.import(BlockOrigin::Own, block)).unwrap();
+		hash
+	};
+	let block1_hash = add_block(1);
+	let block2_hash = add_block(2);
+
+	let (api, _child) = new_full(client.clone(), test_executor(), DenyUnsafe::No, None);
+	let keys = vec![StorageKey(vec![5]), StorageKey(vec![6])];
+	let result = api
+		.query_storage_with_proof(keys.clone(), QueryBlocks::Range { from: genesis_hash, to: None })
+		.unwrap();
+
+	assert_eq!(
+		result.iter().map(|entry| entry.at).collect::<Vec<_>>(),
+		vec![genesis_hash, block1_hash, block2_hash],
+	);
+	for (entry, value) in result.iter().zip([None, Some(1), Some(2)]) {
+		assert_eq!(
+			entry.values,
+			vec![(keys[0].clone(), value.map(|v| StorageData(vec![v]))), (keys[1].clone(), None)],
+		);
+
+		let state_root = client.header(BlockId::Hash(entry.at)).unwrap().unwrap().state_root;
+		let proof = CompactProof::decode(&mut &entry.proof[..]).unwrap();
+		assert!(proof.to_storage_proof::<BlakeTwo256>(Some(&state_root)).is_ok());
+	}
+
+	// A list of blocks is queried in the given order.
+	let result = api
+		.query_storage_with_proof(keys.clone(), QueryBlocks::List(vec![block2_hash, block1_hash]))
+		.unwrap();
+	assert_eq!(
+		result.iter().map(|entry| entry.at).collect::<Vec<_>>(),
+		vec![block2_hash, block1_hash],
+	);
+
+	assert_matches!(
+		api.query_storage_with_proof(vec![StorageKey(vec![5]); 1001], QueryBlocks::List(vec![])),
+		Err(RpcError::Call(RpcCallError::Custom(e))) if e.message() == "count exceeds maximum value. value: 1001, max: 1000"
+	);
+	assert_matches!(
+		api.query_storage_with_proof(keys, QueryBlocks::List(vec![block1_hash; 257])),
+		Err(RpcError::Call(RpcCallError::Custom(e))) if e.message() == "count exceeds maximum value. value: 257, max: 256"
+	);
+}
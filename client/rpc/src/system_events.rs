@@ -0,0 +1,450 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding of the events deposited by `frame_system`.
+//!
+//! The events storage value is a SCALE encoded `Vec<EventRecord<RuntimeEvent, Hash>>`. The
+//! runtime event type is not known to the client, so the records are walked using the type
+//! registry of the runtime metadata.
+
+use codec::{Compact, Decode, Encode};
+use frame_metadata::{PalletMetadata, RuntimeMetadata, RuntimeMetadataPrefixed};
+use sc_rpc_api::author::events::{DispatchOutcome, ExtrinsicEvent};
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive};
+use serde_json::{Map, Value};
+use sp_core::{hashing::twox_128, OpaqueMetadata};
+
+/// Name of the pallet that deposits the dispatch outcome events.
+const SYSTEM_PALLET: &str = "System";
+
+/// Storage key of `frame_system::Events`.
+pub(crate) fn system_events_key() -> Vec<u8> {
+	let mut key = twox_128(b"System").to_vec();
+	key.extend_from_slice(&twox_128(b"Events"));
+	key
+}
+
+/// Mirror of `frame_system::Phase`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub(crate) enum Phase {
+	ApplyExtrinsic(u32),
+	Finalization,
+	Initialization,
+}
+
+/// A decoded `frame_system::EventRecord`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct EventRecord {
+	pub phase: Phase,
+	pub pallet: String,
+	pub variant: String,
+	/// The SCALE encoded runtime event, starting with the pallet index.
+	pub data: Vec<u8>,
+	/// The fields of the event decoded to JSON.
+	pub fields: Value,
+	/// The encoded topics, each `hash_len` bytes long.
+	pub topics: Vec<Vec<u8>>,
+}
+
+/// Dispatch outcome and events of a single extrinsic.
+#[derive(Debug, Default)]
+pub(crate) struct ExtrinsicEvents {
+	pub outcome: Option<DispatchOutcome>,
+	pub events: Vec<ExtrinsicEvent>,
+}
+
+/// Decoded runtime metadata, kept as long as the spec version of the runtime does not change.
+#[derive(Default)]
+pub(crate) struct MetadataCache(Option<(u32, PortableRegistry, Vec<PalletMetadata<PortableForm>>)>);
+
+impl MetadataCache {
+	/// Return the type registry and pallets of the runtime with `spec_version`.
+	///
+	/// The metadata returned by `metadata` is only decoded if the cached metadata belongs to
+	/// another runtime.
+	pub fn get_or_decode(
+		&mut self,
+		spec_version: u32,
+		metadata: impl FnOnce() -> Result<OpaqueMetadata, String>,
+	) -> Result<(&PortableRegistry, &[PalletMetadata<PortableForm>]), String> {
+		if !matches!(&self.0, Some((version, ..)) if *version == spec_version) {
+			let (types, pallets) = decode_metadata(&metadata()?)?;
+			self.0 = Some((spec_version, types, pallets));
+		}
+		let (_, types, pallets) = self.0.as_ref().expect("metadata was set above; qed");
+		Ok((types, pallets))
+	}
+}
+
+/// Decode the type registry and pallets from the SCALE encoded runtime metadata.
+fn decode_metadata(
+	metadata: &[u8],
+) -> Result<(PortableRegistry, Vec<PalletMetadata<PortableForm>>), String> {
+	let prefixed = RuntimeMetadataPrefixed::decode(&mut &metadata[..]).map_err(codec_err)?;
+	match prefixed.1 {
+		RuntimeMetadata::V14(metadata) => Ok((metadata.types, metadata.pallets)),
+		_ => Err("Unsupported metadata version".into()),
+	}
+}
+
+/// Decode all event records.
+///
+/// `hash_len` is the encoded length of the topics stored alongside each event.
+pub(crate) fn decode_events(
+	types: &PortableRegistry,
+	pallets: &[PalletMetadata<PortableForm>],
+	hash_len: usize,
+	mut events: &[u8],
+) -> Result<Vec<EventRecord>, String> {
+	let input = &mut events;
+	let count = <Compact<u32>>::decode(input).map_err(codec_err)?.0;
+	let mut result = Vec::new();
+
+	for _ in 0..count {
+		let phase = Phase::decode(input).map_err(codec_err)?;
+		let start = *input;
+
+		let pallet_index = u8::decode(input).map_err(codec_err)?;
+		let variant_index = u8::decode(input).map_err(codec_err)?;
+		let pallet = pallets
+			.iter()
+			.find(|pallet| pallet.index == pallet_index)
+			.ok_or_else(|| format!("Unknown pallet index {}", pallet_index))?;
+		let event_ty = pallet
+			.event
+			.as_ref()
+			.ok_or_else(|| format!("Pallet {} has no events", pallet.name))?
+			.ty
+			.id();
+		let variant = match types.resolve(event_ty).map(|ty| ty.type_def()) {
+			Some(TypeDef::Variant(def)) => def
+				.variants()
+				.iter()
+				.find(|variant| variant.index() == variant_index)
+				.ok_or_else(|| format!("Unknown {} event index {}", pallet.name, variant_index))?,
+			_ => return Err(format!("Event type of pallet {} is not an enum", pallet.name)),
+		};
+
+		let fields = decode_fields(types, variant.fields(), input)?;
+		let data = start[..start.len() - input.len()].to_vec();
+
+		let topics = <Compact<u32>>::decode(input).map_err(codec_err)?.0 as usize;
+		let topics = (0..topics)
+			.map(|_| take_bytes(input, hash_len).map(|topic| topic.to_vec()))
+			.collect::<Result<_, _>>()?;
+
+		result.push(EventRecord {
+			phase,
+			pallet: pallet.name.clone(),
+			variant: variant.name().clone(),
+			data,
+			fields,
+			topics,
+		});
+	}
+
+	Ok(result)
+}
+
+/// Collect the events deposited while applying the extrinsic at `index`.
+///
+/// `hash_len` is the encoded length of the topics stored alongside each event.
+pub(crate) fn decode_extrinsic_events(
+	types: &PortableRegistry,
+	pallets: &[PalletMetadata<PortableForm>],
+	hash_len: usize,
+	events: &[u8],
+	index: u32,
+) -> Result<ExtrinsicEvents, String> {
+	let mut result = ExtrinsicEvents::default();
+
+	for record in decode_events(types, pallets, hash_len, events)? {
+		if record.phase != Phase::ApplyExtrinsic(index) {
+			continue
+		}
+
+		if record.pallet == SYSTEM_PALLET {
+			match record.variant.as_str() {
+				"ExtrinsicSuccess" => result.outcome = Some(DispatchOutcome::Success),
+				"ExtrinsicFailed" => result.outcome = Some(DispatchOutcome::Failed),
+				_ => {},
+			}
+		}
+
+		result.events.push(ExtrinsicEvent {
+			pallet: record.pallet,
+			variant: record.variant,
+			data: record.data.into(),
+		});
+	}
+
+	Ok(result)
+}
+
+/// Decode the given fields of a composite or enum variant.
+///
+/// Named fields are decoded to an object, a single unnamed field to its value and multiple
+/// unnamed fields to an array.
+fn decode_fields(
+	types: &PortableRegistry,
+	fields: &[Field<PortableForm>],
+	input: &mut &[u8],
+) -> Result<Value, String> {
+	if fields.is_empty() {
+		return Ok(Value::Null)
+	}
+
+	if fields.iter().all(|field| field.name().is_some()) {
+		let mut map = Map::new();
+		for field in fields {
+			let name = field.name().expect("all fields are named; qed").clone();
+			map.insert(name, decode_value(types, field.ty().id(), input)?);
+		}
+		return Ok(Value::Object(map))
+	}
+
+	let mut values = fields
+		.iter()
+		.map(|field| decode_value(types, field.ty().id(), input))
+		.collect::<Result<Vec<_>, _>>()?;
+	if values.len() == 1 {
+		Ok(values.remove(0))
+	} else {
+		Ok(Value::Array(values))
+	}
+}
+
+/// Decode one encoded value of the type `id` to JSON, advancing `input` past it.
+///
+/// Byte sequences and arrays are returned as hex strings, integers wider than 64 bits as
+/// decimal strings and enum variants without fields as their name.
+fn decode_value(types: &PortableRegistry, id: u32, input: &mut &[u8]) -> Result<Value, String> {
+	let ty = types.resolve(id).ok_or_else(|| format!("Unknown type id {}", id))?;
+	match ty.type_def() {
+		TypeDef::Composite(def) => decode_fields(types, def.fields(), input),
+		TypeDef::Variant(def) => {
+			let index = u8::decode(input).map_err(codec_err)?;
+			let variant = def
+				.variants()
+				.iter()
+				.find(|variant| variant.index() == index)
+				.ok_or_else(|| format!("Unknown variant index {} of type {}", index, id))?;
+			if variant.fields().is_empty() {
+				return Ok(Value::String(variant.name().clone()))
+			}
+
+			let mut map = Map::new();
+			map.insert(variant.name().clone(), decode_fields(types, variant.fields(), input)?);
+			Ok(Value::Object(map))
+		},
+		TypeDef::Sequence(def) => {
+			let len = <Compact<u32>>::decode(input).map_err(codec_err)?.0 as usize;
+			decode_items(types, def.type_param().id(), len, input)
+		},
+		TypeDef::Array(def) =>
+			decode_items(types, def.type_param().id(), def.len() as usize, input),
+		TypeDef::Tuple(def) => {
+			if def.fields().is_empty() {
+				return Ok(Value::Null)
+			}
+			def.fields()
+				.iter()
+				.map(|field| decode_value(types, field.id(), input))
+				.collect::<Result<_, _>>()
+				.map(Value::Array)
+		},
+		TypeDef::Primitive(def) => decode_primitive(def, input),
+		TypeDef::Compact(_) => {
+			let value = <Compact<u128>>::decode(input).map_err(codec_err)?.0;
+			Ok(number(value))
+		},
+		TypeDef::BitSequence(def) => {
+			let bits = <Compact<u32>>::decode(input).map_err(codec_err)?.0 as usize;
+			let store_bits = match types.resolve(def.bit_store_type().id()).map(|ty| ty.type_def())
+			{
+				Some(TypeDef::Primitive(TypeDefPrimitive::U8)) => 8,
+				Some(TypeDef::Primitive(TypeDefPrimitive::U16)) => 16,
+				Some(TypeDef::Primitive(TypeDefPrimitive::U32)) => 32,
+				Some(TypeDef::Primitive(TypeDefPrimitive::U64)) => 64,
+				_ => return Err(format!("Unsupported bit store type of type {}", id)),
+			};
+			let len = (bits + store_bits - 1) / store_bits * store_bits / 8;
+			take_bytes(input, len).map(|bytes| hex(bytes))
+		},
+	}
+}
+
+/// Decode `len` items of the type `id`, returning a hex string for bytes.
+fn decode_items(
+	types: &PortableRegistry,
+	id: u32,
+	len: usize,
+	input: &mut &[u8],
+) -> Result<Value, String> {
+	let is_byte = matches!(
+		types.resolve(id).map(|ty| ty.type_def()),
+		Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+	);
+	if is_byte {
+		return take_bytes(input, len).map(|bytes| hex(bytes))
+	}
+
+	(0..len)
+		.map(|_| decode_value(types, id, input))
+		.collect::<Result<_, _>>()
+		.map(Value::Array)
+}
+
+fn decode_primitive(def: &TypeDefPrimitive, input: &mut &[u8]) -> Result<Value, String> {
+	let value = match def {
+		TypeDefPrimitive::Bool => Value::Bool(bool::decode(input).map_err(codec_err)?),
+		TypeDefPrimitive::Char => {
+			let code = u32::decode(input).map_err(codec_err)?;
+			let char = char::from_u32(code).ok_or_else(|| format!("Invalid char {}", code))?;
+			Value::String(char.into())
+		},
+		TypeDefPrimitive::Str => Value::String(String::decode(input).map_err(codec_err)?),
+		TypeDefPrimitive::U8 => u8::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::U16 => u16::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::U32 => u32::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::U64 => u64::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::U128 => number(u128::decode(input).map_err(codec_err)?),
+		TypeDefPrimitive::I8 => i8::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::I16 => i16::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::I32 => i32::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::I64 => i64::decode(input).map_err(codec_err)?.into(),
+		TypeDefPrimitive::I128 =>
+			Value::String(i128::decode(input).map_err(codec_err)?.to_string()),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => hex(take_bytes(input, 32)?),
+	};
+	Ok(value)
+}
+
+/// A JSON number if `value` fits into `u64`, a decimal string otherwise.
+fn number(value: u128) -> Value {
+	u64::try_from(value)
+		.map(Value::from)
+		.unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+/// A `0x` prefixed hex string of `bytes`.
+fn hex(bytes: &[u8]) -> Value {
+	Value::String(sp_core::bytes::to_hex(bytes, false))
+}
+
+fn take_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+	if input.len() < len {
+		return Err("Unexpected end of events".into())
+	}
+	let (bytes, rest) = input.split_at(len);
+	*input = rest;
+	Ok(bytes)
+}
+
+fn codec_err(err: codec::Error) -> String {
+	err.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_metadata::PalletEventMetadata;
+	use scale_info::{meta_type, Registry, TypeInfo};
+	use serde_json::json;
+
+	#[derive(Encode, TypeInfo)]
+	enum Reason {
+		Fee,
+	}
+
+	#[derive(Encode, TypeInfo)]
+	enum BalancesEvent {
+		Transfer {
+			from: [u8; 4],
+			to: [u8; 4],
+			#[codec(compact)]
+			amount: u128,
+		},
+		Withdraw(u64, Reason, (bool, Option<u32>)),
+		Endowed(u128),
+	}
+
+	#[test]
+	fn should_decode_event_fields() {
+		let mut registry = Registry::new();
+		let pallets = vec![PalletMetadata::<PortableForm> {
+			name: "Balances".into(),
+			storage: None,
+			calls: None,
+			event: Some(PalletEventMetadata {
+				ty: registry.register_type(&meta_type::<BalancesEvent>()),
+			}),
+			constants: vec![],
+			error: None,
+			index: 3,
+		}];
+		let types: PortableRegistry = registry.into();
+
+		let event = |event: BalancesEvent| (3u8, event).encode();
+		let transfer = event(BalancesEvent::Transfer { from: [1; 4], to: [2; 4], amount: 10 });
+		let withdraw = event(BalancesEvent::Withdraw(7, Reason::Fee, (true, Some(5))));
+		let endowed = event(BalancesEvent::Endowed(u128::MAX));
+
+		let mut records = Compact(3u32).encode();
+		for (phase, event, topics) in [
+			(Phase::Initialization, &transfer, vec![[9u8; 2]]),
+			(Phase::ApplyExtrinsic(1), &withdraw, vec![]),
+			(Phase::Finalization, &endowed, vec![]),
+		] {
+			records.extend(phase.encode());
+			records.extend(event);
+			records.extend(topics.encode());
+		}
+
+		let events = decode_events(&types, &pallets, 2, &records).unwrap();
+		assert_eq!(
+			events,
+			vec![
+				EventRecord {
+					phase: Phase::Initialization,
+					pallet: "Balances".into(),
+					variant: "Transfer".into(),
+					data: transfer,
+					fields: json!({ "from": "0x01010101", "to": "0x02020202", "amount": 10 }),
+					topics: vec![vec![9, 9]],
+				},
+				EventRecord {
+					phase: Phase::ApplyExtrinsic(1),
+					pallet: "Balances".into(),
+					variant: "Withdraw".into(),
+					data: withdraw,
+					fields: json!([7, "Fee", [true, { "Some": 5 }]]),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Finalization,
+					pallet: "Balances".into(),
+					variant: "Endowed".into(),
+					data: endowed,
+					fields: json!(u128::MAX.to_string()),
+					topics: vec![],
+				},
+			]
+		);
+	}
+}
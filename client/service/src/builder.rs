@@ -21,6 +21,7 @@ use crate::{
 	client::{Client, ClientConfig},
 	config::{Configuration, KeystoreConfig, PrometheusConfig},
 	error::Error,
+	health::{self, NodeHealth},
 	metrics::MetricsService,
 	start_rpc_servers, RpcHandlers, SpawnTaskHandle, TaskManager, TransactionPoolAdapter,
 };
@@ -210,6 +211,8 @@ where
 			state_pruning: config.state_pruning.clone(),
 			source: config.database.clone(),
 			blocks_pruning: config.blocks_pruning,
+			offchain_index_pruning: config.offchain_index_pruning,
+			max_pinned_blocks: config.max_pinned_blocks,
 		};
 
 		let backend = new_db_backend(db_config)?;
@@ -218,7 +221,8 @@ where
 			config.execution_strategies.clone(),
 			Some(keystore_container.sync_keystore()),
 			sc_offchain::OffchainDb::factory_from_backend(&*backend),
-		);
+		)
+		.with_timeouts(config.execution_timeouts);
 
 		let wasm_runtime_substitutes = config
 			.chain_spec
@@ -380,10 +384,19 @@ pub fn build_offchain_workers<TBl, TCl>(
 ) -> Option<Arc<sc_offchain::OffchainWorkers<TCl, TBl>>>
 where
 	TBl: BlockT,
-	TCl: Send + Sync + ProvideRuntimeApi<TBl> + BlockchainEvents<TBl> + 'static,
+	TCl:
+		Send + Sync + ProvideRuntimeApi<TBl> + BlockchainEvents<TBl> + HeaderBackend<TBl> + 'static,
 	<TCl as ProvideRuntimeApi<TBl>>::Api: sc_offchain::OffchainWorkerApi<TBl>,
 {
-	let offchain_workers = Some(Arc::new(sc_offchain::OffchainWorkers::new(client.clone())));
+	let offchain_workers = Some(Arc::new(sc_offchain::OffchainWorkers::new_with_options(
+		client.clone(),
+		sc_offchain::OffchainWorkerOptions {
+			enable_http_requests: true,
+			http_config: config.offchain_worker.http.clone(),
+			scheduling: config.offchain_worker.scheduling.clone(),
+			prometheus_registry: config.prometheus_registry().cloned(),
+		},
+	)));
 
 	// Inform the offchain worker about new imported blocks
 	if let Some(offchain) = offchain_workers.clone() {
@@ -488,10 +501,18 @@ where
 		if let Some(PrometheusConfig { port, registry }) = config.prometheus_config.clone() {
 			// Set static metrics.
 			let metrics = MetricsService::with_prometheus(telemetry, &registry, &config)?;
+
+			// Health endpoints served alongside the metrics.
+			let health = Arc::new(NodeHealth::new(config.health.clone()));
+			spawn_handle.spawn(
+				"health-updates",
+				None,
+				health::run(health.clone(), client.clone(), network.clone()),
+			);
 			spawn_handle.spawn(
 				"prometheus-endpoint",
 				None,
-				prometheus_endpoint::init_prometheus(port, registry).map(drop),
+				prometheus_endpoint::init_prometheus_with_health(port, registry, health).map(drop),
 			);
 
 			metrics
@@ -514,6 +535,7 @@ where
 			deny_unsafe,
 			task_manager.spawn_handle(),
 			client.clone(),
+			backend.clone(),
 			transaction_pool.clone(),
 			keystore.clone(),
 			system_rpc_tx.clone(),
@@ -612,6 +634,7 @@ fn gen_rpc_module<TBl, TBackend, TCl, TRpc, TExPool>(
 	deny_unsafe: DenyUnsafe,
 	spawn_handle: SpawnTaskHandle,
 	client: Arc<TCl>,
+	backend: Arc<TBackend>,
 	transaction_pool: Arc<TExPool>,
 	keystore: SyncCryptoStorePtr,
 	system_rpc_tx: TracingUnboundedSender<sc_rpc::system::Request<TBl>>,
@@ -664,6 +687,28 @@ where
 		(chain, state, child_state)
 	};
 
+	let genesis_hash = client.info().genesis_hash;
+	let chain_head = sc_rpc_spec_v2::chain_head::ChainHead::new(
+		client.clone(),
+		backend.clone(),
+		task_executor.clone(),
+		genesis_hash,
+		// Keep the blocks pinned by the follow subscriptions within the limit of the backend.
+		sc_rpc_spec_v2::chain_head::SubscriptionLimits {
+			global_max_pinned_blocks: config.max_pinned_blocks,
+			..Default::default()
+		},
+	)
+	.into_rpc();
+	let archive =
+		sc_rpc_spec_v2::archive::Archive::new(client.clone(), backend, genesis_hash).into_rpc();
+	let transaction = sc_rpc_spec_v2::transaction::Transaction::new(
+		client.clone(),
+		transaction_pool.clone(),
+		task_executor.clone(),
+	)
+	.into_rpc();
+
 	let author = sc_rpc::author::Author::new(
 		client.clone(),
 		transaction_pool,
@@ -676,9 +721,12 @@ where
 	let system = sc_rpc::system::System::new(system_info, system_rpc_tx, deny_unsafe).into_rpc();
 
 	if let Some(storage) = offchain_storage {
+		let offchain_index =
+			sc_rpc::offchain::OffchainIndex::<_, TBl>::new(storage.clone(), deny_unsafe).into_rpc();
 		let offchain = sc_rpc::offchain::Offchain::new(storage, deny_unsafe).into_rpc();
 
 		rpc_api.merge(offchain).map_err(|e| Error::Application(e.into()))?;
+		rpc_api.merge(offchain_index).map_err(|e| Error::Application(e.into()))?;
 	}
 
 	rpc_api.merge(chain).map_err(|e| Error::Application(e.into()))?;
@@ -686,6 +734,9 @@ where
 	rpc_api.merge(system).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(state).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(child_state).map_err(|e| Error::Application(e.into()))?;
+	rpc_api.merge(chain_head).map_err(|e| Error::Application(e.into()))?;
+	rpc_api.merge(archive).map_err(|e| Error::Application(e.into()))?;
+	rpc_api.merge(transaction).map_err(|e| Error::Application(e.into()))?;
 	// Additional [`RpcModule`]s defined in the node to fit the specific blockchain
 	let extra_rpcs = rpc_builder(deny_unsafe, task_executor.clone())?;
 	rpc_api.merge(extra_rpcs).map_err(|e| Error::Application(e.into()))?;
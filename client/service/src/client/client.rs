@@ -77,9 +77,9 @@ use sp_runtime::{
 };
 use sp_state_machine::{
 	prove_child_read, prove_range_read_with_child_with_size, prove_read,
-	read_range_proof_check_with_child_on_proving_backend, Backend as StateBackend,
+	read_range_proof_check_with_child_on_proving_backend, storage_diff, Backend as StateBackend,
 	ChildStorageCollection, KeyValueStates, KeyValueStorageLevel, StorageCollection,
-	MAX_NESTED_TRIE_DEPTH,
+	StorageDiffEntry, MAX_NESTED_TRIE_DEPTH,
 };
 use sp_trie::{CompactProof, StorageProof};
 use std::{
@@ -1510,6 +1510,26 @@ where
 			.child_storage_hash(child_info, &key.0)
 			.map_err(|e| sp_blockchain::Error::from_state(Box::new(e)))
 	}
+
+	fn storage_diff(
+		&self,
+		from: &BlockId<Block>,
+		to: &BlockId<Block>,
+		prefix: Option<&StorageKey>,
+		start_at: Option<(Option<&ChildInfo>, &StorageKey)>,
+		count: usize,
+	) -> sp_blockchain::Result<Vec<StorageDiffEntry>> {
+		let from = self.state_at(from)?;
+		let to = self.state_at(to)?;
+		storage_diff(
+			&from,
+			&to,
+			prefix.map(|prefix| prefix.0.as_slice()),
+			start_at.map(|(child_info, key)| (child_info, key.0.as_slice())),
+			count,
+		)
+		.map_err(sp_blockchain::Error::from_state)
+	}
 }
 
 impl<B, E, Block, RA> HeaderMetadata<Block> for Client<B, E, Block, RA>
@@ -18,8 +18,13 @@
 
 //! Service configuration.
 
-pub use sc_client_api::execution_extensions::{ExecutionStrategies, ExecutionStrategy};
-pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, PruningMode};
+pub use sc_client_api::execution_extensions::{
+	ExecutionStrategies, ExecutionStrategy, ExecutionTimeouts,
+};
+pub use sc_client_db::{
+	BlocksPruning, Database, DatabaseSource, OffchainIndexPruning, PruningMode,
+	DEFAULT_MAX_PINNED_BLOCKS,
+};
 pub use sc_executor::WasmExecutionMethod;
 #[cfg(feature = "wasmtime")]
 pub use sc_executor::WasmtimeInstantiationStrategy;
@@ -35,16 +40,20 @@ pub use sc_network_common::{
 		IncomingRequest, OutgoingResponse, ProtocolConfig as RequestResponseConfig,
 	},
 };
+pub use sc_offchain::{OffchainHttpConfig, OffchainWorkerSchedule, OffchainWorkerScheduling};
 
 use prometheus_endpoint::Registry;
 use sc_chain_spec::ChainSpec;
+pub use sc_rpc_server::RpcLimits;
 pub use sc_telemetry::TelemetryEndpoints;
 pub use sc_transaction_pool::Options as TransactionPoolOptions;
 use sp_core::crypto::SecretString;
 use std::{
 	io, iter,
 	net::SocketAddr,
+	num::NonZeroUsize,
 	path::{Path, PathBuf},
+	time::Duration,
 };
 use tempfile::TempDir;
 
@@ -79,6 +88,10 @@ pub struct Configuration {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Retention policy of the data written through offchain indexing.
+	pub offchain_index_pruning: OffchainIndexPruning,
+	/// Maximum number of distinct blocks that can be pinned at the same time.
+	pub max_pinned_blocks: usize,
 	/// Chain configuration.
 	pub chain_spec: Box<dyn ChainSpec>,
 	/// Wasm execution method.
@@ -89,6 +102,12 @@ pub struct Configuration {
 	pub wasm_runtime_overrides: Option<PathBuf>,
 	/// Execution strategies.
 	pub execution_strategies: ExecutionStrategies,
+	/// Number of background tasks verifying blocks ahead of their import.
+	///
+	/// `None` verifies every block right before importing it.
+	pub verification_workers: Option<NonZeroUsize>,
+	/// Timeouts of runtime calls made by offchain workers and RPC.
+	pub execution_timeouts: ExecutionTimeouts,
 	/// RPC over HTTP binding address. `None` if disabled.
 	pub rpc_http: Option<SocketAddr>,
 	/// RPC over Websockets binding address. `None` if disabled.
@@ -115,10 +134,14 @@ pub struct Configuration {
 	///
 	/// Default: 1024.
 	pub rpc_max_subs_per_conn: Option<usize>,
+	/// Rate limit, method costs and allowed methods of the RPC servers.
+	pub rpc_limits: RpcLimits,
 	/// Maximum size of the output buffer capacity for websocket connections.
 	pub ws_max_out_buffer_capacity: Option<usize>,
 	/// Prometheus endpoint configuration. `None` if disabled.
 	pub prometheus_config: Option<PrometheusConfig>,
+	/// Thresholds of the health endpoints served alongside the Prometheus metrics.
+	pub health: HealthConfig,
 	/// Telemetry service URL. `None` if disabled.
 	pub telemetry_endpoints: Option<TelemetryEndpoints>,
 	/// The default number of 64KB pages to allocate for Wasm execution
@@ -129,6 +152,8 @@ pub struct Configuration {
 	pub force_authoring: bool,
 	/// Disable GRANDPA when running in validator mode
 	pub disable_grandpa: bool,
+	/// Period (in blocks) at which GRANDPA justifications are archived. `None` if disabled.
+	pub grandpa_justification_archive_period: Option<u32>,
 	/// Development key seed.
 	///
 	/// When running in development mode, the seed will be used to generate authority keys by the
@@ -193,6 +218,10 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// Configuration of the HTTP client used by offchain workers.
+	pub http: OffchainHttpConfig,
+	/// Scheduling of the offchain workers.
+	pub scheduling: OffchainWorkerScheduling,
 }
 
 /// Configuration of the Prometheus endpoint.
@@ -216,6 +245,22 @@ impl PrometheusConfig {
 	}
 }
 
+/// Thresholds of the `/health` and `/health/readiness` endpoints.
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+	/// Minimum number of connected peers for the node to be healthy.
+	pub min_peers: usize,
+	/// Maximum time since the last imported block for the node to be healthy, `None` to not
+	/// check the block imports.
+	pub max_block_age: Option<Duration>,
+}
+
+impl Default for HealthConfig {
+	fn default() -> Self {
+		Self { min_peers: 1, max_block_age: Some(Duration::from_secs(60)) }
+	}
+}
+
 impl Configuration {
 	/// Returns a string displaying the node role.
 	pub fn display_role(&self) -> String {
@@ -242,6 +287,14 @@ impl Configuration {
 		};
 		ProtocolId::from(protocol_id_full)
 	}
+
+	/// Returns the directory in which the executor can cache compiled runtimes, if the node has a
+	/// base path.
+	pub fn runtime_cache_path(&self) -> Option<PathBuf> {
+		self.base_path
+			.as_ref()
+			.map(|base_path| base_path.config_dir(self.chain_spec.id()).join("runtime-cache"))
+	}
 }
 
 /// Available RPC methods.
@@ -0,0 +1,198 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Health of the node, served by the Prometheus endpoint.
+
+use crate::config::HealthConfig;
+use futures::{FutureExt, StreamExt};
+use futures_timer::Delay;
+use parking_lot::Mutex;
+use prometheus_endpoint::HealthCheck;
+use sc_client_api::BlockchainEvents;
+use sc_network_common::{service::NetworkStatusProvider, sync::SyncState};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Interval of the network status and best block updates.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+struct State {
+	peers: usize,
+	is_major_syncing: bool,
+	last_import: Instant,
+}
+
+/// Health of the node checked against the thresholds of [`HealthConfig`].
+///
+/// Kept up to date by [`run`].
+pub(crate) struct NodeHealth {
+	config: HealthConfig,
+	state: Mutex<State>,
+}
+
+impl NodeHealth {
+	/// Create a new instance, counting the node startup as the last block import.
+	pub(crate) fn new(config: HealthConfig) -> Self {
+		Self {
+			config,
+			state: Mutex::new(State {
+				peers: 0,
+				is_major_syncing: false,
+				last_import: Instant::now(),
+			}),
+		}
+	}
+}
+
+impl NodeHealth {
+	/// Check the health of the node at `now`.
+	fn health_at(&self, now: Instant) -> Result<(), String> {
+		let state = self.state.lock();
+
+		if state.peers < self.config.min_peers {
+			return Err(format!(
+				"Connected to {} peers, expected at least {}",
+				state.peers, self.config.min_peers,
+			))
+		}
+
+		if let Some(max_block_age) = self.config.max_block_age {
+			let block_age = now.saturating_duration_since(state.last_import);
+			if block_age > max_block_age {
+				return Err(format!(
+					"Last block imported {}s ago, expected at most {}s",
+					block_age.as_secs(),
+					max_block_age.as_secs(),
+				))
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Check the readiness of the node at `now`.
+	fn readiness_at(&self, now: Instant) -> Result<(), String> {
+		self.health_at(now)?;
+
+		if self.state.lock().is_major_syncing {
+			return Err("Node is syncing".into())
+		}
+
+		Ok(())
+	}
+}
+
+impl HealthCheck for NodeHealth {
+	fn health(&self) -> Result<(), String> {
+		self.health_at(Instant::now())
+	}
+
+	fn readiness(&self) -> Result<(), String> {
+		self.readiness_at(Instant::now())
+	}
+}
+
+/// Returns a never-ending `Future` that updates `health` with the block imports of `client`
+/// and the status of `network`.
+///
+/// Blocks imported during the initial sync are not notified, so the best block of `client` is
+/// polled as well.
+pub(crate) async fn run<TBl, TCl, TNet>(health: Arc<NodeHealth>, client: Arc<TCl>, network: TNet)
+where
+	TBl: BlockT,
+	TCl: BlockchainEvents<TBl> + HeaderBackend<TBl>,
+	TNet: NetworkStatusProvider<TBl>,
+{
+	let mut imports = client.import_notification_stream().fuse();
+	let mut timer = Delay::new(Duration::from_secs(0)).fuse();
+	let mut best_number = client.info().best_number;
+
+	loop {
+		futures::select! {
+			notification = imports.next() => match notification {
+				Some(_) => health.state.lock().last_import = Instant::now(),
+				None => return,
+			},
+			_ = timer => {
+				let info = client.info();
+				if info.best_number != best_number {
+					best_number = info.best_number;
+					health.state.lock().last_import = Instant::now();
+				}
+
+				if let Ok(status) = network.status().await {
+					let mut state = health.state.lock();
+					state.peers = status.num_connected_peers;
+					state.is_major_syncing = matches!(status.sync_state, SyncState::Downloading);
+				}
+				timer = Delay::new(UPDATE_INTERVAL).fuse();
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn node_health(min_peers: usize, max_block_age: Option<Duration>) -> NodeHealth {
+		let health = NodeHealth::new(HealthConfig { min_peers, max_block_age });
+		health.state.lock().peers = 2;
+		health
+	}
+
+	#[test]
+	fn unhealthy_without_enough_peers() {
+		let health = node_health(3, None);
+		assert!(health.health().is_err());
+		assert!(health.readiness().is_err());
+
+		health.state.lock().peers = 3;
+		assert!(health.health().is_ok());
+		assert!(health.readiness().is_ok());
+	}
+
+	#[test]
+	fn unhealthy_without_recent_block_imports() {
+		let health = node_health(1, Some(Duration::from_secs(60)));
+		let later = health.state.lock().last_import + Duration::from_secs(61);
+		assert!(health.health().is_ok());
+		assert!(health.health_at(later).is_err());
+		assert!(health.readiness_at(later).is_err());
+
+		// The block imports are not checked without a maximum block age.
+		let health = node_health(1, None);
+		let later = health.state.lock().last_import + Duration::from_secs(61);
+		assert!(health.health_at(later).is_ok());
+	}
+
+	#[test]
+	fn healthy_but_not_ready_while_syncing() {
+		let health = node_health(1, Some(Duration::from_secs(60)));
+		health.state.lock().is_major_syncing = true;
+		assert!(health.health().is_ok());
+		assert!(health.readiness().is_err());
+
+		health.state.lock().is_major_syncing = false;
+		assert!(health.readiness().is_ok());
+	}
+}
@@ -31,6 +31,7 @@ mod builder;
 pub mod client;
 #[cfg(not(feature = "test-helpers"))]
 mod client;
+mod health;
 mod metrics;
 mod task_manager;
 
@@ -62,7 +63,8 @@ pub use self::{
 	error::Error,
 };
 pub use config::{
-	BasePath, BlocksPruning, Configuration, DatabaseSource, PruningMode, Role, RpcMethods, TaskType,
+	BasePath, BlocksPruning, Configuration, DatabaseSource, HealthConfig, OffchainIndexPruning,
+	PruningMode, Role, RpcMethods, TaskType,
 };
 pub use sc_chain_spec::{
 	ChainSpec, ChainType, Extension as ChainSpecExtension, GenericChainSpec, NoExtension,
@@ -359,6 +361,7 @@ where
 		max_request_size,
 		http_max_response_size,
 		metrics.clone(),
+		&config.rpc_limits,
 		gen_rpc_module(deny_unsafe(ws_addr, &config.rpc_methods))?,
 		config.tokio_handle.clone(),
 	);
@@ -375,6 +378,7 @@ where
 		config.rpc_cors.as_ref(),
 		ws_config,
 		metrics,
+		&config.rpc_limits,
 		gen_rpc_module(deny_unsafe(http_addr, &config.rpc_methods))?,
 		config.tokio_handle.clone(),
 		rpc_id_provider,
@@ -44,6 +44,7 @@ struct PrometheusMetrics {
 	database_cache: Gauge<U64>,
 	state_cache: Gauge<U64>,
 	state_db: GaugeVec<U64>,
+	pinned_blocks: Gauge<U64>,
 }
 
 impl PrometheusMetrics {
@@ -124,6 +125,10 @@ impl PrometheusMetrics {
 				)?,
 				registry,
 			)?,
+			pinned_blocks: register(
+				Gauge::new("substrate_pinned_blocks", "Number of blocks pinned by the backend")?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -266,6 +271,7 @@ impl MetricsService {
 					.state_db
 					.with_label_values(&["pinned"])
 					.set(info.memory.state_db.pinned.as_bytes() as u64);
+				metrics.pinned_blocks.set(info.memory.pinned_blocks);
 			}
 		}
 
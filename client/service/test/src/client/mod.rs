@@ -23,7 +23,10 @@ use sc_block_builder::BlockBuilderProvider;
 use sc_client_api::{
 	in_mem, BlockBackend, BlockchainEvents, FinalityNotifications, StorageProvider,
 };
-use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode};
+use sc_client_db::{
+	Backend, BlocksPruning, DatabaseSettings, DatabaseSource, OffchainIndexPruning, PruningMode,
+	DEFAULT_MAX_PINNED_BLOCKS,
+};
 use sc_consensus::{
 	BlockCheckParams, BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult,
 };
@@ -1200,6 +1203,8 @@ fn doesnt_import_blocks_that_revert_finality() {
 				trie_cache_maximum_size: Some(1 << 20),
 				state_pruning: Some(PruningMode::ArchiveAll),
 				blocks_pruning: BlocksPruning::All,
+				offchain_index_pruning: OffchainIndexPruning::All,
+				max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 			},
 			u64::MAX,
@@ -1426,6 +1431,8 @@ fn returns_status_for_pruned_blocks() {
 				trie_cache_maximum_size: Some(1 << 20),
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				blocks_pruning: BlocksPruning::All,
+				offchain_index_pruning: OffchainIndexPruning::All,
+				max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 			},
 			u64::MAX,
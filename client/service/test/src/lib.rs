@@ -32,9 +32,9 @@ use sc_network_common::{
 };
 use sc_service::{
 	client::Client,
-	config::{BasePath, DatabaseSource, KeystoreConfig},
-	BlocksPruning, ChainSpecExtension, Configuration, Error, GenericChainSpec, Role,
-	RuntimeGenesis, SpawnTaskHandle, TaskManager,
+	config::{BasePath, DatabaseSource, KeystoreConfig, DEFAULT_MAX_PINNED_BLOCKS},
+	BlocksPruning, ChainSpecExtension, Configuration, Error, GenericChainSpec,
+	OffchainIndexPruning, Role, RuntimeGenesis, SpawnTaskHandle, TaskManager,
 };
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::BlockId;
@@ -238,10 +238,14 @@ fn node_config<
 		trie_cache_maximum_size: Some(16 * 1024 * 1024),
 		state_pruning: Default::default(),
 		blocks_pruning: BlocksPruning::All,
+		offchain_index_pruning: OffchainIndexPruning::All,
+		max_pinned_blocks: DEFAULT_MAX_PINNED_BLOCKS,
 		chain_spec: Box::new((*spec).clone()),
 		wasm_method: sc_service::config::WasmExecutionMethod::Interpreted,
 		wasm_runtime_overrides: Default::default(),
 		execution_strategies: Default::default(),
+		verification_workers: None,
+		execution_timeouts: Default::default(),
 		rpc_http: None,
 		rpc_ipc: None,
 		rpc_ws: None,
@@ -253,13 +257,16 @@ fn node_config<
 		rpc_max_response_size: None,
 		rpc_id_provider: None,
 		rpc_max_subs_per_conn: None,
+		rpc_limits: Default::default(),
 		ws_max_out_buffer_capacity: None,
 		prometheus_config: None,
+		health: Default::default(),
 		telemetry_endpoints: None,
 		default_heap_pages: None,
 		offchain_worker: Default::default(),
 		force_authoring: false,
 		disable_grandpa: false,
+		grandpa_justification_archive_period: None,
 		dev_key_seed: key_seed,
 		tracing_targets: None,
 		tracing_receiver: Default::default(),
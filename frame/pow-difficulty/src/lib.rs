@@ -0,0 +1,173 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # PoW Difficulty Pallet
+//!
+//! The PoW Difficulty pallet implements a moving average difficulty adjustment for
+//! proof-of-work chains.
+//!
+//! At the end of every block, the timestamp and difficulty of the block are recorded in a window
+//! of the last [`Config::AdjustmentWindow`] blocks. The difficulty of the next block is the
+//! average difficulty of the window, scaled by the ratio of the expected time to produce the
+//! window, based on [`Config::TargetBlockTime`], to the time it actually took. The ratio is
+//! clamped by [`Config::MaxAdjustmentFactor`], so that a single outlier can't swing the difficulty
+//! arbitrarily, and the result never goes below [`Config::MinDifficulty`].
+//!
+//! ## Usage
+//!
+//! The pallet relies on `pallet_timestamp` to learn the time of every block. The difficulty of
+//! the next block is available through [`Pallet::difficulty`], which should be returned from the
+//! runtime's `sp_consensus_pow::DifficultyApi` implementation:
+//!
+//! ```ignore
+//! impl sp_consensus_pow::DifficultyApi<Block, pallet_pow_difficulty::Difficulty> for Runtime {
+//! 	fn difficulty() -> pallet_pow_difficulty::Difficulty {
+//! 		PowDifficulty::difficulty()
+//! 	}
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_core::U256;
+use sp_runtime::traits::{SaturatedConversion, Saturating};
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+pub use pallet::*;
+
+/// Type of the proof-of-work difficulty.
+pub type Difficulty = U256;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_timestamp::Config {
+		/// The number of past blocks the moving average is computed over.
+		#[pallet::constant]
+		type AdjustmentWindow: Get<u32>;
+
+		/// The time it should take to produce a block.
+		#[pallet::constant]
+		type TargetBlockTime: Get<Self::Moment>;
+
+		/// The maximum factor by which the next difficulty may grow or shrink compared to the
+		/// average difficulty of the window.
+		#[pallet::constant]
+		type MaxAdjustmentFactor: Get<u32>;
+
+		/// The difficulty never drops below this value.
+		#[pallet::constant]
+		type MinDifficulty: Get<Difficulty>;
+	}
+
+	/// The difficulty the next block has to be mined with.
+	#[pallet::storage]
+	#[pallet::getter(fn difficulty)]
+	pub type CurrentDifficulty<T: Config> = StorageValue<_, Difficulty, ValueQuery>;
+
+	/// Timestamps and difficulties of the last `AdjustmentWindow` blocks, oldest first.
+	#[pallet::storage]
+	#[pallet::getter(fn past_blocks)]
+	pub type PastBlocks<T: Config> =
+		StorageValue<_, BoundedVec<(T::Moment, Difficulty), T::AdjustmentWindow>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig {
+		/// The difficulty of the first block.
+		pub initial_difficulty: Difficulty,
+	}
+
+	#[cfg(feature = "std")]
+	impl Default for GenesisConfig {
+		fn default() -> Self {
+			Self { initial_difficulty: U256::one() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			CurrentDifficulty::<T>::put(self.initial_difficulty.max(T::MinDifficulty::get()));
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+			// Weight of `on_finalize`.
+			T::DbWeight::get().reads_writes(3, 2)
+		}
+
+		fn on_finalize(_n: BlockNumberFor<T>) {
+			let now = pallet_timestamp::Pallet::<T>::now();
+			let difficulty = CurrentDifficulty::<T>::get();
+
+			let past_blocks = PastBlocks::<T>::mutate(|past_blocks| {
+				if past_blocks.is_full() && !past_blocks.is_empty() {
+					past_blocks.remove(0);
+				}
+				// Can't fail, we made room above unless the window is empty.
+				let _ = past_blocks.try_push((now, difficulty));
+				past_blocks.clone()
+			});
+
+			CurrentDifficulty::<T>::put(Self::next_difficulty(&past_blocks, difficulty));
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Compute the difficulty following the given window of past blocks.
+	///
+	/// Returns `current` if the window doesn't contain at least two blocks.
+	pub fn next_difficulty(
+		past_blocks: &[(T::Moment, Difficulty)],
+		current: Difficulty,
+	) -> Difficulty {
+		let (first, last) = match (past_blocks.first(), past_blocks.last()) {
+			(Some(first), Some(last)) if past_blocks.len() >= 2 => (first.0, last.0),
+			_ => return current,
+		};
+		let intervals = past_blocks.len() as u64 - 1;
+
+		let total = past_blocks.iter().fold(U256::zero(), |acc, (_, d)| acc.saturating_add(*d));
+		let average = total / U256::from(past_blocks.len());
+
+		let target = T::TargetBlockTime::get().saturated_into::<u64>().max(1);
+		let expected = target.saturating_mul(intervals);
+		let factor = u64::from(T::MaxAdjustmentFactor::get().max(1));
+		let actual = last
+			.saturating_sub(first)
+			.saturated_into::<u64>()
+			.clamp((expected / factor).max(1), expected.saturating_mul(factor));
+
+		(average.saturating_mul(U256::from(expected)) / U256::from(actual))
+			.max(T::MinDifficulty::get())
+	}
+}
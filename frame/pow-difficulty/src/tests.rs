@@ -0,0 +1,99 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020-2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the PoW Difficulty pallet.
+
+use crate::{mock::*, PastBlocks};
+use frame_support::traits::Hooks;
+use sp_core::U256;
+
+/// Finalize `blocks` blocks, each produced `block_time` after the previous one.
+fn produce_blocks(blocks: u64, block_time: u64) {
+	for _ in 0..blocks {
+		let number = System::block_number() + 1;
+		System::set_block_number(number);
+		Timestamp::set_timestamp(Timestamp::now() + block_time);
+		PowDifficulty::on_finalize(number);
+	}
+}
+
+#[test]
+fn genesis_sets_initial_difficulty() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(PowDifficulty::difficulty(), U256::from(INITIAL_DIFFICULTY));
+		assert!(PowDifficulty::past_blocks().is_empty());
+	});
+}
+
+#[test]
+fn difficulty_is_stable_on_target() {
+	new_test_ext().execute_with(|| {
+		produce_blocks(25, TARGET_BLOCK_TIME);
+		assert_eq!(PowDifficulty::difficulty(), U256::from(INITIAL_DIFFICULTY));
+	});
+}
+
+#[test]
+fn window_keeps_last_blocks() {
+	new_test_ext().execute_with(|| {
+		produce_blocks(15, TARGET_BLOCK_TIME);
+		let past_blocks = PastBlocks::<Test>::get();
+		assert_eq!(past_blocks.len(), 10);
+		assert_eq!(past_blocks.first().unwrap().0, 6 * TARGET_BLOCK_TIME);
+		assert_eq!(past_blocks.last().unwrap().0, 15 * TARGET_BLOCK_TIME);
+	});
+}
+
+#[test]
+fn difficulty_increases_when_blocks_are_fast() {
+	new_test_ext().execute_with(|| {
+		produce_blocks(2, TARGET_BLOCK_TIME / 2);
+		assert_eq!(PowDifficulty::difficulty(), U256::from(2 * INITIAL_DIFFICULTY));
+
+		produce_blocks(20, TARGET_BLOCK_TIME / 2);
+		assert!(PowDifficulty::difficulty() > U256::from(2 * INITIAL_DIFFICULTY));
+	});
+}
+
+#[test]
+fn difficulty_decreases_when_blocks_are_slow() {
+	new_test_ext().execute_with(|| {
+		produce_blocks(2, TARGET_BLOCK_TIME * 2);
+		assert_eq!(PowDifficulty::difficulty(), U256::from(INITIAL_DIFFICULTY / 2));
+	});
+}
+
+#[test]
+fn adjustment_is_clamped() {
+	new_test_ext().execute_with(|| {
+		produce_blocks(2, TARGET_BLOCK_TIME / 100);
+		assert_eq!(PowDifficulty::difficulty(), U256::from(4 * INITIAL_DIFFICULTY));
+	});
+
+	new_test_ext().execute_with(|| {
+		produce_blocks(2, TARGET_BLOCK_TIME * 100);
+		assert_eq!(PowDifficulty::difficulty(), U256::from(INITIAL_DIFFICULTY / 4));
+	});
+}
+
+#[test]
+fn difficulty_never_drops_below_minimum() {
+	new_test_ext().execute_with(|| {
+		produce_blocks(50, TARGET_BLOCK_TIME * 100);
+		assert_eq!(PowDifficulty::difficulty(), MinDifficulty::get());
+	});
+}
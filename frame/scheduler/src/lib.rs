@@ -110,6 +110,15 @@ pub struct ScheduledV3<Call, BlockNumber, PalletsOrigin, AccountId> {
 	_phantom: PhantomData<AccountId>,
 }
 
+impl<Call, BlockNumber, PalletsOrigin, AccountId>
+	ScheduledV3<Call, BlockNumber, PalletsOrigin, AccountId>
+{
+	/// The call to be dispatched.
+	pub fn call(&self) -> &Call {
+		&self.call
+	}
+}
+
 use crate::ScheduledV3 as ScheduledV2;
 
 pub type ScheduledV2Of<T> = ScheduledV3<
@@ -23,6 +23,23 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
+use sp_runtime::{traits::Block as BlockT, RuntimeDebug};
+use sp_std::vec::Vec;
+
+/// Effects of a dry run reported by the [`DryRunApi`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct DryRunEffects<Balance> {
+	/// SCALE encoded result of the dry run.
+	pub result: Vec<u8>,
+	/// SCALE encoded events emitted during the dry run.
+	pub events: Vec<u8>,
+	/// Weight actually consumed by the dry run.
+	pub weight: u64,
+	/// Fee paid for the dry run of an extrinsic, or that would be paid for the dry run of a call.
+	pub fee: Balance,
+}
+
 sp_api::decl_runtime_apis! {
 	/// The API to query account nonce (aka transaction index).
 	pub trait AccountNonceApi<AccountId, Index> where
@@ -32,4 +49,24 @@ sp_api::decl_runtime_apis! {
 		/// Get current account nonce of given `AccountId`.
 		fn account_nonce(account: AccountId) -> Index;
 	}
+
+	/// The API to apply extrinsics and dispatch calls without persisting their effects.
+	///
+	/// Both functions are meant to be executed on top of the state of a block, the events
+	/// emitted before the dry run must not be reported.
+	pub trait DryRunApi<AccountId, Balance> where
+		AccountId: codec::Codec,
+		Balance: codec::Codec,
+	{
+		/// Apply `extrinsic` like `BlockBuilder::apply_extrinsic`.
+		///
+		/// The result is a SCALE encoded `ApplyExtrinsicResult`.
+		fn dry_run_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> DryRunEffects<Balance>;
+
+		/// Dispatch the SCALE encoded runtime `call` with a signed origin of `origin`.
+		///
+		/// Signature, nonce and fee checks are skipped. The result is a SCALE encoded
+		/// `DispatchResultWithPostInfo`.
+		fn dry_run_call(origin: AccountId, call: Vec<u8>) -> DryRunEffects<Balance>;
+	}
 }
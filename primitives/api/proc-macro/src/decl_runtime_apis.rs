@@ -37,8 +37,8 @@ use syn::{
 	parse_macro_input, parse_quote,
 	spanned::Spanned,
 	visit::{self, Visit},
-	Attribute, FnArg, GenericParam, Generics, Ident, ItemTrait, Lit, Meta, NestedMeta, TraitBound,
-	TraitItem, TraitItemMethod,
+	Attribute, FnArg, GenericParam, Generics, Ident, ItemTrait, Lit, Meta, NestedMeta, Pat,
+	TraitBound, TraitItem, TraitItemMethod,
 };
 
 use std::collections::{BTreeMap, HashMap};
@@ -211,6 +211,63 @@ fn parse_renamed_attribute(renamed: &Attribute) -> Result<(String, u32)> {
 	}
 }
 
+/// Returns the documentation lines of the given attributes.
+fn get_doc_literals(attrs: &[Attribute]) -> Vec<Lit> {
+	attrs
+		.iter()
+		.filter(|attr| attr.path.is_ident("doc"))
+		.filter_map(|attr| match attr.parse_meta() {
+			Ok(Meta::NameValue(meta)) => Some(meta.lit),
+			_ => None,
+		})
+		.collect()
+}
+
+/// Generates the `RuntimeApiDecl` of the given trait from which `impl_runtime_apis!` generates the
+/// metadata of the implemented runtime api.
+///
+/// Only generated if the `metadata` feature of `sp-api` is enabled.
+fn generate_runtime_api_decl_metadata(
+	decl: &ItemTrait,
+	methods: &[TraitItemMethod],
+) -> TokenStream {
+	let c = generate_crate_access(HIDDEN_INCLUDES_ID);
+	let name = decl.ident.to_string();
+	let docs = get_doc_literals(&decl.attrs);
+
+	let methods = methods.iter().map(|method| {
+		let name = method.sig.ident.to_string();
+		let params = method.sig.inputs.iter().filter_map(|input| match input {
+			FnArg::Typed(arg) => Some(match &*arg.pat {
+				Pat::Ident(pat) => pat.ident.to_string(),
+				_ => "_".into(),
+			}),
+			FnArg::Receiver(_) => None,
+		});
+		let docs = get_doc_literals(&method.attrs);
+
+		quote!(
+			#c::metadata::RuntimeApiMethodDecl {
+				name: #name,
+				params: &[ #( #params ),* ],
+				docs: &[ #( #docs ),* ],
+			}
+		)
+	});
+
+	quote!(
+		#c::__if_metadata! {
+			{
+				pub const METADATA: #c::metadata::RuntimeApiDecl = #c::metadata::RuntimeApiDecl {
+					name: #name,
+					docs: &[ #( #docs ),* ],
+					methods: &[ #( #methods ),* ],
+				};
+			} else {}
+		}
+	)
+}
+
 /// Generate the declaration of the trait for the runtime.
 fn generate_runtime_decls(decls: &[ItemTrait]) -> Result<TokenStream> {
 	let mut result = Vec::new();
@@ -228,6 +285,7 @@ fn generate_runtime_decls(decls: &[ItemTrait]) -> Result<TokenStream> {
 		let trait_api_version = get_api_version(&found_attributes)?;
 
 		let mut methods_by_version: BTreeMap<u64, Vec<TraitItemMethod>> = BTreeMap::new();
+		let mut metadata_methods = Vec::new();
 
 		// Process the items in the declaration. The filter_map function below does a lot of stuff
 		// because the method attributes are stripped at this point
@@ -275,6 +333,8 @@ fn generate_runtime_decls(decls: &[ItemTrait]) -> Result<TokenStream> {
 				// Any method with the `changed_in` attribute isn't required for the runtime
 				// anymore.
 				if !method_attrs.contains_key(CHANGED_IN_ATTRIBUTE) {
+					metadata_methods.push(method.clone());
+
 					// Make sure we replace all the wild card parameter names.
 					replace_wild_card_parameter_names(&mut method.sig);
 
@@ -286,6 +346,7 @@ fn generate_runtime_decls(decls: &[ItemTrait]) -> Result<TokenStream> {
 		});
 
 		let versioned_api_traits = generate_versioned_api_traits(decl.clone(), methods_by_version);
+		let metadata = generate_runtime_api_decl_metadata(&decl, &metadata_methods);
 
 		let main_api_ident = decl.ident.clone();
 		let versioned_ident = &versioned_api_traits
@@ -307,6 +368,8 @@ fn generate_runtime_decls(decls: &[ItemTrait]) -> Result<TokenStream> {
 				pub #api_version
 
 				pub #id
+
+				#metadata
 			}
 		));
 	}
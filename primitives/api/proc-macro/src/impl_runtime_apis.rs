@@ -33,7 +33,7 @@ use syn::{
 	parse::{Error, Parse, ParseStream, Result},
 	parse_macro_input, parse_quote,
 	spanned::Spanned,
-	Attribute, Ident, ImplItem, ItemImpl, Path, Signature, Type, TypePath,
+	Attribute, FnArg, Ident, ImplItem, ItemImpl, Path, ReturnType, Signature, Type, TypePath,
 };
 
 use std::collections::HashSet;
@@ -96,6 +96,27 @@ fn generate_impl_call(
 	))
 }
 
+/// Wraps the call of `Metadata::metadata` to append the metadata of the runtime apis, if the
+/// `metadata` feature of `sp-api` is enabled.
+fn generate_metadata_call(impl_call: TokenStream, runtime: &Type) -> TokenStream {
+	let c = generate_crate_access(HIDDEN_INCLUDES_ID);
+
+	quote!(
+		let metadata = { #impl_call };
+
+		#c::__if_metadata!(
+			{
+				#c::metadata::append_runtime_apis_metadata(
+					metadata,
+					&<#runtime>::runtime_api_metadata(),
+				)
+			} else {
+				metadata
+			}
+		)
+	)
+}
+
 /// Generate all the implementation calls for the given functions.
 fn generate_impl_calls(
 	impls: &[ItemImpl],
@@ -116,9 +137,15 @@ fn generate_impl_calls(
 
 		for item in &impl_.items {
 			if let ImplItem::Method(method) = item {
-				let impl_call =
+				let mut impl_call =
 					generate_impl_call(&method.sig, &impl_.self_ty, input, &impl_trait)?;
 
+				// The metadata of the runtime apis is appended to the runtime metadata. Trait
+				// names are unique across all runtime apis, as they are used for the ids.
+				if impl_trait_ident == "Metadata" && method.sig.ident == "metadata" {
+					impl_call = generate_metadata_call(impl_call, &impl_.self_ty);
+				}
+
 				impl_calls.push((
 					impl_trait_ident.clone(),
 					method.sig.ident.clone(),
@@ -577,6 +604,110 @@ fn populate_runtime_api_versions(
 	));
 }
 
+/// Generates the `runtime_api_metadata` function on the runtime that describes all implemented
+/// runtime apis, using the `RuntimeApiDecl` generated by `decl_runtime_apis!` for each of them.
+///
+/// Only generated if the `metadata` feature of `sp-api` is enabled.
+fn generate_runtime_api_metadata(impls: &[ItemImpl]) -> Result<TokenStream> {
+	let c = generate_crate_access(HIDDEN_INCLUDES_ID);
+	let runtime = match impls.first() {
+		Some(impl_) => &impl_.self_ty,
+		None => return Ok(quote!()),
+	};
+	let mut apis = Vec::with_capacity(impls.len());
+
+	for impl_ in impls {
+		let api_ver = extract_api_version(&impl_.attrs, impl_.span())?.map(|a| a as u32);
+		let trait_ = extract_impl_trait(impl_, RequireQualifiedTraitPath::Yes)?;
+
+		let mut path = extend_with_runtime_decl_path(trait_.clone());
+		path.segments.pop();
+		let version = api_ver.map(|a| quote!( #a )).unwrap_or_else(|| quote!( #path VERSION ));
+
+		let mut methods = Vec::new();
+		for item in &impl_.items {
+			let method = match item {
+				ImplItem::Method(method) => method,
+				_ => continue,
+			};
+
+			let name = method.sig.ident.to_string();
+			let inputs = method.sig.inputs.iter().filter_map(|input| match input {
+				FnArg::Typed(arg) => Some(generate_type_metadata(&arg.ty, &c)),
+				FnArg::Receiver(_) => None,
+			});
+			let output = match &method.sig.output {
+				ReturnType::Default => generate_type_metadata(&parse_quote!(()), &c),
+				ReturnType::Type(_, ty) => generate_type_metadata(ty, &c),
+			};
+
+			methods.push(quote!(
+				#c::metadata::RuntimeApiMethodTypes {
+					name: #name,
+					inputs: #c::metadata::Vec::from([ #( #inputs ),* ]),
+					output: #output,
+				}
+			));
+		}
+
+		let attrs = filter_cfg_attrs(&impl_.attrs);
+		apis.push(quote!(
+			#( #attrs )*
+			apis.push(#path METADATA.runtime_api_metadata(
+				#version,
+				#c::metadata::Vec::from([ #( #methods ),* ]),
+			));
+		));
+	}
+
+	Ok(quote!(
+		#c::__if_metadata! {
+			{
+				impl #runtime {
+					/// Returns the metadata of all runtime apis implemented by this runtime.
+					pub fn runtime_api_metadata() -> #c::metadata::RuntimeApisMetadata {
+						#[allow(unused_imports)]
+						use #c::metadata::{HasTypeInfo as _, NoTypeInfo as _};
+
+						let mut apis = #c::metadata::Vec::new();
+						#( #apis )*
+						#c::metadata::RuntimeApisMetadata::new(apis)
+					}
+				}
+			} else {}
+		}
+	))
+}
+
+/// Generates the `RuntimeApiTypeMetadata` of the given type.
+fn generate_type_metadata(ty: &Type, crate_access: &TokenStream) -> TokenStream {
+	let name = type_name(ty);
+	let ty = match ty {
+		Type::Reference(t) => &*t.elem,
+		t => t,
+	};
+
+	quote!(
+		#crate_access::metadata::RuntimeApiTypeMetadata {
+			name: #name,
+			ty: (&#crate_access::metadata::MaybeTypeInfo::<#ty>::new()).meta_type(),
+		}
+	)
+}
+
+/// Renders the given tokens the way they would usually be written in code.
+fn type_name<T: quote::ToTokens>(tokens: &T) -> String {
+	quote!( #tokens )
+		.to_string()
+		.replace(" :: ", "::")
+		.replace(":: ", "::")
+		.replace("< ", "<")
+		.replace(" <", "<")
+		.replace(" >", ">")
+		.replace(" ,", ",")
+		.replace("& ", "&")
+}
+
 /// Generates `RUNTIME_API_VERSIONS` that holds all version information about the implemented
 /// runtime apis.
 fn generate_runtime_api_versions(impls: &[ItemImpl]) -> Result<TokenStream> {
@@ -641,6 +772,7 @@ fn impl_runtime_apis_impl_inner(api_impls: &[ItemImpl]) -> Result<TokenStream> {
 	let base_runtime_api = generate_runtime_api_base_structures()?;
 	let hidden_includes = generate_hidden_includes(HIDDEN_INCLUDES_ID);
 	let runtime_api_versions = generate_runtime_api_versions(api_impls)?;
+	let runtime_api_metadata = generate_runtime_api_metadata(api_impls)?;
 	let wasm_interface = generate_wasm_interface(api_impls)?;
 	let api_impls_for_runtime_api = generate_api_impl_for_runtime_api(api_impls)?;
 
@@ -655,6 +787,8 @@ fn impl_runtime_apis_impl_inner(api_impls: &[ItemImpl]) -> Result<TokenStream> {
 
 		#runtime_api_versions
 
+		#runtime_api_metadata
+
 		pub mod api {
 			use super::*;
 
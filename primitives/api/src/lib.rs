@@ -111,6 +111,33 @@ pub use sp_version::{create_apis_vec, ApiId, ApisVec, RuntimeVersion};
 #[cfg(feature = "std")]
 use std::{cell::RefCell, panic::UnwindSafe};
 
+#[cfg(feature = "metadata")]
+pub mod metadata;
+
+/// Expands to the first block of tokens if the `metadata` feature is enabled and to the second
+/// block otherwise.
+///
+/// Used by the generated code of the macros, as the features of `sp-api` are not visible to the
+/// procedural macros.
+#[cfg(feature = "metadata")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_metadata {
+	({ $( $enabled:tt )* } else { $( $disabled:tt )* }) => { $( $enabled )* };
+}
+
+/// Expands to the first block of tokens if the `metadata` feature is enabled and to the second
+/// block otherwise.
+///
+/// Used by the generated code of the macros, as the features of `sp-api` are not visible to the
+/// procedural macros.
+#[cfg(not(feature = "metadata"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_metadata {
+	({ $( $enabled:tt )* } else { $( $disabled:tt )* }) => { $( $disabled )* };
+}
+
 /// Maximum nesting level for extrinsics.
 pub const MAX_EXTRINSIC_DEPTH: u32 = 256;
 
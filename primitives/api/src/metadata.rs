@@ -0,0 +1,305 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metadata of the runtime APIs implemented by a runtime.
+//!
+//! [`decl_runtime_apis!`](crate::decl_runtime_apis) records the documentation of every declared
+//! API together with the names of its methods and their parameters.
+//! [`impl_runtime_apis!`](crate::impl_runtime_apis) combines it with the types of the
+//! implementation into a `runtime_api_metadata` function on the runtime, that describes every
+//! implemented API with the signatures of its methods.
+//!
+//! The metadata is appended to the metadata returned by the [`Metadata`](crate::Metadata)
+//! runtime API, see [`append_runtime_apis_metadata`]. Decoders of the runtime metadata ignore
+//! the trailing data, [`extract_runtime_apis_metadata`] extracts it again.
+
+#[cfg(feature = "std")]
+use codec::Decode;
+use codec::Encode;
+use scale_info::{
+	form::{Form, MetaForm, PortableForm},
+	IntoPortable, MetaType, PortableRegistry, Registry, TypeInfo,
+};
+use sp_core::OpaqueMetadata;
+use sp_std::marker::PhantomData;
+#[doc(hidden)]
+pub use sp_std::vec::Vec;
+
+/// Metadata of all runtime APIs implemented by a runtime.
+#[derive(Clone, PartialEq, Eq, Encode)]
+#[cfg_attr(feature = "std", derive(Decode, Debug))]
+pub struct RuntimeApisMetadata {
+	/// Type information of the parameters and return values.
+	pub types: PortableRegistry,
+	/// The implemented runtime APIs.
+	pub apis: Vec<RuntimeApiMetadata<PortableForm>>,
+}
+
+impl RuntimeApisMetadata {
+	/// Create the metadata of the given runtime APIs, registering their types.
+	pub fn new(apis: Vec<RuntimeApiMetadata>) -> Self {
+		let mut registry = Registry::new();
+		let apis = registry.map_into_portable(apis);
+		Self { types: registry.into(), apis }
+	}
+}
+
+/// Metadata of a runtime API trait.
+#[derive(Clone, PartialEq, Eq, Encode)]
+#[cfg_attr(feature = "std", derive(Decode, Debug))]
+pub struct RuntimeApiMetadata<T: Form = MetaForm> {
+	/// Name of the trait.
+	pub name: T::String,
+	/// Version of the trait implemented by the runtime.
+	pub version: u32,
+	/// Methods of the trait.
+	pub methods: Vec<RuntimeApiMethodMetadata<T>>,
+	/// Documentation of the trait.
+	pub docs: Vec<T::String>,
+}
+
+impl IntoPortable for RuntimeApiMetadata {
+	type Output = RuntimeApiMetadata<PortableForm>;
+
+	fn into_portable(self, registry: &mut Registry) -> Self::Output {
+		RuntimeApiMetadata {
+			name: self.name.into_portable(registry),
+			version: self.version,
+			methods: registry.map_into_portable(self.methods),
+			docs: registry.map_into_portable(self.docs),
+		}
+	}
+}
+
+/// Metadata of a runtime API method.
+#[derive(Clone, PartialEq, Eq, Encode)]
+#[cfg_attr(feature = "std", derive(Decode, Debug))]
+pub struct RuntimeApiMethodMetadata<T: Form = MetaForm> {
+	/// Name of the method.
+	pub name: T::String,
+	/// Parameters of the method.
+	pub inputs: Vec<RuntimeApiParamMetadata<T>>,
+	/// Return type of the method.
+	pub output: RuntimeApiTypeMetadata<T>,
+	/// Documentation of the method.
+	pub docs: Vec<T::String>,
+}
+
+impl IntoPortable for RuntimeApiMethodMetadata {
+	type Output = RuntimeApiMethodMetadata<PortableForm>;
+
+	fn into_portable(self, registry: &mut Registry) -> Self::Output {
+		RuntimeApiMethodMetadata {
+			name: self.name.into_portable(registry),
+			inputs: registry.map_into_portable(self.inputs),
+			output: self.output.into_portable(registry),
+			docs: registry.map_into_portable(self.docs),
+		}
+	}
+}
+
+/// Metadata of a runtime API method parameter.
+#[derive(Clone, PartialEq, Eq, Encode)]
+#[cfg_attr(feature = "std", derive(Decode, Debug))]
+pub struct RuntimeApiParamMetadata<T: Form = MetaForm> {
+	/// Name of the parameter.
+	pub name: T::String,
+	/// Type of the parameter.
+	pub ty: RuntimeApiTypeMetadata<T>,
+}
+
+impl IntoPortable for RuntimeApiParamMetadata {
+	type Output = RuntimeApiParamMetadata<PortableForm>;
+
+	fn into_portable(self, registry: &mut Registry) -> Self::Output {
+		RuntimeApiParamMetadata {
+			name: self.name.into_portable(registry),
+			ty: self.ty.into_portable(registry),
+		}
+	}
+}
+
+/// Metadata of the type of a parameter or return value.
+#[derive(Clone, PartialEq, Eq, Encode)]
+#[cfg_attr(feature = "std", derive(Decode, Debug))]
+pub struct RuntimeApiTypeMetadata<T: Form = MetaForm> {
+	/// The type as written in the implementation of the runtime API.
+	pub name: T::String,
+	/// Type information, `None` if the type does not implement [`TypeInfo`].
+	pub ty: Option<T::Type>,
+}
+
+impl IntoPortable for RuntimeApiTypeMetadata {
+	type Output = RuntimeApiTypeMetadata<PortableForm>;
+
+	fn into_portable(self, registry: &mut Registry) -> Self::Output {
+		RuntimeApiTypeMetadata {
+			name: self.name.into_portable(registry),
+			ty: self.ty.map(|ty| registry.register_type(&ty)),
+		}
+	}
+}
+
+/// Declaration of a runtime API trait, generated by
+/// [`decl_runtime_apis!`](crate::decl_runtime_apis).
+#[doc(hidden)]
+pub struct RuntimeApiDecl {
+	/// Name of the trait.
+	pub name: &'static str,
+	/// Documentation of the trait.
+	pub docs: &'static [&'static str],
+	/// Methods of the trait.
+	pub methods: &'static [RuntimeApiMethodDecl],
+}
+
+/// Declaration of a runtime API method, part of [`RuntimeApiDecl`].
+#[doc(hidden)]
+pub struct RuntimeApiMethodDecl {
+	/// Name of the method.
+	pub name: &'static str,
+	/// Names of the parameters.
+	pub params: &'static [&'static str],
+	/// Documentation of the method.
+	pub docs: &'static [&'static str],
+}
+
+/// Types of a runtime API method as implemented by the runtime, generated by
+/// [`impl_runtime_apis!`](crate::impl_runtime_apis).
+#[doc(hidden)]
+pub struct RuntimeApiMethodTypes {
+	/// Name of the method.
+	pub name: &'static str,
+	/// Types of the parameters.
+	pub inputs: Vec<RuntimeApiTypeMetadata>,
+	/// Return type of the method.
+	pub output: RuntimeApiTypeMetadata,
+}
+
+impl RuntimeApiDecl {
+	/// Create the metadata of the trait implemented at the given `version` with the types of the
+	/// implemented `methods`.
+	pub fn runtime_api_metadata(
+		&self,
+		version: u32,
+		methods: Vec<RuntimeApiMethodTypes>,
+	) -> RuntimeApiMetadata {
+		let methods = methods
+			.into_iter()
+			.map(|method| {
+				let decl = self.methods.iter().find(|decl| decl.name == method.name);
+				let params = decl.map(|decl| decl.params).unwrap_or_default();
+				let inputs = method
+					.inputs
+					.into_iter()
+					.enumerate()
+					.map(|(index, ty)| RuntimeApiParamMetadata {
+						name: params.get(index).copied().unwrap_or("_"),
+						ty,
+					})
+					.collect();
+
+				RuntimeApiMethodMetadata {
+					name: method.name,
+					inputs,
+					output: method.output,
+					docs: decl.map(|decl| decl.docs.to_vec()).unwrap_or_default(),
+				}
+			})
+			.collect();
+
+		RuntimeApiMetadata { name: self.name, version, methods, docs: self.docs.to_vec() }
+	}
+}
+
+/// Marks the end of the runtime APIs metadata appended to the runtime metadata.
+pub const RUNTIME_APIS_METADATA_MAGIC: [u8; 4] = *b"rapi";
+
+/// Append the encoded `apis` to the runtime `metadata`.
+///
+/// The metadata of the runtime APIs is followed by [`RUNTIME_APIS_METADATA_MAGIC`] and its
+/// length as little endian `u32`, so it can be found without decoding the runtime metadata.
+pub fn append_runtime_apis_metadata(
+	metadata: OpaqueMetadata,
+	apis: &RuntimeApisMetadata,
+) -> OpaqueMetadata {
+	let mut metadata = metadata.to_vec();
+	let start = metadata.len();
+	apis.encode_to(&mut metadata);
+	let len = (metadata.len() - start) as u32;
+	metadata.extend_from_slice(&RUNTIME_APIS_METADATA_MAGIC);
+	metadata.extend_from_slice(&len.to_le_bytes());
+	OpaqueMetadata::new(metadata)
+}
+
+/// Extract the runtime APIs metadata appended by [`append_runtime_apis_metadata`] from the
+/// runtime `metadata`.
+///
+/// Returns `None` if the runtime did not append the metadata of its runtime APIs.
+#[cfg(feature = "std")]
+pub fn extract_runtime_apis_metadata(
+	metadata: &[u8],
+) -> Option<Result<RuntimeApisMetadata, codec::Error>> {
+	let (rest, len) = metadata.split_at(metadata.len().checked_sub(4)?);
+	let (rest, magic) = rest.split_at(rest.len().checked_sub(RUNTIME_APIS_METADATA_MAGIC.len())?);
+	if magic != RUNTIME_APIS_METADATA_MAGIC {
+		return None
+	}
+
+	let len = u32::from_le_bytes(len.try_into().expect("`len` has 4 bytes; qed")) as usize;
+	let encoded = match rest.len().checked_sub(len) {
+		Some(start) => &rest[start..],
+		None => return Some(Err("Invalid length of the runtime APIs metadata".into())),
+	};
+	Some(RuntimeApisMetadata::decode(&mut &encoded[..]))
+}
+
+/// Resolves the [`MetaType`] of `T` if it implements [`TypeInfo`].
+///
+/// The lookup is done with `(&MaybeTypeInfo::<T>::new()).meta_type()` with [`HasTypeInfo`] and
+/// [`NoTypeInfo`] in scope: method resolution prefers the impl that does not need the extra
+/// reference, which only applies when `T` implements [`TypeInfo`].
+#[doc(hidden)]
+pub struct MaybeTypeInfo<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> MaybeTypeInfo<T> {
+	/// Create a new instance.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+#[doc(hidden)]
+pub trait HasTypeInfo {
+	fn meta_type(&self) -> Option<MetaType>;
+}
+
+impl<T: TypeInfo + ?Sized + 'static> HasTypeInfo for MaybeTypeInfo<T> {
+	fn meta_type(&self) -> Option<MetaType> {
+		Some(MetaType::new::<T>())
+	}
+}
+
+#[doc(hidden)]
+pub trait NoTypeInfo {
+	fn meta_type(&self) -> Option<MetaType>;
+}
+
+impl<T: ?Sized> NoTypeInfo for &MaybeTypeInfo<T> {
+	fn meta_type(&self) -> Option<MetaType> {
+		None
+	}
+}
@@ -0,0 +1,561 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+//! A BEEFY light client.
+//!
+//! The crate verifies the data a BEEFY light client is fed with, as produced by a chain running
+//! `pallet-beefy`, `pallet-mmr` and `pallet-beefy-mmr`:
+//!
+//! 1. A validator set is checked against the [`BeefyAuthoritySet`] the light client knows about,
+//!    i.e. against the `beefy-merkle-tree` root of the validators' Ethereum addresses.
+//! 2. A [`SignedCommitment`] is checked to carry enough valid signatures of that validator set.
+//! 3. MMR leaves are checked against the MMR root found in the commitment payload.
+//!
+//! [`LightClient`] ties these together: the latest MMR leaf proven against a commitment announces
+//! the next validator set, which the light client switches to once it signs a commitment.
+
+use beefy_merkle_tree::Keccak256 as MerkleKeccak256;
+use beefy_primitives::{
+	crypto::{AuthorityId, Signature},
+	known_payload_ids::MMR_ROOT_ID,
+	mmr::{BeefyAuthoritySet, BeefyNextAuthoritySet, MmrLeaf},
+	Commitment, SignedCommitment, ValidatorSet,
+};
+use codec::Encode;
+use sp_core::H256;
+use sp_mmr_primitives::{BatchProof, DataOrHash, FullLeaf, NodeIndex};
+use sp_runtime::traits::{Hash as _, Keccak256};
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// Light client verification error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+	/// The validator set id doesn't match the expected one.
+	UnexpectedValidatorSetId,
+	/// The validator set length doesn't match the authority set.
+	InvalidValidatorSetLength,
+	/// The validator set doesn't hash to the authority set merkle root.
+	InvalidValidatorSetRoot,
+	/// A validator public key can't be converted to an Ethereum address.
+	InvalidAuthorityId,
+	/// The commitment is signed by a validator set the light client doesn't know about.
+	UnknownValidatorSet,
+	/// The commitment is not newer than the latest imported one.
+	StaleCommitment,
+	/// The number of signatures doesn't match the validator set length.
+	InvalidSignatureCount,
+	/// A signature is not from the validator at its position.
+	InvalidSignature,
+	/// Less than the threshold of validators signed the commitment.
+	NotEnoughSignatures,
+	/// The commitment payload doesn't contain an MMR root.
+	MissingMmrRoot,
+	/// The MMR proof is not for the latest leaf of the MMR.
+	NotLatestLeaf,
+	/// The MMR proof is invalid.
+	InvalidMmrProof,
+}
+
+/// Number of signatures required for a commitment signed by a set of `authorities` validators.
+///
+/// Same as the BEEFY voter's: more than two thirds of the validators.
+pub fn threshold(authorities: usize) -> usize {
+	let faulty = authorities.saturating_sub(1) / 3;
+	authorities - faulty
+}
+
+/// Convert a BEEFY authority id to the Ethereum address `pallet-beefy-mmr` merkelizes.
+pub fn authority_address(id: &AuthorityId) -> Result<[u8; 20], Error> {
+	use k256::{elliptic_curve::sec1::ToEncodedPoint, PublicKey};
+
+	let public: &[u8] = id.as_ref();
+	let public = PublicKey::from_sec1_bytes(public).map_err(|_| Error::InvalidAuthorityId)?;
+	let uncompressed = public.to_encoded_point(false);
+	let hash = sp_io::hashing::keccak_256(&uncompressed.as_bytes()[1..]);
+
+	let mut address = [0u8; 20];
+	address.copy_from_slice(&hash[12..]);
+	Ok(address)
+}
+
+/// Verify that `validator_set` is the one described by `authority_set`.
+pub fn verify_validator_set(
+	validator_set: &ValidatorSet<AuthorityId>,
+	authority_set: &BeefyAuthoritySet<H256>,
+) -> Result<(), Error> {
+	if validator_set.id() != authority_set.id {
+		return Err(Error::UnexpectedValidatorSetId)
+	}
+	if validator_set.len() != authority_set.len as usize {
+		return Err(Error::InvalidValidatorSetLength)
+	}
+
+	let addresses = validator_set
+		.validators()
+		.iter()
+		.map(authority_address)
+		.collect::<Result<Vec<_>, _>>()?;
+	let root = beefy_merkle_tree::merkle_root::<MerkleKeccak256, _, _>(addresses);
+
+	if root != authority_set.root.to_fixed_bytes() {
+		return Err(Error::InvalidValidatorSetRoot)
+	}
+	Ok(())
+}
+
+/// Verify that `signed` is signed by at least [`threshold`] validators of `validator_set`.
+///
+/// The validator set is expected to be verified already, see [`verify_validator_set`].
+pub fn verify_signed_commitment<N: Encode>(
+	signed: &SignedCommitment<N, Signature>,
+	validator_set: &ValidatorSet<AuthorityId>,
+) -> Result<(), Error> {
+	if signed.commitment.validator_set_id != validator_set.id() {
+		return Err(Error::UnexpectedValidatorSetId)
+	}
+	if signed.signatures.len() != validator_set.len() {
+		return Err(Error::InvalidSignatureCount)
+	}
+
+	let message = sp_io::hashing::keccak_256(&signed.commitment.encode());
+	let mut valid = 0;
+	for (signature, id) in signed.signatures.iter().zip(validator_set.validators()) {
+		let signature = match signature {
+			Some(signature) => signature,
+			None => continue,
+		};
+
+		let signature: &[u8] = signature.as_ref();
+		let signature = <[u8; 65]>::try_from(signature).map_err(|_| Error::InvalidSignature)?;
+		let signer = sp_io::crypto::secp256k1_ecdsa_recover_compressed(&signature, &message)
+			.map_err(|_| Error::InvalidSignature)?;
+
+		let public: &[u8] = id.as_ref();
+		if public != &signer[..] {
+			return Err(Error::InvalidSignature)
+		}
+		valid += 1;
+	}
+
+	if valid < threshold(validator_set.len()) {
+		return Err(Error::NotEnoughSignatures)
+	}
+	Ok(())
+}
+
+/// Extract the MMR root from the payload of `commitment`.
+pub fn mmr_root<N>(commitment: &Commitment<N>) -> Result<H256, Error> {
+	commitment
+		.payload
+		.get_decoded::<H256>(&MMR_ROOT_ID)
+		.ok_or(Error::MissingMmrRoot)
+}
+
+/// Merging and hashing of MMR nodes, the same as `pallet-mmr` with `Keccak256` hashing.
+struct Hasher<L>(PhantomData<L>);
+
+impl<L: FullLeaf> mmr_lib::Merge for Hasher<L> {
+	type Item = DataOrHash<Keccak256, L>;
+
+	fn merge(left: &Self::Item, right: &Self::Item) -> Self::Item {
+		let mut concat = left.hash().as_bytes().to_vec();
+		concat.extend_from_slice(right.hash().as_bytes());
+
+		DataOrHash::Hash(Keccak256::hash(&concat))
+	}
+}
+
+/// Number of nodes of an MMR with `leaf_count` leaves.
+///
+/// Returns `None` if the MMR would have more than `NodeIndex::MAX` nodes.
+fn mmr_size(leaf_count: NodeIndex) -> Option<NodeIndex> {
+	leaf_count.checked_mul(2).map(|size| size - u64::from(leaf_count.count_ones()))
+}
+
+/// Verify that `leaves` are part of the MMR with the given `root`.
+pub fn verify_mmr_leaves<L: FullLeaf>(
+	root: H256,
+	leaves: Vec<L>,
+	proof: BatchProof<H256>,
+) -> Result<(), Error> {
+	if leaves.is_empty() || leaves.len() != proof.leaf_indices.len() {
+		return Err(Error::InvalidMmrProof)
+	}
+	if proof.leaf_indices.iter().any(|index| *index >= proof.leaf_count) {
+		return Err(Error::InvalidMmrProof)
+	}
+	let mmr_size = mmr_size(proof.leaf_count).ok_or(Error::InvalidMmrProof)?;
+
+	let leaves = proof
+		.leaf_indices
+		.into_iter()
+		.map(mmr_lib::leaf_index_to_pos)
+		.zip(leaves.into_iter().map(DataOrHash::Data))
+		.collect();
+	let proof = mmr_lib::MerkleProof::<_, Hasher<L>>::new(
+		mmr_size,
+		proof.items.into_iter().map(DataOrHash::Hash).collect(),
+	);
+
+	match proof.verify(DataOrHash::Hash(root), leaves) {
+		Ok(true) => Ok(()),
+		_ => Err(Error::InvalidMmrProof),
+	}
+}
+
+/// State of a BEEFY light client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightClient<N> {
+	current_set: BeefyAuthoritySet<H256>,
+	next_set: BeefyNextAuthoritySet<H256>,
+	latest_block: Option<N>,
+	latest_mmr_root: Option<H256>,
+}
+
+impl<N: Encode + Clone + PartialOrd> LightClient<N> {
+	/// Create a light client trusting the `current_set` and `next_set` authority sets.
+	///
+	/// Both are usually taken from `pallet-beefy-mmr` storage at a trusted block.
+	pub fn new(
+		current_set: BeefyAuthoritySet<H256>,
+		next_set: BeefyNextAuthoritySet<H256>,
+	) -> Self {
+		Self { current_set, next_set, latest_block: None, latest_mmr_root: None }
+	}
+
+	/// The authority set the light client expects commitments from.
+	pub fn current_set(&self) -> &BeefyAuthoritySet<H256> {
+		&self.current_set
+	}
+
+	/// The authority set announced to follow the current one.
+	pub fn next_set(&self) -> &BeefyNextAuthoritySet<H256> {
+		&self.next_set
+	}
+
+	/// Block number of the latest imported commitment.
+	pub fn latest_block(&self) -> Option<&N> {
+		self.latest_block.as_ref()
+	}
+
+	/// MMR root of the latest imported commitment.
+	///
+	/// Historical MMR leaves can be verified against it with [`verify_mmr_leaves`].
+	pub fn latest_mmr_root(&self) -> Option<H256> {
+		self.latest_mmr_root
+	}
+
+	/// Import a commitment signed by `validator_set`, along with the latest MMR leaf at the
+	/// committed block and its proof.
+	///
+	/// The commitment may be signed by either the current or the next authority set. In the
+	/// latter case the light client switches to the next set. In both cases the next set is then
+	/// taken from `latest_leaf`.
+	pub fn import<H, E>(
+		&mut self,
+		signed: &SignedCommitment<N, Signature>,
+		validator_set: &ValidatorSet<AuthorityId>,
+		latest_leaf: MmrLeaf<N, H, H256, E>,
+		proof: BatchProof<H256>,
+	) -> Result<(), Error>
+	where
+		MmrLeaf<N, H, H256, E>: FullLeaf,
+	{
+		let commitment = &signed.commitment;
+		if self
+			.latest_block
+			.as_ref()
+			.map_or(false, |latest| commitment.block_number <= *latest)
+		{
+			return Err(Error::StaleCommitment)
+		}
+
+		let authority_set = if commitment.validator_set_id == self.current_set.id {
+			&self.current_set
+		} else if commitment.validator_set_id == self.next_set.id {
+			&self.next_set
+		} else {
+			return Err(Error::UnknownValidatorSet)
+		};
+		verify_validator_set(validator_set, authority_set)?;
+		verify_signed_commitment(signed, validator_set)?;
+
+		let root = mmr_root(commitment)?;
+		if proof.leaf_indices.len() != 1 ||
+			proof.leaf_indices[0].checked_add(1) != Some(proof.leaf_count)
+		{
+			return Err(Error::NotLatestLeaf)
+		}
+		let next_set = latest_leaf.beefy_next_authority_set.clone();
+		verify_mmr_leaves(root, vec![latest_leaf], proof)?;
+
+		if commitment.validator_set_id == self.next_set.id {
+			self.current_set = sp_std::mem::replace(&mut self.next_set, next_set);
+		} else {
+			self.next_set = next_set;
+		}
+		self.latest_block = Some(commitment.block_number.clone());
+		self.latest_mmr_root = Some(root);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use beefy_primitives::{mmr::MmrLeafVersion, Payload};
+	use hex_literal::hex;
+	use sp_core::{ecdsa, Pair};
+	use sp_runtime::traits::Hash as _;
+
+	type TestLeaf = MmrLeaf<u64, H256, H256, Vec<u8>>;
+
+	/// Same as `pallet-beefy-mmr`'s mock.
+	fn mock_beefy_id(id: u8) -> AuthorityId {
+		let mut buf: [u8; 33] = [id; 33];
+		buf[0] = 0x02;
+		ecdsa::Public::from_raw(buf).into()
+	}
+
+	/// Leaf added by `pallet-beefy-mmr`'s mock at block `block`, see its
+	/// `should_contain_valid_leaf_data`.
+	fn mock_leaf(block: u64) -> TestLeaf {
+		MmrLeaf {
+			version: MmrLeafVersion::new(1, 5),
+			parent_number_and_hash: (block - 1, H256::repeat_byte(0x45)),
+			beefy_next_authority_set: BeefyNextAuthoritySet {
+				id: block + 1,
+				len: 2,
+				root: hex!("9c6b2c1b0d0b25a008e6c882cc7b415f309965c72ad2b944ac0931048ca31cd5")
+					.into(),
+			},
+			leaf_extra: hex!("55b8e9e1cc9f0db7776fac0ca66318ef8acfb8ec26db11e373120583e07ee648")
+				.to_vec(),
+		}
+	}
+
+	fn leaf_hash<L: Encode>(leaf: &L) -> H256 {
+		Keccak256::hash(&leaf.encode())
+	}
+
+	fn pairs(seeds: &[&str]) -> Vec<ecdsa::Pair> {
+		seeds
+			.iter()
+			.map(|seed| ecdsa::Pair::from_string(&format!("//{}", seed), None).unwrap())
+			.collect()
+	}
+
+	fn validator_set(pairs: &[ecdsa::Pair], id: u64) -> ValidatorSet<AuthorityId> {
+		ValidatorSet::new(pairs.iter().map(|pair| pair.public().into()), id).unwrap()
+	}
+
+	fn authority_set(validator_set: &ValidatorSet<AuthorityId>) -> BeefyAuthoritySet<H256> {
+		let addresses = validator_set.validators().iter().map(|id| authority_address(id).unwrap());
+		BeefyAuthoritySet {
+			id: validator_set.id(),
+			len: validator_set.len() as u32,
+			root: beefy_merkle_tree::merkle_root::<MerkleKeccak256, _, _>(addresses).into(),
+		}
+	}
+
+	fn sign(
+		pairs: &[ecdsa::Pair],
+		signers: &[bool],
+		block_number: u64,
+		validator_set_id: u64,
+		mmr_root: H256,
+	) -> SignedCommitment<u64, Signature> {
+		let commitment = Commitment {
+			payload: Payload::new(MMR_ROOT_ID, mmr_root.encode()),
+			block_number,
+			validator_set_id,
+		};
+		let message = sp_io::hashing::keccak_256(&commitment.encode());
+		let signatures = pairs
+			.iter()
+			.zip(signers)
+			.map(|(pair, signs)| signs.then(|| pair.sign_prehashed(&message).into()))
+			.collect();
+		SignedCommitment { commitment, signatures }
+	}
+
+	#[test]
+	fn should_verify_validator_sets_of_pallet_beefy_mmr() {
+		let set = ValidatorSet::new(vec![mock_beefy_id(1), mock_beefy_id(2)], 0).unwrap();
+		let authority_set = BeefyAuthoritySet {
+			id: 0,
+			len: 2,
+			root: hex!("176e73f1bf656478b728e28dd1a7733c98621b8acf830bff585949763dca7a96").into(),
+		};
+		assert_eq!(verify_validator_set(&set, &authority_set), Ok(()));
+
+		let next_set = ValidatorSet::new(vec![mock_beefy_id(3), mock_beefy_id(4)], 2).unwrap();
+		assert_eq!(verify_validator_set(&next_set, &mock_leaf(1).beefy_next_authority_set), Ok(()));
+
+		// wrong id, length or validators
+		let other_id = BeefyAuthoritySet { id: 1, ..authority_set.clone() };
+		assert_eq!(verify_validator_set(&set, &other_id), Err(Error::UnexpectedValidatorSetId));
+		let other_len = BeefyAuthoritySet { len: 3, ..authority_set.clone() };
+		assert_eq!(verify_validator_set(&set, &other_len), Err(Error::InvalidValidatorSetLength));
+		let swapped = ValidatorSet::new(vec![mock_beefy_id(2), mock_beefy_id(1)], 0).unwrap();
+		assert_eq!(
+			verify_validator_set(&swapped, &authority_set),
+			Err(Error::InvalidValidatorSetRoot)
+		);
+	}
+
+	#[test]
+	fn should_verify_mmr_leaves_of_pallet_beefy_mmr() {
+		let root_1: H256 =
+			hex!("95803defe6ea9f41e7ec6afa497064f21bfded027d8812efacbdf984e630cbdc").into();
+		let root_2: H256 =
+			hex!("a73271a0974f1e67d6e9b8dd58e506177a2e556519a330796721e98279a753e2").into();
+		let (leaf_1, leaf_2) = (mock_leaf(1), mock_leaf(2));
+
+		// MMR with a single leaf
+		let proof = BatchProof { leaf_indices: vec![0], leaf_count: 1, items: vec![] };
+		assert_eq!(verify_mmr_leaves(root_1, vec![leaf_1.clone()], proof.clone()), Ok(()));
+		assert_eq!(
+			verify_mmr_leaves(root_1, vec![leaf_2.clone()], proof),
+			Err(Error::InvalidMmrProof)
+		);
+
+		// MMR with two leaves
+		let proof =
+			BatchProof { leaf_indices: vec![1], leaf_count: 2, items: vec![leaf_hash(&leaf_1)] };
+		assert_eq!(verify_mmr_leaves(root_2, vec![leaf_2.clone()], proof.clone()), Ok(()));
+		assert_eq!(
+			verify_mmr_leaves(root_1, vec![leaf_2.clone()], proof),
+			Err(Error::InvalidMmrProof)
+		);
+		let proof = BatchProof { leaf_indices: vec![0, 1], leaf_count: 2, items: vec![] };
+		assert_eq!(verify_mmr_leaves(root_2, vec![leaf_1.clone(), leaf_2], proof), Ok(()));
+
+		// MMR that would have more than `u64::MAX` nodes
+		let proof = BatchProof { leaf_indices: vec![0], leaf_count: u64::MAX, items: vec![] };
+		assert_eq!(verify_mmr_leaves(root_1, vec![leaf_1], proof), Err(Error::InvalidMmrProof));
+	}
+
+	#[test]
+	fn should_verify_signed_commitments() {
+		let pairs = pairs(&["Alice", "Bob", "Charlie", "Dave"]);
+		let set = validator_set(&pairs, 0);
+		let root = H256::repeat_byte(1);
+
+		let signed = sign(&pairs, &[true, false, true, true], 1, 0, root);
+		assert_eq!(verify_signed_commitment(&signed, &set), Ok(()));
+		assert_eq!(mmr_root(&signed.commitment), Ok(root));
+
+		// less than threshold
+		let signed = sign(&pairs, &[true, false, false, true], 1, 0, root);
+		assert_eq!(verify_signed_commitment(&signed, &set), Err(Error::NotEnoughSignatures));
+
+		// signature not matching its position
+		let mut signed = sign(&pairs, &[true, true, true, true], 1, 0, root);
+		signed.signatures.swap(0, 1);
+		assert_eq!(verify_signed_commitment(&signed, &set), Err(Error::InvalidSignature));
+
+		// signatures of another commitment
+		let mut signed = sign(&pairs, &[true, true, true, true], 1, 0, root);
+		signed.commitment.block_number = 2;
+		assert_eq!(verify_signed_commitment(&signed, &set), Err(Error::InvalidSignature));
+
+		// wrong set id or number of signatures
+		let signed = sign(&pairs, &[true, true, true, true], 1, 1, root);
+		assert_eq!(verify_signed_commitment(&signed, &set), Err(Error::UnexpectedValidatorSetId));
+		let signed = sign(&pairs, &[true, true, true], 1, 0, root);
+		assert_eq!(verify_signed_commitment(&signed, &set), Err(Error::InvalidSignatureCount));
+	}
+
+	#[test]
+	fn should_follow_validator_set_transitions() {
+		let (pairs_0, pairs_1) = (pairs(&["Alice", "Bob"]), pairs(&["Charlie", "Dave"]));
+		let (set_0, set_1) = (validator_set(&pairs_0, 0), validator_set(&pairs_1, 1));
+		let set_2 = validator_set(&pairs_0, 2);
+
+		let leaf = |block: u64, next_set: &ValidatorSet<AuthorityId>| TestLeaf {
+			version: MmrLeafVersion::new(1, 5),
+			parent_number_and_hash: (block - 1, H256::repeat_byte(0x45)),
+			beefy_next_authority_set: authority_set(next_set),
+			leaf_extra: vec![],
+		};
+
+		let mut client = LightClient::<u64>::new(authority_set(&set_0), authority_set(&set_1));
+
+		// block 1, signed by set 0, still announcing set 1
+		let leaf_1 = leaf(1, &set_1);
+		let root_1 = leaf_hash(&leaf_1);
+		let proof_1 = BatchProof { leaf_indices: vec![0], leaf_count: 1, items: vec![] };
+		let signed = sign(&pairs_0, &[true, true], 1, 0, root_1);
+		assert_eq!(client.import(&signed, &set_0, leaf_1.clone(), proof_1.clone()), Ok(()));
+		assert_eq!(client.latest_block(), Some(&1));
+		assert_eq!(client.latest_mmr_root(), Some(root_1));
+		assert_eq!(client.current_set(), &authority_set(&set_0));
+
+		// can't import the same block twice
+		assert_eq!(
+			client.import(&signed, &set_0, leaf_1.clone(), proof_1),
+			Err(Error::StaleCommitment)
+		);
+
+		// block 2, signed by set 1, announcing set 2
+		let leaf_2 = leaf(2, &set_2);
+		let root_2 = Keccak256::hash(
+			&[leaf_hash(&leaf_1).as_bytes(), leaf_hash(&leaf_2).as_bytes()].concat(),
+		);
+		let proof_2 =
+			BatchProof { leaf_indices: vec![1], leaf_count: 2, items: vec![leaf_hash(&leaf_1)] };
+
+		// signed by a set that is not known yet
+		let signed = sign(&pairs_0, &[true, true], 2, 2, root_2);
+		assert_eq!(
+			client.import(&signed, &set_2, leaf_2.clone(), proof_2.clone()),
+			Err(Error::UnknownValidatorSet)
+		);
+
+		// the proof must be for the latest leaf
+		let signed = sign(&pairs_1, &[true, true], 2, 1, root_2);
+		let proof_of_first =
+			BatchProof { leaf_indices: vec![0], leaf_count: 2, items: vec![leaf_hash(&leaf_2)] };
+		assert_eq!(
+			client.import(&signed, &set_1, leaf_1, proof_of_first),
+			Err(Error::NotLatestLeaf)
+		);
+
+		assert_eq!(client.import(&signed, &set_1, leaf_2, proof_2), Ok(()));
+		assert_eq!(client.current_set(), &authority_set(&set_1));
+		assert_eq!(client.next_set(), &authority_set(&set_2));
+		assert_eq!(client.latest_mmr_root(), Some(root_2));
+	}
+
+	#[test]
+	fn should_reject_proofs_of_the_last_possible_leaf_index() {
+		let pairs = pairs(&["Alice", "Bob"]);
+		let set = validator_set(&pairs, 0);
+		let mut client = LightClient::<u64>::new(authority_set(&set), authority_set(&set));
+
+		let leaf = mock_leaf(1);
+		let signed = sign(&pairs, &[true, true], 1, 0, leaf_hash(&leaf));
+		// `u64::MAX + 1` must not wrap around to a `leaf_count` of zero
+		let proof = BatchProof { leaf_indices: vec![u64::MAX], leaf_count: 0, items: vec![] };
+		assert_eq!(client.import(&signed, &set, leaf, proof), Err(Error::NotLatestLeaf));
+		assert_eq!(client.latest_block(), None);
+	}
+}
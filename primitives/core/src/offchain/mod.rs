@@ -44,6 +44,24 @@ pub trait OffchainStorage: Clone + Send + Sync {
 	/// Retrieve a value from storage under given key and prefix.
 	fn get(&self, prefix: &[u8], key: &[u8]) -> Option<Vec<u8>>;
 
+	/// Retrieve up to `count` keys under given prefix that start with `key_prefix`.
+	///
+	/// The keys are returned without `prefix` and in lexicographic order. Only keys that are
+	/// greater than `start_key` are returned, if given.
+	///
+	/// Returns `None` if the keys can't be iterated over, e.g. because the underlying database
+	/// doesn't support it, which is the default.
+	fn keys(
+		&self,
+		prefix: &[u8],
+		key_prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		let _ = (prefix, key_prefix, start_key, count);
+		None
+	}
+
 	/// Replace the value in storage if given old_value matches the current one.
 	///
 	/// Returns `true` if the value has been set and false otherwise.
@@ -667,6 +685,21 @@ pub trait DbExternalities: Send {
 	/// Note this storage is not part of the consensus, it's only accessible by
 	/// offchain worker tasks running on the same machine. It _is_ persisted between runs.
 	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>>;
+
+	/// Gets up to `count` keys from the local storage that start with `prefix`.
+	///
+	/// The keys are returned in lexicographic order. Only keys that are greater than `start_key`
+	/// are returned, if given, which allows iterating over all keys with a prefix in batches.
+	/// No keys are returned if the storage doesn't support iterating over them.
+	/// Note this storage is not part of the consensus, it's only accessible by
+	/// offchain worker tasks running on the same machine. It _is_ persisted between runs.
+	fn local_storage_keys(
+		&mut self,
+		kind: StorageKind,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: u32,
+	) -> Vec<Vec<u8>>;
 }
 
 impl<T: DbExternalities + ?Sized> DbExternalities for Box<T> {
@@ -691,6 +724,16 @@ impl<T: DbExternalities + ?Sized> DbExternalities for Box<T> {
 	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
 		(&mut **self).local_storage_get(kind, key)
 	}
+
+	fn local_storage_keys(
+		&mut self,
+		kind: StorageKind,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: u32,
+	) -> Vec<Vec<u8>> {
+		(&mut **self).local_storage_keys(kind, prefix, start_key, count)
+	}
 }
 
 impl<T: DbExternalities> DbExternalities for LimitedExternalities<T> {
@@ -720,6 +763,17 @@ impl<T: DbExternalities> DbExternalities for LimitedExternalities<T> {
 		self.check(Capabilities::OFFCHAIN_DB_READ, "local_storage_get");
 		self.externalities.local_storage_get(kind, key)
 	}
+
+	fn local_storage_keys(
+		&mut self,
+		kind: StorageKind,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: u32,
+	) -> Vec<Vec<u8>> {
+		self.check(Capabilities::OFFCHAIN_DB_READ, "local_storage_keys");
+		self.externalities.local_storage_keys(kind, prefix, start_key, count)
+	}
 }
 
 #[cfg(feature = "std")]
@@ -63,6 +63,24 @@ impl OffchainStorage for InMemOffchainStorage {
 		self.storage.get(&key).cloned()
 	}
 
+	fn keys(
+		&self,
+		prefix: &[u8],
+		key_prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		let mut keys = self
+			.storage
+			.keys()
+			.filter_map(|key| key.strip_prefix(prefix))
+			.filter(|key| key.starts_with(key_prefix))
+			.filter(|key| start_key.map_or(true, |start_key| *key > start_key))
+			.collect::<Vec<_>>();
+		keys.sort_unstable();
+		Some(keys.into_iter().take(count).map(ToOwned::to_owned).collect())
+	}
+
 	fn compare_and_set(
 		&mut self,
 		prefix: &[u8],
@@ -106,6 +106,16 @@ impl OffchainStorage for TestPersistentOffchainDB {
 		self.persistent.read().get(prefix, key)
 	}
 
+	fn keys(
+		&self,
+		prefix: &[u8],
+		key_prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		self.persistent.read().keys(prefix, key_prefix, start_key, count)
+	}
+
 	fn compare_and_set(
 		&mut self,
 		prefix: &[u8],
@@ -395,6 +405,23 @@ impl offchain::DbExternalities for TestOffchainExt {
 			StorageKind::PERSISTENT => state.persistent_storage.get(key),
 		}
 	}
+
+	fn local_storage_keys(
+		&mut self,
+		kind: StorageKind,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: u32,
+	) -> Vec<Vec<u8>> {
+		let state = self.0.read();
+		let (db_prefix, count) = (TestPersistentOffchainDB::PREFIX, count as usize);
+		match kind {
+			StorageKind::LOCAL => state.local_storage.keys(db_prefix, prefix, start_key, count),
+			StorageKind::PERSISTENT =>
+				state.persistent_storage.keys(db_prefix, prefix, start_key, count),
+		}
+		.unwrap_or_default()
+	}
 }
 
 /// The internal state of the fake transaction pool.
@@ -164,6 +164,13 @@ sp_externalities::decl_extension! {
 	pub struct TaskExecutorExt(Box<dyn SpawnNamed>);
 }
 
+sp_externalities::decl_extension! {
+	/// Extension that limits how long a runtime call may execute.
+	///
+	/// Executors that support it abort the call once it runs longer than the given duration.
+	pub struct ExecutionTimeoutExt(std::time::Duration);
+}
+
 impl TaskExecutorExt {
 	/// New instance of task executor extension.
 	pub fn new(spawn_handle: impl SpawnNamed + Send + 'static) -> Self {
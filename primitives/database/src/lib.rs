@@ -111,6 +111,66 @@ pub trait Database<H: Clone + AsRef<[u8]>>: Send + Sync {
 		false
 	}
 
+	/// Call `f` with every key-value pair of `col` whose key starts with `prefix`, in lexicographic
+	/// order of the keys, until `f` returns `false`.
+	///
+	/// Returns an error if the database doesn't support iterating over `col`.
+	fn iter_with_prefix(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) -> error::Result<()> {
+		let _ = (col, prefix, f);
+		Err(error::DatabaseError(Box::new(std::io::Error::new(
+			std::io::ErrorKind::Unsupported,
+			"the database doesn't support iterating over keys",
+		))))
+	}
+
+	/// Same as [`Self::iter_with_prefix`], but only calls `f` with the keys greater than
+	/// `start_key`.
+	///
+	/// By default, the keys greater than `start_key` are visited with [`Self::iter_with_prefix`]
+	/// in ranges sharing a prefix: first the keys starting with `start_key`, then for every byte
+	/// of `start_key` after `prefix`, from the last one, the keys starting with the bytes before
+	/// it followed by a greater byte. This seeks to every range instead of iterating over all the
+	/// keys lower than `start_key`.
+	fn iter_with_prefix_from(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		start_key: &[u8],
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) -> error::Result<()> {
+		if !start_key.starts_with(prefix) {
+			return if start_key < prefix { self.iter_with_prefix(col, prefix, f) } else { Ok(()) }
+		}
+
+		let mut more = true;
+		self.iter_with_prefix(col, start_key, &mut |key, value| {
+			more = key == start_key || f(key, value);
+			more
+		})?;
+
+		let mut range_prefix = start_key.to_vec();
+		for len in (prefix.len()..start_key.len()).rev() {
+			range_prefix.truncate(len);
+			for byte in (start_key[len]..=u8::MAX).skip(1) {
+				if !more {
+					return Ok(())
+				}
+				range_prefix.push(byte);
+				self.iter_with_prefix(col, &range_prefix, &mut |key, value| {
+					more = f(key, value);
+					more
+				})?;
+				range_prefix.pop();
+			}
+		}
+		Ok(())
+	}
+
 	/// Remove a possible path-prefix from the key.
 	///
 	/// Not all database implementations use a prefix for keys, so this function may be a noop.
@@ -1275,6 +1275,28 @@ pub trait Offchain {
 			.local_storage_get(kind, key)
 	}
 
+	/// Gets up to `count` keys from the local storage that start with `prefix`.
+	///
+	/// The keys are returned in lexicographic order. If `start_key` is given, only keys greater
+	/// than it are returned, so all keys with `prefix` can be iterated over in batches by passing
+	/// the last key of the previous batch.
+	/// Note this storage is not part of the consensus, it's only accessible by
+	/// offchain worker tasks running on the same machine. It IS persisted between runs.
+	fn local_storage_keys(
+		&mut self,
+		kind: StorageKind,
+		prefix: &[u8],
+		start_key: Option<Vec<u8>>,
+		count: u32,
+	) -> Vec<Vec<u8>> {
+		self.extension::<OffchainDbExt>()
+			.expect(
+				"local_storage_keys can be called only in the offchain call context with
+				OffchainDb extension",
+			)
+			.local_storage_keys(kind, prefix, start_key.as_deref(), count)
+	}
+
 	/// Initiates a http request given HTTP verb and the URL.
 	///
 	/// Meta is a future-reserved field containing additional, parity-scale-codec encoded
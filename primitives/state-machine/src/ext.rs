@@ -309,82 +309,19 @@ where
 	}
 
 	fn next_storage_key(&self, key: &[u8]) -> Option<StorageKey> {
-		let mut next_backend_key =
-			self.backend.next_storage_key(key).expect(EXT_NOT_ALLOWED_TO_FAIL);
-		let mut overlay_changes = self.overlay.iter_after(key).peekable();
-
-		match (&next_backend_key, overlay_changes.peek()) {
-			(_, None) => next_backend_key,
-			(Some(_), Some(_)) => {
-				for overlay_key in overlay_changes {
-					let cmp = next_backend_key.as_deref().map(|v| v.cmp(overlay_key.0));
-
-					// If `backend_key` is less than the `overlay_key`, we found out next key.
-					if cmp == Some(Ordering::Less) {
-						return next_backend_key
-					} else if overlay_key.1.value().is_some() {
-						// If there exists a value for the `overlay_key` in the overlay
-						// (aka the key is still valid), it means we have found our next key.
-						return Some(overlay_key.0.to_vec())
-					} else if cmp == Some(Ordering::Equal) {
-						// If the `backend_key` and `overlay_key` are equal, it means that we need
-						// to search for the next backend key, because the overlay has overwritten
-						// this key.
-						next_backend_key = self
-							.backend
-							.next_storage_key(overlay_key.0)
-							.expect(EXT_NOT_ALLOWED_TO_FAIL);
-					}
-				}
-
-				next_backend_key
-			},
-			(None, Some(_)) => {
-				// Find the next overlay key that has a value attached.
-				overlay_changes.find_map(|k| k.1.value().as_ref().map(|_| k.0.to_vec()))
-			},
+		let next_key = self.find_next_storage_key(key);
+		if let Some(next_key) = &next_key {
+			self.overlay.record_read(None, next_key);
 		}
+		next_key
 	}
 
 	fn next_child_storage_key(&self, child_info: &ChildInfo, key: &[u8]) -> Option<StorageKey> {
-		let mut next_backend_key = self
-			.backend
-			.next_child_storage_key(child_info, key)
-			.expect(EXT_NOT_ALLOWED_TO_FAIL);
-		let mut overlay_changes =
-			self.overlay.child_iter_after(child_info.storage_key(), key).peekable();
-
-		match (&next_backend_key, overlay_changes.peek()) {
-			(_, None) => next_backend_key,
-			(Some(_), Some(_)) => {
-				for overlay_key in overlay_changes {
-					let cmp = next_backend_key.as_deref().map(|v| v.cmp(overlay_key.0));
-
-					// If `backend_key` is less than the `overlay_key`, we found out next key.
-					if cmp == Some(Ordering::Less) {
-						return next_backend_key
-					} else if overlay_key.1.value().is_some() {
-						// If there exists a value for the `overlay_key` in the overlay
-						// (aka the key is still valid), it means we have found our next key.
-						return Some(overlay_key.0.to_vec())
-					} else if cmp == Some(Ordering::Equal) {
-						// If the `backend_key` and `overlay_key` are equal, it means that we need
-						// to search for the next backend key, because the overlay has overwritten
-						// this key.
-						next_backend_key = self
-							.backend
-							.next_child_storage_key(child_info, overlay_key.0)
-							.expect(EXT_NOT_ALLOWED_TO_FAIL);
-					}
-				}
-
-				next_backend_key
-			},
-			(None, Some(_)) => {
-				// Find the next overlay key that has a value attached.
-				overlay_changes.find_map(|k| k.1.value().as_ref().map(|_| k.0.to_vec()))
-			},
+		let next_key = self.find_next_child_storage_key(child_info, key);
+		if let Some(next_key) = &next_key {
+			self.overlay.record_read(Some(child_info), next_key);
 		}
+		next_key
 	}
 
 	fn place_storage(&mut self, key: StorageKey, value: Option<StorageValue>) {
@@ -747,6 +684,89 @@ where
 	H::Out: Ord + 'static + codec::Codec,
 	B: Backend<H>,
 {
+	fn find_next_storage_key(&self, key: &[u8]) -> Option<StorageKey> {
+		let mut next_backend_key =
+			self.backend.next_storage_key(key).expect(EXT_NOT_ALLOWED_TO_FAIL);
+		let mut overlay_changes = self.overlay.iter_after(key).peekable();
+
+		match (&next_backend_key, overlay_changes.peek()) {
+			(_, None) => next_backend_key,
+			(Some(_), Some(_)) => {
+				for overlay_key in overlay_changes {
+					let cmp = next_backend_key.as_deref().map(|v| v.cmp(overlay_key.0));
+
+					// If `backend_key` is less than the `overlay_key`, we found out next key.
+					if cmp == Some(Ordering::Less) {
+						return next_backend_key
+					} else if overlay_key.1.value().is_some() {
+						// If there exists a value for the `overlay_key` in the overlay
+						// (aka the key is still valid), it means we have found our next key.
+						return Some(overlay_key.0.to_vec())
+					} else if cmp == Some(Ordering::Equal) {
+						// If the `backend_key` and `overlay_key` are equal, it means that we need
+						// to search for the next backend key, because the overlay has overwritten
+						// this key.
+						next_backend_key = self
+							.backend
+							.next_storage_key(overlay_key.0)
+							.expect(EXT_NOT_ALLOWED_TO_FAIL);
+					}
+				}
+
+				next_backend_key
+			},
+			(None, Some(_)) => {
+				// Find the next overlay key that has a value attached.
+				overlay_changes.find_map(|k| k.1.value().as_ref().map(|_| k.0.to_vec()))
+			},
+		}
+	}
+
+	fn find_next_child_storage_key(
+		&self,
+		child_info: &ChildInfo,
+		key: &[u8],
+	) -> Option<StorageKey> {
+		let mut next_backend_key = self
+			.backend
+			.next_child_storage_key(child_info, key)
+			.expect(EXT_NOT_ALLOWED_TO_FAIL);
+		let mut overlay_changes =
+			self.overlay.child_iter_after(child_info.storage_key(), key).peekable();
+
+		match (&next_backend_key, overlay_changes.peek()) {
+			(_, None) => next_backend_key,
+			(Some(_), Some(_)) => {
+				for overlay_key in overlay_changes {
+					let cmp = next_backend_key.as_deref().map(|v| v.cmp(overlay_key.0));
+
+					// If `backend_key` is less than the `overlay_key`, we found out next key.
+					if cmp == Some(Ordering::Less) {
+						return next_backend_key
+					} else if overlay_key.1.value().is_some() {
+						// If there exists a value for the `overlay_key` in the overlay
+						// (aka the key is still valid), it means we have found our next key.
+						return Some(overlay_key.0.to_vec())
+					} else if cmp == Some(Ordering::Equal) {
+						// If the `backend_key` and `overlay_key` are equal, it means that we need
+						// to search for the next backend key, because the overlay has overwritten
+						// this key.
+						next_backend_key = self
+							.backend
+							.next_child_storage_key(child_info, overlay_key.0)
+							.expect(EXT_NOT_ALLOWED_TO_FAIL);
+					}
+				}
+
+				next_backend_key
+			},
+			(None, Some(_)) => {
+				// Find the next overlay key that has a value attached.
+				overlay_changes.find_map(|k| k.1.value().as_ref().map(|_| k.0.to_vec()))
+			},
+		}
+	}
+
 	fn limit_remove_from_backend(
 		&mut self,
 		maybe_child: Option<&ChildInfo>,
@@ -128,8 +128,8 @@ pub use crate::{
 	ext::Ext,
 	overlayed_changes::{
 		ChildStorageCollection, IndexOperation, OffchainChangesCollection,
-		OffchainOverlayedChanges, OverlayedChanges, StorageChanges, StorageCollection, StorageKey,
-		StorageTransactionCache, StorageValue,
+		OffchainOverlayedChanges, OverlayedChanges, RecordedReads, StorageChanges,
+		StorageCollection, StorageKey, StorageTransactionCache, StorageValue,
 	},
 	stats::{StateMachineStats, UsageInfo, UsageUnit},
 	trie_backend::{TrieBackend, TrieBackendBuilder},
@@ -1017,6 +1017,221 @@ mod execution {
 		Ok((proof, count))
 	}
 
+	/// A storage entry that differs between two states.
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct StorageDiffEntry {
+		/// The child trie of the entry, `None` for the main trie.
+		pub child_info: Option<ChildInfo>,
+		/// The key of the entry.
+		pub key: StorageKey,
+		/// The value in the old state, `None` if the key was added.
+		pub old_value: Option<StorageValue>,
+		/// The value in the new state, `None` if the key was removed.
+		pub new_value: Option<StorageValue>,
+	}
+
+	/// Compute the storage entries that differ between the `old` and the `new` state.
+	///
+	/// Only keys of the main trie starting with `prefix` are compared. A child trie is compared
+	/// when its root changed and its prefixed storage key starts with `prefix`, its entries
+	/// follow the entry of its root.
+	///
+	/// At most `limit` entries are returned. If `start_at` is given, only the entries following
+	/// the entry with the given child trie and key are returned, such that the difference can
+	/// be retrieved in pages.
+	pub fn storage_diff<A, B, H>(
+		old: &A,
+		new: &B,
+		prefix: Option<&[u8]>,
+		start_at: Option<(Option<&ChildInfo>, &[u8])>,
+		limit: usize,
+	) -> Result<Vec<StorageDiffEntry>, Box<dyn Error>>
+	where
+		A: AsTrieBackend<H>,
+		B: AsTrieBackend<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		storage_diff_on_trie_backend(
+			old.as_trie_backend(),
+			new.as_trie_backend(),
+			prefix,
+			start_at,
+			limit,
+		)
+	}
+
+	/// Compute the storage entries that differ between two trie backends.
+	///
+	/// See [`storage_diff`] for details.
+	pub fn storage_diff_on_trie_backend<SA, SB, H>(
+		old: &TrieBackend<SA, H>,
+		new: &TrieBackend<SB, H>,
+		prefix: Option<&[u8]>,
+		start_at: Option<(Option<&ChildInfo>, &[u8])>,
+		limit: usize,
+	) -> Result<Vec<StorageDiffEntry>, Box<dyn Error>>
+	where
+		SA: trie_backend_essence::TrieBackendStorage<H>,
+		SB: trie_backend_essence::TrieBackendStorage<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let prefix = prefix.unwrap_or_default();
+		let mut diff = Vec::new();
+		if limit == 0 {
+			return Ok(diff)
+		}
+
+		// The entries of a child trie follow the entry of its root, thus the diff starting at an
+		// entry of a child trie or at the entry of its root resumes with that child trie.
+		let (start_key, child_start_key) = match start_at {
+			Some((Some(child_info), key)) =>
+				(Some(child_info.prefixed_storage_key().into_inner()), Some(key)),
+			Some((None, key)) => (Some(key.to_vec()), None),
+			None => (None, None),
+		};
+		if let Some(start_key) = start_key.as_ref().filter(|key| key.starts_with(prefix)) {
+			if let Some(child_info) = default_child_info(start_key) {
+				let old_root = old.storage(start_key).map_err(|e| Box::new(e) as Box<dyn Error>)?;
+				let new_root = new.storage(start_key).map_err(|e| Box::new(e) as Box<dyn Error>)?;
+				if old_root != new_root {
+					child_storage_diff(
+						old,
+						new,
+						&child_info,
+						(
+							child_trie_root::<H>(old_root.as_ref())?,
+							child_trie_root::<H>(new_root.as_ref())?,
+						),
+						child_start_key,
+						limit,
+						&mut diff,
+					)?;
+				}
+			}
+		}
+
+		let mut top = Vec::new();
+		let top_limit = limit - diff.len();
+		if top_limit > 0 {
+			sp_trie::diff_tries::<H, _, _, _>(
+				old.essence(),
+				old.root(),
+				new.essence(),
+				new.root(),
+				prefix,
+				start_key.as_deref(),
+				|entry| {
+					top.push(entry);
+					top.len() < top_limit
+				},
+			)
+			.map_err(|e| Box::new(e) as Box<dyn Error>)?;
+		}
+
+		for entry in top {
+			if diff.len() >= limit {
+				break
+			}
+
+			let child = match default_child_info(&entry.key) {
+				Some(child_info) => Some((
+					child_info,
+					child_trie_root::<H>(entry.old_value.as_ref())?,
+					child_trie_root::<H>(entry.new_value.as_ref())?,
+				)),
+				None => None,
+			};
+
+			diff.push(StorageDiffEntry {
+				child_info: None,
+				key: entry.key,
+				old_value: entry.old_value,
+				new_value: entry.new_value,
+			});
+
+			if let Some((child_info, old_root, new_root)) = child {
+				if diff.len() < limit {
+					child_storage_diff(
+						old,
+						new,
+						&child_info,
+						(old_root, new_root),
+						None,
+						limit,
+						&mut diff,
+					)?;
+				}
+			}
+		}
+
+		Ok(diff)
+	}
+
+	/// Append the entries of the child trie that differ between the given roots to `diff`,
+	/// until `diff` holds `limit` entries.
+	fn child_storage_diff<SA, SB, H>(
+		old: &TrieBackend<SA, H>,
+		new: &TrieBackend<SB, H>,
+		child_info: &ChildInfo,
+		(old_root, new_root): (H::Out, H::Out),
+		start_at: Option<&[u8]>,
+		limit: usize,
+		diff: &mut Vec<StorageDiffEntry>,
+	) -> Result<(), Box<dyn Error>>
+	where
+		SA: trie_backend_essence::TrieBackendStorage<H>,
+		SB: trie_backend_essence::TrieBackendStorage<H>,
+		H: Hasher,
+	{
+		let old_db = sp_trie::KeySpacedDB::new(old.essence(), child_info.keyspace());
+		let new_db = sp_trie::KeySpacedDB::new(new.essence(), child_info.keyspace());
+		sp_trie::diff_tries::<H, _, _, _>(
+			&old_db,
+			&old_root,
+			&new_db,
+			&new_root,
+			&[],
+			start_at,
+			|entry| {
+				diff.push(StorageDiffEntry {
+					child_info: Some(child_info.clone()),
+					key: entry.key,
+					old_value: entry.old_value,
+					new_value: entry.new_value,
+				});
+				diff.len() < limit
+			},
+		)
+		.map_err(|e| Box::new(e) as Box<dyn Error>)
+	}
+
+	/// The child info of the default child trie with the given prefixed storage key.
+	fn default_child_info(prefixed_storage_key: &Vec<u8>) -> Option<ChildInfo> {
+		match ChildType::from_prefixed_key(PrefixedStorageKey::new_ref(prefixed_storage_key)) {
+			Some((ChildType::ParentKeyId, storage_key)) =>
+				Some(ChildInfo::new_default(storage_key)),
+			None => None,
+		}
+	}
+
+	/// Decode the root of a child trie stored in the main trie, an absent root is the root of
+	/// the empty trie.
+	fn child_trie_root<H: Hasher>(value: Option<&StorageValue>) -> Result<H::Out, Box<dyn Error>> {
+		match value {
+			Some(value) => {
+				let mut root = H::Out::default();
+				if value.len() != root.as_ref().len() {
+					return Err(Box::new("Invalid child trie root."))
+				}
+				root.as_mut().copy_from_slice(value);
+				Ok(root)
+			},
+			None => Ok(sp_trie::empty_child_trie_root::<sp_trie::LayoutV1<H>>()),
+		}
+	}
+
 	/// Generate child storage read proof.
 	pub fn prove_child_read<B, H, I>(
 		backend: B,
@@ -2307,4 +2522,96 @@ mod tests {
 		run_state_machine(&mut state_machine);
 		run_state_machine(&mut state_machine);
 	}
+
+	/// Builds a state with the given entries in the top trie and in the child trie.
+	fn diff_state(
+		child_info: &ChildInfo,
+		top: Vec<(&[u8], &[u8])>,
+		child: Vec<(&[u8], &[u8])>,
+	) -> InMemoryBackend<BlakeTwo256> {
+		let mut storage: HashMap<Option<ChildInfo>, BTreeMap<StorageKey, StorageValue>> =
+			Default::default();
+		storage.insert(None, top.into_iter().map(|(k, v)| (k.to_vec(), v.to_vec())).collect());
+		storage.insert(
+			Some(child_info.clone()),
+			child.into_iter().map(|(k, v)| (k.to_vec(), v.to_vec())).collect(),
+		);
+		InMemoryBackend::<BlakeTwo256>::from((storage, StateVersion::V1))
+	}
+
+	#[test]
+	fn storage_diff_works() {
+		let child_info = ChildInfo::new_default(b"sub");
+		let state = |top, child| diff_state(&child_info, top, child);
+
+		let old = state(vec![(b"a", b"1"), (b"b", b"2")], vec![(b"x", b"1")]);
+		let new =
+			state(vec![(b"a", b"1"), (b"b", b"3"), (b"d", b"4")], vec![(b"x", b"1"), (b"y", b"2")]);
+
+		let diff = storage_diff(&old, &new, None, None, usize::MAX).unwrap();
+		let child_root_key = child_info.prefixed_storage_key().into_inner();
+		assert_eq!(
+			diff.iter()
+				.map(|entry| (entry.child_info.clone(), entry.key.clone()))
+				.collect::<Vec<_>>(),
+			vec![
+				(None, child_root_key.clone()),
+				(Some(child_info.clone()), b"y".to_vec()),
+				(None, b"b".to_vec()),
+				(None, b"d".to_vec()),
+			],
+		);
+		assert_eq!(diff[1].old_value, None);
+		assert_eq!(diff[1].new_value, Some(b"2".to_vec()));
+		assert_eq!(diff[2].old_value, Some(b"2".to_vec()));
+		assert_eq!(diff[2].new_value, Some(b"3".to_vec()));
+		assert_eq!(diff[3].old_value, None);
+
+		// Only the keys with the given prefix are compared.
+		let diff = storage_diff(&old, &new, Some(b"b"), None, usize::MAX).unwrap();
+		assert_eq!(diff.len(), 1);
+		assert_eq!(diff[0].key, b"b".to_vec());
+
+		assert!(storage_diff(&old, &old, None, None, usize::MAX).unwrap().is_empty());
+	}
+
+	#[test]
+	fn storage_diff_in_pages() {
+		let child_info = ChildInfo::new_default(b"sub");
+		let state = |top, child| diff_state(&child_info, top, child);
+
+		let old = state(vec![(b"a", b"1")], vec![(b"x", b"1")]);
+		let new = state(vec![(b"a", b"2"), (b"b", b"3")], vec![(b"x", b"2"), (b"y", b"3")]);
+		let child_root_key = child_info.prefixed_storage_key().into_inner();
+		let keys = |start_at: Option<(Option<&ChildInfo>, &[u8])>, limit| {
+			storage_diff(&old, &new, None, start_at, limit)
+				.unwrap()
+				.into_iter()
+				.map(|entry| (entry.child_info, entry.key))
+				.collect::<Vec<_>>()
+		};
+
+		assert!(keys(None, 0).is_empty());
+		assert_eq!(
+			keys(None, 2),
+			vec![(None, child_root_key.clone()), (Some(child_info.clone()), b"x".to_vec())]
+		);
+		// Resuming at the root of a child trie starts with the entries of the child trie.
+		assert_eq!(
+			keys(Some((None, &child_root_key[..])), 2),
+			vec![
+				(Some(child_info.clone()), b"x".to_vec()),
+				(Some(child_info.clone()), b"y".to_vec()),
+			]
+		);
+		assert_eq!(
+			keys(Some((Some(&child_info), &b"x"[..])), 2),
+			vec![(Some(child_info.clone()), b"y".to_vec()), (None, b"a".to_vec())]
+		);
+		assert_eq!(
+			keys(Some((Some(&child_info), &b"y"[..])), 10),
+			vec![(None, b"a".to_vec()), (None, b"b".to_vec())]
+		);
+		assert_eq!(keys(Some((None, &b"a"[..])), 10), vec![(None, b"b".to_vec())]);
+	}
 }
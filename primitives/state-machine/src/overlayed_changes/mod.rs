@@ -33,7 +33,7 @@ use sp_core::{
 use sp_externalities::{Extension, Extensions};
 #[cfg(not(feature = "std"))]
 use sp_std::collections::btree_map::BTreeMap as Map;
-use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+use sp_std::{cell::RefCell, collections::btree_set::BTreeSet, vec::Vec};
 #[cfg(feature = "std")]
 use std::collections::{hash_map::Entry as MapEntry, HashMap as Map};
 #[cfg(feature = "std")]
@@ -59,6 +59,11 @@ pub type StorageCollection = Vec<(StorageKey, Option<StorageValue>)>;
 /// In memory arrays of storage values for multiple child tries.
 pub type ChildStorageCollection = Vec<(StorageKey, StorageCollection)>;
 
+/// Keys read through the [`OverlayedChanges`], see [`OverlayedChanges::set_record_reads`].
+///
+/// Keys of child tries are paired with the storage key of the child trie.
+pub type RecordedReads = BTreeSet<(Option<StorageKey>, StorageKey)>;
+
 /// In memory array of storage values.
 pub type OffchainChangesCollection = Vec<((Vec<u8>, Vec<u8>), OffchainOverlayedChange)>;
 
@@ -102,6 +107,8 @@ pub struct OverlayedChanges {
 	collect_extrinsics: bool,
 	/// Collect statistic on this execution.
 	stats: StateMachineStats,
+	/// Keys read through this overlay, `None` if reads are not recorded.
+	recorded_reads: Option<RefCell<RecordedReads>>,
 }
 
 /// Transaction index operation.
@@ -222,10 +229,34 @@ impl OverlayedChanges {
 		self.collect_extrinsics = collect_extrinsics;
 	}
 
+	/// Ask to record/not to record the keys read through the overlay.
+	///
+	/// The externalities query the overlay before the backend, so this records every key read
+	/// during an execution, whether it was modified or not. The keys found when iterating the
+	/// storage and the keys of the backend removed by prefix clears are recorded as well, the
+	/// keys only modified in the overlay before being cleared are not.
+	pub fn set_record_reads(&mut self, record_reads: bool) {
+		self.recorded_reads = record_reads.then(Default::default);
+	}
+
+	/// Take the keys read since recording was enabled with [`Self::set_record_reads`].
+	pub fn take_recorded_reads(&mut self) -> RecordedReads {
+		self.recorded_reads.as_mut().map(|reads| reads.take()).unwrap_or_default()
+	}
+
+	pub(crate) fn record_read(&self, child_info: Option<&ChildInfo>, key: &[u8]) {
+		if let Some(reads) = &self.recorded_reads {
+			reads
+				.borrow_mut()
+				.insert((child_info.map(|info| info.storage_key().to_vec()), key.to_vec()));
+		}
+	}
+
 	/// Returns a double-Option: None if the key is unknown (i.e. and the query should be referred
 	/// to the backend); Some(None) if the key has been deleted. Some(Some(...)) for a key whose
 	/// value has been set.
 	pub fn storage(&self, key: &[u8]) -> Option<Option<&[u8]>> {
+		self.record_read(None, key);
 		self.top.get(key).map(|x| {
 			let value = x.value();
 			let size_read = value.map(|x| x.len() as u64).unwrap_or(0);
@@ -255,6 +286,7 @@ impl OverlayedChanges {
 	/// to the backend); Some(None) if the key has been deleted. Some(Some(...)) for a key whose
 	/// value has been set.
 	pub fn child_storage(&self, child_info: &ChildInfo, key: &[u8]) -> Option<Option<&[u8]>> {
+		self.record_read(Some(child_info), key);
 		let map = self.children.get(child_info.storage_key())?;
 		let value = map.0.get(key)?.value();
 		let size_read = value.map(|x| x.len() as u64).unwrap_or(0);
@@ -876,6 +908,48 @@ mod tests {
 		assert_eq!(&ext.storage_root(state_version)[..], &ROOT);
 	}
 
+	#[test]
+	fn reads_are_recorded() {
+		let state_version = StateVersion::default();
+		let initial: BTreeMap<_, _> = vec![
+			(b"doe".to_vec(), b"reindeer".to_vec()),
+			(b"horse".to_vec(), b"foal".to_vec()),
+			(b"hound".to_vec(), b"pup".to_vec()),
+		]
+		.into_iter()
+		.collect();
+		let backend = InMemoryBackend::<Blake2Hasher>::from((initial, state_version));
+		let child_info = ChildInfo::new_default(b"child");
+		let mut overlay = OverlayedChanges::default();
+
+		overlay.set_storage(b"dog".to_vec(), Some(b"puppy".to_vec()));
+		overlay.set_record_reads(true);
+
+		let mut cache = StorageTransactionCache::default();
+		let mut ext = Ext::new(&mut overlay, &mut cache, &backend, None);
+		assert_eq!(ext.storage(b"doe"), Some(b"reindeer".to_vec()));
+		assert_eq!(ext.storage(b"dog"), Some(b"puppy".to_vec()));
+		assert!(!ext.exists_storage(b"doug"));
+		assert_eq!(ext.child_storage(&child_info, b"cat"), None);
+		assert_eq!(ext.next_storage_key(b"dog"), Some(b"horse".to_vec()));
+		ext.set_storage(b"mouse".to_vec(), b"squeak".to_vec());
+		let _ = ext.clear_prefix(b"hou", None, None);
+		drop(ext);
+
+		assert_eq!(
+			overlay.take_recorded_reads().into_iter().collect::<Vec<_>>(),
+			vec![
+				(None, b"doe".to_vec()),
+				(None, b"dog".to_vec()),
+				(None, b"doug".to_vec()),
+				(None, b"horse".to_vec()),
+				(None, b"hound".to_vec()),
+				(Some(b"child".to_vec()), b"cat".to_vec()),
+			],
+		);
+		assert!(overlay.take_recorded_reads().is_empty());
+	}
+
 	#[test]
 	fn extrinsic_changes_are_collected() {
 		let mut overlay = OverlayedChanges::default();
@@ -28,6 +28,7 @@ mod node_header;
 pub mod recorder;
 mod storage_proof;
 mod trie_codec;
+mod trie_diff;
 mod trie_stream;
 
 /// Our `NodeCodec`-specific error.
@@ -53,6 +54,7 @@ pub use trie_db::{
 	CError, DBValue, Query, Recorder, Trie, TrieCache, TrieConfiguration, TrieDBIterator,
 	TrieDBKeyIterator, TrieLayout, TrieMut, TrieRecorder,
 };
+pub use trie_diff::{diff_tries, TrieDiffEntry, TrieDiffError};
 /// The Substrate format implementation of `TrieStream`.
 pub use trie_stream::TrieStream;
 
@@ -0,0 +1,502 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Difference between two tries.
+//!
+//! Both tries are walked in lockstep and subtrees with the same node hash are skipped, thus the
+//! cost of a diff is proportional to the number of changed nodes and not to the size of the
+//! tries.
+
+use crate::{node_codec::NodeCodec, LayoutV1, TrieError};
+use hash_db::{HashDBRef, Hasher};
+use sp_std::{boxed::Box, rc::Rc, vec::Vec};
+use trie_db::{
+	nibble_ops,
+	node::{NodeHandlePlan, NodePlan, ValuePlan},
+	DBValue, NodeCodec as NodeCodecT,
+};
+
+/// Error returned when computing the difference between two tries.
+pub type TrieDiffError<H> = Box<TrieError<LayoutV1<H>>>;
+
+/// A key whose value differs between two tries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieDiffEntry {
+	/// The key of the entry.
+	pub key: Vec<u8>,
+	/// The value in the old trie, `None` if the key was added.
+	pub old_value: Option<Vec<u8>>,
+	/// The value in the new trie, `None` if the key was removed.
+	pub new_value: Option<Vec<u8>>,
+}
+
+/// Reference to a trie node.
+#[derive(Clone, PartialEq)]
+enum NodeRef<H> {
+	Hash(H),
+	Inline(Vec<u8>),
+}
+
+/// Value stored in a trie node.
+#[derive(Clone, PartialEq)]
+enum NodeValue<H> {
+	Inline(Vec<u8>),
+	Node(H),
+}
+
+/// A decoded trie node, with its partial key expanded to one nibble per byte.
+struct OwnedNode<H> {
+	partial: Vec<u8>,
+	value: Option<NodeValue<H>>,
+	children: Vec<Option<NodeRef<H>>>,
+}
+
+/// A position in a trie: a node and the number of nibbles of its partial key that are
+/// already part of the current path.
+struct Cursor<H> {
+	reference: NodeRef<H>,
+	node: Rc<OwnedNode<H>>,
+	consumed: usize,
+}
+
+impl<H: Clone> Cursor<H> {
+	/// The value stored at the current path.
+	fn value(&self) -> Option<&NodeValue<H>> {
+		if self.consumed == self.node.partial.len() {
+			self.node.value.as_ref()
+		} else {
+			None
+		}
+	}
+
+	/// The reference of the child node at `nibble`, if the cursor is at the end of the partial
+	/// key of its node.
+	fn child_ref(&self, nibble: u8) -> Option<Option<&NodeRef<H>>> {
+		if self.consumed == self.node.partial.len() {
+			Some(self.node.children[nibble as usize].as_ref())
+		} else {
+			None
+		}
+	}
+}
+
+/// Compute the difference between the trie at `old_root` and the trie at `new_root`.
+///
+/// Only keys starting with `prefix` and, if `start_at` is given, strictly greater than
+/// `start_at` are compared. `f` is called for every key that was added, removed or modified, in
+/// lexicographic order of the keys, until it returns `false`.
+pub fn diff_tries<H, A, B, F>(
+	old_db: &A,
+	old_root: &H::Out,
+	new_db: &B,
+	new_root: &H::Out,
+	prefix: &[u8],
+	start_at: Option<&[u8]>,
+	mut f: F,
+) -> Result<(), TrieDiffError<H>>
+where
+	H: Hasher,
+	A: HashDBRef<H, DBValue> + ?Sized,
+	B: HashDBRef<H, DBValue> + ?Sized,
+	F: FnMut(TrieDiffEntry) -> bool,
+{
+	let prefix = bytes_to_nibbles(prefix);
+	let start_at = start_at.map(bytes_to_nibbles);
+	let mut path = Vec::new();
+	let old = load(old_db, NodeRef::Hash(*old_root), &path)?;
+	let new = load(new_db, NodeRef::Hash(*new_root), &path)?;
+
+	diff(old_db, new_db, old, new, &prefix, start_at.as_deref(), &mut path, &mut f).map(|_| ())
+}
+
+/// Walk both subtrees at `path`.
+///
+/// `start_at` is only given while `path` is a prefix of it, i.e. while the keys below `path`
+/// are not all greater than `start_at`. Returns `false` once `f` asked to stop.
+fn diff<H, A, B, F>(
+	old_db: &A,
+	new_db: &B,
+	old: Option<Cursor<H::Out>>,
+	new: Option<Cursor<H::Out>>,
+	prefix: &[u8],
+	start_at: Option<&[u8]>,
+	path: &mut Vec<u8>,
+	f: &mut F,
+) -> Result<bool, TrieDiffError<H>>
+where
+	H: Hasher,
+	A: HashDBRef<H, DBValue> + ?Sized,
+	B: HashDBRef<H, DBValue> + ?Sized,
+	F: FnMut(TrieDiffEntry) -> bool,
+{
+	match (&old, &new) {
+		(None, None) => return Ok(true),
+		// Identical subtrees.
+		(Some(old), Some(new))
+			if old.consumed == 0 && new.consumed == 0 && old.reference == new.reference =>
+			return Ok(true),
+		_ => {},
+	}
+
+	// The key at `path` is not greater than `start_at` as long as it is a prefix of it.
+	if path.len() >= prefix.len() && start_at.is_none() {
+		let old_value = old.as_ref().and_then(|cursor| cursor.value());
+		let new_value = new.as_ref().and_then(|cursor| cursor.value());
+		if old_value != new_value {
+			let old_value = old_value.map(|value| load_value(old_db, value, path)).transpose()?;
+			let new_value = new_value.map(|value| load_value(new_db, value, path)).transpose()?;
+			if old_value != new_value &&
+				!f(TrieDiffEntry { key: nibbles_to_bytes(path), old_value, new_value })
+			{
+				return Ok(false)
+			}
+		}
+	}
+
+	let start_nibble = start_at.and_then(|start_at| start_at.get(path.len())).copied();
+	let nibbles = match prefix.get(path.len()) {
+		Some(nibble) => *nibble..*nibble + 1,
+		None => 0..nibble_ops::NIBBLE_LENGTH as u8,
+	};
+	for nibble in nibbles.filter(|nibble| start_nibble.map_or(true, |start| *nibble >= start)) {
+		// Skip children that are the same node in both tries, without loading them.
+		if let (Some(old), Some(new)) = (&old, &new) {
+			if let (Some(old_child), Some(new_child)) =
+				(old.child_ref(nibble), new.child_ref(nibble))
+			{
+				if old_child == new_child {
+					continue
+				}
+			}
+		}
+
+		let old_child = child(old_db, old.as_ref(), nibble, path)?;
+		let new_child = child(new_db, new.as_ref(), nibble, path)?;
+		if old_child.is_none() && new_child.is_none() {
+			continue
+		}
+
+		let child_start_at = start_at.filter(|_| start_nibble == Some(nibble));
+		path.push(nibble);
+		let proceed = diff(old_db, new_db, old_child, new_child, prefix, child_start_at, path, f)?;
+		path.pop();
+		if !proceed {
+			return Ok(false)
+		}
+	}
+
+	Ok(true)
+}
+
+/// Move the cursor one nibble down the trie.
+fn child<H, DB>(
+	db: &DB,
+	cursor: Option<&Cursor<H::Out>>,
+	nibble: u8,
+	path: &[u8],
+) -> Result<Option<Cursor<H::Out>>, TrieDiffError<H>>
+where
+	H: Hasher,
+	DB: HashDBRef<H, DBValue> + ?Sized,
+{
+	let cursor = match cursor {
+		Some(cursor) => cursor,
+		None => return Ok(None),
+	};
+
+	if cursor.consumed < cursor.node.partial.len() {
+		if cursor.node.partial[cursor.consumed] != nibble {
+			return Ok(None)
+		}
+		return Ok(Some(Cursor {
+			reference: cursor.reference.clone(),
+			node: cursor.node.clone(),
+			consumed: cursor.consumed + 1,
+		}))
+	}
+
+	match &cursor.node.children[nibble as usize] {
+		Some(reference) => {
+			let mut child_path = path.to_vec();
+			child_path.push(nibble);
+			load(db, reference.clone(), &child_path)
+		},
+		None => Ok(None),
+	}
+}
+
+/// Load and decode the node at the given path.
+///
+/// Returns `None` for the empty trie.
+fn load<H, DB>(
+	db: &DB,
+	reference: NodeRef<H::Out>,
+	path: &[u8],
+) -> Result<Option<Cursor<H::Out>>, TrieDiffError<H>>
+where
+	H: Hasher,
+	DB: HashDBRef<H, DBValue> + ?Sized,
+{
+	let (hash, data) = match &reference {
+		NodeRef::Hash(hash) => {
+			if *hash == <NodeCodec<H> as NodeCodecT>::hashed_null_node() {
+				return Ok(None)
+			}
+			let (key, padded) = nibbles_to_prefix(path);
+			let data = db
+				.get(hash, (&key, padded))
+				.ok_or_else(|| Box::new(TrieError::<LayoutV1<H>>::IncompleteDatabase(*hash)))?;
+			(*hash, data)
+		},
+		NodeRef::Inline(data) => (H::hash(data), data.clone()),
+	};
+
+	let plan = <NodeCodec<H> as NodeCodecT>::decode_plan(&data)
+		.map_err(|e| Box::new(TrieError::<LayoutV1<H>>::DecoderError(hash, e)))?;
+	let (partial, value, children) = match plan {
+		NodePlan::Empty => return Ok(None),
+		NodePlan::Leaf { partial, value } => (partial, Some(value), Default::default()),
+		NodePlan::NibbledBranch { partial, value, children } => (partial, value, children),
+		NodePlan::Branch { .. } | NodePlan::Extension { .. } => unreachable!("No extension codec."),
+	};
+
+	let partial = partial.build(&data);
+	let node = OwnedNode {
+		partial: (0..partial.len()).map(|i| partial.at(i)).collect(),
+		value: value.map(|value| match value {
+			ValuePlan::Inline(range) => NodeValue::Inline(data[range].to_vec()),
+			ValuePlan::Node(range) => NodeValue::Node(decode_hash::<H>(&data[range])),
+		}),
+		children: children
+			.iter()
+			.map(|child| {
+				child.as_ref().map(|child| match child {
+					NodeHandlePlan::Hash(range) =>
+						NodeRef::Hash(decode_hash::<H>(&data[range.clone()])),
+					NodeHandlePlan::Inline(range) => NodeRef::Inline(data[range.clone()].to_vec()),
+				})
+			})
+			.collect(),
+	};
+
+	Ok(Some(Cursor { reference, node: Rc::new(node), consumed: 0 }))
+}
+
+/// Resolve a value, loading it from the database if it is stored in its own node.
+fn load_value<H, DB>(
+	db: &DB,
+	value: &NodeValue<H::Out>,
+	path: &[u8],
+) -> Result<Vec<u8>, TrieDiffError<H>>
+where
+	H: Hasher,
+	DB: HashDBRef<H, DBValue> + ?Sized,
+{
+	match value {
+		NodeValue::Inline(value) => Ok(value.clone()),
+		NodeValue::Node(hash) => {
+			let (key, padded) = nibbles_to_prefix(path);
+			db.get(hash, (&key, padded))
+				.ok_or_else(|| Box::new(TrieError::<LayoutV1<H>>::IncompleteDatabase(*hash)))
+		},
+	}
+}
+
+fn decode_hash<H: Hasher>(data: &[u8]) -> H::Out {
+	let mut hash = H::Out::default();
+	hash.as_mut().copy_from_slice(data);
+	hash
+}
+
+/// Unpack bytes into a path of nibbles.
+fn bytes_to_nibbles(bytes: &[u8]) -> Vec<u8> {
+	bytes
+		.iter()
+		.flat_map(|byte| [byte >> nibble_ops::BIT_PER_NIBBLE, byte & nibble_ops::PADDING_BITMASK])
+		.collect()
+}
+
+/// Pack a path of nibbles into bytes.
+fn nibbles_to_bytes(path: &[u8]) -> Vec<u8> {
+	path.chunks(nibble_ops::NIBBLE_PER_BYTE)
+		.map(|pair| {
+			(pair[0] << nibble_ops::BIT_PER_NIBBLE) | pair.get(1).copied().unwrap_or_default()
+		})
+		.collect()
+}
+
+/// Convert a path of nibbles into the database prefix of the node at that path.
+fn nibbles_to_prefix(path: &[u8]) -> (Vec<u8>, Option<u8>) {
+	let even = path.len() - path.len() % nibble_ops::NIBBLE_PER_BYTE;
+	let padded = path.get(even).map(|nibble| nibble << nibble_ops::BIT_PER_NIBBLE);
+	(nibbles_to_bytes(&path[..even]), padded)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{PrefixedMemoryDB, TrieDBMutBuilder};
+	use sp_core::Blake2Hasher;
+	use trie_db::TrieMut;
+
+	type Layout = LayoutV1<Blake2Hasher>;
+
+	fn build(
+		db: &mut PrefixedMemoryDB<Blake2Hasher>,
+		entries: &[(Vec<u8>, Vec<u8>)],
+	) -> sp_core::H256 {
+		let mut root = Default::default();
+		{
+			let mut trie = TrieDBMutBuilder::<Layout>::new(db, &mut root).build();
+			for (key, value) in entries {
+				trie.insert(key, value).unwrap();
+			}
+		}
+		root
+	}
+
+	fn diff_of(
+		old: &[(Vec<u8>, Vec<u8>)],
+		new: &[(Vec<u8>, Vec<u8>)],
+		prefix: &[u8],
+	) -> Vec<TrieDiffEntry> {
+		paged_diff_of(old, new, prefix, None, usize::MAX)
+	}
+
+	fn paged_diff_of(
+		old: &[(Vec<u8>, Vec<u8>)],
+		new: &[(Vec<u8>, Vec<u8>)],
+		prefix: &[u8],
+		start_at: Option<&[u8]>,
+		limit: usize,
+	) -> Vec<TrieDiffEntry> {
+		let mut db = PrefixedMemoryDB::default();
+		let old_root = build(&mut db, old);
+		let new_root = build(&mut db, new);
+
+		let mut diff = Vec::new();
+		diff_tries::<Blake2Hasher, _, _, _>(
+			&db,
+			&old_root,
+			&db,
+			&new_root,
+			prefix,
+			start_at,
+			|entry| {
+				diff.push(entry);
+				diff.len() < limit
+			},
+		)
+		.unwrap();
+		diff
+	}
+
+	fn entry(key: &[u8], old: Option<&[u8]>, new: Option<&[u8]>) -> TrieDiffEntry {
+		TrieDiffEntry {
+			key: key.to_vec(),
+			old_value: old.map(|v| v.to_vec()),
+			new_value: new.map(|v| v.to_vec()),
+		}
+	}
+
+	#[test]
+	fn diff_of_same_trie_is_empty() {
+		let entries = vec![(b"alpha".to_vec(), b"1".to_vec()), (b"beta".to_vec(), b"2".to_vec())];
+		assert!(diff_of(&entries, &entries, &[]).is_empty());
+	}
+
+	#[test]
+	fn diff_reports_added_removed_and_modified_keys() {
+		let long_value = vec![7u8; 64];
+		let old = vec![
+			(b"alpha".to_vec(), b"1".to_vec()),
+			(b"alphabet".to_vec(), b"2".to_vec()),
+			(b"beta".to_vec(), long_value.clone()),
+			(b"gamma".to_vec(), b"3".to_vec()),
+		];
+		let new = vec![
+			(b"alpha".to_vec(), b"1".to_vec()),
+			(b"alphabet".to_vec(), b"4".to_vec()),
+			(b"alpine".to_vec(), b"5".to_vec()),
+			(b"beta".to_vec(), vec![8u8; 64]),
+		];
+
+		assert_eq!(
+			diff_of(&old, &new, &[]),
+			vec![
+				entry(b"alphabet", Some(b"2"), Some(b"4")),
+				entry(b"alpine", None, Some(b"5")),
+				entry(b"beta", Some(&long_value), Some(&[8u8; 64])),
+				entry(b"gamma", Some(b"3"), None),
+			],
+		);
+
+		assert_eq!(
+			diff_of(&old, &new, b"alp"),
+			vec![entry(b"alphabet", Some(b"2"), Some(b"4")), entry(b"alpine", None, Some(b"5"))],
+		);
+		assert!(diff_of(&old, &new, b"delta").is_empty());
+	}
+
+	#[test]
+	fn diff_against_empty_trie() {
+		let entries = vec![(b"a".to_vec(), b"1".to_vec()), (b"ab".to_vec(), b"2".to_vec())];
+
+		assert_eq!(
+			diff_of(&[], &entries, &[]),
+			vec![entry(b"a", None, Some(b"1")), entry(b"ab", None, Some(b"2"))],
+		);
+		assert_eq!(
+			diff_of(&entries, &[], &[]),
+			vec![entry(b"a", Some(b"1"), None), entry(b"ab", Some(b"2"), None)],
+		);
+	}
+
+	#[test]
+	fn diff_in_pages() {
+		let old = vec![
+			(b"a".to_vec(), b"1".to_vec()),
+			(b"ab".to_vec(), b"2".to_vec()),
+			(b"b".to_vec(), b"3".to_vec()),
+		];
+		let new = vec![
+			(b"a".to_vec(), b"4".to_vec()),
+			(b"ab".to_vec(), b"5".to_vec()),
+			(b"abc".to_vec(), b"6".to_vec()),
+			(b"c".to_vec(), b"7".to_vec()),
+		];
+
+		assert_eq!(
+			paged_diff_of(&old, &new, &[], None, 2),
+			vec![entry(b"a", Some(b"1"), Some(b"4")), entry(b"ab", Some(b"2"), Some(b"5"))],
+		);
+		assert_eq!(
+			paged_diff_of(&old, &new, &[], Some(b"ab"), 2),
+			vec![entry(b"abc", None, Some(b"6")), entry(b"b", Some(b"3"), None)],
+		);
+		assert_eq!(
+			paged_diff_of(&old, &new, &[], Some(b"b"), 2),
+			vec![entry(b"c", None, Some(b"7"))],
+		);
+		// The start key doesn't need to exist in either trie.
+		assert_eq!(
+			paged_diff_of(&old, &new, b"a", Some(b"aa"), 10),
+			vec![entry(b"ab", Some(b"2"), Some(b"5")), entry(b"abc", None, Some(b"6"))],
+		);
+		assert!(paged_diff_of(&old, &new, b"a", Some(b"b"), 10).is_empty());
+	}
+}
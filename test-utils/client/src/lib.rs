@@ -23,7 +23,7 @@ pub mod client_ext;
 
 pub use self::client_ext::{ClientBlockImportExt, ClientExt};
 pub use sc_client_api::{
-	execution_extensions::{ExecutionExtensions, ExecutionStrategies},
+	execution_extensions::{ExecutionExtensions, ExecutionStrategies, ExecutionTimeouts},
 	BadBlocks, ForkBlocks,
 };
 pub use sc_client_db::{self, Backend};
@@ -75,6 +75,7 @@ pub struct TestClientBuilder<Block: BlockT, ExecutorDispatch, Backend, G: Genesi
 	bad_blocks: BadBlocks<Block>,
 	enable_offchain_indexing_api: bool,
 	no_genesis: bool,
+	execution_timeouts: ExecutionTimeouts,
 }
 
 impl<Block: BlockT, ExecutorDispatch, G: GenesisInit> Default
@@ -123,6 +124,7 @@ impl<Block: BlockT, ExecutorDispatch, Backend, G: GenesisInit>
 			bad_blocks: None,
 			enable_offchain_indexing_api: false,
 			no_genesis: false,
+			execution_timeouts: Default::default(),
 		}
 	}
 
@@ -192,6 +194,12 @@ impl<Block: BlockT, ExecutorDispatch, Backend, G: GenesisInit>
 		self
 	}
 
+	/// Set the timeouts of the runtime calls made by offchain workers and RPC.
+	pub fn set_execution_timeouts(mut self, timeouts: ExecutionTimeouts) -> Self {
+		self.execution_timeouts = timeouts;
+		self
+	}
+
 	/// Build the test client with the given native executor.
 	pub fn build_with_executor<RuntimeApi>(
 		self,
@@ -232,7 +240,8 @@ impl<Block: BlockT, ExecutorDispatch, Backend, G: GenesisInit>
 				self.execution_strategies,
 				self.keystore,
 				sc_offchain::OffchainDb::factory_from_backend(&*self.backend),
-			),
+			)
+			.with_timeouts(self.execution_timeouts),
 			None,
 			None,
 			ClientConfig {
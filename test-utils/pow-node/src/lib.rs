@@ -0,0 +1,412 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A proof-of-work test node.
+//!
+//! Mines blocks of the [`substrate_test_pow_runtime`] with the [`CpuMiner`]. The difficulty of
+//! every block is computed by `pallet-pow-difficulty` and read through the `DifficultyApi`.
+
+#![warn(missing_docs)]
+
+use codec::Encode;
+use futures::Future;
+use sc_consensus::{BasicQueue, LongestChain};
+use sc_consensus_pow::{
+	CpuMiner, CpuMinerConfig, CpuMiningAlgorithm, Error, MiningMetadata, PowAlgorithm,
+	PowBlockImport,
+};
+use sp_api::{ProvideRuntimeApi, TransactionFor};
+use sp_consensus::{AlwaysCanAuthor, NoNetwork};
+use sp_consensus_pow::{DifficultyApi, Seal};
+use sp_core::{hashing::blake2_256, traits::SpawnEssentialNamed, U256};
+use sp_runtime::generic::BlockId;
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+use substrate_test_client::{client, sc_executor, GenesisInit, NativeElseWasmExecutor, Storage};
+use substrate_test_pow_runtime::{BuildStorage, Difficulty, Hash};
+
+pub use substrate_test_pow_runtime as runtime;
+pub use substrate_test_pow_runtime::Block;
+
+/// How long the mining worker waits for a block import before proposing a new block anyway.
+const PROPOSAL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long the proposer may take to build a block.
+const BUILD_TIME: Duration = Duration::from_secs(10);
+
+/// A unit struct which implements `NativeExecutionDispatch` feeding in the PoW test runtime.
+pub struct LocalExecutorDispatch;
+
+impl sc_executor::NativeExecutionDispatch for LocalExecutorDispatch {
+	type ExtendHostFunctions = ();
+
+	fn dispatch(method: &str, data: &[u8]) -> Option<Vec<u8>> {
+		runtime::api::dispatch(method, data)
+	}
+
+	fn native_version() -> sc_executor::NativeVersion {
+		runtime::native_version()
+	}
+}
+
+/// Test node database backend.
+pub type Backend = substrate_test_client::Backend<Block>;
+
+/// Test node executor.
+pub type Executor =
+	client::LocalCallExecutor<Block, Backend, NativeElseWasmExecutor<LocalExecutorDispatch>>;
+
+/// Test node client.
+pub type Client = client::Client<Backend, Executor, Block, runtime::RuntimeApi>;
+
+/// Genesis parameters of the PoW test runtime.
+#[derive(Default)]
+pub struct GenesisParameters {
+	/// Difficulty of the first block, raised to the minimum difficulty of the runtime.
+	pub initial_difficulty: Difficulty,
+}
+
+impl GenesisInit for GenesisParameters {
+	fn genesis_storage(&self) -> Storage {
+		runtime::GenesisConfig {
+			system: runtime::SystemConfig {
+				code: runtime::WASM_BINARY
+					.expect("Development wasm binary is not available")
+					.to_vec(),
+			},
+			pow_difficulty: runtime::PowDifficultyConfig {
+				initial_difficulty: self.initial_difficulty,
+			},
+		}
+		.build_storage()
+		.expect("Builds the genesis storage of the PoW test runtime")
+	}
+}
+
+/// A `TestClientBuilder` for the PoW test runtime.
+pub type TestClientBuilder =
+	substrate_test_client::TestClientBuilder<Block, Executor, Backend, GenesisParameters>;
+
+/// Create a new client, starting with the given difficulty.
+pub fn new_client(initial_difficulty: Difficulty) -> (Arc<Client>, LongestChain<Backend, Block>) {
+	let mut builder = TestClientBuilder::with_default_backend();
+	builder.genesis_init_mut().initial_difficulty = initial_difficulty;
+	let (client, select_chain): (Client, _) = builder.build_with_native_executor(None);
+
+	(Arc::new(client), select_chain)
+}
+
+/// Proof-of-work algorithm of the test node.
+///
+/// A seal is the encoded nonce. It is valid if `blake2_256(pre_hash ++ seal)` multiplied by the
+/// difficulty doesn't overflow.
+pub struct Blake2Algorithm<C> {
+	client: Arc<C>,
+}
+
+impl<C> Blake2Algorithm<C> {
+	/// Create a new algorithm reading the difficulty from the runtime of `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+// Manual implementation to not require `C: Clone`.
+impl<C> Clone for Blake2Algorithm<C> {
+	fn clone(&self) -> Self {
+		Self { client: self.client.clone() }
+	}
+}
+
+/// Check whether `seal` satisfies `difficulty` for the block with the given pre-hash.
+fn seal_meets_difficulty(pre_hash: &Hash, seal: &Seal, difficulty: Difficulty) -> bool {
+	let hash = blake2_256(&(pre_hash, seal).encode());
+	!U256::from(&hash[..]).overflowing_mul(difficulty).1
+}
+
+impl<C> PowAlgorithm<Block> for Blake2Algorithm<C>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: DifficultyApi<Block, Difficulty>,
+{
+	type Difficulty = Difficulty;
+
+	fn difficulty(&self, parent: Hash) -> Result<Difficulty, Error<Block>> {
+		self.client.runtime_api().difficulty(&BlockId::hash(parent)).map_err(|err| {
+			Error::Environment(format!("Fetching difficulty from runtime failed: {:?}", err))
+		})
+	}
+
+	fn verify(
+		&self,
+		_parent: &BlockId<Block>,
+		pre_hash: &Hash,
+		_pre_digest: Option<&[u8]>,
+		seal: &Seal,
+		difficulty: Difficulty,
+	) -> Result<bool, Error<Block>> {
+		Ok(seal_meets_difficulty(pre_hash, seal, difficulty))
+	}
+}
+
+impl<C> CpuMiningAlgorithm<Block> for Blake2Algorithm<C>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: DifficultyApi<Block, Difficulty>,
+{
+	fn try_nonce(
+		&self,
+		metadata: &MiningMetadata<Hash, Difficulty>,
+		nonce: u64,
+	) -> Result<Option<Seal>, Error<Block>> {
+		let seal = nonce.encode();
+		Ok(seal_meets_difficulty(&metadata.pre_hash, &seal, metadata.difficulty).then(|| seal))
+	}
+}
+
+/// Inherent data providers of the blocks built and imported by the test node.
+async fn create_inherent_data_providers(
+	_parent: Hash,
+	_: (),
+) -> Result<sp_timestamp::InherentDataProvider, Box<dyn std::error::Error + Send + Sync>> {
+	Ok(sp_timestamp::InherentDataProvider::from_system_time())
+}
+
+/// Create the PoW block import of the test node.
+fn block_import(
+	client: Arc<Client>,
+	select_chain: LongestChain<Backend, Block>,
+) -> PowBlockImport<
+	Block,
+	Arc<Client>,
+	Client,
+	LongestChain<Backend, Block>,
+	Blake2Algorithm<Client>,
+	AlwaysCanAuthor,
+	impl sp_inherents::CreateInherentDataProviders<Block, ()> + Send + Sync,
+> {
+	PowBlockImport::new(
+		client.clone(),
+		client.clone(),
+		Blake2Algorithm::new(client),
+		0,
+		select_chain,
+		create_inherent_data_providers,
+		AlwaysCanAuthor,
+	)
+}
+
+/// Start mining blocks on top of the best block of `client`.
+///
+/// Returns the miner, which stops mining when dropped, and the future driving the mining worker,
+/// which has to be polled for blocks to be proposed and imported.
+pub fn start_mining(
+	client: Arc<Client>,
+	select_chain: LongestChain<Backend, Block>,
+	config: CpuMinerConfig,
+) -> std::io::Result<(CpuMiner, impl Future<Output = ()>)> {
+	let spawner = sp_core::testing::TaskExecutor::new();
+	let pool = sc_transaction_pool::BasicPool::new_full(
+		Default::default(),
+		true.into(),
+		None,
+		spawner.clone(),
+		client.clone(),
+	);
+	let proposer_factory =
+		sc_basic_authorship::ProposerFactory::new(spawner, client.clone(), pool, None, None);
+	let algorithm = Blake2Algorithm::new(client.clone());
+
+	let (handle, worker) = sc_consensus_pow::start_mining_worker(
+		Box::new(block_import(client.clone(), select_chain.clone())),
+		client,
+		select_chain,
+		algorithm.clone(),
+		proposer_factory,
+		NoNetwork,
+		(),
+		None,
+		create_inherent_data_providers,
+		PROPOSAL_TIMEOUT,
+		BUILD_TIME,
+		AlwaysCanAuthor,
+	);
+	let miner = CpuMiner::start(handle, algorithm, config, None)?;
+
+	Ok((miner, worker))
+}
+
+/// Create an import queue for blocks mined by other nodes.
+///
+/// If `verification_workers` is given, the seals of the blocks are checked on that many
+/// background tasks ahead of their import.
+pub fn import_queue(
+	client: Arc<Client>,
+	select_chain: LongestChain<Backend, Block>,
+	verification_workers: Option<NonZeroUsize>,
+	spawner: &impl SpawnEssentialNamed,
+) -> Result<BasicQueue<Block, TransactionFor<Client, Block>>, sp_consensus::Error> {
+	let algorithm = Blake2Algorithm::new(client.clone());
+	let block_import = Box::new(block_import(client, select_chain));
+
+	match verification_workers {
+		Some(workers) => sc_consensus_pow::import_queue_with_verification_workers(
+			block_import,
+			None,
+			algorithm,
+			workers,
+			spawner,
+			None,
+		),
+		None => sc_consensus_pow::import_queue(block_import, None, algorithm, spawner, None),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::{executor::block_on, future, StreamExt};
+	use sc_client_api::{BlockBackend, BlockchainEvents};
+	use sc_consensus::{BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link};
+	use sc_consensus_pow::PowAux;
+	use sp_blockchain::HeaderBackend;
+	use sp_consensus::BlockOrigin;
+	use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+	use std::task::Poll;
+
+	const INITIAL_DIFFICULTY: u64 = 1_000;
+
+	fn miner_config() -> CpuMinerConfig {
+		CpuMinerConfig {
+			threads: NonZeroUsize::new(2).expect("2 is not zero; qed"),
+			nonces_per_round: 1_000,
+		}
+	}
+
+	/// Mine blocks on `client` until its best block is at least `number`.
+	fn mine_until(client: &Arc<Client>, select_chain: &LongestChain<Backend, Block>, number: u32) {
+		let (miner, worker) =
+			start_mining(client.clone(), select_chain.clone(), miner_config()).unwrap();
+		let mut notifications = client.import_notification_stream();
+
+		block_on(async {
+			let imported = async {
+				while let Some(notification) = notifications.next().await {
+					if *notification.header.number() >= number {
+						break
+					}
+				}
+			};
+			futures::pin_mut!(worker, imported);
+			future::select(worker, imported).await;
+		});
+
+		assert!(miner.hashes() > 0);
+		miner.stop();
+	}
+
+	#[test]
+	fn mines_blocks_with_the_difficulty_of_the_runtime() {
+		let (client, select_chain) = new_client(INITIAL_DIFFICULTY.into());
+		mine_until(&client, &select_chain, 5);
+
+		let genesis_hash = client.info().genesis_hash;
+		let mut parent_hash = genesis_hash;
+		for number in 1..=5u32 {
+			let hash = client.hash(number).unwrap().unwrap();
+			let header = client.header(&BlockId::Hash(hash)).unwrap().unwrap();
+			assert_eq!(*header.parent_hash(), parent_hash);
+
+			// Every block is mined with the difficulty the runtime computed at its parent.
+			let expected = client.runtime_api().difficulty(&BlockId::Hash(parent_hash)).unwrap();
+			let aux = PowAux::<Difficulty>::read::<_, Block>(&*client, &hash).unwrap();
+			assert_eq!(aux.difficulty, expected);
+
+			parent_hash = hash;
+		}
+
+		// Blocks are mined much faster than the target block time, which raises the difficulty.
+		let best_difficulty = client.runtime_api().difficulty(&BlockId::Hash(parent_hash)).unwrap();
+		assert!(best_difficulty > INITIAL_DIFFICULTY.into());
+	}
+
+	#[derive(Default)]
+	struct TestLink {
+		results: Vec<Result<BlockImportStatus<u32>, BlockImportError>>,
+	}
+
+	impl Link<Block> for TestLink {
+		fn blocks_processed(
+			&mut self,
+			_imported: usize,
+			_count: usize,
+			results: Vec<(Result<BlockImportStatus<u32>, BlockImportError>, Hash)>,
+		) {
+			self.results.extend(results.into_iter().map(|(result, _)| result));
+		}
+	}
+
+	#[test]
+	fn imports_mined_blocks_with_verification_workers() {
+		let (miner_client, miner_select_chain) = new_client(INITIAL_DIFFICULTY.into());
+		mine_until(&miner_client, &miner_select_chain, 5);
+
+		let blocks = (1..=5u32)
+			.map(|number| {
+				let hash = miner_client.hash(number).unwrap().unwrap();
+				let (header, extrinsics) =
+					miner_client.block(&BlockId::Hash(hash)).unwrap().unwrap().block.deconstruct();
+
+				IncomingBlock {
+					hash,
+					header: Some(header),
+					body: Some(extrinsics),
+					indexed_body: None,
+					justifications: None,
+					origin: None,
+					allow_missing_state: false,
+					import_existing: false,
+					state: None,
+					skip_execution: false,
+				}
+			})
+			.collect::<Vec<_>>();
+
+		let (client, select_chain) = new_client(INITIAL_DIFFICULTY.into());
+		let mut queue = import_queue(
+			client.clone(),
+			select_chain,
+			Some(NonZeroUsize::new(4).expect("4 is not zero; qed")),
+			&sp_core::testing::TaskExecutor::new(),
+		)
+		.unwrap();
+		queue.import_blocks(BlockOrigin::NetworkInitialSync, blocks);
+
+		let mut link = TestLink::default();
+		block_on(future::poll_fn(|cx| {
+			queue.poll_actions(cx, &mut link);
+
+			if link.results.len() < 5 {
+				Poll::Pending
+			} else {
+				Poll::Ready(())
+			}
+		}));
+
+		assert!(link.results.iter().all(|result| result.is_ok()));
+		assert_eq!(client.info().best_hash, miner_client.info().best_hash);
+	}
+}
@@ -374,6 +374,8 @@ cfg_if! {
 				fn test_multiple_arguments(data: Vec<u8>, other: Vec<u8>, num: u32);
 				/// Traces log "Hey I'm runtime."
 				fn do_trace_log();
+				/// Never returns.
+				fn run_forever();
 			}
 		}
 	} else {
@@ -424,6 +426,8 @@ cfg_if! {
 				fn test_multiple_arguments(data: Vec<u8>, other: Vec<u8>, num: u32);
 				/// Traces log "Hey I'm runtime."
 				fn do_trace_log();
+				/// Never returns.
+				fn run_forever();
 			}
 		}
 	}
@@ -706,7 +710,8 @@ cfg_if! {
 
 			impl sp_api::Metadata<Block> for Runtime {
 				fn metadata() -> OpaqueMetadata {
-					unimplemented!()
+					// Only the metadata of the runtime apis is appended.
+					OpaqueMetadata::new(Vec::new())
 				}
 			}
 
@@ -833,6 +838,12 @@ cfg_if! {
 				fn do_trace_log() {
 					log::trace!("Hey I'm runtime");
 				}
+
+				fn run_forever() {
+					loop {
+						core::hint::spin_loop();
+					}
+				}
 			}
 
 			impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
@@ -957,6 +968,32 @@ cfg_if! {
 					0
 				}
 			}
+
+			impl frame_system_rpc_runtime_api::DryRunApi<Block, AccountId, u64> for Runtime {
+				fn dry_run_extrinsic(
+					extrinsic: <Block as BlockT>::Extrinsic,
+				) -> frame_system_rpc_runtime_api::DryRunEffects<u64> {
+					frame_system_rpc_runtime_api::DryRunEffects {
+						result: system::execute_transaction(extrinsic).encode(),
+						events: Vec::new(),
+						weight: 0,
+						fee: 0,
+					}
+				}
+
+				fn dry_run_call(
+					origin: AccountId,
+					call: Vec<u8>,
+				) -> frame_system_rpc_runtime_api::DryRunEffects<u64> {
+					// Transfers are the only calls.
+					frame_system_rpc_runtime_api::DryRunEffects {
+						result: system::dispatch_transfer(&origin, &call).encode(),
+						events: Vec::new(),
+						weight: 0,
+						fee: 0,
+					}
+				}
+			}
 		}
 	} else {
 		impl_runtime_apis! {
@@ -976,7 +1013,8 @@ cfg_if! {
 
 			impl sp_api::Metadata<Block> for Runtime {
 				fn metadata() -> OpaqueMetadata {
-					unimplemented!()
+					// Only the metadata of the runtime apis is appended.
+					OpaqueMetadata::new(Vec::new())
 				}
 			}
 
@@ -1107,6 +1145,12 @@ cfg_if! {
 				fn do_trace_log() {
 					log::trace!("Hey I'm runtime: {}", log::STATIC_MAX_LEVEL);
 				}
+
+				fn run_forever() {
+					loop {
+						core::hint::spin_loop();
+					}
+				}
 			}
 
 			impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
@@ -1188,6 +1232,32 @@ cfg_if! {
 					0
 				}
 			}
+
+			impl frame_system_rpc_runtime_api::DryRunApi<Block, AccountId, u64> for Runtime {
+				fn dry_run_extrinsic(
+					extrinsic: <Block as BlockT>::Extrinsic,
+				) -> frame_system_rpc_runtime_api::DryRunEffects<u64> {
+					frame_system_rpc_runtime_api::DryRunEffects {
+						result: system::execute_transaction(extrinsic).encode(),
+						events: Vec::new(),
+						weight: 0,
+						fee: 0,
+					}
+				}
+
+				fn dry_run_call(
+					origin: AccountId,
+					call: Vec<u8>,
+				) -> frame_system_rpc_runtime_api::DryRunEffects<u64> {
+					// Transfers are the only calls.
+					frame_system_rpc_runtime_api::DryRunEffects {
+						result: system::dispatch_transfer(&origin, &call).encode(),
+						events: Vec::new(),
+						weight: 0,
+						fee: 0,
+					}
+				}
+			}
 		}
 	}
 }
@@ -22,7 +22,7 @@ use crate::{
 	AccountId, AuthorityId, Block, BlockNumber, Digest, Extrinsic, Header, Transfer, H256 as Hash,
 };
 use codec::{Decode, Encode, KeyedVec};
-use frame_support::{decl_module, decl_storage, storage};
+use frame_support::{decl_module, decl_storage, dispatch::DispatchResultWithPostInfo, storage};
 use frame_system::Config;
 use sp_core::storage::well_known_keys;
 use sp_io::{hashing::blake2_256, storage::root as storage_root, trie};
@@ -32,7 +32,7 @@ use sp_runtime::{
 	transaction_validity::{
 		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
 	},
-	ApplyExtrinsicResult,
+	ApplyExtrinsicResult, DispatchError,
 };
 use sp_std::prelude::*;
 
@@ -192,6 +192,22 @@ pub fn execute_transaction(utx: Extrinsic) -> ApplyExtrinsicResult {
 	result
 }
 
+/// Dispatch the SCALE encoded transfer `call` with a signed origin of `origin`.
+///
+/// The signature of the transfer is not checked.
+pub fn dispatch_transfer(origin: &AccountId, call: &[u8]) -> DispatchResultWithPostInfo {
+	let transfer = Transfer::decode(&mut &call[..])
+		.map_err(|_| DispatchError::Other("Unable to decode call"))?;
+	if transfer.from != *origin {
+		return Err(DispatchError::BadOrigin.into())
+	}
+
+	match execute_transfer_backend(&transfer) {
+		Ok(result) => result.map(|()| Default::default()).map_err(Into::into),
+		Err(err) => Err(DispatchError::Other(err.into()).into()),
+	}
+}
+
 /// Finalize the block.
 pub fn finalize_block() -> Header {
 	use sp_core::storage::StateVersion;
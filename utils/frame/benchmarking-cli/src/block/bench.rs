@@ -23,6 +23,7 @@ use frame_system::ConsumedWeight;
 use sc_block_builder::{BlockBuilderApi, BlockBuilderProvider};
 use sc_cli::{Error, Result};
 use sc_client_api::{Backend as ClientBackend, BlockBackend, StorageProvider, UsageProvider};
+use sc_executor::host_profiler;
 use sp_api::{ApiExt, Core, HeaderT, ProvideRuntimeApi};
 use sp_blockchain::Error::RuntimeApiError;
 use sp_runtime::{generic::BlockId, traits::Block as BlockT, DigestItem, OpaqueExtrinsic};
@@ -39,6 +40,9 @@ use crate::shared::{StatSelect, Stats};
 /// Log target for printing block weight info.
 const LOG_TARGET: &'static str = "benchmark::block::weight";
 
+/// Log target for printing the host function profile of a block.
+const PROFILE_LOG_TARGET: &'static str = "benchmark::block::host";
+
 /// Parameters for modifying the benchmark behaviour.
 #[derive(Debug, Default, Serialize, Clone, PartialEq, Args)]
 pub struct BenchmarkParams {
@@ -53,6 +57,14 @@ pub struct BenchmarkParams {
 	/// Number of times that the benchmark should be repeated for each block.
 	#[clap(long, default_value = "10")]
 	pub repeat: u32,
+
+	/// Profile the host functions that are called while executing each block.
+	///
+	/// Each block is executed one additional time with the profiler enabled, so that the
+	/// profiling overhead does not influence the measured execution time.
+	/// Only wasm execution is profiled, so this needs `--execution wasm`.
+	#[clap(long)]
+	pub profile_host_functions: bool,
 }
 
 /// Convenience closure for the [`Benchmark::run()`] function.
@@ -98,6 +110,10 @@ where
 			let took = self.measure_block(&block, &parent_num)?;
 
 			self.log_weight(i, block.extrinsics().len(), consumed, took);
+
+			if self.params.profile_host_functions {
+				self.profile_block(i, &block, &parent_num)?;
+			}
 		}
 
 		Ok(())
@@ -124,6 +140,40 @@ where
 		Ok(took)
 	}
 
+	/// Execute the block once with the host function profiler enabled and log the results.
+	fn profile_block(&self, num: u32, block: &Block, parent_num: &BlockId<Block>) -> Result<()> {
+		let runtime_api = self.client.runtime_api();
+		let (result, profile) =
+			host_profiler::profile(|| runtime_api.execute_block(&parent_num, block.clone()));
+		result.map_err(|e| Error::Client(RuntimeApiError(e)))?;
+
+		if profile.is_empty() {
+			warn!(
+				target: PROFILE_LOG_TARGET,
+				"Block {} did not call any host functions, was it executed natively?", num
+			);
+			return Ok(())
+		}
+
+		let mut profile = profile.into_iter().collect::<Vec<_>>();
+		profile.sort_by(|(_, a), (_, b)| b.time.cmp(&a.time));
+
+		info!(target: PROFILE_LOG_TARGET, "Host functions called by block {}:", num);
+		for (name, stats) in profile {
+			info!(
+				target: PROFILE_LOG_TARGET,
+				"{: <48} {: >10} calls {: >14} ns {: >14} bytes read {: >14} bytes written",
+				name,
+				stats.calls.separate_with_commas(),
+				(stats.time.as_nanos() as NanoSeconds).separate_with_commas(),
+				stats.bytes_read.separate_with_commas(),
+				stats.bytes_written.separate_with_commas(),
+			);
+		}
+
+		Ok(())
+	}
+
 	/// Returns the total nanoseconds of a [`frame_system::ConsumedWeight`] for a block number.
 	///
 	/// This is the post-dispatch corrected weight and is only available
@@ -0,0 +1,247 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry runs of extrinsics and calls with storage overrides.
+
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use serde::{Deserialize, Serialize};
+
+use sc_rpc_api::DenyUnsafe;
+use sp_api::{ApiError, ApiExt, CallApiAt, CallApiAtParams, ExecutionContext, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
+use sp_runtime::{generic::BlockId, traits};
+use sp_state_machine::OverlayedChanges;
+
+use crate::Error;
+pub use frame_system_rpc_runtime_api::{DryRunApi, DryRunEffects};
+
+/// What to dry run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DryRunInput<AccountId> {
+	/// A SCALE encoded extrinsic, applied like it would be in a block.
+	Extrinsic(Bytes),
+	/// A SCALE encoded runtime call, dispatched with a signed origin of `origin` without checking
+	/// the signature, nonce or fees.
+	Call {
+		/// Account the call is dispatched from.
+		origin: AccountId,
+		/// The SCALE encoded call.
+		call: Bytes,
+	},
+}
+
+/// A storage key accessed during a dry run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunStorageAccess {
+	/// Storage key of the child trie holding the key, absent for the main trie.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub child_trie: Option<Bytes>,
+	/// The accessed key.
+	pub key: StorageKey,
+	/// The value written, only set for writes.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub value: Option<Option<StorageData>>,
+}
+
+/// Effects of a dry run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunTrace<Balance> {
+	/// SCALE encoded `ApplyExtrinsicResult` for extrinsics and `DispatchResultWithPostInfo` for
+	/// calls.
+	pub result: Bytes,
+	/// SCALE encoded events emitted.
+	pub events: Bytes,
+	/// Weight actually consumed.
+	pub weight: u64,
+	/// Fee paid for extrinsics, or that would be paid for calls.
+	pub fee: Balance,
+	/// Keys read, including the keys read from the storage overrides.
+	pub storage_reads: Vec<DryRunStorageAccess>,
+	/// Keys written with the value written. Overridden keys are only reported if their value
+	/// changed.
+	pub storage_writes: Vec<DryRunStorageAccess>,
+}
+
+/// Dry run RPC methods.
+#[rpc(client, server)]
+pub trait SystemDryRunApi<BlockHash, AccountId, Balance> {
+	/// Dry run an extrinsic or a call on top of a block and report its effects.
+	///
+	/// The `overrides` are written to the storage before the dry run, `None` values remove the
+	/// key. If the block hash is not supplied the best block is used.
+	#[method(name = "system_dryRunWithTrace", blocking)]
+	fn dry_run_with_trace(
+		&self,
+		input: DryRunInput<AccountId>,
+		overrides: Option<Vec<(StorageKey, Option<StorageData>)>>,
+		at: Option<BlockHash>,
+	) -> RpcResult<DryRunTrace<Balance>>;
+}
+
+/// An implementation of the dry run RPC methods on full client.
+pub struct SystemDryRun<C, B> {
+	client: Arc<C>,
+	deny_unsafe: DenyUnsafe,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> SystemDryRun<C, B> {
+	/// Create new `SystemDryRun` given client.
+	pub fn new(client: Arc<C>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, deny_unsafe, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, AccountId, Balance>
+	SystemDryRunApiServer<<Block as traits::Block>::Hash, AccountId, Balance>
+	for SystemDryRun<C, Block>
+where
+	C: ProvideRuntimeApi<Block> + CallApiAt<Block>,
+	C: HeaderBackend<Block>,
+	C: Send + Sync + 'static,
+	C::Api: DryRunApi<Block, AccountId, Balance>,
+	Block: traits::Block,
+	AccountId: Encode + Decode + Send + 'static,
+	Balance: Encode + Decode + PartialEq + Send + 'static,
+{
+	fn dry_run_with_trace(
+		&self,
+		input: DryRunInput<AccountId>,
+		overrides: Option<Vec<(StorageKey, Option<StorageData>)>>,
+		at: Option<<Block as traits::Block>::Hash>,
+	) -> RpcResult<DryRunTrace<Balance>> {
+		self.deny_unsafe.check_if_safe()?;
+		let at = BlockId::<Block>::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let has_api = self
+			.client
+			.runtime_api()
+			.has_api::<dyn DryRunApi<Block, AccountId, Balance>>(&at)
+			.map_err(|e| runtime_error(e.to_string()))?;
+		if !has_api {
+			return Err(runtime_error(format!(
+				"Could not find `DryRunApi` api for block `{:?}`.",
+				at
+			)))
+		}
+
+		let (function, arguments) = match input {
+			DryRunInput::Extrinsic(extrinsic) => {
+				let uxt: <Block as traits::Block>::Extrinsic = Decode::decode(&mut &*extrinsic)
+					.map_err(|e| {
+						CallError::Custom(ErrorObject::owned(
+							Error::DecodeError.into(),
+							"Unable to dry run extrinsic",
+							Some(e.to_string()),
+						))
+					})?;
+				("DryRunApi_dry_run_extrinsic", uxt.encode())
+			},
+			DryRunInput::Call { origin, call } =>
+				("DryRunApi_dry_run_call", (origin, call.0).encode()),
+		};
+
+		let overrides = overrides
+			.unwrap_or_default()
+			.into_iter()
+			.map(|(key, value)| (key.0, value.map(|value| value.0)))
+			.collect::<HashMap<_, _>>();
+		let mut changes = OverlayedChanges::default();
+		for (key, value) in &overrides {
+			changes.set_storage(key.clone(), value.clone());
+		}
+		changes.set_record_reads(true);
+		let changes = RefCell::new(changes);
+
+		let effects = self
+			.client
+			.call_api_at(CallApiAtParams {
+				at: &at,
+				function,
+				native_call: None::<fn() -> Result<DryRunEffects<Balance>, ApiError>>,
+				arguments,
+				overlayed_changes: &changes,
+				storage_transaction_cache: &RefCell::new(Default::default()),
+				context: ExecutionContext::OffchainCall(None),
+				recorder: &None,
+			})
+			.map_err(|e| runtime_error(e.to_string()))?
+			.into_encoded();
+		let effects = DryRunEffects::<Balance>::decode(&mut &effects[..])
+			.map_err(|e| runtime_error(e.to_string()))?;
+
+		let mut changes = changes.into_inner();
+		let storage_reads = changes
+			.take_recorded_reads()
+			.into_iter()
+			.map(|(child_trie, key)| DryRunStorageAccess {
+				child_trie: child_trie.map(Into::into),
+				key: StorageKey(key),
+				value: None,
+			})
+			.collect();
+
+		let top_writes = changes
+			.changes()
+			.filter(|(key, value)| overrides.get(*key) != Some(&value.value().cloned()))
+			.map(|(key, value)| (None, key, value));
+		let child_writes = changes.children().flat_map(|(writes, child_info)| {
+			writes.map(move |(key, value)| (Some(child_info.storage_key()), key, value))
+		});
+		let storage_writes = top_writes
+			.chain(child_writes)
+			.map(|(child_trie, key, value)| DryRunStorageAccess {
+				child_trie: child_trie.map(|child_trie| child_trie.to_vec().into()),
+				key: StorageKey(key.clone()),
+				value: Some(value.value().cloned().map(StorageData)),
+			})
+			.collect();
+
+		Ok(DryRunTrace {
+			result: effects.result.into(),
+			events: effects.events.into(),
+			weight: effects.weight,
+			fee: effects.fee,
+			storage_reads,
+			storage_writes,
+		})
+	}
+}
+
+fn runtime_error(message: String) -> CallError {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		"Unable to dry run extrinsic.",
+		Some(message),
+	))
+}
@@ -36,6 +36,12 @@ use sp_runtime::{generic::BlockId, legacy, traits};
 
 pub use frame_system_rpc_runtime_api::AccountNonceApi;
 
+pub use self::dry_run::{
+	DryRunApi, DryRunInput, DryRunStorageAccess, DryRunTrace, SystemDryRun, SystemDryRunApiServer,
+};
+
+mod dry_run;
+
 /// System RPC methods.
 #[rpc(client, server)]
 pub trait SystemApi<BlockHash, AccountId, Index> {
@@ -216,14 +222,19 @@ mod tests {
 	use super::*;
 
 	use assert_matches::assert_matches;
+	use frame_support::dispatch::DispatchResultWithPostInfo;
 	use futures::executor::block_on;
 	use jsonrpsee::{core::Error as JsonRpseeError, types::error::CallError};
 	use sc_transaction_pool::BasicPool;
+	use sp_core::storage::{StorageData, StorageKey};
 	use sp_runtime::{
 		transaction_validity::{InvalidTransaction, TransactionValidityError},
-		ApplyExtrinsicResult,
+		ApplyExtrinsicResult, DispatchError,
+	};
+	use substrate_test_runtime_client::{
+		runtime::{system::balance_of_key, Transfer},
+		AccountKeyring,
 	};
-	use substrate_test_runtime_client::{runtime::Transfer, AccountKeyring};
 
 	#[tokio::test]
 	async fn should_return_next_nonce_for_some_account() {
@@ -334,4 +345,106 @@ mod tests {
 		let apply_res: ApplyExtrinsicResult = Decode::decode(&mut bytes.as_ref()).unwrap();
 		assert_eq!(apply_res, Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)));
 	}
+
+	#[test]
+	fn dry_run_with_trace_should_deny_unsafe() {
+		sp_tracing::try_init_simple();
+
+		// given
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let dry_run = SystemDryRun::new(client, DenyUnsafe::Yes);
+
+		// when
+		let res = dry_run.dry_run_with_trace(DryRunInput::Extrinsic(vec![].into()), None, None);
+		assert_matches!(res, Err(JsonRpseeError::Call(CallError::Custom(e))) => {
+			assert!(e.message().contains("RPC call is unsafe to be called externally"));
+		});
+	}
+
+	#[test]
+	fn dry_run_with_trace_should_work() {
+		sp_tracing::try_init_simple();
+
+		// given
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let dry_run = SystemDryRun::new(client, DenyUnsafe::No);
+
+		let transfer = Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Bob.into(),
+			amount: 5,
+			nonce: 0,
+		};
+		let alice_balance = balance_of_key(AccountKeyring::Alice.into());
+		let extrinsic = DryRunInput::Extrinsic(transfer.clone().into_signed_tx().encode().into());
+
+		// when
+		let trace = dry_run
+			.dry_run_with_trace(extrinsic.clone(), None, None)
+			.expect("Call is successful");
+
+		// then
+		let apply_res: ApplyExtrinsicResult = Decode::decode(&mut &trace.result[..]).unwrap();
+		assert_eq!(apply_res, Ok(Ok(())));
+		assert!(trace.storage_reads.iter().any(|read| read.key.0 == alice_balance));
+		assert!(trace
+			.storage_writes
+			.iter()
+			.any(|write| write.key.0 == alice_balance && write.value.is_some()));
+
+		// when the balance of the sender is overridden
+		let overrides = vec![(StorageKey(alice_balance.clone()), Some(StorageData(1u64.encode())))];
+		let trace = dry_run
+			.dry_run_with_trace(extrinsic, Some(overrides), None)
+			.expect("Call is successful");
+
+		// then
+		let apply_res: ApplyExtrinsicResult = Decode::decode(&mut &trace.result[..]).unwrap();
+		assert_eq!(apply_res, Err(TransactionValidityError::Invalid(InvalidTransaction::Payment)));
+		assert!(trace.storage_writes.iter().all(|write| write.key.0 != alice_balance));
+	}
+
+	#[test]
+	fn dry_run_with_trace_should_spoof_origin() {
+		sp_tracing::try_init_simple();
+
+		// given
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let dry_run = SystemDryRun::new(client, DenyUnsafe::No);
+
+		let call = Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Bob.into(),
+			amount: 5,
+			nonce: 0,
+		}
+		.encode();
+
+		// when
+		let trace = dry_run
+			.dry_run_with_trace(
+				DryRunInput::Call {
+					origin: AccountKeyring::Alice.into(),
+					call: call.clone().into(),
+				},
+				None,
+				None,
+			)
+			.expect("Call is successful");
+		let bad_origin = dry_run
+			.dry_run_with_trace(
+				DryRunInput::Call { origin: AccountKeyring::Bob.into(), call: call.into() },
+				None,
+				None,
+			)
+			.expect("Call is successful");
+
+		// then
+		let dispatch_res: DispatchResultWithPostInfo =
+			Decode::decode(&mut &trace.result[..]).unwrap();
+		assert_eq!(dispatch_res, Ok(Default::default()));
+		let dispatch_res: DispatchResultWithPostInfo =
+			Decode::decode(&mut &bad_origin.result[..]).unwrap();
+		assert_eq!(dispatch_res.map_err(|err| err.error), Err(DispatchError::BadOrigin));
+	}
 }
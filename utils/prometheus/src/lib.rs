@@ -31,7 +31,7 @@ pub use prometheus::{
 	Registry,
 };
 use prometheus::{core::Collector, Encoder, TextEncoder};
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 mod sourced;
 
@@ -63,7 +63,38 @@ pub enum Error {
 	PortInUse(SocketAddr),
 }
 
-async fn request_metrics(req: Request<Body>, registry: Registry) -> Result<Response<Body>, Error> {
+/// Health of the node served at `/health` and `/health/readiness`.
+///
+/// An `Err` is answered with `503 Service Unavailable` and the reason as body.
+pub trait HealthCheck: Send + Sync {
+	/// Whether the node is alive and following the chain.
+	fn health(&self) -> Result<(), String>;
+
+	/// Whether the node is ready to serve requests.
+	fn readiness(&self) -> Result<(), String>;
+}
+
+fn health_response(status: Result<(), String>) -> Result<Response<Body>, Error> {
+	match status {
+		Ok(()) => Response::builder().status(StatusCode::OK).body(Body::from("OK")),
+		Err(reason) => Response::builder()
+			.status(StatusCode::SERVICE_UNAVAILABLE)
+			.body(Body::from(reason)),
+	}
+	.map_err(Error::Http)
+}
+
+async fn request_metrics(
+	req: Request<Body>,
+	registry: Registry,
+	health: Option<Arc<dyn HealthCheck>>,
+) -> Result<Response<Body>, Error> {
+	match (req.uri().path(), health) {
+		("/health", Some(health)) => return health_response(health.health()),
+		("/health/readiness", Some(health)) => return health_response(health.readiness()),
+		_ => {},
+	}
+
 	if req.uri().path() == "/metrics" {
 		let metric_families = registry.gather();
 		let mut buffer = vec![];
@@ -90,23 +121,39 @@ pub async fn init_prometheus(prometheus_addr: SocketAddr, registry: Registry) ->
 		.await
 		.map_err(|_| Error::PortInUse(prometheus_addr))?;
 
-	init_prometheus_with_listener(listener, registry).await
+	init_prometheus_with_listener(listener, registry, None).await
+}
+
+/// Initializes the metrics context, and starts an HTTP server
+/// to serve metrics and the health endpoints backed by `health`.
+pub async fn init_prometheus_with_health(
+	prometheus_addr: SocketAddr,
+	registry: Registry,
+	health: Arc<dyn HealthCheck>,
+) -> Result<(), Error> {
+	let listener = tokio::net::TcpListener::bind(&prometheus_addr)
+		.await
+		.map_err(|_| Error::PortInUse(prometheus_addr))?;
+
+	init_prometheus_with_listener(listener, registry, Some(health)).await
 }
 
 /// Init prometheus using the given listener.
 async fn init_prometheus_with_listener(
 	listener: tokio::net::TcpListener,
 	registry: Registry,
+	health: Option<Arc<dyn HealthCheck>>,
 ) -> Result<(), Error> {
 	let listener = hyper::server::conn::AddrIncoming::from_listener(listener)?;
 	log::info!("〽️ Prometheus exporter started at {}", listener.local_addr());
 
 	let service = make_service_fn(move |_| {
 		let registry = registry.clone();
+		let health = health.clone();
 
 		async move {
 			Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
-				request_metrics(req, registry.clone())
+				request_metrics(req, registry.clone(), health.clone())
 			}))
 		}
 	});
@@ -142,7 +189,7 @@ mod tests {
 		)
 		.expect("Registers the test metric");
 
-		runtime.spawn(init_prometheus_with_listener(listener, registry));
+		runtime.spawn(init_prometheus_with_listener(listener, registry, None));
 
 		runtime.block_on(async {
 			let client = Client::new();
@@ -158,4 +205,50 @@ mod tests {
 			assert!(body.contains(&format!("{} 0", METRIC_NAME)));
 		});
 	}
+
+	#[test]
+	fn health_endpoints_work() {
+		struct Syncing;
+
+		impl HealthCheck for Syncing {
+			fn health(&self) -> Result<(), String> {
+				Ok(())
+			}
+
+			fn readiness(&self) -> Result<(), String> {
+				Err("Node is syncing".into())
+			}
+		}
+
+		let runtime = tokio::runtime::Runtime::new().expect("Creates the runtime");
+
+		let listener = runtime
+			.block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+			.expect("Creates listener");
+
+		let local_addr = listener.local_addr().expect("Returns the local addr");
+
+		runtime.spawn(init_prometheus_with_listener(
+			listener,
+			Registry::default(),
+			Some(Arc::new(Syncing)),
+		));
+
+		runtime.block_on(async {
+			let client = Client::new();
+			let get = |path: &str| {
+				client.get(
+					Uri::try_from(&format!("http://{}{}", local_addr, path)).expect("Parses URI"),
+				)
+			};
+
+			let res = get("/health").await.expect("Requests health");
+			assert_eq!(res.status(), StatusCode::OK);
+
+			let res = get("/health/readiness").await.expect("Requests readiness");
+			assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+			let buf = hyper::body::to_bytes(res).await.expect("Converts body to bytes");
+			assert_eq!(&buf[..], b"Node is syncing");
+		});
+	}
 }